@@ -0,0 +1,18 @@
+#version 450
+
+layout(location = 0) in vec3 translation;
+layout(location = 1) in vec2 in_uv;
+
+layout(location = 2) in mat4 model;
+
+layout(location = 0) out vec2 fragment_uv;
+
+layout(set = 0, binding = 0) uniform Uniforms {
+    mat4 view_projection;
+};
+
+void main()
+{
+    gl_Position = view_projection * model * vec4(translation, 1.0);
+    fragment_uv = in_uv;
+}
@@ -1,13 +1,16 @@
-use amethyst_input::{Button, InputEvent, InputHandler, ScrollDirection, StringBindings};
+use amethyst_input::{InputHandler, StringBindings};
 use shaderc::{Compiler, ShaderKind};
-use shrev::{EventChannel, ReaderId};
-use ultraviolet::{Mat4, Rotor3, Vec2, Vec3, Vec4};
+use ultraviolet::{Mat4, Vec2, Vec3, Vec4};
 use winit::{
     event::{Event, KeyboardInput, VirtualKeyCode, WindowEvent},
     event_loop::{ControlFlow, EventLoop},
     window::WindowBuilder,
 };
 
+use learnopengl_but_its_wgpu::{
+    instance_buffer::InstanceBuffer, scene::Scene, time::Time, Instance,
+};
+
 const DEPTH_FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Depth32Float;
 
 #[rustfmt::skip]
@@ -55,31 +58,21 @@ const VERTICES: &[Vertex] = &[
    Vertex { translation: Vec3 { x: -0.5, y:  0.5, z:  0.5, }, uv: Vec2 { x: 0.0, y: 0.0, } },
 ];
 
-#[rustfmt::skip]
-const CUBES: &[Instance] = &[
-    Instance { model: Mat4 {
-        cols: [
-            Vec4 { x:  1.0, y:  0.0, z:  0.0, w:  0.0 },
-            Vec4 { x:  0.0, y:  1.0, z:  0.0, w:  0.0 },
-            Vec4 { x:  0.0, y:  0.0, z:  1.0, w:  0.0 },
-            Vec4 { x:  0.0, y:  0.0, z:  0.0, w:  1.0 },
-        ]
-    }},
-];
-
-#[rustfmt::skip]
-const LIGHT_SOURCES: &[Instance] = &[
-    Instance { model: Mat4 {
-        cols: [
-            Vec4 { x:  0.2, y:  0.0,  z:  0.0, w:  0.0 },
-            Vec4 { x:  0.0, y:  0.2,  z:  0.0, w:  0.0 },
-            Vec4 { x:  0.0, y:  0.0,  z:  0.2, w:  0.0 },
-            Vec4 { x:  1.2, y:  1.0,  z:  2.0, w:  1.0 },
-        ]
-    }},
-];
-
 fn main() {
+    let scene_path = std::env::args().nth(1).unwrap_or_else(|| {
+        concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/01-lighting/00-colors/scene.ron"
+        )
+        .to_owned()
+    });
+    let scene = Scene::load(&scene_path).unwrap();
+    let light = scene
+        .point_lights
+        .first()
+        .cloned()
+        .expect("The scene has no point light");
+
     let (event_loop, window, mut size) = {
         let event_loop = EventLoop::new();
         let window = WindowBuilder::new()
@@ -132,12 +125,12 @@ fn main() {
         .create_buffer_mapped(VERTICES.len(), wgpu::BufferUsage::VERTEX)
         .fill_from_slice(VERTICES);
 
+    let cubes = scene.cube_instances();
     let cube_instance_buffer = device
-        .create_buffer_mapped(CUBES.len(), wgpu::BufferUsage::VERTEX)
-        .fill_from_slice(CUBES);
-    let light_source_instance_buffer = device
-        .create_buffer_mapped(LIGHT_SOURCES.len(), wgpu::BufferUsage::VERTEX)
-        .fill_from_slice(LIGHT_SOURCES);
+        .create_buffer_mapped(cubes.len(), wgpu::BufferUsage::VERTEX)
+        .fill_from_slice(&cubes);
+    let mut light_source_instance_buffer =
+        InstanceBuffer::from_slice(&device, &scene.light_source_instances());
 
     let vertex_uniform_bind_group_layout =
         device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
@@ -161,17 +154,7 @@ fn main() {
     let event_reader = event_channel.register_reader();
     let mut input_handler = InputHandler::<StringBindings>::new();
 
-    let mut camera = Camera {
-        translation: Vec3::new(0.0, 0.0, 3.0),
-        pitch: 0.0,
-        yaw: std::f32::consts::PI,
-        roll: 0.0,
-        movement_speed: 0.1,
-        mouse_sensitivity: 0.0025,
-        zoom: std::f32::consts::FRAC_PI_3,
-        is_controlled: false,
-        event_reader,
-    };
+    let mut camera = scene.create_camera(event_reader);
 
     let vertex_uniform_buffer = device
         .create_buffer_mapped(1, wgpu::BufferUsage::UNIFORM | wgpu::BufferUsage::COPY_DST)
@@ -198,8 +181,13 @@ fn main() {
     let fragment_uniform_buffer = device
         .create_buffer_mapped(2, wgpu::BufferUsage::UNIFORM | wgpu::BufferUsage::COPY_DST)
         .fill_from_slice(&[
-            Vec4::new(1.0, 0.5, 0.31, 0.0),
-            Vec4::new(1.0, 1.0, 1.0, 0.0),
+            Vec4::new(
+                scene.material.diffuse[0],
+                scene.material.diffuse[1],
+                scene.material.diffuse[2],
+                0.0,
+            ),
+            Vec4::new(light.diffuse[0], light.diffuse[1], light.diffuse[2], 0.0),
         ]);
 
     let fragment_uniform_bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
@@ -215,7 +203,7 @@ fn main() {
 
     let mut swap_chain_descriptor = wgpu::SwapChainDescriptor {
         usage: wgpu::TextureUsage::OUTPUT_ATTACHMENT,
-        format: wgpu::TextureFormat::Bgra8Unorm,
+        format: wgpu::TextureFormat::Bgra8UnormSrgb,
         width: size.width,
         height: size.height,
         present_mode: wgpu::PresentMode::Vsync,
@@ -330,6 +318,8 @@ fn main() {
 
     let mut swap_chain = device.create_swap_chain(&surface, &swap_chain_descriptor);
 
+    let mut time = Time::new();
+
     event_loop.run(move |event, _, control_flow| {
         input_handler.send_event(&event, &mut event_channel);
 
@@ -393,6 +383,9 @@ fn main() {
                 input_handler.send_frame_begin();
                 camera.update(&input_handler);
 
+                time.update();
+                let animated_light = light.sample(time.elapsed());
+
                 let frame = swap_chain.get_next_texture();
                 let mut encoder =
                     device.create_command_encoder(&wgpu::CommandEncoderDescriptor { todo: 0 });
@@ -404,8 +397,14 @@ fn main() {
                         100.0,
                     );
                     let staging_buffer = device
-                        .create_buffer_mapped(1, wgpu::BufferUsage::COPY_SRC)
-                        .fill_from_slice(&[view_projection]);
+                        .create_buffer_mapped(5, wgpu::BufferUsage::COPY_SRC)
+                        .fill_from_slice(&[
+                            view_projection[0],
+                            view_projection[1],
+                            view_projection[2],
+                            view_projection[3],
+                            animated_light.diffuse.0.into_homogeneous_vector(),
+                        ]);
                     encoder.copy_buffer_to_buffer(
                         &staging_buffer,
                         0,
@@ -413,6 +412,19 @@ fn main() {
                         0,
                         std::mem::size_of::<Mat4>() as wgpu::BufferAddress,
                     );
+                    encoder.copy_buffer_to_buffer(
+                        &staging_buffer,
+                        std::mem::size_of::<Mat4>() as wgpu::BufferAddress,
+                        &fragment_uniform_buffer,
+                        std::mem::size_of::<Vec4>() as wgpu::BufferAddress,
+                        std::mem::size_of::<Vec4>() as wgpu::BufferAddress,
+                    );
+
+                    light_source_instance_buffer.update(
+                        &device,
+                        &mut encoder,
+                        &scene.light_source_instances_at(time.elapsed()),
+                    );
 
                     let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
                         color_attachments: &[wgpu::RenderPassColorAttachmentDescriptor {
@@ -444,15 +456,21 @@ fn main() {
                     render_pass.set_bind_group(1, &fragment_uniform_bind_group, &[]);
                     render_pass
                         .set_vertex_buffers(0, &[(&vertex_buffer, 0), (&cube_instance_buffer, 0)]);
-                    render_pass.draw(0..VERTICES.len() as u32, 0..CUBES.len() as u32);
+                    render_pass.draw(0..VERTICES.len() as u32, 0..cubes.len() as u32);
 
                     render_pass.set_pipeline(&light_source_pipeline);
                     render_pass.set_bind_group(0, &vertex_uniform_bind_group, &[]);
                     render_pass.set_vertex_buffers(
                         0,
-                        &[(&vertex_buffer, 0), (&light_source_instance_buffer, 0)],
+                        &[
+                            (&vertex_buffer, 0),
+                            (light_source_instance_buffer.buffer(), 0),
+                        ],
+                    );
+                    render_pass.draw(
+                        0..VERTICES.len() as u32,
+                        0..light_source_instance_buffer.len() as u32,
                     );
-                    render_pass.draw(0..VERTICES.len() as u32, 0..LIGHT_SOURCES.len() as u32);
                 }
 
                 queue.submit(&[encoder.finish()]);
@@ -497,116 +515,3 @@ pub struct Vertex {
     pub translation: Vec3,
     pub uv: Vec2,
 }
-
-#[repr(C)]
-#[derive(Clone, Copy, Debug)]
-pub struct Instance {
-    pub model: Mat4,
-}
-
-pub struct Camera {
-    pub translation: Vec3,
-    pub pitch: f32,
-    pub yaw: f32,
-    pub roll: f32,
-    pub movement_speed: f32,
-    pub mouse_sensitivity: f32,
-    pub zoom: f32,
-    pub is_controlled: bool,
-
-    pub event_reader: ReaderId<InputEvent<StringBindings>>,
-}
-
-impl Camera {
-    pub fn get_view_projection_matrix(&self, width: f32, height: f32, near: f32, far: f32) -> Mat4 {
-        ultraviolet::projection::perspective_vk(self.zoom, width / height, near, far)
-            * (Rotor3::from_rotation_xy(self.roll)
-                * Rotor3::from_rotation_yz(self.pitch)
-                * Rotor3::from_rotation_xz(self.yaw))
-            .into_matrix()
-            .into_homogeneous()
-            * Mat4::from_translation(self.translation)
-    }
-
-    pub fn on_event(&mut self, event_channel: &EventChannel<InputEvent<StringBindings>>) {
-        if !self.is_controlled {
-            return;
-        }
-
-        for event in event_channel.read(&mut self.event_reader) {
-            match event {
-                InputEvent::MouseMoved { delta_x, delta_y } => {
-                    self.pitch += delta_y * self.mouse_sensitivity;
-                    self.yaw += -(delta_x * self.mouse_sensitivity);
-
-                    if self.pitch > std::f32::consts::FRAC_PI_2 {
-                        self.pitch = std::f32::consts::FRAC_PI_2;
-                    } else if self.pitch < -std::f32::consts::FRAC_PI_2 {
-                        self.pitch = -std::f32::consts::FRAC_PI_2;
-                    }
-                    if self.yaw > 2.0 * std::f32::consts::PI {
-                        self.yaw -= 2.0 * std::f32::consts::PI;
-                    } else if self.yaw < 0.0 {
-                        self.yaw = 2.0 * std::f32::consts::PI - self.yaw;
-                    }
-                }
-                InputEvent::MouseWheelMoved(direction) => match direction {
-                    ScrollDirection::ScrollUp => {
-                        if self.zoom > 0.2 {
-                            self.zoom -= 0.1;
-                        } else {
-                            self.zoom = 0.1;
-                        }
-                    }
-                    ScrollDirection::ScrollDown => {
-                        if self.zoom < std::f32::consts::PI - 0.1 {
-                            self.zoom += 0.1;
-                        } else {
-                            self.zoom = std::f32::consts::PI - 0.1;
-                        }
-                    }
-                    _ => {}
-                },
-                _ => {}
-            }
-        }
-    }
-
-    pub fn update(&mut self, input_handler: &InputHandler<StringBindings>) {
-        let forward_displacement = (if input_handler.button_is_down(Button::ScanCode(0x11)) {
-            -1.0
-        } else {
-            0.0
-        } + if input_handler.button_is_down(Button::ScanCode(0x1F)) {
-            1.0
-        } else {
-            0.0
-        }) * self.movement_speed;
-        let sideways_displacement = (if input_handler.button_is_down(Button::ScanCode(0x1E)) {
-            -1.0
-        } else {
-            0.0
-        } + if input_handler.button_is_down(Button::ScanCode(0x20)) {
-            1.0
-        } else {
-            0.0
-        }) * self.movement_speed;
-        let vertical_displacement = (if input_handler.button_is_down(Button::ScanCode(0x39)) {
-            -1.0
-        } else {
-            0.0
-        } + if input_handler.button_is_down(Button::ScanCode(0x2e)) {
-            1.0
-        } else {
-            0.0
-        }) * self.movement_speed;
-
-        let yaw = self.yaw - std::f32::consts::FRAC_PI_2;
-
-        let x_displacement = -yaw.cos() * forward_displacement + yaw.sin() * sideways_displacement;
-        let y_displacement = -self.pitch.sin() * forward_displacement + vertical_displacement;
-        let z_displacement = yaw.sin() * forward_displacement + yaw.cos() * sideways_displacement;
-
-        self.translation += Vec3::new(x_displacement, y_displacement, z_displacement);
-    }
-}
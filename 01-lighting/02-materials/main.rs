@@ -7,7 +7,12 @@ use winit::{
     window::WindowBuilder,
 };
 
-use learnopengl_but_its_wgpu::{Align16, Camera};
+use learnopengl_but_its_wgpu::{
+    instance_buffer::InstanceBuffer,
+    scene::{self, Scene},
+    time::Time,
+    Align16, Instance,
+};
 
 const DEPTH_FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Depth32Float;
 
@@ -57,34 +62,21 @@ const VERTICES: &[Vertex] = &[
 
 ];
 
-#[rustfmt::skip]
-const CUBES: &[Instance] = &[
-    Instance { model: Mat4 {
-        cols: [
-            Vec4 { x:  1.0, y:  0.0, z:  0.0, w:  0.0 },
-            Vec4 { x:  0.0, y:  1.0, z:  0.0, w:  0.0 },
-            Vec4 { x:  0.0, y:  0.0, z:  1.0, w:  0.0 },
-            Vec4 { x:  0.0, y:  0.0, z:  0.0, w:  1.0 },
-        ]
-    }},
-];
-
-#[rustfmt::skip]
-const LIGHT_TRANLSATION: Vec3 = Vec3 { x:  1.2, y:  1.0, z:  2.0 };
-
-#[rustfmt::skip]
-const LIGHT_SOURCES: &[Instance] = &[
-    Instance { model: Mat4 {
-        cols: [
-            Vec4 { x:  0.2,                 y: 0.0,                   z:  0.0,                w:  0.0 },
-            Vec4 { x:  0.0,                 y: 0.2,                   z:  0.0,                w:  0.0 },
-            Vec4 { x:  0.0,                 y: 0.0,                   z:  0.2,                w:  0.0 },
-            Vec4 { x:  LIGHT_TRANLSATION.x, y:  LIGHT_TRANLSATION.y,  z: LIGHT_TRANLSATION.z, w:  1.0 },
-        ]
-    }},
-];
-
 fn main() {
+    let scene_path = std::env::args().nth(1).unwrap_or_else(|| {
+        concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/01-lighting/02-materials/scene.ron"
+        )
+        .to_owned()
+    });
+    let scene = Scene::load(&scene_path).unwrap();
+    let light = scene
+        .point_lights
+        .first()
+        .cloned()
+        .expect("The scene has no point light");
+
     let (event_loop, window, mut size) = {
         let event_loop = EventLoop::new();
         let window = WindowBuilder::new()
@@ -144,12 +136,12 @@ fn main() {
         .create_buffer_mapped(VERTICES.len(), wgpu::BufferUsage::VERTEX)
         .fill_from_slice(VERTICES);
 
+    let cubes = scene.cube_instances();
     let cube_instance_buffer = device
-        .create_buffer_mapped(CUBES.len(), wgpu::BufferUsage::VERTEX)
-        .fill_from_slice(CUBES);
-    let light_source_instance_buffer = device
-        .create_buffer_mapped(LIGHT_SOURCES.len(), wgpu::BufferUsage::VERTEX)
-        .fill_from_slice(LIGHT_SOURCES);
+        .create_buffer_mapped(cubes.len(), wgpu::BufferUsage::VERTEX)
+        .fill_from_slice(&cubes);
+    let mut light_source_instance_buffer =
+        InstanceBuffer::from_slice(&device, &scene.light_source_instances());
 
     let vertex_uniform_bind_group_layout =
         device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
@@ -191,17 +183,7 @@ fn main() {
     let event_reader = event_channel.register_reader();
     let mut input_handler = InputHandler::<StringBindings>::new();
 
-    let mut camera = Camera {
-        translation: Vec3::new(0.0, 0.0, 3.0),
-        pitch: 0.0,
-        yaw: std::f32::consts::PI,
-        roll: 0.0,
-        movement_speed: 0.1,
-        mouse_sensitivity: 0.0025,
-        zoom: std::f32::consts::FRAC_PI_3,
-        is_controlled: false,
-        event_reader,
-    };
+    let mut camera = scene.create_camera(event_reader);
 
     let vertex_uniform_buffer = device
         .create_buffer_mapped(1, wgpu::BufferUsage::UNIFORM | wgpu::BufferUsage::COPY_DST)
@@ -243,10 +225,10 @@ fn main() {
     let material_uniform_buffer = device
         .create_buffer_mapped(1, wgpu::BufferUsage::UNIFORM | wgpu::BufferUsage::COPY_DST)
         .fill_from_slice(&[Material {
-            ambient: Vec3::new(1.0, 0.5, 0.31).into(),
-            diffuse: Vec3::new(1.0, 0.5, 0.31).into(),
-            specular: Vec3::new(0.5, 0.5, 0.5).into(),
-            shininess: 32.0.into(),
+            ambient: scene::vec3(scene.material.ambient).into(),
+            diffuse: scene::vec3(scene.material.diffuse).into(),
+            specular: scene::vec3(scene.material.specular),
+            shininess: scene.material.shininess,
         }]);
 
     let material_uniform_bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
@@ -263,10 +245,10 @@ fn main() {
     let light_uniform_buffer = device
         .create_buffer_mapped(1, wgpu::BufferUsage::UNIFORM | wgpu::BufferUsage::COPY_DST)
         .fill_from_slice(&[Light {
-            translation: LIGHT_TRANLSATION.into(),
-            ambient: Vec3::new(0.2, 0.2, 0.2).into(),
-            diffuse: Vec3::new(0.5, 0.5, 0.5).into(),
-            specular: Vec3::new(1.0, 1.0, 1.0).into(),
+            translation: scene::vec3(light.translation).into(),
+            ambient: scene::vec3(light.ambient).into(),
+            diffuse: scene::vec3(light.diffuse).into(),
+            specular: scene::vec3(light.specular),
         }]);
 
     let light_uniform_bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
@@ -282,7 +264,7 @@ fn main() {
 
     let mut swap_chain_descriptor = wgpu::SwapChainDescriptor {
         usage: wgpu::TextureUsage::OUTPUT_ATTACHMENT,
-        format: wgpu::TextureFormat::Bgra8Unorm,
+        format: wgpu::TextureFormat::Bgra8UnormSrgb,
         width: size.width,
         height: size.height,
         present_mode: wgpu::PresentMode::Vsync,
@@ -380,6 +362,23 @@ fn main() {
                         format: wgpu::VertexFormat::Float4,
                         shader_location: 6,
                     },
+                    wgpu::VertexAttributeDescriptor {
+                        offset: std::mem::size_of::<Mat4>() as wgpu::BufferAddress,
+                        format: wgpu::VertexFormat::Float3,
+                        shader_location: 7,
+                    },
+                    wgpu::VertexAttributeDescriptor {
+                        offset: (std::mem::size_of::<Mat4>() + std::mem::size_of::<Vec3>())
+                            as wgpu::BufferAddress,
+                        format: wgpu::VertexFormat::Float3,
+                        shader_location: 8,
+                    },
+                    wgpu::VertexAttributeDescriptor {
+                        offset: (std::mem::size_of::<Mat4>() + std::mem::size_of::<Vec3>() * 2)
+                            as wgpu::BufferAddress,
+                        format: wgpu::VertexFormat::Float3,
+                        shader_location: 9,
+                    },
                 ],
             },
         ],
@@ -408,6 +407,8 @@ fn main() {
 
     let mut swap_chain = device.create_swap_chain(&surface, &swap_chain_descriptor);
 
+    let mut time = Time::new();
+
     event_loop.run(move |event, _, control_flow| {
         input_handler.send_event(&event, &mut event_channel);
 
@@ -471,6 +472,9 @@ fn main() {
                 input_handler.send_frame_begin();
                 camera.update(&input_handler);
 
+                time.update();
+                let animated_light = light.sample(time.elapsed());
+
                 let frame = swap_chain.get_next_texture();
                 let mut encoder =
                     device.create_command_encoder(&wgpu::CommandEncoderDescriptor { todo: 0 });
@@ -488,8 +492,7 @@ fn main() {
                             view_projection[1],
                             view_projection[2],
                             view_projection[3],
-                            // TODO: Figure out why I need to invert this
-                            -camera.translation.into_homogeneous_vector(),
+                            camera.world_translation().into_homogeneous_vector(),
                         ]);
                     encoder.copy_buffer_to_buffer(
                         &staging_buffer,
@@ -506,6 +509,28 @@ fn main() {
                         std::mem::size_of::<Vec4>() as wgpu::BufferAddress,
                     );
 
+                    let staging_buffer = device
+                        .create_buffer_mapped(1, wgpu::BufferUsage::COPY_SRC)
+                        .fill_from_slice(&[Light {
+                            translation: animated_light.translation,
+                            ambient: animated_light.ambient,
+                            diffuse: animated_light.diffuse,
+                            specular: animated_light.specular,
+                        }]);
+                    encoder.copy_buffer_to_buffer(
+                        &staging_buffer,
+                        0,
+                        &light_uniform_buffer,
+                        0,
+                        std::mem::size_of::<Light>() as wgpu::BufferAddress,
+                    );
+
+                    light_source_instance_buffer.update(
+                        &device,
+                        &mut encoder,
+                        &scene.light_source_instances_at(time.elapsed()),
+                    );
+
                     let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
                         color_attachments: &[wgpu::RenderPassColorAttachmentDescriptor {
                             attachment: &frame.view,
@@ -538,15 +563,21 @@ fn main() {
                     render_pass.set_bind_group(3, &light_uniform_bind_group, &[]);
                     render_pass
                         .set_vertex_buffers(0, &[(&vertex_buffer, 0), (&cube_instance_buffer, 0)]);
-                    render_pass.draw(0..VERTICES.len() as u32, 0..CUBES.len() as u32);
+                    render_pass.draw(0..VERTICES.len() as u32, 0..cubes.len() as u32);
 
                     render_pass.set_pipeline(&light_source_pipeline);
                     render_pass.set_bind_group(0, &vertex_uniform_bind_group, &[]);
                     render_pass.set_vertex_buffers(
                         0,
-                        &[(&vertex_buffer, 0), (&light_source_instance_buffer, 0)],
+                        &[
+                            (&vertex_buffer, 0),
+                            (light_source_instance_buffer.buffer(), 0),
+                        ],
+                    );
+                    render_pass.draw(
+                        0..VERTICES.len() as u32,
+                        0..light_source_instance_buffer.len() as u32,
                     );
-                    render_pass.draw(0..VERTICES.len() as u32, 0..LIGHT_SOURCES.len() as u32);
                 }
 
                 queue.submit(&[encoder.finish()]);
@@ -593,12 +624,6 @@ pub struct Vertex {
     pub uv: Vec2,
 }
 
-#[repr(C)]
-#[derive(Clone, Copy, Debug)]
-pub struct Instance {
-    pub model: Mat4,
-}
-
 #[repr(C)]
 #[derive(Clone, Copy, Debug)]
 pub struct FragmentUniforms {
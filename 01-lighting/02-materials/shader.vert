@@ -4,6 +4,7 @@ layout(location = 0) in vec3 translation;
 layout(location = 1) in vec3 normal;
 
 layout(location = 3) in mat4 model;
+layout(location = 7) in mat3 normal_matrix;
 
 layout(location = 0) out vec3 frag_translation;
 layout(location = 1) out vec3 frag_normal;
@@ -16,5 +17,5 @@ void main()
 {
     gl_Position = view_projection * model * vec4(translation, 1.0);
     frag_translation = vec3(model * vec4(translation, 1.0));
-    frag_normal = mat3(transpose(inverse(model))) * normal;
+    frag_normal = normal_matrix * normal;
 }
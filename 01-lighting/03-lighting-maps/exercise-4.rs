@@ -0,0 +1,966 @@
+use amethyst_input::{InputHandler, StringBindings};
+use shaderc::{Compiler, ShaderKind};
+use ultraviolet::{Mat4, Vec2, Vec3, Vec4};
+use winit::{
+    event::{Event, KeyboardInput, VirtualKeyCode, WindowEvent},
+    event_loop::{ControlFlow, EventLoop},
+    window::WindowBuilder,
+};
+
+use learnopengl_but_its_wgpu::{
+    instance_buffer::InstanceBuffer,
+    scene::{self, Scene},
+    time::Time,
+    Align16, Instance,
+};
+
+const DEPTH_FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Depth32Float;
+
+#[rustfmt::skip]
+const VERTICES: &[Vertex] = &[
+    Vertex { translation: Vec3 { x:  0.5, y: -0.5, z: -0.5, }, normal: Vec3 { x:  0.0, y:  0.0, z: -1.0 }, uv: Vec2 { x: 1.0, y: 0.0, }, tangent: Vec4 { x:  1.0, y:  0.0, z:  0.0, w: -1.0 } },
+    Vertex { translation: Vec3 { x: -0.5, y: -0.5, z: -0.5, }, normal: Vec3 { x:  0.0, y:  0.0, z: -1.0 }, uv: Vec2 { x: 0.0, y: 0.0, }, tangent: Vec4 { x:  1.0, y:  0.0, z:  0.0, w: -1.0 } },
+    Vertex { translation: Vec3 { x:  0.5, y:  0.5, z: -0.5, }, normal: Vec3 { x:  0.0, y:  0.0, z: -1.0 }, uv: Vec2 { x: 1.0, y: 1.0, }, tangent: Vec4 { x:  1.0, y:  0.0, z:  0.0, w: -1.0 } },
+    Vertex { translation: Vec3 { x:  0.5, y:  0.5, z: -0.5, }, normal: Vec3 { x:  0.0, y:  0.0, z: -1.0 }, uv: Vec2 { x: 1.0, y: 1.0, }, tangent: Vec4 { x:  1.0, y:  0.0, z:  0.0, w: -1.0 } },
+    Vertex { translation: Vec3 { x: -0.5, y: -0.5, z: -0.5, }, normal: Vec3 { x:  0.0, y:  0.0, z: -1.0 }, uv: Vec2 { x: 0.0, y: 0.0, }, tangent: Vec4 { x:  1.0, y:  0.0, z:  0.0, w: -1.0 } },
+    Vertex { translation: Vec3 { x: -0.5, y:  0.5, z: -0.5, }, normal: Vec3 { x:  0.0, y:  0.0, z: -1.0 }, uv: Vec2 { x: 0.0, y: 1.0, }, tangent: Vec4 { x:  1.0, y:  0.0, z:  0.0, w: -1.0 } },
+
+    Vertex { translation: Vec3 { x: -0.5, y: -0.5, z:  0.5, }, normal: Vec3 { x:  0.0, y:  0.0, z:  1.0 }, uv: Vec2 { x: 0.0, y: 0.0, }, tangent: Vec4 { x:  1.0, y:  0.0, z:  0.0, w:  1.0 } },
+    Vertex { translation: Vec3 { x:  0.5, y: -0.5, z:  0.5, }, normal: Vec3 { x:  0.0, y:  0.0, z:  1.0 }, uv: Vec2 { x: 1.0, y: 0.0, }, tangent: Vec4 { x:  1.0, y:  0.0, z:  0.0, w:  1.0 } },
+    Vertex { translation: Vec3 { x:  0.5, y:  0.5, z:  0.5, }, normal: Vec3 { x:  0.0, y:  0.0, z:  1.0 }, uv: Vec2 { x: 1.0, y: 1.0, }, tangent: Vec4 { x:  1.0, y:  0.0, z:  0.0, w:  1.0 } },
+    Vertex { translation: Vec3 { x:  0.5, y:  0.5, z:  0.5, }, normal: Vec3 { x:  0.0, y:  0.0, z:  1.0 }, uv: Vec2 { x: 1.0, y: 1.0, }, tangent: Vec4 { x:  1.0, y:  0.0, z:  0.0, w:  1.0 } },
+    Vertex { translation: Vec3 { x: -0.5, y:  0.5, z:  0.5, }, normal: Vec3 { x:  0.0, y:  0.0, z:  1.0 }, uv: Vec2 { x: 0.0, y: 1.0, }, tangent: Vec4 { x:  1.0, y:  0.0, z:  0.0, w:  1.0 } },
+    Vertex { translation: Vec3 { x: -0.5, y: -0.5, z:  0.5, }, normal: Vec3 { x:  0.0, y:  0.0, z:  1.0 }, uv: Vec2 { x: 0.0, y: 0.0, }, tangent: Vec4 { x:  1.0, y:  0.0, z:  0.0, w:  1.0 } },
+
+    Vertex { translation: Vec3 { x: -0.5, y:  0.5, z:  0.5, }, normal: Vec3 { x: -1.0, y:  0.0, z:  0.0 }, uv: Vec2 { x: 1.0, y: 0.0, }, tangent: Vec4 { x:  0.0, y:  1.0, z:  0.0, w:  1.0 } },
+    Vertex { translation: Vec3 { x: -0.5, y:  0.5, z: -0.5, }, normal: Vec3 { x: -1.0, y:  0.0, z:  0.0 }, uv: Vec2 { x: 1.0, y: 1.0, }, tangent: Vec4 { x:  0.0, y:  1.0, z:  0.0, w:  1.0 } },
+    Vertex { translation: Vec3 { x: -0.5, y: -0.5, z: -0.5, }, normal: Vec3 { x: -1.0, y:  0.0, z:  0.0 }, uv: Vec2 { x: 0.0, y: 1.0, }, tangent: Vec4 { x:  0.0, y:  1.0, z:  0.0, w:  1.0 } },
+    Vertex { translation: Vec3 { x: -0.5, y: -0.5, z: -0.5, }, normal: Vec3 { x: -1.0, y:  0.0, z:  0.0 }, uv: Vec2 { x: 0.0, y: 1.0, }, tangent: Vec4 { x:  0.0, y:  1.0, z:  0.0, w:  1.0 } },
+    Vertex { translation: Vec3 { x: -0.5, y: -0.5, z:  0.5, }, normal: Vec3 { x: -1.0, y:  0.0, z:  0.0 }, uv: Vec2 { x: 0.0, y: 0.0, }, tangent: Vec4 { x:  0.0, y:  1.0, z:  0.0, w:  1.0 } },
+    Vertex { translation: Vec3 { x: -0.5, y:  0.5, z:  0.5, }, normal: Vec3 { x: -1.0, y:  0.0, z:  0.0 }, uv: Vec2 { x: 1.0, y: 0.0, }, tangent: Vec4 { x:  0.0, y:  1.0, z:  0.0, w:  1.0 } },
+
+    Vertex { translation: Vec3 { x:  0.5, y:  0.5, z:  0.5, }, normal: Vec3 { x:  1.0, y:  0.0, z:  0.0 }, uv: Vec2 { x: 1.0, y: 0.0, }, tangent: Vec4 { x:  0.0, y:  1.0, z:  0.0, w: -1.0 } },
+    Vertex { translation: Vec3 { x:  0.5, y: -0.5, z: -0.5, }, normal: Vec3 { x:  1.0, y:  0.0, z:  0.0 }, uv: Vec2 { x: 0.0, y: 1.0, }, tangent: Vec4 { x:  0.0, y:  1.0, z:  0.0, w: -1.0 } },
+    Vertex { translation: Vec3 { x:  0.5, y:  0.5, z: -0.5, }, normal: Vec3 { x:  1.0, y:  0.0, z:  0.0 }, uv: Vec2 { x: 1.0, y: 1.0, }, tangent: Vec4 { x:  0.0, y:  1.0, z:  0.0, w: -1.0 } },
+    Vertex { translation: Vec3 { x:  0.5, y: -0.5, z: -0.5, }, normal: Vec3 { x:  1.0, y:  0.0, z:  0.0 }, uv: Vec2 { x: 0.0, y: 1.0, }, tangent: Vec4 { x:  0.0, y:  1.0, z:  0.0, w: -1.0 } },
+    Vertex { translation: Vec3 { x:  0.5, y:  0.5, z:  0.5, }, normal: Vec3 { x:  1.0, y:  0.0, z:  0.0 }, uv: Vec2 { x: 1.0, y: 0.0, }, tangent: Vec4 { x:  0.0, y:  1.0, z:  0.0, w: -1.0 } },
+    Vertex { translation: Vec3 { x:  0.5, y: -0.5, z:  0.5, }, normal: Vec3 { x:  1.0, y:  0.0, z:  0.0 }, uv: Vec2 { x: 0.0, y: 0.0, }, tangent: Vec4 { x:  0.0, y:  1.0, z:  0.0, w: -1.0 } },
+
+    Vertex { translation: Vec3 { x: -0.5, y: -0.5, z: -0.5, }, normal: Vec3 { x:  0.0, y: -1.0, z:  0.0 }, uv: Vec2 { x: 0.0, y: 1.0, }, tangent: Vec4 { x:  1.0, y:  0.0, z:  0.0, w: -1.0 } },
+    Vertex { translation: Vec3 { x:  0.5, y: -0.5, z: -0.5, }, normal: Vec3 { x:  0.0, y: -1.0, z:  0.0 }, uv: Vec2 { x: 1.0, y: 1.0, }, tangent: Vec4 { x:  1.0, y:  0.0, z:  0.0, w: -1.0 } },
+    Vertex { translation: Vec3 { x:  0.5, y: -0.5, z:  0.5, }, normal: Vec3 { x:  0.0, y: -1.0, z:  0.0 }, uv: Vec2 { x: 1.0, y: 0.0, }, tangent: Vec4 { x:  1.0, y:  0.0, z:  0.0, w: -1.0 } },
+    Vertex { translation: Vec3 { x:  0.5, y: -0.5, z:  0.5, }, normal: Vec3 { x:  0.0, y: -1.0, z:  0.0 }, uv: Vec2 { x: 1.0, y: 0.0, }, tangent: Vec4 { x:  1.0, y:  0.0, z:  0.0, w: -1.0 } },
+    Vertex { translation: Vec3 { x: -0.5, y: -0.5, z:  0.5, }, normal: Vec3 { x:  0.0, y: -1.0, z:  0.0 }, uv: Vec2 { x: 0.0, y: 0.0, }, tangent: Vec4 { x:  1.0, y:  0.0, z:  0.0, w: -1.0 } },
+    Vertex { translation: Vec3 { x: -0.5, y: -0.5, z: -0.5, }, normal: Vec3 { x:  0.0, y: -1.0, z:  0.0 }, uv: Vec2 { x: 0.0, y: 1.0, }, tangent: Vec4 { x:  1.0, y:  0.0, z:  0.0, w: -1.0 } },
+
+    Vertex { translation: Vec3 { x:  0.5, y:  0.5, z: -0.5, }, normal: Vec3 { x:  0.0, y:  1.0, z:  0.0 }, uv: Vec2 { x: 1.0, y: 1.0, }, tangent: Vec4 { x:  1.0, y:  0.0, z:  0.0, w:  1.0 } },
+    Vertex { translation: Vec3 { x: -0.5, y:  0.5, z: -0.5, }, normal: Vec3 { x:  0.0, y:  1.0, z:  0.0 }, uv: Vec2 { x: 0.0, y: 1.0, }, tangent: Vec4 { x:  1.0, y:  0.0, z:  0.0, w:  1.0 } },
+    Vertex { translation: Vec3 { x:  0.5, y:  0.5, z:  0.5, }, normal: Vec3 { x:  0.0, y:  1.0, z:  0.0 }, uv: Vec2 { x: 1.0, y: 0.0, }, tangent: Vec4 { x:  1.0, y:  0.0, z:  0.0, w:  1.0 } },
+    Vertex { translation: Vec3 { x:  0.5, y:  0.5, z:  0.5, }, normal: Vec3 { x:  0.0, y:  1.0, z:  0.0 }, uv: Vec2 { x: 1.0, y: 0.0, }, tangent: Vec4 { x:  1.0, y:  0.0, z:  0.0, w:  1.0 } },
+    Vertex { translation: Vec3 { x: -0.5, y:  0.5, z: -0.5, }, normal: Vec3 { x:  0.0, y:  1.0, z:  0.0 }, uv: Vec2 { x: 0.0, y: 1.0, }, tangent: Vec4 { x:  1.0, y:  0.0, z:  0.0, w:  1.0 } },
+    Vertex { translation: Vec3 { x: -0.5, y:  0.5, z:  0.5, }, normal: Vec3 { x:  0.0, y:  1.0, z:  0.0 }, uv: Vec2 { x: 0.0, y: 0.0, }, tangent: Vec4 { x:  1.0, y:  0.0, z:  0.0, w:  1.0 } },
+
+];
+
+fn main() {
+    let scene_path = std::env::args().nth(1).unwrap_or_else(|| {
+        concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/01-lighting/03-lighting-maps/exercise-4.ron"
+        )
+        .to_owned()
+    });
+    let scene = Scene::load(&scene_path).unwrap();
+    let light = scene
+        .point_lights
+        .first()
+        .cloned()
+        .expect("The scene has no point light");
+
+    let (event_loop, window, mut size) = {
+        let event_loop = EventLoop::new();
+        let window = WindowBuilder::new()
+            .with_title("LearnOpenGL but it's WGPU-rs")
+            .build(&event_loop)
+            .unwrap();
+        let size = window.inner_size();
+        (event_loop, window, size)
+    };
+
+    let surface = wgpu::Surface::create(&window);
+
+    let (device, mut queue) = wgpu::Adapter::request(&wgpu::RequestAdapterOptions {
+        power_preference: wgpu::PowerPreference::HighPerformance,
+        backends: wgpu::BackendBit::all(),
+    })
+    .unwrap()
+    .request_device(&wgpu::DeviceDescriptor {
+        extensions: wgpu::Extensions {
+            anisotropic_filtering: false,
+        },
+        ..Default::default()
+    });
+
+    let mut shader_compiler = Compiler::new().expect("Could not initialize shader compiler");
+
+    let vs_module = prepare_shader(
+        &mut shader_compiler,
+        include_str!("shader.vert"),
+        ShaderKind::Vertex,
+        "shader.vert",
+        &device,
+    );
+    let fs_module = prepare_shader(
+        &mut shader_compiler,
+        include_str!("exercise-4.frag"),
+        ShaderKind::Fragment,
+        "exercise-4.frag",
+        &device,
+    );
+    let light_source_vs_module = prepare_shader(
+        &mut shader_compiler,
+        include_str!("light_source.vert"),
+        ShaderKind::Vertex,
+        "light_source.vert",
+        &device,
+    );
+    let light_source_fs_module = prepare_shader(
+        &mut shader_compiler,
+        include_str!("light_source.frag"),
+        ShaderKind::Fragment,
+        "light_source.frag",
+        &device,
+    );
+
+    let mut init_encoder =
+        device.create_command_encoder(&wgpu::CommandEncoderDescriptor { todo: 0 });
+
+    let vertex_buffer = device
+        .create_buffer_mapped(VERTICES.len(), wgpu::BufferUsage::VERTEX)
+        .fill_from_slice(VERTICES);
+
+    let cubes = scene.cube_instances();
+    let cube_instance_buffer = device
+        .create_buffer_mapped(cubes.len(), wgpu::BufferUsage::VERTEX)
+        .fill_from_slice(&cubes);
+    let mut light_source_instance_buffer =
+        InstanceBuffer::from_slice(&device, &scene.light_source_instances());
+
+    let vertex_uniform_bind_group_layout =
+        device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            bindings: &[wgpu::BindGroupLayoutBinding {
+                binding: 0,
+                visibility: wgpu::ShaderStage::VERTEX,
+                ty: wgpu::BindingType::UniformBuffer { dynamic: false },
+            }],
+        });
+
+    let fragment_uniform_bind_group_layout =
+        device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            bindings: &[wgpu::BindGroupLayoutBinding {
+                binding: 0,
+                visibility: wgpu::ShaderStage::FRAGMENT,
+                ty: wgpu::BindingType::UniformBuffer { dynamic: false },
+            }],
+        });
+
+    let material_bind_group_layout =
+        device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            bindings: &[
+                wgpu::BindGroupLayoutBinding {
+                    binding: 0,
+                    visibility: wgpu::ShaderStage::FRAGMENT,
+                    ty: wgpu::BindingType::UniformBuffer { dynamic: false },
+                },
+                wgpu::BindGroupLayoutBinding {
+                    binding: 1,
+                    visibility: wgpu::ShaderStage::FRAGMENT,
+                    ty: wgpu::BindingType::SampledTexture {
+                        multisampled: false,
+                        dimension: wgpu::TextureViewDimension::D2,
+                    },
+                },
+                wgpu::BindGroupLayoutBinding {
+                    binding: 2,
+                    visibility: wgpu::ShaderStage::FRAGMENT,
+                    ty: wgpu::BindingType::Sampler,
+                },
+                wgpu::BindGroupLayoutBinding {
+                    binding: 3,
+                    visibility: wgpu::ShaderStage::FRAGMENT,
+                    ty: wgpu::BindingType::SampledTexture {
+                        multisampled: false,
+                        dimension: wgpu::TextureViewDimension::D2,
+                    },
+                },
+                wgpu::BindGroupLayoutBinding {
+                    binding: 4,
+                    visibility: wgpu::ShaderStage::FRAGMENT,
+                    ty: wgpu::BindingType::Sampler,
+                },
+                wgpu::BindGroupLayoutBinding {
+                    binding: 5,
+                    visibility: wgpu::ShaderStage::FRAGMENT,
+                    ty: wgpu::BindingType::SampledTexture {
+                        multisampled: false,
+                        dimension: wgpu::TextureViewDimension::D2,
+                    },
+                },
+                wgpu::BindGroupLayoutBinding {
+                    binding: 6,
+                    visibility: wgpu::ShaderStage::FRAGMENT,
+                    ty: wgpu::BindingType::Sampler,
+                },
+                wgpu::BindGroupLayoutBinding {
+                    binding: 7,
+                    visibility: wgpu::ShaderStage::FRAGMENT,
+                    ty: wgpu::BindingType::SampledTexture {
+                        multisampled: false,
+                        dimension: wgpu::TextureViewDimension::D2,
+                    },
+                },
+                wgpu::BindGroupLayoutBinding {
+                    binding: 8,
+                    visibility: wgpu::ShaderStage::FRAGMENT,
+                    ty: wgpu::BindingType::Sampler,
+                },
+            ],
+        });
+
+    let light_uniform_bind_group_layout =
+        device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            bindings: &[wgpu::BindGroupLayoutBinding {
+                binding: 0,
+                visibility: wgpu::ShaderStage::FRAGMENT,
+                ty: wgpu::BindingType::UniformBuffer { dynamic: false },
+            }],
+        });
+
+    let mut event_channel = shrev::EventChannel::new();
+    let event_reader = event_channel.register_reader();
+    let mut input_handler = InputHandler::<StringBindings>::new();
+
+    let mut camera = scene.create_camera(event_reader);
+
+    let vertex_uniform_buffer = device
+        .create_buffer_mapped(1, wgpu::BufferUsage::UNIFORM | wgpu::BufferUsage::COPY_DST)
+        .fill_from_slice(&[camera.get_view_projection_matrix(
+            size.width as f32,
+            size.height as f32,
+            0.1,
+            100.0,
+        )]);
+
+    let vertex_uniform_bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+        layout: &vertex_uniform_bind_group_layout,
+        bindings: &[wgpu::Binding {
+            binding: 0,
+            resource: wgpu::BindingResource::Buffer {
+                buffer: &vertex_uniform_buffer,
+                range: 0..std::mem::size_of::<Mat4>() as wgpu::BufferAddress,
+            },
+        }],
+    });
+
+    let fragment_uniform_buffer = device
+        .create_buffer_mapped(1, wgpu::BufferUsage::UNIFORM | wgpu::BufferUsage::COPY_DST)
+        .fill_from_slice(&[FragmentUniforms {
+            view_translation: camera.translation.into(),
+        }]);
+
+    let fragment_uniform_bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+        layout: &fragment_uniform_bind_group_layout,
+        bindings: &[wgpu::Binding {
+            binding: 0,
+            resource: wgpu::BindingResource::Buffer {
+                buffer: &fragment_uniform_buffer,
+                range: 0..std::mem::size_of::<FragmentUniforms>() as wgpu::BufferAddress,
+            },
+        }],
+    });
+
+    let mut material = Material {
+        shininess: scene.material.shininess,
+        emissive: scene::vec3(scene.material.emissive).into(),
+        emissive_offset: Vec2::zero(),
+    };
+    let emissive_scroll = Vec2::from(scene.material.emissive_scroll);
+    let material_uniform_buffer = device
+        .create_buffer_mapped(1, wgpu::BufferUsage::UNIFORM | wgpu::BufferUsage::COPY_DST)
+        .fill_from_slice(&[material]);
+
+    let material_diffuse_texture_image = image::open(
+        scene.resolve_path(
+            scene
+                .material
+                .diffuse_texture
+                .as_ref()
+                .expect("The material has no diffuse texture"),
+        ),
+    )
+    .unwrap()
+    .to_rgba();
+    let (width, height) = material_diffuse_texture_image.dimensions();
+    let material_diffuse_texture_raw = material_diffuse_texture_image.to_vec();
+    let (_, material_diffuse_texture_view, material_diffuse_texture_sampler) =
+        create_sampled_texture2d(
+            &device,
+            &mut init_encoder,
+            &material_diffuse_texture_raw,
+            width,
+            height,
+            wgpu::TextureFormat::Rgba8UnormSrgb,
+        );
+
+    let material_specular_texture_image = image::open(
+        scene.resolve_path(
+            scene
+                .material
+                .specular_texture
+                .as_ref()
+                .expect("The material has no specular texture"),
+        ),
+    )
+    .unwrap()
+    .to_rgba();
+    let (width, height) = material_specular_texture_image.dimensions();
+    let material_specular_texture_raw = material_specular_texture_image.to_vec();
+    let (_, material_specular_texture_view, material_specular_texture_sampler) =
+        create_sampled_texture2d(
+            &device,
+            &mut init_encoder,
+            &material_specular_texture_raw,
+            width,
+            height,
+            wgpu::TextureFormat::Rgba8Unorm,
+        );
+
+    let material_normal_texture_image = match &scene.material.normal_texture {
+        Some(path) => image::open(scene.resolve_path(path)).unwrap().to_rgba(),
+        // A flat normal map leaves the vertex normals as they are.
+        None => image::RgbaImage::from_pixel(1, 1, image::Rgba([128, 128, 255, 255])),
+    };
+    let (width, height) = material_normal_texture_image.dimensions();
+    let material_normal_texture_raw = material_normal_texture_image.to_vec();
+    let (_, material_normal_texture_view, material_normal_texture_sampler) =
+        create_sampled_texture2d(
+            &device,
+            &mut init_encoder,
+            &material_normal_texture_raw,
+            width,
+            height,
+            wgpu::TextureFormat::Rgba8Unorm,
+        );
+
+    let (width, height, material_emissive_texture_raw) = match &scene.material.emissive_texture {
+        Some(path) => {
+            let image = image::open(scene.resolve_path(path)).unwrap().to_rgba();
+            let (width, height) = image.dimensions();
+            (width, height, image.to_vec())
+        }
+        None => (256, 256, digital_rain(256, 8, &mut Random(0x2f6b_4e91))),
+    };
+    let (_, material_emissive_texture_view, _) = create_sampled_texture2d(
+        &device,
+        &mut init_encoder,
+        &material_emissive_texture_raw,
+        width,
+        height,
+        wgpu::TextureFormat::Rgba8UnormSrgb,
+    );
+    // The emissive texture repeats, so that it wraps around while it's scrolling.
+    let material_emissive_texture_sampler = device.create_sampler(&wgpu::SamplerDescriptor {
+        address_mode_u: wgpu::AddressMode::Repeat,
+        address_mode_v: wgpu::AddressMode::Repeat,
+        address_mode_w: wgpu::AddressMode::Repeat,
+        mag_filter: wgpu::FilterMode::Nearest,
+        min_filter: wgpu::FilterMode::Linear,
+        mipmap_filter: wgpu::FilterMode::Nearest,
+        lod_min_clamp: 0.0,
+        lod_max_clamp: 0.0,
+        compare_function: wgpu::CompareFunction::Never,
+    });
+
+    let material_bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+        layout: &material_bind_group_layout,
+        bindings: &[
+            wgpu::Binding {
+                binding: 0,
+                resource: wgpu::BindingResource::Buffer {
+                    buffer: &material_uniform_buffer,
+                    range: 0..std::mem::size_of::<Material>() as wgpu::BufferAddress,
+                },
+            },
+            wgpu::Binding {
+                binding: 1,
+                resource: wgpu::BindingResource::TextureView(&material_diffuse_texture_view),
+            },
+            wgpu::Binding {
+                binding: 2,
+                resource: wgpu::BindingResource::Sampler(&material_diffuse_texture_sampler),
+            },
+            wgpu::Binding {
+                binding: 3,
+                resource: wgpu::BindingResource::TextureView(&material_specular_texture_view),
+            },
+            wgpu::Binding {
+                binding: 4,
+                resource: wgpu::BindingResource::Sampler(&material_specular_texture_sampler),
+            },
+            wgpu::Binding {
+                binding: 5,
+                resource: wgpu::BindingResource::TextureView(&material_normal_texture_view),
+            },
+            wgpu::Binding {
+                binding: 6,
+                resource: wgpu::BindingResource::Sampler(&material_normal_texture_sampler),
+            },
+            wgpu::Binding {
+                binding: 7,
+                resource: wgpu::BindingResource::TextureView(&material_emissive_texture_view),
+            },
+            wgpu::Binding {
+                binding: 8,
+                resource: wgpu::BindingResource::Sampler(&material_emissive_texture_sampler),
+            },
+        ],
+    });
+
+    let light_uniform_buffer = device
+        .create_buffer_mapped(1, wgpu::BufferUsage::UNIFORM | wgpu::BufferUsage::COPY_DST)
+        .fill_from_slice(&[Light {
+            translation: scene::vec3(light.translation).into(),
+            ambient: scene::vec3(light.ambient).into(),
+            diffuse: scene::vec3(light.diffuse).into(),
+            specular: scene::vec3(light.specular),
+        }]);
+
+    let light_uniform_bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+        layout: &light_uniform_bind_group_layout,
+        bindings: &[wgpu::Binding {
+            binding: 0,
+            resource: wgpu::BindingResource::Buffer {
+                buffer: &light_uniform_buffer,
+                range: 0..std::mem::size_of::<Light>() as wgpu::BufferAddress,
+            },
+        }],
+    });
+
+    let mut swap_chain_descriptor = wgpu::SwapChainDescriptor {
+        usage: wgpu::TextureUsage::OUTPUT_ATTACHMENT,
+        format: wgpu::TextureFormat::Bgra8UnormSrgb,
+        width: size.width,
+        height: size.height,
+        present_mode: wgpu::PresentMode::Vsync,
+    };
+
+    let (mut depth_texture, mut depth_texture_view) =
+        create_depth_texture(&device, &swap_chain_descriptor);
+
+    let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+        bind_group_layouts: &[
+            &vertex_uniform_bind_group_layout,
+            &fragment_uniform_bind_group_layout,
+            &material_bind_group_layout,
+            &light_uniform_bind_group_layout,
+        ],
+    });
+
+    let render_pipeline_descriptor = wgpu::RenderPipelineDescriptor {
+        layout: &pipeline_layout,
+        vertex_stage: wgpu::ProgrammableStageDescriptor {
+            module: &vs_module,
+            entry_point: "main",
+        },
+        fragment_stage: Some(wgpu::ProgrammableStageDescriptor {
+            module: &fs_module,
+            entry_point: "main",
+        }),
+        rasterization_state: Some(wgpu::RasterizationStateDescriptor {
+            front_face: wgpu::FrontFace::Ccw,
+            cull_mode: wgpu::CullMode::Back,
+            depth_bias: 0,
+            depth_bias_slope_scale: 0.0,
+            depth_bias_clamp: 0.0,
+        }),
+        primitive_topology: wgpu::PrimitiveTopology::TriangleList,
+        color_states: &[wgpu::ColorStateDescriptor {
+            format: swap_chain_descriptor.format,
+            alpha_blend: wgpu::BlendDescriptor::REPLACE,
+            color_blend: wgpu::BlendDescriptor::REPLACE,
+            write_mask: wgpu::ColorWrite::ALL,
+        }],
+        depth_stencil_state: Some(wgpu::DepthStencilStateDescriptor {
+            format: DEPTH_FORMAT,
+            depth_write_enabled: true,
+            depth_compare: wgpu::CompareFunction::Less,
+            stencil_front: wgpu::StencilStateFaceDescriptor::IGNORE,
+            stencil_back: wgpu::StencilStateFaceDescriptor::IGNORE,
+            stencil_read_mask: 0,
+            stencil_write_mask: 0,
+        }),
+        index_format: wgpu::IndexFormat::Uint16,
+        vertex_buffers: &[
+            wgpu::VertexBufferDescriptor {
+                stride: std::mem::size_of::<Vertex>() as wgpu::BufferAddress,
+                step_mode: wgpu::InputStepMode::Vertex,
+                attributes: &[
+                    wgpu::VertexAttributeDescriptor {
+                        offset: 0,
+                        format: wgpu::VertexFormat::Float3,
+                        shader_location: 0,
+                    },
+                    wgpu::VertexAttributeDescriptor {
+                        offset: std::mem::size_of::<Vec3>() as wgpu::BufferAddress,
+                        format: wgpu::VertexFormat::Float3,
+                        shader_location: 1,
+                    },
+                    wgpu::VertexAttributeDescriptor {
+                        offset: (std::mem::size_of::<Vec3>() * 2) as wgpu::BufferAddress,
+                        format: wgpu::VertexFormat::Float2,
+                        shader_location: 2,
+                    },
+                    wgpu::VertexAttributeDescriptor {
+                        offset: (std::mem::size_of::<Vec3>() * 2 + std::mem::size_of::<Vec2>())
+                            as wgpu::BufferAddress,
+                        format: wgpu::VertexFormat::Float4,
+                        shader_location: 10,
+                    },
+                ],
+            },
+            wgpu::VertexBufferDescriptor {
+                stride: std::mem::size_of::<Instance>() as wgpu::BufferAddress,
+                step_mode: wgpu::InputStepMode::Instance,
+                attributes: &[
+                    wgpu::VertexAttributeDescriptor {
+                        offset: 0,
+                        format: wgpu::VertexFormat::Float4,
+                        shader_location: 3,
+                    },
+                    wgpu::VertexAttributeDescriptor {
+                        offset: std::mem::size_of::<Vec4>() as wgpu::BufferAddress,
+                        format: wgpu::VertexFormat::Float4,
+                        shader_location: 4,
+                    },
+                    wgpu::VertexAttributeDescriptor {
+                        offset: std::mem::size_of::<Vec4>() as wgpu::BufferAddress * 2,
+                        format: wgpu::VertexFormat::Float4,
+                        shader_location: 5,
+                    },
+                    wgpu::VertexAttributeDescriptor {
+                        offset: std::mem::size_of::<Vec4>() as wgpu::BufferAddress * 3,
+                        format: wgpu::VertexFormat::Float4,
+                        shader_location: 6,
+                    },
+                    wgpu::VertexAttributeDescriptor {
+                        offset: std::mem::size_of::<Mat4>() as wgpu::BufferAddress,
+                        format: wgpu::VertexFormat::Float3,
+                        shader_location: 7,
+                    },
+                    wgpu::VertexAttributeDescriptor {
+                        offset: (std::mem::size_of::<Mat4>() + std::mem::size_of::<Vec3>())
+                            as wgpu::BufferAddress,
+                        format: wgpu::VertexFormat::Float3,
+                        shader_location: 8,
+                    },
+                    wgpu::VertexAttributeDescriptor {
+                        offset: (std::mem::size_of::<Mat4>() + std::mem::size_of::<Vec3>() * 2)
+                            as wgpu::BufferAddress,
+                        format: wgpu::VertexFormat::Float3,
+                        shader_location: 9,
+                    },
+                ],
+            },
+        ],
+        sample_count: 1,
+        sample_mask: !0,
+        alpha_to_coverage_enabled: false,
+    };
+    let pipeline = device.create_render_pipeline(&render_pipeline_descriptor);
+
+    let light_source_pipeline_layout =
+        device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            bind_group_layouts: &[&vertex_uniform_bind_group_layout],
+        });
+
+    let mut light_source_pipeline_descriptor = render_pipeline_descriptor.clone();
+    light_source_pipeline_descriptor.layout = &light_source_pipeline_layout;
+    light_source_pipeline_descriptor.vertex_stage = wgpu::ProgrammableStageDescriptor {
+        module: &light_source_vs_module,
+        entry_point: "main",
+    };
+    light_source_pipeline_descriptor.fragment_stage = Some(wgpu::ProgrammableStageDescriptor {
+        module: &light_source_fs_module,
+        entry_point: "main",
+    });
+    let light_source_pipeline = device.create_render_pipeline(&light_source_pipeline_descriptor);
+
+    let mut swap_chain = device.create_swap_chain(&surface, &swap_chain_descriptor);
+
+    queue.submit(&[init_encoder.finish()]);
+
+    let mut time = Time::new();
+
+    event_loop.run(move |event, _, control_flow| {
+        input_handler.send_event(&event, &mut event_channel);
+
+        camera.on_event(&event_channel);
+
+        match event {
+            Event::WindowEvent {
+                event: WindowEvent::CloseRequested,
+                ..
+            } => *control_flow = ControlFlow::Exit,
+            Event::WindowEvent {
+                event: WindowEvent::Resized(new_size),
+                ..
+            } => {
+                size = new_size;
+                swap_chain_descriptor.width = size.width;
+                swap_chain_descriptor.height = size.height;
+                swap_chain = device.create_swap_chain(&surface, &swap_chain_descriptor);
+                let dt = create_depth_texture(&device, &swap_chain_descriptor);
+                depth_texture = dt.0;
+                depth_texture_view = dt.1;
+                *control_flow = ControlFlow::Poll;
+            }
+            Event::WindowEvent {
+                event:
+                    WindowEvent::MouseInput {
+                        button: winit::event::MouseButton::Left,
+                        state: winit::event::ElementState::Pressed,
+                        ..
+                    },
+                ..
+            } => {
+                camera.is_controlled = true;
+                window.set_cursor_grab(true).ok();
+                window.set_cursor_visible(false);
+                *control_flow = ControlFlow::Poll;
+            }
+            Event::WindowEvent {
+                event:
+                    WindowEvent::KeyboardInput {
+                        input:
+                            KeyboardInput {
+                                virtual_keycode: Some(VirtualKeyCode::Escape),
+                                state: winit::event::ElementState::Pressed,
+                                ..
+                            },
+                        ..
+                    },
+                ..
+            } => {
+                camera.is_controlled = false;
+                window.set_cursor_grab(false).ok();
+                window.set_cursor_visible(true);
+                *control_flow = ControlFlow::Poll;
+            }
+            Event::MainEventsCleared => {
+                window.request_redraw();
+                *control_flow = ControlFlow::Poll;
+            }
+            Event::RedrawRequested(_) => {
+                input_handler.send_frame_begin();
+                camera.update(&input_handler);
+
+                time.update();
+                let animated_light = light.sample(time.elapsed());
+
+                let frame = swap_chain.get_next_texture();
+                let mut encoder =
+                    device.create_command_encoder(&wgpu::CommandEncoderDescriptor { todo: 0 });
+                {
+                    let view_projection = camera.get_view_projection_matrix(
+                        size.width as f32,
+                        size.height as f32,
+                        0.1,
+                        100.0,
+                    );
+                    let staging_buffer = device
+                        .create_buffer_mapped(5, wgpu::BufferUsage::COPY_SRC)
+                        .fill_from_slice(&[
+                            view_projection[0],
+                            view_projection[1],
+                            view_projection[2],
+                            view_projection[3],
+                            camera.world_translation().into_homogeneous_vector(),
+                        ]);
+                    encoder.copy_buffer_to_buffer(
+                        &staging_buffer,
+                        0,
+                        &vertex_uniform_buffer,
+                        0,
+                        std::mem::size_of::<Mat4>() as wgpu::BufferAddress,
+                    );
+                    encoder.copy_buffer_to_buffer(
+                        &staging_buffer,
+                        std::mem::size_of::<Mat4>() as wgpu::BufferAddress,
+                        &fragment_uniform_buffer,
+                        0,
+                        std::mem::size_of::<Vec4>() as wgpu::BufferAddress,
+                    );
+
+                    // Only the fractional part is kept, since the texture repeats anyway and large texture
+                    // coordinates lose precision.
+                    let emissive_offset = emissive_scroll * time.elapsed();
+                    material.emissive_offset = emissive_offset
+                        - Vec2::new(emissive_offset.x.floor(), emissive_offset.y.floor());
+                    let staging_buffer = device
+                        .create_buffer_mapped(1, wgpu::BufferUsage::COPY_SRC)
+                        .fill_from_slice(&[material]);
+                    encoder.copy_buffer_to_buffer(
+                        &staging_buffer,
+                        0,
+                        &material_uniform_buffer,
+                        0,
+                        std::mem::size_of::<Material>() as wgpu::BufferAddress,
+                    );
+
+                    let staging_buffer = device
+                        .create_buffer_mapped(1, wgpu::BufferUsage::COPY_SRC)
+                        .fill_from_slice(&[Light {
+                            translation: animated_light.translation,
+                            ambient: animated_light.ambient,
+                            diffuse: animated_light.diffuse,
+                            specular: animated_light.specular,
+                        }]);
+                    encoder.copy_buffer_to_buffer(
+                        &staging_buffer,
+                        0,
+                        &light_uniform_buffer,
+                        0,
+                        std::mem::size_of::<Light>() as wgpu::BufferAddress,
+                    );
+
+                    light_source_instance_buffer.update(
+                        &device,
+                        &mut encoder,
+                        &scene.light_source_instances_at(time.elapsed()),
+                    );
+
+                    let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                        color_attachments: &[wgpu::RenderPassColorAttachmentDescriptor {
+                            attachment: &frame.view,
+                            resolve_target: None,
+                            load_op: wgpu::LoadOp::Clear,
+                            store_op: wgpu::StoreOp::Store,
+                            clear_color: wgpu::Color {
+                                r: 0.1,
+                                g: 0.1,
+                                b: 0.1,
+                                a: 1.0,
+                            },
+                        }],
+                        depth_stencil_attachment: Some(
+                            wgpu::RenderPassDepthStencilAttachmentDescriptor {
+                                attachment: &depth_texture_view,
+                                depth_load_op: wgpu::LoadOp::Clear,
+                                depth_store_op: wgpu::StoreOp::Store,
+                                clear_depth: 1.0,
+                                stencil_load_op: wgpu::LoadOp::Clear,
+                                stencil_store_op: wgpu::StoreOp::Store,
+                                clear_stencil: 0,
+                            },
+                        ),
+                    });
+                    render_pass.set_pipeline(&pipeline);
+                    render_pass.set_bind_group(0, &vertex_uniform_bind_group, &[]);
+                    render_pass.set_bind_group(1, &fragment_uniform_bind_group, &[]);
+                    render_pass.set_bind_group(2, &material_bind_group, &[]);
+                    render_pass.set_bind_group(3, &light_uniform_bind_group, &[]);
+                    render_pass
+                        .set_vertex_buffers(0, &[(&vertex_buffer, 0), (&cube_instance_buffer, 0)]);
+                    render_pass.draw(0..VERTICES.len() as u32, 0..cubes.len() as u32);
+
+                    render_pass.set_pipeline(&light_source_pipeline);
+                    render_pass.set_bind_group(0, &vertex_uniform_bind_group, &[]);
+                    render_pass.set_vertex_buffers(
+                        0,
+                        &[
+                            (&vertex_buffer, 0),
+                            (light_source_instance_buffer.buffer(), 0),
+                        ],
+                    );
+                    render_pass.draw(
+                        0..VERTICES.len() as u32,
+                        0..light_source_instance_buffer.len() as u32,
+                    );
+                }
+
+                queue.submit(&[encoder.finish()]);
+
+                *control_flow = ControlFlow::Poll;
+            }
+            _ => {}
+        }
+    })
+}
+
+/// Generates the pixels of a square grayscale RGBA texture with streaks of glyph-like specks in columns of `cell_size`
+/// wide cells, standing in for the "matrix" texture of the LearnOpenGL exercise. The streaks wrap around the edges, so
+/// the texture tiles seamlessly while it's scrolling.
+fn digital_rain(size: u32, cell_size: u32, random: &mut Random) -> Vec<u8> {
+    let cells = size / cell_size;
+    let mut pixels = [0, 0, 0, 255].repeat((size * size) as usize);
+    for column in 0..cells {
+        let head = (random.next() * cells as f32) as u32;
+        let length = 2 + (random.next() * (cells / 2) as f32) as u32;
+        for i in 0..length {
+            // The streak fades out behind its brightest cell.
+            let row = (head + i) % cells;
+            let brightness = ((1.0 - i as f32 / length as f32) * 255.0) as u8;
+            // The border of each cell stays dark, which keeps the glyphs apart.
+            for y in 1..cell_size - 1 {
+                for x in 1..cell_size - 1 {
+                    if random.next() < 0.5 {
+                        continue;
+                    }
+                    let index =
+                        (((row * cell_size + y) * size + column * cell_size + x) * 4) as usize;
+                    pixels[index..index + 3].copy_from_slice(&[brightness; 3]);
+                }
+            }
+        }
+    }
+    pixels
+}
+
+pub fn prepare_shader(
+    compiler: &mut Compiler,
+    source_text: &str,
+    shader_kind: ShaderKind,
+    input_file_name: &str,
+    device: &wgpu::Device,
+) -> wgpu::ShaderModule {
+    let artifact = compiler
+        .compile_into_spirv(source_text, shader_kind, input_file_name, "main", None)
+        .unwrap();
+    device.create_shader_module(artifact.as_binary())
+}
+
+pub fn create_depth_texture(
+    device: &wgpu::Device,
+    swap_chain_desc: &wgpu::SwapChainDescriptor,
+) -> (wgpu::Texture, wgpu::TextureView) {
+    let desc = wgpu::TextureDescriptor {
+        format: DEPTH_FORMAT,
+        usage: wgpu::TextureUsage::OUTPUT_ATTACHMENT,
+        ..swap_chain_desc.to_texture_desc()
+    };
+    let texture = device.create_texture(&desc);
+    let view = texture.create_default_view();
+    (texture, view)
+}
+
+/// Uploads an RGBA texture. Color textures should use an sRGB `format`, so that they're decoded into linear space
+/// when sampled, while textures holding other data like specular intensities or normals have to use a linear one.
+pub fn create_sampled_texture2d(
+    device: &wgpu::Device,
+    encoder: &mut wgpu::CommandEncoder,
+    raw_texture: &[u8],
+    width: u32,
+    height: u32,
+    format: wgpu::TextureFormat,
+) -> (wgpu::Texture, wgpu::TextureView, wgpu::Sampler) {
+    let texture_extent = wgpu::Extent3d {
+        width,
+        height,
+        depth: 1,
+    };
+
+    let texture_buffer = device
+        .create_buffer_mapped(raw_texture.len(), wgpu::BufferUsage::COPY_SRC)
+        .fill_from_slice(&raw_texture);
+    let texture = device.create_texture(&wgpu::TextureDescriptor {
+        size: texture_extent,
+        array_layer_count: 1,
+        mip_level_count: 0,
+        sample_count: 1,
+        dimension: wgpu::TextureDimension::D2,
+        format,
+        usage: wgpu::TextureUsage::SAMPLED | wgpu::TextureUsage::COPY_DST,
+    });
+
+    encoder.copy_buffer_to_texture(
+        wgpu::BufferCopyView {
+            buffer: &texture_buffer,
+            offset: 0,
+            row_pitch: 4 * width,
+            image_height: height,
+        },
+        wgpu::TextureCopyView {
+            texture: &texture,
+            mip_level: 0,
+            array_layer: 0,
+            origin: wgpu::Origin3d {
+                x: 0.0,
+                y: 0.0,
+                z: 0.0,
+            },
+        },
+        texture_extent,
+    );
+
+    let texture_view = texture.create_default_view();
+    let texture_sampler = device.create_sampler(&wgpu::SamplerDescriptor {
+        address_mode_u: wgpu::AddressMode::ClampToEdge,
+        address_mode_v: wgpu::AddressMode::ClampToEdge,
+        address_mode_w: wgpu::AddressMode::ClampToEdge,
+        mag_filter: wgpu::FilterMode::Nearest,
+        min_filter: wgpu::FilterMode::Linear,
+        mipmap_filter: wgpu::FilterMode::Nearest,
+        lod_min_clamp: 0.0,
+        lod_max_clamp: 0.0,
+        compare_function: wgpu::CompareFunction::Never,
+    });
+
+    (texture, texture_view, texture_sampler)
+}
+
+#[repr(C)]
+#[derive(Clone, Copy, Debug)]
+pub struct Vertex {
+    pub translation: Vec3,
+    pub normal: Vec3,
+    pub uv: Vec2,
+    /// The direction of increasing `u` texture coordinates, with the handedness of the bitangent in W.
+    pub tangent: Vec4,
+}
+
+#[repr(C)]
+#[derive(Clone, Copy, Debug)]
+pub struct FragmentUniforms {
+    pub view_translation: Vec3,
+}
+
+#[repr(C)]
+#[derive(Clone, Copy, Debug)]
+pub struct Material {
+    pub shininess: f32,
+    pub emissive: Align16<Vec3>,
+    /// How far the emissive texture has scrolled.
+    pub emissive_offset: Vec2,
+}
+
+#[repr(C)]
+#[derive(Clone, Copy, Debug)]
+pub struct Light {
+    pub translation: Align16<Vec3>,
+    pub ambient: Align16<Vec3>,
+    pub diffuse: Align16<Vec3>,
+    pub specular: Vec3,
+}
+
+/// A xorshift random number generator, which is plenty for the digital rain.
+struct Random(u32);
+
+impl Random {
+    /// A random number in `0.0..1.0`.
+    fn next(&mut self) -> f32 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 17;
+        self.0 ^= self.0 << 5;
+        (self.0 >> 8) as f32 / (1 << 24) as f32
+    }
+}
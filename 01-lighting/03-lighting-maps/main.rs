@@ -7,84 +7,76 @@ use winit::{
     window::WindowBuilder,
 };
 
-use learnopengl_but_its_wgpu::{Align16, Camera};
+use learnopengl_but_its_wgpu::{
+    instance_buffer::InstanceBuffer,
+    scene::{self, Scene},
+    time::Time,
+    Align16, Instance,
+};
 
 const DEPTH_FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Depth32Float;
 
 #[rustfmt::skip]
 const VERTICES: &[Vertex] = &[
-    Vertex { translation: Vec3 { x:  0.5, y: -0.5, z: -0.5, }, normal: Vec3 { x:  0.0, y:  0.0, z: -1.0 }, uv: Vec2 { x: 1.0, y: 0.0, } },
-    Vertex { translation: Vec3 { x: -0.5, y: -0.5, z: -0.5, }, normal: Vec3 { x:  0.0, y:  0.0, z: -1.0 }, uv: Vec2 { x: 0.0, y: 0.0, } },
-    Vertex { translation: Vec3 { x:  0.5, y:  0.5, z: -0.5, }, normal: Vec3 { x:  0.0, y:  0.0, z: -1.0 }, uv: Vec2 { x: 1.0, y: 1.0, } },
-    Vertex { translation: Vec3 { x:  0.5, y:  0.5, z: -0.5, }, normal: Vec3 { x:  0.0, y:  0.0, z: -1.0 }, uv: Vec2 { x: 1.0, y: 1.0, } },
-    Vertex { translation: Vec3 { x: -0.5, y: -0.5, z: -0.5, }, normal: Vec3 { x:  0.0, y:  0.0, z: -1.0 }, uv: Vec2 { x: 0.0, y: 0.0, } },
-    Vertex { translation: Vec3 { x: -0.5, y:  0.5, z: -0.5, }, normal: Vec3 { x:  0.0, y:  0.0, z: -1.0 }, uv: Vec2 { x: 0.0, y: 1.0, } },
-
-    Vertex { translation: Vec3 { x: -0.5, y: -0.5, z:  0.5, }, normal: Vec3 { x:  0.0, y:  0.0, z:  1.0 }, uv: Vec2 { x: 0.0, y: 0.0, } },
-    Vertex { translation: Vec3 { x:  0.5, y: -0.5, z:  0.5, }, normal: Vec3 { x:  0.0, y:  0.0, z:  1.0 }, uv: Vec2 { x: 1.0, y: 0.0, } },
-    Vertex { translation: Vec3 { x:  0.5, y:  0.5, z:  0.5, }, normal: Vec3 { x:  0.0, y:  0.0, z:  1.0 }, uv: Vec2 { x: 1.0, y: 1.0, } },
-    Vertex { translation: Vec3 { x:  0.5, y:  0.5, z:  0.5, }, normal: Vec3 { x:  0.0, y:  0.0, z:  1.0 }, uv: Vec2 { x: 1.0, y: 1.0, } },
-    Vertex { translation: Vec3 { x: -0.5, y:  0.5, z:  0.5, }, normal: Vec3 { x:  0.0, y:  0.0, z:  1.0 }, uv: Vec2 { x: 0.0, y: 1.0, } },
-    Vertex { translation: Vec3 { x: -0.5, y: -0.5, z:  0.5, }, normal: Vec3 { x:  0.0, y:  0.0, z:  1.0 }, uv: Vec2 { x: 0.0, y: 0.0, } },
-
-    Vertex { translation: Vec3 { x: -0.5, y:  0.5, z:  0.5, }, normal: Vec3 { x: -1.0, y:  0.0, z:  0.0 }, uv: Vec2 { x: 1.0, y: 0.0, } },
-    Vertex { translation: Vec3 { x: -0.5, y:  0.5, z: -0.5, }, normal: Vec3 { x: -1.0, y:  0.0, z:  0.0 }, uv: Vec2 { x: 1.0, y: 1.0, } },
-    Vertex { translation: Vec3 { x: -0.5, y: -0.5, z: -0.5, }, normal: Vec3 { x: -1.0, y:  0.0, z:  0.0 }, uv: Vec2 { x: 0.0, y: 1.0, } },
-    Vertex { translation: Vec3 { x: -0.5, y: -0.5, z: -0.5, }, normal: Vec3 { x: -1.0, y:  0.0, z:  0.0 }, uv: Vec2 { x: 0.0, y: 1.0, } },
-    Vertex { translation: Vec3 { x: -0.5, y: -0.5, z:  0.5, }, normal: Vec3 { x: -1.0, y:  0.0, z:  0.0 }, uv: Vec2 { x: 0.0, y: 0.0, } },
-    Vertex { translation: Vec3 { x: -0.5, y:  0.5, z:  0.5, }, normal: Vec3 { x: -1.0, y:  0.0, z:  0.0 }, uv: Vec2 { x: 1.0, y: 0.0, } },
-
-    Vertex { translation: Vec3 { x:  0.5, y:  0.5, z:  0.5, }, normal: Vec3 { x:  1.0, y:  0.0, z:  0.0 }, uv: Vec2 { x: 1.0, y: 0.0, } },
-    Vertex { translation: Vec3 { x:  0.5, y: -0.5, z: -0.5, }, normal: Vec3 { x:  1.0, y:  0.0, z:  0.0 }, uv: Vec2 { x: 0.0, y: 1.0, } },
-    Vertex { translation: Vec3 { x:  0.5, y:  0.5, z: -0.5, }, normal: Vec3 { x:  1.0, y:  0.0, z:  0.0 }, uv: Vec2 { x: 1.0, y: 1.0, } },
-    Vertex { translation: Vec3 { x:  0.5, y: -0.5, z: -0.5, }, normal: Vec3 { x:  1.0, y:  0.0, z:  0.0 }, uv: Vec2 { x: 0.0, y: 1.0, } },
-    Vertex { translation: Vec3 { x:  0.5, y:  0.5, z:  0.5, }, normal: Vec3 { x:  1.0, y:  0.0, z:  0.0 }, uv: Vec2 { x: 1.0, y: 0.0, } },
-    Vertex { translation: Vec3 { x:  0.5, y: -0.5, z:  0.5, }, normal: Vec3 { x:  1.0, y:  0.0, z:  0.0 }, uv: Vec2 { x: 0.0, y: 0.0, } },
-
-    Vertex { translation: Vec3 { x: -0.5, y: -0.5, z: -0.5, }, normal: Vec3 { x:  0.0, y: -1.0, z:  0.0 }, uv: Vec2 { x: 0.0, y: 1.0, } },
-    Vertex { translation: Vec3 { x:  0.5, y: -0.5, z: -0.5, }, normal: Vec3 { x:  0.0, y: -1.0, z:  0.0 }, uv: Vec2 { x: 1.0, y: 1.0, } },
-    Vertex { translation: Vec3 { x:  0.5, y: -0.5, z:  0.5, }, normal: Vec3 { x:  0.0, y: -1.0, z:  0.0 }, uv: Vec2 { x: 1.0, y: 0.0, } },
-    Vertex { translation: Vec3 { x:  0.5, y: -0.5, z:  0.5, }, normal: Vec3 { x:  0.0, y: -1.0, z:  0.0 }, uv: Vec2 { x: 1.0, y: 0.0, } },
-    Vertex { translation: Vec3 { x: -0.5, y: -0.5, z:  0.5, }, normal: Vec3 { x:  0.0, y: -1.0, z:  0.0 }, uv: Vec2 { x: 0.0, y: 0.0, } },
-    Vertex { translation: Vec3 { x: -0.5, y: -0.5, z: -0.5, }, normal: Vec3 { x:  0.0, y: -1.0, z:  0.0 }, uv: Vec2 { x: 0.0, y: 1.0, } },
-
-    Vertex { translation: Vec3 { x:  0.5, y:  0.5, z: -0.5, }, normal: Vec3 { x:  0.0, y:  1.0, z:  0.0 }, uv: Vec2 { x: 1.0, y: 1.0, } },
-    Vertex { translation: Vec3 { x: -0.5, y:  0.5, z: -0.5, }, normal: Vec3 { x:  0.0, y:  1.0, z:  0.0 }, uv: Vec2 { x: 0.0, y: 1.0, } },
-    Vertex { translation: Vec3 { x:  0.5, y:  0.5, z:  0.5, }, normal: Vec3 { x:  0.0, y:  1.0, z:  0.0 }, uv: Vec2 { x: 1.0, y: 0.0, } },
-    Vertex { translation: Vec3 { x:  0.5, y:  0.5, z:  0.5, }, normal: Vec3 { x:  0.0, y:  1.0, z:  0.0 }, uv: Vec2 { x: 1.0, y: 0.0, } },
-    Vertex { translation: Vec3 { x: -0.5, y:  0.5, z: -0.5, }, normal: Vec3 { x:  0.0, y:  1.0, z:  0.0 }, uv: Vec2 { x: 0.0, y: 1.0, } },
-    Vertex { translation: Vec3 { x: -0.5, y:  0.5, z:  0.5, }, normal: Vec3 { x:  0.0, y:  1.0, z:  0.0 }, uv: Vec2 { x: 0.0, y: 0.0, } },
-
-];
+    Vertex { translation: Vec3 { x:  0.5, y: -0.5, z: -0.5, }, normal: Vec3 { x:  0.0, y:  0.0, z: -1.0 }, uv: Vec2 { x: 1.0, y: 0.0, }, tangent: Vec4 { x:  1.0, y:  0.0, z:  0.0, w: -1.0 } },
+    Vertex { translation: Vec3 { x: -0.5, y: -0.5, z: -0.5, }, normal: Vec3 { x:  0.0, y:  0.0, z: -1.0 }, uv: Vec2 { x: 0.0, y: 0.0, }, tangent: Vec4 { x:  1.0, y:  0.0, z:  0.0, w: -1.0 } },
+    Vertex { translation: Vec3 { x:  0.5, y:  0.5, z: -0.5, }, normal: Vec3 { x:  0.0, y:  0.0, z: -1.0 }, uv: Vec2 { x: 1.0, y: 1.0, }, tangent: Vec4 { x:  1.0, y:  0.0, z:  0.0, w: -1.0 } },
+    Vertex { translation: Vec3 { x:  0.5, y:  0.5, z: -0.5, }, normal: Vec3 { x:  0.0, y:  0.0, z: -1.0 }, uv: Vec2 { x: 1.0, y: 1.0, }, tangent: Vec4 { x:  1.0, y:  0.0, z:  0.0, w: -1.0 } },
+    Vertex { translation: Vec3 { x: -0.5, y: -0.5, z: -0.5, }, normal: Vec3 { x:  0.0, y:  0.0, z: -1.0 }, uv: Vec2 { x: 0.0, y: 0.0, }, tangent: Vec4 { x:  1.0, y:  0.0, z:  0.0, w: -1.0 } },
+    Vertex { translation: Vec3 { x: -0.5, y:  0.5, z: -0.5, }, normal: Vec3 { x:  0.0, y:  0.0, z: -1.0 }, uv: Vec2 { x: 0.0, y: 1.0, }, tangent: Vec4 { x:  1.0, y:  0.0, z:  0.0, w: -1.0 } },
+
+    Vertex { translation: Vec3 { x: -0.5, y: -0.5, z:  0.5, }, normal: Vec3 { x:  0.0, y:  0.0, z:  1.0 }, uv: Vec2 { x: 0.0, y: 0.0, }, tangent: Vec4 { x:  1.0, y:  0.0, z:  0.0, w:  1.0 } },
+    Vertex { translation: Vec3 { x:  0.5, y: -0.5, z:  0.5, }, normal: Vec3 { x:  0.0, y:  0.0, z:  1.0 }, uv: Vec2 { x: 1.0, y: 0.0, }, tangent: Vec4 { x:  1.0, y:  0.0, z:  0.0, w:  1.0 } },
+    Vertex { translation: Vec3 { x:  0.5, y:  0.5, z:  0.5, }, normal: Vec3 { x:  0.0, y:  0.0, z:  1.0 }, uv: Vec2 { x: 1.0, y: 1.0, }, tangent: Vec4 { x:  1.0, y:  0.0, z:  0.0, w:  1.0 } },
+    Vertex { translation: Vec3 { x:  0.5, y:  0.5, z:  0.5, }, normal: Vec3 { x:  0.0, y:  0.0, z:  1.0 }, uv: Vec2 { x: 1.0, y: 1.0, }, tangent: Vec4 { x:  1.0, y:  0.0, z:  0.0, w:  1.0 } },
+    Vertex { translation: Vec3 { x: -0.5, y:  0.5, z:  0.5, }, normal: Vec3 { x:  0.0, y:  0.0, z:  1.0 }, uv: Vec2 { x: 0.0, y: 1.0, }, tangent: Vec4 { x:  1.0, y:  0.0, z:  0.0, w:  1.0 } },
+    Vertex { translation: Vec3 { x: -0.5, y: -0.5, z:  0.5, }, normal: Vec3 { x:  0.0, y:  0.0, z:  1.0 }, uv: Vec2 { x: 0.0, y: 0.0, }, tangent: Vec4 { x:  1.0, y:  0.0, z:  0.0, w:  1.0 } },
+
+    Vertex { translation: Vec3 { x: -0.5, y:  0.5, z:  0.5, }, normal: Vec3 { x: -1.0, y:  0.0, z:  0.0 }, uv: Vec2 { x: 1.0, y: 0.0, }, tangent: Vec4 { x:  0.0, y:  1.0, z:  0.0, w:  1.0 } },
+    Vertex { translation: Vec3 { x: -0.5, y:  0.5, z: -0.5, }, normal: Vec3 { x: -1.0, y:  0.0, z:  0.0 }, uv: Vec2 { x: 1.0, y: 1.0, }, tangent: Vec4 { x:  0.0, y:  1.0, z:  0.0, w:  1.0 } },
+    Vertex { translation: Vec3 { x: -0.5, y: -0.5, z: -0.5, }, normal: Vec3 { x: -1.0, y:  0.0, z:  0.0 }, uv: Vec2 { x: 0.0, y: 1.0, }, tangent: Vec4 { x:  0.0, y:  1.0, z:  0.0, w:  1.0 } },
+    Vertex { translation: Vec3 { x: -0.5, y: -0.5, z: -0.5, }, normal: Vec3 { x: -1.0, y:  0.0, z:  0.0 }, uv: Vec2 { x: 0.0, y: 1.0, }, tangent: Vec4 { x:  0.0, y:  1.0, z:  0.0, w:  1.0 } },
+    Vertex { translation: Vec3 { x: -0.5, y: -0.5, z:  0.5, }, normal: Vec3 { x: -1.0, y:  0.0, z:  0.0 }, uv: Vec2 { x: 0.0, y: 0.0, }, tangent: Vec4 { x:  0.0, y:  1.0, z:  0.0, w:  1.0 } },
+    Vertex { translation: Vec3 { x: -0.5, y:  0.5, z:  0.5, }, normal: Vec3 { x: -1.0, y:  0.0, z:  0.0 }, uv: Vec2 { x: 1.0, y: 0.0, }, tangent: Vec4 { x:  0.0, y:  1.0, z:  0.0, w:  1.0 } },
+
+    Vertex { translation: Vec3 { x:  0.5, y:  0.5, z:  0.5, }, normal: Vec3 { x:  1.0, y:  0.0, z:  0.0 }, uv: Vec2 { x: 1.0, y: 0.0, }, tangent: Vec4 { x:  0.0, y:  1.0, z:  0.0, w: -1.0 } },
+    Vertex { translation: Vec3 { x:  0.5, y: -0.5, z: -0.5, }, normal: Vec3 { x:  1.0, y:  0.0, z:  0.0 }, uv: Vec2 { x: 0.0, y: 1.0, }, tangent: Vec4 { x:  0.0, y:  1.0, z:  0.0, w: -1.0 } },
+    Vertex { translation: Vec3 { x:  0.5, y:  0.5, z: -0.5, }, normal: Vec3 { x:  1.0, y:  0.0, z:  0.0 }, uv: Vec2 { x: 1.0, y: 1.0, }, tangent: Vec4 { x:  0.0, y:  1.0, z:  0.0, w: -1.0 } },
+    Vertex { translation: Vec3 { x:  0.5, y: -0.5, z: -0.5, }, normal: Vec3 { x:  1.0, y:  0.0, z:  0.0 }, uv: Vec2 { x: 0.0, y: 1.0, }, tangent: Vec4 { x:  0.0, y:  1.0, z:  0.0, w: -1.0 } },
+    Vertex { translation: Vec3 { x:  0.5, y:  0.5, z:  0.5, }, normal: Vec3 { x:  1.0, y:  0.0, z:  0.0 }, uv: Vec2 { x: 1.0, y: 0.0, }, tangent: Vec4 { x:  0.0, y:  1.0, z:  0.0, w: -1.0 } },
+    Vertex { translation: Vec3 { x:  0.5, y: -0.5, z:  0.5, }, normal: Vec3 { x:  1.0, y:  0.0, z:  0.0 }, uv: Vec2 { x: 0.0, y: 0.0, }, tangent: Vec4 { x:  0.0, y:  1.0, z:  0.0, w: -1.0 } },
+
+    Vertex { translation: Vec3 { x: -0.5, y: -0.5, z: -0.5, }, normal: Vec3 { x:  0.0, y: -1.0, z:  0.0 }, uv: Vec2 { x: 0.0, y: 1.0, }, tangent: Vec4 { x:  1.0, y:  0.0, z:  0.0, w: -1.0 } },
+    Vertex { translation: Vec3 { x:  0.5, y: -0.5, z: -0.5, }, normal: Vec3 { x:  0.0, y: -1.0, z:  0.0 }, uv: Vec2 { x: 1.0, y: 1.0, }, tangent: Vec4 { x:  1.0, y:  0.0, z:  0.0, w: -1.0 } },
+    Vertex { translation: Vec3 { x:  0.5, y: -0.5, z:  0.5, }, normal: Vec3 { x:  0.0, y: -1.0, z:  0.0 }, uv: Vec2 { x: 1.0, y: 0.0, }, tangent: Vec4 { x:  1.0, y:  0.0, z:  0.0, w: -1.0 } },
+    Vertex { translation: Vec3 { x:  0.5, y: -0.5, z:  0.5, }, normal: Vec3 { x:  0.0, y: -1.0, z:  0.0 }, uv: Vec2 { x: 1.0, y: 0.0, }, tangent: Vec4 { x:  1.0, y:  0.0, z:  0.0, w: -1.0 } },
+    Vertex { translation: Vec3 { x: -0.5, y: -0.5, z:  0.5, }, normal: Vec3 { x:  0.0, y: -1.0, z:  0.0 }, uv: Vec2 { x: 0.0, y: 0.0, }, tangent: Vec4 { x:  1.0, y:  0.0, z:  0.0, w: -1.0 } },
+    Vertex { translation: Vec3 { x: -0.5, y: -0.5, z: -0.5, }, normal: Vec3 { x:  0.0, y: -1.0, z:  0.0 }, uv: Vec2 { x: 0.0, y: 1.0, }, tangent: Vec4 { x:  1.0, y:  0.0, z:  0.0, w: -1.0 } },
+
+    Vertex { translation: Vec3 { x:  0.5, y:  0.5, z: -0.5, }, normal: Vec3 { x:  0.0, y:  1.0, z:  0.0 }, uv: Vec2 { x: 1.0, y: 1.0, }, tangent: Vec4 { x:  1.0, y:  0.0, z:  0.0, w:  1.0 } },
+    Vertex { translation: Vec3 { x: -0.5, y:  0.5, z: -0.5, }, normal: Vec3 { x:  0.0, y:  1.0, z:  0.0 }, uv: Vec2 { x: 0.0, y: 1.0, }, tangent: Vec4 { x:  1.0, y:  0.0, z:  0.0, w:  1.0 } },
+    Vertex { translation: Vec3 { x:  0.5, y:  0.5, z:  0.5, }, normal: Vec3 { x:  0.0, y:  1.0, z:  0.0 }, uv: Vec2 { x: 1.0, y: 0.0, }, tangent: Vec4 { x:  1.0, y:  0.0, z:  0.0, w:  1.0 } },
+    Vertex { translation: Vec3 { x:  0.5, y:  0.5, z:  0.5, }, normal: Vec3 { x:  0.0, y:  1.0, z:  0.0 }, uv: Vec2 { x: 1.0, y: 0.0, }, tangent: Vec4 { x:  1.0, y:  0.0, z:  0.0, w:  1.0 } },
+    Vertex { translation: Vec3 { x: -0.5, y:  0.5, z: -0.5, }, normal: Vec3 { x:  0.0, y:  1.0, z:  0.0 }, uv: Vec2 { x: 0.0, y: 1.0, }, tangent: Vec4 { x:  1.0, y:  0.0, z:  0.0, w:  1.0 } },
+    Vertex { translation: Vec3 { x: -0.5, y:  0.5, z:  0.5, }, normal: Vec3 { x:  0.0, y:  1.0, z:  0.0 }, uv: Vec2 { x: 0.0, y: 0.0, }, tangent: Vec4 { x:  1.0, y:  0.0, z:  0.0, w:  1.0 } },
 
-#[rustfmt::skip]
-const CUBES: &[Instance] = &[
-    Instance { model: Mat4 {
-        cols: [
-            Vec4 { x:  1.0, y:  0.0, z:  0.0, w:  0.0 },
-            Vec4 { x:  0.0, y:  1.0, z:  0.0, w:  0.0 },
-            Vec4 { x:  0.0, y:  0.0, z:  1.0, w:  0.0 },
-            Vec4 { x:  0.0, y:  0.0, z:  0.0, w:  1.0 },
-        ]
-    }},
-];
-
-#[rustfmt::skip]
-const LIGHT_TRANLSATION: Vec3 = Vec3 { x:  1.2, y:  1.0, z:  2.0 };
-
-#[rustfmt::skip]
-const LIGHT_SOURCES: &[Instance] = &[
-    Instance { model: Mat4 {
-        cols: [
-            Vec4 { x:  0.2,                 y: 0.0,                   z:  0.0,                w:  0.0 },
-            Vec4 { x:  0.0,                 y: 0.2,                   z:  0.0,                w:  0.0 },
-            Vec4 { x:  0.0,                 y: 0.0,                   z:  0.2,                w:  0.0 },
-            Vec4 { x:  LIGHT_TRANLSATION.x, y:  LIGHT_TRANLSATION.y,  z: LIGHT_TRANLSATION.z, w:  1.0 },
-        ]
-    }},
 ];
 
 fn main() {
+    let scene_path = std::env::args().nth(1).unwrap_or_else(|| {
+        concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/01-lighting/03-lighting-maps/scene.ron"
+        )
+        .to_owned()
+    });
+    let scene = Scene::load(&scene_path).unwrap();
+    let light = scene
+        .point_lights
+        .first()
+        .cloned()
+        .expect("The scene has no point light");
+
     let (event_loop, window, mut size) = {
         let event_loop = EventLoop::new();
         let window = WindowBuilder::new()
@@ -147,12 +139,12 @@ fn main() {
         .create_buffer_mapped(VERTICES.len(), wgpu::BufferUsage::VERTEX)
         .fill_from_slice(VERTICES);
 
+    let cubes = scene.cube_instances();
     let cube_instance_buffer = device
-        .create_buffer_mapped(CUBES.len(), wgpu::BufferUsage::VERTEX)
-        .fill_from_slice(CUBES);
-    let light_source_instance_buffer = device
-        .create_buffer_mapped(LIGHT_SOURCES.len(), wgpu::BufferUsage::VERTEX)
-        .fill_from_slice(LIGHT_SOURCES);
+        .create_buffer_mapped(cubes.len(), wgpu::BufferUsage::VERTEX)
+        .fill_from_slice(&cubes);
+    let mut light_source_instance_buffer =
+        InstanceBuffer::from_slice(&device, &scene.light_source_instances());
 
     let vertex_uniform_bind_group_layout =
         device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
@@ -206,6 +198,32 @@ fn main() {
                     visibility: wgpu::ShaderStage::FRAGMENT,
                     ty: wgpu::BindingType::Sampler,
                 },
+                wgpu::BindGroupLayoutBinding {
+                    binding: 5,
+                    visibility: wgpu::ShaderStage::FRAGMENT,
+                    ty: wgpu::BindingType::SampledTexture {
+                        multisampled: false,
+                        dimension: wgpu::TextureViewDimension::D2,
+                    },
+                },
+                wgpu::BindGroupLayoutBinding {
+                    binding: 6,
+                    visibility: wgpu::ShaderStage::FRAGMENT,
+                    ty: wgpu::BindingType::Sampler,
+                },
+                wgpu::BindGroupLayoutBinding {
+                    binding: 7,
+                    visibility: wgpu::ShaderStage::FRAGMENT,
+                    ty: wgpu::BindingType::SampledTexture {
+                        multisampled: false,
+                        dimension: wgpu::TextureViewDimension::D2,
+                    },
+                },
+                wgpu::BindGroupLayoutBinding {
+                    binding: 8,
+                    visibility: wgpu::ShaderStage::FRAGMENT,
+                    ty: wgpu::BindingType::Sampler,
+                },
             ],
         });
 
@@ -222,17 +240,7 @@ fn main() {
     let event_reader = event_channel.register_reader();
     let mut input_handler = InputHandler::<StringBindings>::new();
 
-    let mut camera = Camera {
-        translation: Vec3::new(0.0, 0.0, 3.0),
-        pitch: 0.0,
-        yaw: std::f32::consts::PI,
-        roll: 0.0,
-        movement_speed: 0.1,
-        mouse_sensitivity: 0.0025,
-        zoom: std::f32::consts::FRAC_PI_3,
-        is_controlled: false,
-        event_reader,
-    };
+    let mut camera = scene.create_camera(event_reader);
 
     let vertex_uniform_buffer = device
         .create_buffer_mapped(1, wgpu::BufferUsage::UNIFORM | wgpu::BufferUsage::COPY_DST)
@@ -271,14 +279,27 @@ fn main() {
         }],
     });
 
+    let mut material = Material {
+        shininess: scene.material.shininess,
+        emissive: scene::vec3(scene.material.emissive).into(),
+        emissive_offset: Vec2::zero(),
+    };
+    let emissive_scroll = Vec2::from(scene.material.emissive_scroll);
     let material_uniform_buffer = device
         .create_buffer_mapped(1, wgpu::BufferUsage::UNIFORM | wgpu::BufferUsage::COPY_DST)
-        .fill_from_slice(&[Material { shininess: 32.0 }]);
-
-    let material_diffuse_texture_source = include_bytes!("container2.png");
-    let material_diffuse_texture_image = image::load_from_memory(material_diffuse_texture_source)
-        .unwrap()
-        .to_rgba();
+        .fill_from_slice(&[material]);
+
+    let material_diffuse_texture_image = image::open(
+        scene.resolve_path(
+            scene
+                .material
+                .diffuse_texture
+                .as_ref()
+                .expect("The material has no diffuse texture"),
+        ),
+    )
+    .unwrap()
+    .to_rgba();
     let (width, height) = material_diffuse_texture_image.dimensions();
     let material_diffuse_texture_raw = material_diffuse_texture_image.to_vec();
     let (_, material_diffuse_texture_view, material_diffuse_texture_sampler) =
@@ -288,12 +309,20 @@ fn main() {
             &material_diffuse_texture_raw,
             width,
             height,
+            wgpu::TextureFormat::Rgba8UnormSrgb,
         );
 
-    let material_specular_texture_source = include_bytes!("container2_specular.png");
-    let material_specular_texture_image = image::load_from_memory(material_specular_texture_source)
-        .unwrap()
-        .to_rgba();
+    let material_specular_texture_image = image::open(
+        scene.resolve_path(
+            scene
+                .material
+                .specular_texture
+                .as_ref()
+                .expect("The material has no specular texture"),
+        ),
+    )
+    .unwrap()
+    .to_rgba();
     let (width, height) = material_specular_texture_image.dimensions();
     let material_specular_texture_raw = material_specular_texture_image.to_vec();
     let (_, material_specular_texture_view, material_specular_texture_sampler) =
@@ -303,8 +332,54 @@ fn main() {
             &material_specular_texture_raw,
             width,
             height,
+            wgpu::TextureFormat::Rgba8Unorm,
         );
 
+    let material_normal_texture_image = match &scene.material.normal_texture {
+        Some(path) => image::open(scene.resolve_path(path)).unwrap().to_rgba(),
+        // A flat normal map leaves the vertex normals as they are.
+        None => image::RgbaImage::from_pixel(1, 1, image::Rgba([128, 128, 255, 255])),
+    };
+    let (width, height) = material_normal_texture_image.dimensions();
+    let material_normal_texture_raw = material_normal_texture_image.to_vec();
+    let (_, material_normal_texture_view, material_normal_texture_sampler) =
+        create_sampled_texture2d(
+            &device,
+            &mut init_encoder,
+            &material_normal_texture_raw,
+            width,
+            height,
+            wgpu::TextureFormat::Rgba8Unorm,
+        );
+
+    let material_emissive_texture_image = match &scene.material.emissive_texture {
+        Some(path) => image::open(scene.resolve_path(path)).unwrap().to_rgba(),
+        // Without a texture, the emissive color covers the whole surface.
+        None => image::RgbaImage::from_pixel(1, 1, image::Rgba([255, 255, 255, 255])),
+    };
+    let (width, height) = material_emissive_texture_image.dimensions();
+    let material_emissive_texture_raw = material_emissive_texture_image.to_vec();
+    let (_, material_emissive_texture_view, _) = create_sampled_texture2d(
+        &device,
+        &mut init_encoder,
+        &material_emissive_texture_raw,
+        width,
+        height,
+        wgpu::TextureFormat::Rgba8UnormSrgb,
+    );
+    // The emissive texture repeats, so that it wraps around while it's scrolling.
+    let material_emissive_texture_sampler = device.create_sampler(&wgpu::SamplerDescriptor {
+        address_mode_u: wgpu::AddressMode::Repeat,
+        address_mode_v: wgpu::AddressMode::Repeat,
+        address_mode_w: wgpu::AddressMode::Repeat,
+        mag_filter: wgpu::FilterMode::Nearest,
+        min_filter: wgpu::FilterMode::Linear,
+        mipmap_filter: wgpu::FilterMode::Nearest,
+        lod_min_clamp: 0.0,
+        lod_max_clamp: 0.0,
+        compare_function: wgpu::CompareFunction::Never,
+    });
+
     let material_bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
         layout: &material_bind_group_layout,
         bindings: &[
@@ -331,16 +406,32 @@ fn main() {
                 binding: 4,
                 resource: wgpu::BindingResource::Sampler(&material_specular_texture_sampler),
             },
+            wgpu::Binding {
+                binding: 5,
+                resource: wgpu::BindingResource::TextureView(&material_normal_texture_view),
+            },
+            wgpu::Binding {
+                binding: 6,
+                resource: wgpu::BindingResource::Sampler(&material_normal_texture_sampler),
+            },
+            wgpu::Binding {
+                binding: 7,
+                resource: wgpu::BindingResource::TextureView(&material_emissive_texture_view),
+            },
+            wgpu::Binding {
+                binding: 8,
+                resource: wgpu::BindingResource::Sampler(&material_emissive_texture_sampler),
+            },
         ],
     });
 
     let light_uniform_buffer = device
         .create_buffer_mapped(1, wgpu::BufferUsage::UNIFORM | wgpu::BufferUsage::COPY_DST)
         .fill_from_slice(&[Light {
-            translation: LIGHT_TRANLSATION.into(),
-            ambient: Vec3::new(0.2, 0.2, 0.2).into(),
-            diffuse: Vec3::new(0.5, 0.5, 0.5).into(),
-            specular: Vec3::new(1.0, 1.0, 1.0).into(),
+            translation: scene::vec3(light.translation).into(),
+            ambient: scene::vec3(light.ambient).into(),
+            diffuse: scene::vec3(light.diffuse).into(),
+            specular: scene::vec3(light.specular),
         }]);
 
     let light_uniform_bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
@@ -356,7 +447,7 @@ fn main() {
 
     let mut swap_chain_descriptor = wgpu::SwapChainDescriptor {
         usage: wgpu::TextureUsage::OUTPUT_ATTACHMENT,
-        format: wgpu::TextureFormat::Bgra8Unorm,
+        format: wgpu::TextureFormat::Bgra8UnormSrgb,
         width: size.width,
         height: size.height,
         present_mode: wgpu::PresentMode::Vsync,
@@ -428,6 +519,12 @@ fn main() {
                         format: wgpu::VertexFormat::Float2,
                         shader_location: 2,
                     },
+                    wgpu::VertexAttributeDescriptor {
+                        offset: (std::mem::size_of::<Vec3>() * 2 + std::mem::size_of::<Vec2>())
+                            as wgpu::BufferAddress,
+                        format: wgpu::VertexFormat::Float4,
+                        shader_location: 10,
+                    },
                 ],
             },
             wgpu::VertexBufferDescriptor {
@@ -454,6 +551,23 @@ fn main() {
                         format: wgpu::VertexFormat::Float4,
                         shader_location: 6,
                     },
+                    wgpu::VertexAttributeDescriptor {
+                        offset: std::mem::size_of::<Mat4>() as wgpu::BufferAddress,
+                        format: wgpu::VertexFormat::Float3,
+                        shader_location: 7,
+                    },
+                    wgpu::VertexAttributeDescriptor {
+                        offset: (std::mem::size_of::<Mat4>() + std::mem::size_of::<Vec3>())
+                            as wgpu::BufferAddress,
+                        format: wgpu::VertexFormat::Float3,
+                        shader_location: 8,
+                    },
+                    wgpu::VertexAttributeDescriptor {
+                        offset: (std::mem::size_of::<Mat4>() + std::mem::size_of::<Vec3>() * 2)
+                            as wgpu::BufferAddress,
+                        format: wgpu::VertexFormat::Float3,
+                        shader_location: 9,
+                    },
                 ],
             },
         ],
@@ -484,6 +598,8 @@ fn main() {
 
     queue.submit(&[init_encoder.finish()]);
 
+    let mut time = Time::new();
+
     event_loop.run(move |event, _, control_flow| {
         input_handler.send_event(&event, &mut event_channel);
 
@@ -547,6 +663,9 @@ fn main() {
                 input_handler.send_frame_begin();
                 camera.update(&input_handler);
 
+                time.update();
+                let animated_light = light.sample(time.elapsed());
+
                 let frame = swap_chain.get_next_texture();
                 let mut encoder =
                     device.create_command_encoder(&wgpu::CommandEncoderDescriptor { todo: 0 });
@@ -564,8 +683,7 @@ fn main() {
                             view_projection[1],
                             view_projection[2],
                             view_projection[3],
-                            // TODO: Figure out why I need to invert this
-                            -camera.translation.into_homogeneous_vector(),
+                            camera.world_translation().into_homogeneous_vector(),
                         ]);
                     encoder.copy_buffer_to_buffer(
                         &staging_buffer,
@@ -582,6 +700,44 @@ fn main() {
                         std::mem::size_of::<Vec4>() as wgpu::BufferAddress,
                     );
 
+                    // Only the fractional part is kept, since the texture repeats anyway and large texture
+                    // coordinates lose precision.
+                    let emissive_offset = emissive_scroll * time.elapsed();
+                    material.emissive_offset = emissive_offset
+                        - Vec2::new(emissive_offset.x.floor(), emissive_offset.y.floor());
+                    let staging_buffer = device
+                        .create_buffer_mapped(1, wgpu::BufferUsage::COPY_SRC)
+                        .fill_from_slice(&[material]);
+                    encoder.copy_buffer_to_buffer(
+                        &staging_buffer,
+                        0,
+                        &material_uniform_buffer,
+                        0,
+                        std::mem::size_of::<Material>() as wgpu::BufferAddress,
+                    );
+
+                    let staging_buffer = device
+                        .create_buffer_mapped(1, wgpu::BufferUsage::COPY_SRC)
+                        .fill_from_slice(&[Light {
+                            translation: animated_light.translation,
+                            ambient: animated_light.ambient,
+                            diffuse: animated_light.diffuse,
+                            specular: animated_light.specular,
+                        }]);
+                    encoder.copy_buffer_to_buffer(
+                        &staging_buffer,
+                        0,
+                        &light_uniform_buffer,
+                        0,
+                        std::mem::size_of::<Light>() as wgpu::BufferAddress,
+                    );
+
+                    light_source_instance_buffer.update(
+                        &device,
+                        &mut encoder,
+                        &scene.light_source_instances_at(time.elapsed()),
+                    );
+
                     let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
                         color_attachments: &[wgpu::RenderPassColorAttachmentDescriptor {
                             attachment: &frame.view,
@@ -614,15 +770,21 @@ fn main() {
                     render_pass.set_bind_group(3, &light_uniform_bind_group, &[]);
                     render_pass
                         .set_vertex_buffers(0, &[(&vertex_buffer, 0), (&cube_instance_buffer, 0)]);
-                    render_pass.draw(0..VERTICES.len() as u32, 0..CUBES.len() as u32);
+                    render_pass.draw(0..VERTICES.len() as u32, 0..cubes.len() as u32);
 
                     render_pass.set_pipeline(&light_source_pipeline);
                     render_pass.set_bind_group(0, &vertex_uniform_bind_group, &[]);
                     render_pass.set_vertex_buffers(
                         0,
-                        &[(&vertex_buffer, 0), (&light_source_instance_buffer, 0)],
+                        &[
+                            (&vertex_buffer, 0),
+                            (light_source_instance_buffer.buffer(), 0),
+                        ],
+                    );
+                    render_pass.draw(
+                        0..VERTICES.len() as u32,
+                        0..light_source_instance_buffer.len() as u32,
                     );
-                    render_pass.draw(0..VERTICES.len() as u32, 0..LIGHT_SOURCES.len() as u32);
                 }
 
                 queue.submit(&[encoder.finish()]);
@@ -661,12 +823,15 @@ pub fn create_depth_texture(
     (texture, view)
 }
 
+/// Uploads an RGBA texture. Color textures should use an sRGB `format`, so that they're decoded into linear space
+/// when sampled, while textures holding other data like specular intensities or normals have to use a linear one.
 pub fn create_sampled_texture2d(
     device: &wgpu::Device,
     encoder: &mut wgpu::CommandEncoder,
     raw_texture: &[u8],
     width: u32,
     height: u32,
+    format: wgpu::TextureFormat,
 ) -> (wgpu::Texture, wgpu::TextureView, wgpu::Sampler) {
     let texture_extent = wgpu::Extent3d {
         width,
@@ -683,7 +848,7 @@ pub fn create_sampled_texture2d(
         mip_level_count: 0,
         sample_count: 1,
         dimension: wgpu::TextureDimension::D2,
-        format: wgpu::TextureFormat::Rgba8Unorm,
+        format,
         usage: wgpu::TextureUsage::SAMPLED | wgpu::TextureUsage::COPY_DST,
     });
 
@@ -729,12 +894,8 @@ pub struct Vertex {
     pub translation: Vec3,
     pub normal: Vec3,
     pub uv: Vec2,
-}
-
-#[repr(C)]
-#[derive(Clone, Copy, Debug)]
-pub struct Instance {
-    pub model: Mat4,
+    /// The direction of increasing `u` texture coordinates, with the handedness of the bitangent in W.
+    pub tangent: Vec4,
 }
 
 #[repr(C)]
@@ -747,6 +908,9 @@ pub struct FragmentUniforms {
 #[derive(Clone, Copy, Debug)]
 pub struct Material {
     pub shininess: f32,
+    pub emissive: Align16<Vec3>,
+    /// How far the emissive texture has scrolled.
+    pub emissive_offset: Vec2,
 }
 
 #[repr(C)]
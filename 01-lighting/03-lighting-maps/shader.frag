@@ -3,6 +3,7 @@
 layout(location = 0) in vec3 translation;
 layout(location = 1) in vec3 normal;
 layout(location = 2) in vec2 uv;
+layout(location = 3) in vec4 tangent;
 
 layout(location = 0) out vec4 fragment_color;
 
@@ -12,11 +13,17 @@ layout(set = 1, binding = 0) uniform Uniforms {
 
 layout(set = 2, binding = 0) uniform Material {
     float shininess;
+    vec3 emissive;
+    vec2 emissive_offset;
 } material;
 layout(set = 2, binding = 1) uniform texture2D material_diffuse_texture;
 layout(set = 2, binding = 2) uniform sampler material_diffuse_sampler;
 layout(set = 2, binding = 3) uniform texture2D material_specular_texture;
 layout(set = 2, binding = 4) uniform sampler material_specular_sampler;
+layout(set = 2, binding = 5) uniform texture2D material_normal_texture;
+layout(set = 2, binding = 6) uniform sampler material_normal_sampler;
+layout(set = 2, binding = 7) uniform texture2D material_emissive_texture;
+layout(set = 2, binding = 8) uniform sampler material_emissive_sampler;
 
 layout(set = 3, binding = 0) uniform Light {
     vec3 translation;
@@ -26,11 +33,13 @@ layout(set = 3, binding = 0) uniform Light {
     vec3 specular;
 } light;
 
+vec3 calculate_normal();
+
 void main()
 {
     vec3 ambient = light.ambient * vec3(texture(sampler2D(material_diffuse_texture, material_diffuse_sampler), uv));
 
-    vec3 normalized_normal = normalize(normal);
+    vec3 normalized_normal = calculate_normal();
     vec3 light_direction = normalize(light.translation - translation);
     float diff = max(dot(normalized_normal, light_direction), 0.0);
     vec3 diffuse = light.diffuse * (diff * vec3(texture(sampler2D(material_diffuse_texture, material_diffuse_sampler), uv)));
@@ -40,6 +49,24 @@ void main()
     float spec = pow(max(dot(view_direction, reflect_direction), 0.0), material.shininess);
     vec3 specular = light.specular * (spec * vec3(texture(sampler2D(material_specular_texture, material_specular_sampler), uv)));
 
-    vec3 result = ambient + diffuse + specular;
+    // The surface gives off this light by itself, so it's added as is instead of being lit or attenuated.
+    vec3 emission = material.emissive
+        * vec3(texture(sampler2D(material_emissive_texture, material_emissive_sampler), uv + material.emissive_offset));
+
+    vec3 result = ambient + diffuse + specular + emission;
     fragment_color = vec4(result, 1.0);
 }
+
+// Transforms the normal map's normal from tangent space into world space. A flat normal map pointing straight along
+// the Z axis reproduces the vertex normal.
+vec3 calculate_normal()
+{
+    vec3 n = normalize(normal);
+    // Interpolation leaves the tangent slightly off perpendicular to the normal, so it's straightened out again.
+    vec3 t = normalize(tangent.xyz - dot(tangent.xyz, n) * n);
+    // The sign in W flips the bitangent for mirrored texture coordinates.
+    vec3 b = tangent.w * cross(n, t);
+
+    vec3 tangent_space_normal = texture(sampler2D(material_normal_texture, material_normal_sampler), uv).rgb * 2.0 - 1.0;
+    return normalize(mat3(t, b, n) * tangent_space_normal);
+}
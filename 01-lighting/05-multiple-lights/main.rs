@@ -7,185 +7,97 @@ use winit::{
     window::WindowBuilder,
 };
 
-use learnopengl_but_its_wgpu::{Align16, Camera};
+use learnopengl_but_its_wgpu::{
+    lighting::{LightManager, Material, PointLight, SpecularModel, MAX_POINT_LIGHT_SHADOWS},
+    mesh::Mesh,
+    scene::{Scene, SceneBuffers},
+    shadow::{self, PointShadowMaps, SHADOW_MAP_FORMAT},
+    spherical_harmonics::COEFFICIENT_COUNT,
+    time::Time,
+    Instance,
+};
 
 const DEPTH_FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Depth32Float;
 
+const POINT_SHADOW_MAP_SIZE: u32 = 1024;
+const POINT_SHADOW_NEAR_PLANE: f32 = 0.1;
+/// Must match `POINT_SHADOW_FAR_PLANE` in `shader.frag`.
+const POINT_SHADOW_FAR_PLANE: f32 = 25.0;
+
 #[rustfmt::skip]
 const VERTICES: &[Vertex] = &[
-    Vertex { translation: Vec3 { x:  0.5, y: -0.5, z: -0.5, }, normal: Vec3 { x:  0.0, y:  0.0, z: -1.0 }, uv: Vec2 { x: 1.0, y: 0.0, } },
-    Vertex { translation: Vec3 { x: -0.5, y: -0.5, z: -0.5, }, normal: Vec3 { x:  0.0, y:  0.0, z: -1.0 }, uv: Vec2 { x: 0.0, y: 0.0, } },
-    Vertex { translation: Vec3 { x:  0.5, y:  0.5, z: -0.5, }, normal: Vec3 { x:  0.0, y:  0.0, z: -1.0 }, uv: Vec2 { x: 1.0, y: 1.0, } },
-    Vertex { translation: Vec3 { x:  0.5, y:  0.5, z: -0.5, }, normal: Vec3 { x:  0.0, y:  0.0, z: -1.0 }, uv: Vec2 { x: 1.0, y: 1.0, } },
-    Vertex { translation: Vec3 { x: -0.5, y: -0.5, z: -0.5, }, normal: Vec3 { x:  0.0, y:  0.0, z: -1.0 }, uv: Vec2 { x: 0.0, y: 0.0, } },
-    Vertex { translation: Vec3 { x: -0.5, y:  0.5, z: -0.5, }, normal: Vec3 { x:  0.0, y:  0.0, z: -1.0 }, uv: Vec2 { x: 0.0, y: 1.0, } },
-
-    Vertex { translation: Vec3 { x: -0.5, y: -0.5, z:  0.5, }, normal: Vec3 { x:  0.0, y:  0.0, z:  1.0 }, uv: Vec2 { x: 0.0, y: 0.0, } },
-    Vertex { translation: Vec3 { x:  0.5, y: -0.5, z:  0.5, }, normal: Vec3 { x:  0.0, y:  0.0, z:  1.0 }, uv: Vec2 { x: 1.0, y: 0.0, } },
-    Vertex { translation: Vec3 { x:  0.5, y:  0.5, z:  0.5, }, normal: Vec3 { x:  0.0, y:  0.0, z:  1.0 }, uv: Vec2 { x: 1.0, y: 1.0, } },
-    Vertex { translation: Vec3 { x:  0.5, y:  0.5, z:  0.5, }, normal: Vec3 { x:  0.0, y:  0.0, z:  1.0 }, uv: Vec2 { x: 1.0, y: 1.0, } },
-    Vertex { translation: Vec3 { x: -0.5, y:  0.5, z:  0.5, }, normal: Vec3 { x:  0.0, y:  0.0, z:  1.0 }, uv: Vec2 { x: 0.0, y: 1.0, } },
-    Vertex { translation: Vec3 { x: -0.5, y: -0.5, z:  0.5, }, normal: Vec3 { x:  0.0, y:  0.0, z:  1.0 }, uv: Vec2 { x: 0.0, y: 0.0, } },
-
-    Vertex { translation: Vec3 { x: -0.5, y:  0.5, z:  0.5, }, normal: Vec3 { x: -1.0, y:  0.0, z:  0.0 }, uv: Vec2 { x: 1.0, y: 0.0, } },
-    Vertex { translation: Vec3 { x: -0.5, y:  0.5, z: -0.5, }, normal: Vec3 { x: -1.0, y:  0.0, z:  0.0 }, uv: Vec2 { x: 1.0, y: 1.0, } },
-    Vertex { translation: Vec3 { x: -0.5, y: -0.5, z: -0.5, }, normal: Vec3 { x: -1.0, y:  0.0, z:  0.0 }, uv: Vec2 { x: 0.0, y: 1.0, } },
-    Vertex { translation: Vec3 { x: -0.5, y: -0.5, z: -0.5, }, normal: Vec3 { x: -1.0, y:  0.0, z:  0.0 }, uv: Vec2 { x: 0.0, y: 1.0, } },
-    Vertex { translation: Vec3 { x: -0.5, y: -0.5, z:  0.5, }, normal: Vec3 { x: -1.0, y:  0.0, z:  0.0 }, uv: Vec2 { x: 0.0, y: 0.0, } },
-    Vertex { translation: Vec3 { x: -0.5, y:  0.5, z:  0.5, }, normal: Vec3 { x: -1.0, y:  0.0, z:  0.0 }, uv: Vec2 { x: 1.0, y: 0.0, } },
-
-    Vertex { translation: Vec3 { x:  0.5, y:  0.5, z:  0.5, }, normal: Vec3 { x:  1.0, y:  0.0, z:  0.0 }, uv: Vec2 { x: 1.0, y: 0.0, } },
-    Vertex { translation: Vec3 { x:  0.5, y: -0.5, z: -0.5, }, normal: Vec3 { x:  1.0, y:  0.0, z:  0.0 }, uv: Vec2 { x: 0.0, y: 1.0, } },
-    Vertex { translation: Vec3 { x:  0.5, y:  0.5, z: -0.5, }, normal: Vec3 { x:  1.0, y:  0.0, z:  0.0 }, uv: Vec2 { x: 1.0, y: 1.0, } },
-    Vertex { translation: Vec3 { x:  0.5, y: -0.5, z: -0.5, }, normal: Vec3 { x:  1.0, y:  0.0, z:  0.0 }, uv: Vec2 { x: 0.0, y: 1.0, } },
-    Vertex { translation: Vec3 { x:  0.5, y:  0.5, z:  0.5, }, normal: Vec3 { x:  1.0, y:  0.0, z:  0.0 }, uv: Vec2 { x: 1.0, y: 0.0, } },
-    Vertex { translation: Vec3 { x:  0.5, y: -0.5, z:  0.5, }, normal: Vec3 { x:  1.0, y:  0.0, z:  0.0 }, uv: Vec2 { x: 0.0, y: 0.0, } },
-
-    Vertex { translation: Vec3 { x: -0.5, y: -0.5, z: -0.5, }, normal: Vec3 { x:  0.0, y: -1.0, z:  0.0 }, uv: Vec2 { x: 0.0, y: 1.0, } },
-    Vertex { translation: Vec3 { x:  0.5, y: -0.5, z: -0.5, }, normal: Vec3 { x:  0.0, y: -1.0, z:  0.0 }, uv: Vec2 { x: 1.0, y: 1.0, } },
-    Vertex { translation: Vec3 { x:  0.5, y: -0.5, z:  0.5, }, normal: Vec3 { x:  0.0, y: -1.0, z:  0.0 }, uv: Vec2 { x: 1.0, y: 0.0, } },
-    Vertex { translation: Vec3 { x:  0.5, y: -0.5, z:  0.5, }, normal: Vec3 { x:  0.0, y: -1.0, z:  0.0 }, uv: Vec2 { x: 1.0, y: 0.0, } },
-    Vertex { translation: Vec3 { x: -0.5, y: -0.5, z:  0.5, }, normal: Vec3 { x:  0.0, y: -1.0, z:  0.0 }, uv: Vec2 { x: 0.0, y: 0.0, } },
-    Vertex { translation: Vec3 { x: -0.5, y: -0.5, z: -0.5, }, normal: Vec3 { x:  0.0, y: -1.0, z:  0.0 }, uv: Vec2 { x: 0.0, y: 1.0, } },
-
-    Vertex { translation: Vec3 { x:  0.5, y:  0.5, z: -0.5, }, normal: Vec3 { x:  0.0, y:  1.0, z:  0.0 }, uv: Vec2 { x: 1.0, y: 1.0, } },
-    Vertex { translation: Vec3 { x: -0.5, y:  0.5, z: -0.5, }, normal: Vec3 { x:  0.0, y:  1.0, z:  0.0 }, uv: Vec2 { x: 0.0, y: 1.0, } },
-    Vertex { translation: Vec3 { x:  0.5, y:  0.5, z:  0.5, }, normal: Vec3 { x:  0.0, y:  1.0, z:  0.0 }, uv: Vec2 { x: 1.0, y: 0.0, } },
-    Vertex { translation: Vec3 { x:  0.5, y:  0.5, z:  0.5, }, normal: Vec3 { x:  0.0, y:  1.0, z:  0.0 }, uv: Vec2 { x: 1.0, y: 0.0, } },
-    Vertex { translation: Vec3 { x: -0.5, y:  0.5, z: -0.5, }, normal: Vec3 { x:  0.0, y:  1.0, z:  0.0 }, uv: Vec2 { x: 0.0, y: 1.0, } },
-    Vertex { translation: Vec3 { x: -0.5, y:  0.5, z:  0.5, }, normal: Vec3 { x:  0.0, y:  1.0, z:  0.0 }, uv: Vec2 { x: 0.0, y: 0.0, } },
+    Vertex { translation: Vec3 { x:  0.5, y: -0.5, z: -0.5, }, normal: Vec3 { x:  0.0, y:  0.0, z: -1.0 }, uv: Vec2 { x: 1.0, y: 0.0, }, tangent: Vec4 { x:  1.0, y:  0.0, z:  0.0, w: -1.0 } },
+    Vertex { translation: Vec3 { x: -0.5, y: -0.5, z: -0.5, }, normal: Vec3 { x:  0.0, y:  0.0, z: -1.0 }, uv: Vec2 { x: 0.0, y: 0.0, }, tangent: Vec4 { x:  1.0, y:  0.0, z:  0.0, w: -1.0 } },
+    Vertex { translation: Vec3 { x:  0.5, y:  0.5, z: -0.5, }, normal: Vec3 { x:  0.0, y:  0.0, z: -1.0 }, uv: Vec2 { x: 1.0, y: 1.0, }, tangent: Vec4 { x:  1.0, y:  0.0, z:  0.0, w: -1.0 } },
+    Vertex { translation: Vec3 { x:  0.5, y:  0.5, z: -0.5, }, normal: Vec3 { x:  0.0, y:  0.0, z: -1.0 }, uv: Vec2 { x: 1.0, y: 1.0, }, tangent: Vec4 { x:  1.0, y:  0.0, z:  0.0, w: -1.0 } },
+    Vertex { translation: Vec3 { x: -0.5, y: -0.5, z: -0.5, }, normal: Vec3 { x:  0.0, y:  0.0, z: -1.0 }, uv: Vec2 { x: 0.0, y: 0.0, }, tangent: Vec4 { x:  1.0, y:  0.0, z:  0.0, w: -1.0 } },
+    Vertex { translation: Vec3 { x: -0.5, y:  0.5, z: -0.5, }, normal: Vec3 { x:  0.0, y:  0.0, z: -1.0 }, uv: Vec2 { x: 0.0, y: 1.0, }, tangent: Vec4 { x:  1.0, y:  0.0, z:  0.0, w: -1.0 } },
+
+    Vertex { translation: Vec3 { x: -0.5, y: -0.5, z:  0.5, }, normal: Vec3 { x:  0.0, y:  0.0, z:  1.0 }, uv: Vec2 { x: 0.0, y: 0.0, }, tangent: Vec4 { x:  1.0, y:  0.0, z:  0.0, w:  1.0 } },
+    Vertex { translation: Vec3 { x:  0.5, y: -0.5, z:  0.5, }, normal: Vec3 { x:  0.0, y:  0.0, z:  1.0 }, uv: Vec2 { x: 1.0, y: 0.0, }, tangent: Vec4 { x:  1.0, y:  0.0, z:  0.0, w:  1.0 } },
+    Vertex { translation: Vec3 { x:  0.5, y:  0.5, z:  0.5, }, normal: Vec3 { x:  0.0, y:  0.0, z:  1.0 }, uv: Vec2 { x: 1.0, y: 1.0, }, tangent: Vec4 { x:  1.0, y:  0.0, z:  0.0, w:  1.0 } },
+    Vertex { translation: Vec3 { x:  0.5, y:  0.5, z:  0.5, }, normal: Vec3 { x:  0.0, y:  0.0, z:  1.0 }, uv: Vec2 { x: 1.0, y: 1.0, }, tangent: Vec4 { x:  1.0, y:  0.0, z:  0.0, w:  1.0 } },
+    Vertex { translation: Vec3 { x: -0.5, y:  0.5, z:  0.5, }, normal: Vec3 { x:  0.0, y:  0.0, z:  1.0 }, uv: Vec2 { x: 0.0, y: 1.0, }, tangent: Vec4 { x:  1.0, y:  0.0, z:  0.0, w:  1.0 } },
+    Vertex { translation: Vec3 { x: -0.5, y: -0.5, z:  0.5, }, normal: Vec3 { x:  0.0, y:  0.0, z:  1.0 }, uv: Vec2 { x: 0.0, y: 0.0, }, tangent: Vec4 { x:  1.0, y:  0.0, z:  0.0, w:  1.0 } },
+
+    Vertex { translation: Vec3 { x: -0.5, y:  0.5, z:  0.5, }, normal: Vec3 { x: -1.0, y:  0.0, z:  0.0 }, uv: Vec2 { x: 1.0, y: 0.0, }, tangent: Vec4 { x:  0.0, y:  1.0, z:  0.0, w:  1.0 } },
+    Vertex { translation: Vec3 { x: -0.5, y:  0.5, z: -0.5, }, normal: Vec3 { x: -1.0, y:  0.0, z:  0.0 }, uv: Vec2 { x: 1.0, y: 1.0, }, tangent: Vec4 { x:  0.0, y:  1.0, z:  0.0, w:  1.0 } },
+    Vertex { translation: Vec3 { x: -0.5, y: -0.5, z: -0.5, }, normal: Vec3 { x: -1.0, y:  0.0, z:  0.0 }, uv: Vec2 { x: 0.0, y: 1.0, }, tangent: Vec4 { x:  0.0, y:  1.0, z:  0.0, w:  1.0 } },
+    Vertex { translation: Vec3 { x: -0.5, y: -0.5, z: -0.5, }, normal: Vec3 { x: -1.0, y:  0.0, z:  0.0 }, uv: Vec2 { x: 0.0, y: 1.0, }, tangent: Vec4 { x:  0.0, y:  1.0, z:  0.0, w:  1.0 } },
+    Vertex { translation: Vec3 { x: -0.5, y: -0.5, z:  0.5, }, normal: Vec3 { x: -1.0, y:  0.0, z:  0.0 }, uv: Vec2 { x: 0.0, y: 0.0, }, tangent: Vec4 { x:  0.0, y:  1.0, z:  0.0, w:  1.0 } },
+    Vertex { translation: Vec3 { x: -0.5, y:  0.5, z:  0.5, }, normal: Vec3 { x: -1.0, y:  0.0, z:  0.0 }, uv: Vec2 { x: 1.0, y: 0.0, }, tangent: Vec4 { x:  0.0, y:  1.0, z:  0.0, w:  1.0 } },
+
+    Vertex { translation: Vec3 { x:  0.5, y:  0.5, z:  0.5, }, normal: Vec3 { x:  1.0, y:  0.0, z:  0.0 }, uv: Vec2 { x: 1.0, y: 0.0, }, tangent: Vec4 { x:  0.0, y:  1.0, z:  0.0, w: -1.0 } },
+    Vertex { translation: Vec3 { x:  0.5, y: -0.5, z: -0.5, }, normal: Vec3 { x:  1.0, y:  0.0, z:  0.0 }, uv: Vec2 { x: 0.0, y: 1.0, }, tangent: Vec4 { x:  0.0, y:  1.0, z:  0.0, w: -1.0 } },
+    Vertex { translation: Vec3 { x:  0.5, y:  0.5, z: -0.5, }, normal: Vec3 { x:  1.0, y:  0.0, z:  0.0 }, uv: Vec2 { x: 1.0, y: 1.0, }, tangent: Vec4 { x:  0.0, y:  1.0, z:  0.0, w: -1.0 } },
+    Vertex { translation: Vec3 { x:  0.5, y: -0.5, z: -0.5, }, normal: Vec3 { x:  1.0, y:  0.0, z:  0.0 }, uv: Vec2 { x: 0.0, y: 1.0, }, tangent: Vec4 { x:  0.0, y:  1.0, z:  0.0, w: -1.0 } },
+    Vertex { translation: Vec3 { x:  0.5, y:  0.5, z:  0.5, }, normal: Vec3 { x:  1.0, y:  0.0, z:  0.0 }, uv: Vec2 { x: 1.0, y: 0.0, }, tangent: Vec4 { x:  0.0, y:  1.0, z:  0.0, w: -1.0 } },
+    Vertex { translation: Vec3 { x:  0.5, y: -0.5, z:  0.5, }, normal: Vec3 { x:  1.0, y:  0.0, z:  0.0 }, uv: Vec2 { x: 0.0, y: 0.0, }, tangent: Vec4 { x:  0.0, y:  1.0, z:  0.0, w: -1.0 } },
+
+    Vertex { translation: Vec3 { x: -0.5, y: -0.5, z: -0.5, }, normal: Vec3 { x:  0.0, y: -1.0, z:  0.0 }, uv: Vec2 { x: 0.0, y: 1.0, }, tangent: Vec4 { x:  1.0, y:  0.0, z:  0.0, w: -1.0 } },
+    Vertex { translation: Vec3 { x:  0.5, y: -0.5, z: -0.5, }, normal: Vec3 { x:  0.0, y: -1.0, z:  0.0 }, uv: Vec2 { x: 1.0, y: 1.0, }, tangent: Vec4 { x:  1.0, y:  0.0, z:  0.0, w: -1.0 } },
+    Vertex { translation: Vec3 { x:  0.5, y: -0.5, z:  0.5, }, normal: Vec3 { x:  0.0, y: -1.0, z:  0.0 }, uv: Vec2 { x: 1.0, y: 0.0, }, tangent: Vec4 { x:  1.0, y:  0.0, z:  0.0, w: -1.0 } },
+    Vertex { translation: Vec3 { x:  0.5, y: -0.5, z:  0.5, }, normal: Vec3 { x:  0.0, y: -1.0, z:  0.0 }, uv: Vec2 { x: 1.0, y: 0.0, }, tangent: Vec4 { x:  1.0, y:  0.0, z:  0.0, w: -1.0 } },
+    Vertex { translation: Vec3 { x: -0.5, y: -0.5, z:  0.5, }, normal: Vec3 { x:  0.0, y: -1.0, z:  0.0 }, uv: Vec2 { x: 0.0, y: 0.0, }, tangent: Vec4 { x:  1.0, y:  0.0, z:  0.0, w: -1.0 } },
+    Vertex { translation: Vec3 { x: -0.5, y: -0.5, z: -0.5, }, normal: Vec3 { x:  0.0, y: -1.0, z:  0.0 }, uv: Vec2 { x: 0.0, y: 1.0, }, tangent: Vec4 { x:  1.0, y:  0.0, z:  0.0, w: -1.0 } },
+
+    Vertex { translation: Vec3 { x:  0.5, y:  0.5, z: -0.5, }, normal: Vec3 { x:  0.0, y:  1.0, z:  0.0 }, uv: Vec2 { x: 1.0, y: 1.0, }, tangent: Vec4 { x:  1.0, y:  0.0, z:  0.0, w:  1.0 } },
+    Vertex { translation: Vec3 { x: -0.5, y:  0.5, z: -0.5, }, normal: Vec3 { x:  0.0, y:  1.0, z:  0.0 }, uv: Vec2 { x: 0.0, y: 1.0, }, tangent: Vec4 { x:  1.0, y:  0.0, z:  0.0, w:  1.0 } },
+    Vertex { translation: Vec3 { x:  0.5, y:  0.5, z:  0.5, }, normal: Vec3 { x:  0.0, y:  1.0, z:  0.0 }, uv: Vec2 { x: 1.0, y: 0.0, }, tangent: Vec4 { x:  1.0, y:  0.0, z:  0.0, w:  1.0 } },
+    Vertex { translation: Vec3 { x:  0.5, y:  0.5, z:  0.5, }, normal: Vec3 { x:  0.0, y:  1.0, z:  0.0 }, uv: Vec2 { x: 1.0, y: 0.0, }, tangent: Vec4 { x:  1.0, y:  0.0, z:  0.0, w:  1.0 } },
+    Vertex { translation: Vec3 { x: -0.5, y:  0.5, z: -0.5, }, normal: Vec3 { x:  0.0, y:  1.0, z:  0.0 }, uv: Vec2 { x: 0.0, y: 1.0, }, tangent: Vec4 { x:  1.0, y:  0.0, z:  0.0, w:  1.0 } },
+    Vertex { translation: Vec3 { x: -0.5, y:  0.5, z:  0.5, }, normal: Vec3 { x:  0.0, y:  1.0, z:  0.0 }, uv: Vec2 { x: 0.0, y: 0.0, }, tangent: Vec4 { x:  1.0, y:  0.0, z:  0.0, w:  1.0 } },
 
 ];
 
-#[rustfmt::skip]
-const CUBES: &[Instance] = &[
-    Instance { model: Mat4 {
-        cols: [
-            Vec4 { x:   1.0, y:   0.0, z:   0.0, w:   0.0 },
-            Vec4 { x:   0.0, y:   1.0, z:   0.0, w:   0.0 },
-            Vec4 { x:   0.0, y:   0.0, z:   1.0, w:   0.0 },
-            Vec4 { x:   0.0, y:   0.0, z:   0.0, w:   1.0 },
-        ]
-    }},
-    Instance { model: Mat4 {
-        cols: [
-            Vec4 { x:   1.0, y:   0.0, z:   0.0, w:   0.0 },
-            Vec4 { x:   0.0, y:   1.0, z:   0.0, w:   0.0 },
-            Vec4 { x:   0.0, y:   0.0, z:   1.0, w:   0.0 },
-            Vec4 { x:   2.0, y:   5.0, z: -15.0, w:   1.0 },
-        ]
-    }},
-    Instance { model: Mat4 {
-        cols: [
-            Vec4 { x:   1.0, y:   0.0, z:   0.0, w:   0.0 },
-            Vec4 { x:   0.0, y:   1.0, z:   0.0, w:   0.0 },
-            Vec4 { x:   0.0, y:   0.0, z:   1.0, w:   0.0 },
-            Vec4 { x:  -1.5, y:  -2.2, z:  -2.5, w:   1.0 },
-        ]
-    }},
-    Instance { model: Mat4 {
-        cols: [
-            Vec4 { x:   1.0, y:   0.0, z:   0.0, w:   0.0 },
-            Vec4 { x:   0.0, y:   1.0, z:   0.0, w:   0.0 },
-            Vec4 { x:   0.0, y:   0.0, z:   1.0, w:   0.0 },
-            Vec4 { x:  -3.8, y:  -2.0, z: -12.0, w:   1.0 },
-        ]
-    }},
-    Instance { model: Mat4 {
-        cols: [
-            Vec4 { x:   1.0, y:   0.0, z:   0.0, w:   0.0 },
-            Vec4 { x:   0.0, y:   1.0, z:   0.0, w:   0.0 },
-            Vec4 { x:   0.0, y:   0.0, z:   1.0, w:   0.0 },
-            Vec4 { x:   2.4, y:  -0.4, z:  -3.5, w:   1.0 },
-        ]
-    }},
-    Instance { model: Mat4 {
-        cols: [
-            Vec4 { x:   1.0, y:   0.0, z:   0.0, w:   0.0 },
-            Vec4 { x:   0.0, y:   1.0, z:   0.0, w:   0.0 },
-            Vec4 { x:   0.0, y:   0.0, z:   1.0, w:   0.0 },
-            Vec4 { x:  -1.7, y:   3.0, z:  -7.5, w:   1.0 },
-        ]
-    }},
-    Instance { model: Mat4 {
-        cols: [
-            Vec4 { x:   1.0, y:   0.0, z:   0.0, w:   0.0 },
-            Vec4 { x:   0.0, y:   1.0, z:   0.0, w:   0.0 },
-            Vec4 { x:   0.0, y:   0.0, z:   1.0, w:   0.0 },
-            Vec4 { x:   1.3, y:  -2.0, z:  -2.5, w:   1.0 },
-        ]
-    }},
-    Instance { model: Mat4 {
-        cols: [
-            Vec4 { x:   1.0, y:   0.0, z:   0.0, w:   0.0 },
-            Vec4 { x:   0.0, y:   1.0, z:   0.0, w:   0.0 },
-            Vec4 { x:   0.0, y:   0.0, z:   1.0, w:   0.0 },
-            Vec4 { x:   1.5, y:   2.0, z:  -2.5, w:   1.0 },
-        ]
-    }},
-    Instance { model: Mat4 {
-        cols: [
-            Vec4 { x:   1.0, y:   0.0, z:   0.0, w:   0.0 },
-            Vec4 { x:   0.0, y:   1.0, z:   0.0, w:   0.0 },
-            Vec4 { x:   0.0, y:   0.0, z:   1.0, w:   0.0 },
-            Vec4 { x:   1.5, y:   0.2, z:  -1.5, w:   1.0 },
-        ]
-    }},
-    Instance { model: Mat4 {
-        cols: [
-            Vec4 { x:   1.0, y:   0.0, z:   0.0, w:   0.0 },
-            Vec4 { x:   0.0, y:   1.0, z:   0.0, w:   0.0 },
-            Vec4 { x:   0.0, y:   0.0, z:   1.0, w:   0.0 },
-            Vec4 { x:  -1.3, y:   1.0, z:  -1.5, w:   1.0 },
-        ]
-    }},
-];
-
-#[rustfmt::skip]
-const LIGHT_TRANSLATIONS: [Vec3; 4] = [
-    Vec3 { x:   0.7, y:   0.2, z:   2.0 },
-    Vec3 { x:   2.3, y:  -3.3, z:  -4.0 },
-    Vec3 { x:  -4.0, y:   2.0, z: -12.0 },
-    Vec3 { x:   0.0, y:   0.0, z:  -3.0 },
-];
+/// The uniforms used to render one face of a point light's shadow cube map.
+#[repr(C)]
+#[derive(Clone, Copy, Debug)]
+struct PointShadowUniforms {
+    light_space: Mat4,
+    light_translation: Vec3,
+    far_plane: f32,
+}
 
-#[rustfmt::skip]
-const LIGHT_SOURCES: [Instance; 4] = [
-    Instance { model: Mat4 {
-        cols: [
-            Vec4 { x:  0.2,                     y: 0.0,                       z:  0.0,                    w:  0.0 },
-            Vec4 { x:  0.0,                     y: 0.2,                       z:  0.0,                    w:  0.0 },
-            Vec4 { x:  0.0,                     y: 0.0,                       z:  0.2,                    w:  0.0 },
-            Vec4 { x:  LIGHT_TRANSLATIONS[0].x, y:  LIGHT_TRANSLATIONS[0].y,  z: LIGHT_TRANSLATIONS[0].z, w:  1.0 },
-        ]
-    }},
-    Instance { model: Mat4 {
-        cols: [
-            Vec4 { x:  0.2,                     y: 0.0,                       z:  0.0,                    w:  0.0 },
-            Vec4 { x:  0.0,                     y: 0.2,                       z:  0.0,                    w:  0.0 },
-            Vec4 { x:  0.0,                     y: 0.0,                       z:  0.2,                    w:  0.0 },
-            Vec4 { x:  LIGHT_TRANSLATIONS[1].x, y:  LIGHT_TRANSLATIONS[1].y,  z: LIGHT_TRANSLATIONS[1].z, w:  1.0 },
-        ]
-    }},
-    Instance { model: Mat4 {
-        cols: [
-            Vec4 { x:  0.2,                     y: 0.0,                       z:  0.0,                    w:  0.0 },
-            Vec4 { x:  0.0,                     y: 0.2,                       z:  0.0,                    w:  0.0 },
-            Vec4 { x:  0.0,                     y: 0.0,                       z:  0.2,                    w:  0.0 },
-            Vec4 { x:  LIGHT_TRANSLATIONS[2].x, y:  LIGHT_TRANSLATIONS[2].y,  z: LIGHT_TRANSLATIONS[2].z, w:  1.0 },
-        ]
-    }},
-    Instance { model: Mat4 {
-        cols: [
-            Vec4 { x:  0.2,                     y: 0.0,                       z:  0.0,                    w:  0.0 },
-            Vec4 { x:  0.0,                     y: 0.2,                       z:  0.0,                    w:  0.0 },
-            Vec4 { x:  0.0,                     y: 0.0,                       z:  0.2,                    w:  0.0 },
-            Vec4 { x:  LIGHT_TRANSLATIONS[3].x, y:  LIGHT_TRANSLATIONS[3].y,  z: LIGHT_TRANSLATIONS[3].z, w:  1.0 },
-        ]
-    }},
-];
+/// The ambient light arriving from the scene's surroundings, as spherical harmonics.
+#[repr(C)]
+#[derive(Clone, Copy, Debug)]
+struct AmbientUniforms {
+    irradiance_coefficients: [Vec4; COEFFICIENT_COUNT],
+    /// Whether the spherical harmonics replace the directional light's constant ambient colour.
+    spherical_harmonics: u32,
+}
 
 fn main() {
+    let scene_path = std::env::args().nth(1).unwrap_or_else(|| {
+        concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/01-lighting/05-multiple-lights/scene.ron"
+        )
+        .to_owned()
+    });
+    let scene = Scene::load(&scene_path).unwrap();
+
     let (event_loop, window, mut size) = {
         let event_loop = EventLoop::new();
         let window = WindowBuilder::new()
@@ -226,6 +138,20 @@ fn main() {
         "shader.frag",
         &device,
     );
+    let point_shadow_vs_module = prepare_shader(
+        &mut shader_compiler,
+        include_str!("point_shadow.vert"),
+        ShaderKind::Vertex,
+        "point_shadow.vert",
+        &device,
+    );
+    let point_shadow_fs_module = prepare_shader(
+        &mut shader_compiler,
+        include_str!("point_shadow.frag"),
+        ShaderKind::Fragment,
+        "point_shadow.frag",
+        &device,
+    );
     let light_source_vs_module = prepare_shader(
         &mut shader_compiler,
         include_str!("light_source.vert"),
@@ -244,34 +170,40 @@ fn main() {
     let mut init_encoder =
         device.create_command_encoder(&wgpu::CommandEncoderDescriptor { todo: 0 });
 
-    let vertex_buffer = device
-        .create_buffer_mapped(VERTICES.len(), wgpu::BufferUsage::VERTEX)
-        .fill_from_slice(VERTICES);
+    let mut cube_mesh = Mesh::weld(VERTICES, Vertex::weld_key);
+    cube_mesh.optimize();
 
-    let cube_instance_buffer = device
-        .create_buffer_mapped(CUBES.len(), wgpu::BufferUsage::VERTEX)
-        .fill_from_slice(CUBES);
-    let light_source_instance_buffer = device
-        .create_buffer_mapped(LIGHT_SOURCES.len(), wgpu::BufferUsage::VERTEX)
-        .fill_from_slice(&LIGHT_SOURCES);
-
-    let vertex_uniform_bind_group_layout =
+    let vertex_buffer = device
+        .create_buffer_mapped(cube_mesh.vertices.len(), wgpu::BufferUsage::VERTEX)
+        .fill_from_slice(&cube_mesh.vertices);
+    // A cube has far fewer vertices than 16 bit indices can address.
+    let indices: Vec<u16> = cube_mesh
+        .indices
+        .iter()
+        .map(|&index| index as u16)
+        .collect();
+    let index_buffer = device
+        .create_buffer_mapped(indices.len(), wgpu::BufferUsage::INDEX)
+        .fill_from_slice(&indices);
+    let index_count = indices.len() as u32;
+
+    let uniform_bind_group_layout =
         device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
-            bindings: &[wgpu::BindGroupLayoutBinding {
-                binding: 0,
-                visibility: wgpu::ShaderStage::VERTEX,
-                ty: wgpu::BindingType::UniformBuffer { dynamic: false },
-            }],
+            bindings: &[
+                wgpu::BindGroupLayoutBinding {
+                    binding: 0,
+                    visibility: wgpu::ShaderStage::VERTEX,
+                    ty: wgpu::BindingType::UniformBuffer { dynamic: false },
+                },
+                wgpu::BindGroupLayoutBinding {
+                    binding: 1,
+                    visibility: wgpu::ShaderStage::FRAGMENT,
+                    ty: wgpu::BindingType::UniformBuffer { dynamic: false },
+                },
+            ],
         });
 
-    let fragment_uniform_bind_group_layout =
-        device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
-            bindings: &[wgpu::BindGroupLayoutBinding {
-                binding: 0,
-                visibility: wgpu::ShaderStage::FRAGMENT,
-                ty: wgpu::BindingType::UniformBuffer { dynamic: false },
-            }],
-        });
+    let light_bind_group_layout = LightManager::create_bind_group_layout(&device);
 
     let material_bind_group_layout =
         device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
@@ -307,24 +239,65 @@ fn main() {
                     visibility: wgpu::ShaderStage::FRAGMENT,
                     ty: wgpu::BindingType::Sampler,
                 },
+                wgpu::BindGroupLayoutBinding {
+                    binding: 5,
+                    visibility: wgpu::ShaderStage::FRAGMENT,
+                    ty: wgpu::BindingType::SampledTexture {
+                        multisampled: false,
+                        dimension: wgpu::TextureViewDimension::D2,
+                    },
+                },
+                wgpu::BindGroupLayoutBinding {
+                    binding: 6,
+                    visibility: wgpu::ShaderStage::FRAGMENT,
+                    ty: wgpu::BindingType::Sampler,
+                },
+            ],
+        });
+
+    let point_shadow_bind_group_layout =
+        device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            bindings: &[
+                wgpu::BindGroupLayoutBinding {
+                    binding: 0,
+                    visibility: wgpu::ShaderStage::FRAGMENT,
+                    ty: wgpu::BindingType::SampledTexture {
+                        multisampled: false,
+                        dimension: wgpu::TextureViewDimension::CubeArray,
+                    },
+                },
+                wgpu::BindGroupLayoutBinding {
+                    binding: 1,
+                    visibility: wgpu::ShaderStage::FRAGMENT,
+                    ty: wgpu::BindingType::Sampler,
+                },
             ],
         });
 
+    let point_shadow_face_bind_group_layout =
+        device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            bindings: &[wgpu::BindGroupLayoutBinding {
+                binding: 0,
+                visibility: wgpu::ShaderStage::VERTEX | wgpu::ShaderStage::FRAGMENT,
+                ty: wgpu::BindingType::UniformBuffer { dynamic: false },
+            }],
+        });
+
     let mut event_channel = shrev::EventChannel::new();
     let event_reader = event_channel.register_reader();
     let mut input_handler = InputHandler::<StringBindings>::new();
 
-    let mut camera = Camera {
-        translation: Vec3::new(0.0, 0.0, 3.0),
-        pitch: 0.0,
-        yaw: std::f32::consts::PI,
-        roll: 0.0,
-        movement_speed: 0.1,
-        mouse_sensitivity: 0.0025,
-        zoom: std::f32::consts::FRAC_PI_3,
-        is_controlled: false,
-        event_reader,
-    };
+    let mut camera = scene.create_camera(event_reader);
+
+    let SceneBuffers {
+        cube_instance_buffer,
+        cube_count,
+        material_uniform_buffer,
+    } = scene.create_buffers(&device);
+
+    let mut light_manager = scene
+        .create_light_manager(&device, &light_bind_group_layout, &camera)
+        .unwrap();
 
     let vertex_uniform_buffer = device
         .create_buffer_mapped(1, wgpu::BufferUsage::UNIFORM | wgpu::BufferUsage::COPY_DST)
@@ -335,98 +308,89 @@ fn main() {
             100.0,
         )]);
 
-    let vertex_uniform_bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
-        layout: &vertex_uniform_bind_group_layout,
-        bindings: &[wgpu::Binding {
-            binding: 0,
-            resource: wgpu::BindingResource::Buffer {
-                buffer: &vertex_uniform_buffer,
-                range: 0..std::mem::size_of::<Mat4>() as wgpu::BufferAddress,
-            },
-        }],
-    });
-
-    let fragment_uniform_buffer = device
+    let ambient_spherical_harmonics = scene.ambient_spherical_harmonics().unwrap();
+    let mut ambient_uniforms = AmbientUniforms {
+        irradiance_coefficients: ambient_spherical_harmonics.irradiance_coefficients(),
+        spherical_harmonics: 1,
+    };
+    let ambient_uniform_buffer = device
         .create_buffer_mapped(1, wgpu::BufferUsage::UNIFORM | wgpu::BufferUsage::COPY_DST)
-        .fill_from_slice(&[FragmentUniforms {
-            view_translation: camera.translation.into(),
-            directional_light: DirectionalLight {
-                direction: Vec3::new(-0.2, -1.0, -0.3).into(),
-                ambient: Vec3::new(0.05, 0.05, 0.05).into(),
-                diffuse: Vec3::new(0.1, 0.1, 0.1).into(),
-                specular: Vec3::new(0.1, 0.1, 0.1),
-            },
-            point_lights: [
-                PointLight {
-                    translation: LIGHT_TRANSLATIONS[0].into(),
-                    ambient: Vec3::new(0.05, 0.05, 0.05).into(),
-                    diffuse: Vec3::new(0.1, 0.1, 0.1).into(),
-                    specular: Vec3::new(0.3, 0.3, 0.3),
-                    constant: 1.0,
-                    linear: 0.09,
-                    quadratic: 0.032,
-                },
-                PointLight {
-                    translation: LIGHT_TRANSLATIONS[1].into(),
-                    ambient: Vec3::new(0.05, 0.05, 0.05).into(),
-                    diffuse: Vec3::new(0.1, 0.1, 0.1).into(),
-                    specular: Vec3::new(0.3, 0.3, 0.3),
-                    constant: 1.0,
-                    linear: 0.09,
-                    quadratic: 0.032,
-                },
-                PointLight {
-                    translation: LIGHT_TRANSLATIONS[2].into(),
-                    ambient: Vec3::new(0.05, 0.05, 0.05).into(),
-                    diffuse: Vec3::new(0.1, 0.1, 0.1).into(),
-                    specular: Vec3::new(0.3, 0.3, 0.3),
-                    constant: 1.0,
-                    linear: 0.09,
-                    quadratic: 0.032,
-                },
-                PointLight {
-                    translation: LIGHT_TRANSLATIONS[3].into(),
-                    ambient: Vec3::new(0.05, 0.05, 0.05).into(),
-                    diffuse: Vec3::new(0.1, 0.1, 0.1).into(),
-                    specular: Vec3::new(0.3, 0.3, 0.3),
-                    constant: 1.0,
-                    linear: 0.09,
-                    quadratic: 0.032,
+        .fill_from_slice(&[ambient_uniforms]);
+
+    let uniform_bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+        layout: &uniform_bind_group_layout,
+        bindings: &[
+            wgpu::Binding {
+                binding: 0,
+                resource: wgpu::BindingResource::Buffer {
+                    buffer: &vertex_uniform_buffer,
+                    range: 0..std::mem::size_of::<Mat4>() as wgpu::BufferAddress,
                 },
-            ],
-            spot_light: SpotLight {
-                translation: camera.translation.into(),
-                direction: camera.get_direction_vector(),
-                cutoff: 12.5f32.to_radians().cos(),
-                outer_cutoff: 15.0f32.to_radians().cos(),
-                constant: 1.0,
-                linear: 0.045,
-                quadratic: 0.032,
-                ambient: Vec3::new(0.0, 0.0, 0.0).into(),
-                diffuse: Vec3::new(0.7, 0.7, 0.7).into(),
-                specular: Vec3::new(1.0, 1.0, 1.0),
             },
-        }]);
-
-    let fragment_uniform_bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
-        layout: &fragment_uniform_bind_group_layout,
-        bindings: &[wgpu::Binding {
-            binding: 0,
-            resource: wgpu::BindingResource::Buffer {
-                buffer: &fragment_uniform_buffer,
-                range: 0..std::mem::size_of::<FragmentUniforms>() as wgpu::BufferAddress,
+            wgpu::Binding {
+                binding: 1,
+                resource: wgpu::BindingResource::Buffer {
+                    buffer: &ambient_uniform_buffer,
+                    range: 0..std::mem::size_of::<AmbientUniforms>() as wgpu::BufferAddress,
+                },
             },
-        }],
+        ],
     });
 
-    let material_uniform_buffer = device
-        .create_buffer_mapped(1, wgpu::BufferUsage::UNIFORM | wgpu::BufferUsage::COPY_DST)
-        .fill_from_slice(&[Material { shininess: 32.0 }]);
+    let point_shadow_maps = PointShadowMaps::new(
+        &device,
+        POINT_SHADOW_MAP_SIZE,
+        MAX_POINT_LIGHT_SHADOWS as u32,
+    );
+    let point_shadow_bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+        layout: &point_shadow_bind_group_layout,
+        bindings: &[
+            wgpu::Binding {
+                binding: 0,
+                resource: wgpu::BindingResource::TextureView(&point_shadow_maps.view),
+            },
+            wgpu::Binding {
+                binding: 1,
+                resource: wgpu::BindingResource::Sampler(&point_shadow_maps.sampler),
+            },
+        ],
+    });
 
-    let material_diffuse_texture_source = include_bytes!("container2.png");
-    let material_diffuse_texture_image = image::load_from_memory(material_diffuse_texture_source)
-        .unwrap()
-        .to_rgba();
+    // One uniform buffer for every face of every cube map, since they're all rendered in the same command buffer.
+    let point_shadow_faces: Vec<(wgpu::Buffer, wgpu::BindGroup)> = (0..MAX_POINT_LIGHT_SHADOWS * 6)
+        .map(|_| {
+            let buffer = device
+                .create_buffer_mapped(1, wgpu::BufferUsage::UNIFORM | wgpu::BufferUsage::COPY_DST)
+                .fill_from_slice(&[PointShadowUniforms {
+                    light_space: Mat4::identity(),
+                    light_translation: Vec3::zero(),
+                    far_plane: POINT_SHADOW_FAR_PLANE,
+                }]);
+            let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+                layout: &point_shadow_face_bind_group_layout,
+                bindings: &[wgpu::Binding {
+                    binding: 0,
+                    resource: wgpu::BindingResource::Buffer {
+                        buffer: &buffer,
+                        range: 0..std::mem::size_of::<PointShadowUniforms>() as wgpu::BufferAddress,
+                    },
+                }],
+            });
+            (buffer, bind_group)
+        })
+        .collect();
+
+    let material_diffuse_texture_image = image::open(
+        scene.resolve_path(
+            scene
+                .material
+                .diffuse_texture
+                .as_ref()
+                .expect("The material has no diffuse texture"),
+        ),
+    )
+    .unwrap()
+    .to_rgba();
     let (width, height) = material_diffuse_texture_image.dimensions();
     let material_diffuse_texture_raw = material_diffuse_texture_image.to_vec();
     let (_, material_diffuse_texture_view, material_diffuse_texture_sampler) =
@@ -436,12 +400,20 @@ fn main() {
             &material_diffuse_texture_raw,
             width,
             height,
+            wgpu::TextureFormat::Rgba8UnormSrgb,
         );
 
-    let material_specular_texture_source = include_bytes!("container2_specular.png");
-    let material_specular_texture_image = image::load_from_memory(material_specular_texture_source)
-        .unwrap()
-        .to_rgba();
+    let material_specular_texture_image = image::open(
+        scene.resolve_path(
+            scene
+                .material
+                .specular_texture
+                .as_ref()
+                .expect("The material has no specular texture"),
+        ),
+    )
+    .unwrap()
+    .to_rgba();
     let (width, height) = material_specular_texture_image.dimensions();
     let material_specular_texture_raw = material_specular_texture_image.to_vec();
     let (_, material_specular_texture_view, material_specular_texture_sampler) =
@@ -451,6 +423,24 @@ fn main() {
             &material_specular_texture_raw,
             width,
             height,
+            wgpu::TextureFormat::Rgba8Unorm,
+        );
+
+    let material_normal_texture_image = match &scene.material.normal_texture {
+        Some(path) => image::open(scene.resolve_path(path)).unwrap().to_rgba(),
+        // A flat normal map leaves the vertex normals as they are.
+        None => image::RgbaImage::from_pixel(1, 1, image::Rgba([128, 128, 255, 255])),
+    };
+    let (width, height) = material_normal_texture_image.dimensions();
+    let material_normal_texture_raw = material_normal_texture_image.to_vec();
+    let (_, material_normal_texture_view, material_normal_texture_sampler) =
+        create_sampled_texture2d(
+            &device,
+            &mut init_encoder,
+            &material_normal_texture_raw,
+            width,
+            height,
+            wgpu::TextureFormat::Rgba8Unorm,
         );
 
     let material_bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
@@ -479,12 +469,20 @@ fn main() {
                 binding: 4,
                 resource: wgpu::BindingResource::Sampler(&material_specular_texture_sampler),
             },
+            wgpu::Binding {
+                binding: 5,
+                resource: wgpu::BindingResource::TextureView(&material_normal_texture_view),
+            },
+            wgpu::Binding {
+                binding: 6,
+                resource: wgpu::BindingResource::Sampler(&material_normal_texture_sampler),
+            },
         ],
     });
 
     let mut swap_chain_descriptor = wgpu::SwapChainDescriptor {
         usage: wgpu::TextureUsage::OUTPUT_ATTACHMENT,
-        format: wgpu::TextureFormat::Bgra8Unorm,
+        format: wgpu::TextureFormat::Bgra8UnormSrgb,
         width: size.width,
         height: size.height,
         present_mode: wgpu::PresentMode::Vsync,
@@ -495,9 +493,10 @@ fn main() {
 
     let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
         bind_group_layouts: &[
-            &vertex_uniform_bind_group_layout,
-            &fragment_uniform_bind_group_layout,
+            &uniform_bind_group_layout,
+            &light_bind_group_layout,
             &material_bind_group_layout,
+            &point_shadow_bind_group_layout,
         ],
     });
 
@@ -555,6 +554,12 @@ fn main() {
                         format: wgpu::VertexFormat::Float2,
                         shader_location: 2,
                     },
+                    wgpu::VertexAttributeDescriptor {
+                        offset: (std::mem::size_of::<Vec3>() * 2 + std::mem::size_of::<Vec2>())
+                            as wgpu::BufferAddress,
+                        format: wgpu::VertexFormat::Float4,
+                        shader_location: 10,
+                    },
                 ],
             },
             wgpu::VertexBufferDescriptor {
@@ -581,6 +586,23 @@ fn main() {
                         format: wgpu::VertexFormat::Float4,
                         shader_location: 6,
                     },
+                    wgpu::VertexAttributeDescriptor {
+                        offset: std::mem::size_of::<Mat4>() as wgpu::BufferAddress,
+                        format: wgpu::VertexFormat::Float3,
+                        shader_location: 7,
+                    },
+                    wgpu::VertexAttributeDescriptor {
+                        offset: (std::mem::size_of::<Mat4>() + std::mem::size_of::<Vec3>())
+                            as wgpu::BufferAddress,
+                        format: wgpu::VertexFormat::Float3,
+                        shader_location: 8,
+                    },
+                    wgpu::VertexAttributeDescriptor {
+                        offset: (std::mem::size_of::<Mat4>() + std::mem::size_of::<Vec3>() * 2)
+                            as wgpu::BufferAddress,
+                        format: wgpu::VertexFormat::Float3,
+                        shader_location: 9,
+                    },
                 ],
             },
         ],
@@ -592,7 +614,7 @@ fn main() {
 
     let light_source_pipeline_layout =
         device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
-            bind_group_layouts: &[&vertex_uniform_bind_group_layout],
+            bind_group_layouts: &[&uniform_bind_group_layout],
         });
 
     let mut light_source_pipeline_descriptor = render_pipeline_descriptor.clone();
@@ -607,10 +629,40 @@ fn main() {
     });
     let light_source_pipeline = device.create_render_pipeline(&light_source_pipeline_descriptor);
 
+    let point_shadow_pipeline_layout =
+        device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            bind_group_layouts: &[&point_shadow_face_bind_group_layout],
+        });
+
+    let mut point_shadow_pipeline_descriptor = render_pipeline_descriptor.clone();
+    point_shadow_pipeline_descriptor.layout = &point_shadow_pipeline_layout;
+    point_shadow_pipeline_descriptor.vertex_stage = wgpu::ProgrammableStageDescriptor {
+        module: &point_shadow_vs_module,
+        entry_point: "main",
+    };
+    point_shadow_pipeline_descriptor.fragment_stage = Some(wgpu::ProgrammableStageDescriptor {
+        module: &point_shadow_fs_module,
+        entry_point: "main",
+    });
+    point_shadow_pipeline_descriptor.color_states = &[];
+    point_shadow_pipeline_descriptor.depth_stencil_state =
+        Some(wgpu::DepthStencilStateDescriptor {
+            format: SHADOW_MAP_FORMAT,
+            depth_write_enabled: true,
+            depth_compare: wgpu::CompareFunction::Less,
+            stencil_front: wgpu::StencilStateFaceDescriptor::IGNORE,
+            stencil_back: wgpu::StencilStateFaceDescriptor::IGNORE,
+            stencil_read_mask: 0,
+            stencil_write_mask: 0,
+        });
+    let point_shadow_pipeline = device.create_render_pipeline(&point_shadow_pipeline_descriptor);
+
     let mut swap_chain = device.create_swap_chain(&surface, &swap_chain_descriptor);
 
     queue.submit(&[init_encoder.finish()]);
 
+    let mut time = Time::new();
+
     event_loop.run(move |event, _, control_flow| {
         input_handler.send_event(&event, &mut event_channel);
 
@@ -666,6 +718,127 @@ fn main() {
                 window.set_cursor_visible(true);
                 *control_flow = ControlFlow::Poll;
             }
+            Event::WindowEvent {
+                event:
+                    WindowEvent::KeyboardInput {
+                        input:
+                            KeyboardInput {
+                                virtual_keycode: Some(VirtualKeyCode::B),
+                                state: winit::event::ElementState::Pressed,
+                                ..
+                            },
+                        ..
+                    },
+                ..
+            } => {
+                let specular_model = match light_manager.specular_model() {
+                    SpecularModel::Phong => SpecularModel::BlinnPhong,
+                    SpecularModel::BlinnPhong => SpecularModel::Phong,
+                };
+                light_manager.set_specular_model(specular_model);
+                *control_flow = ControlFlow::Poll;
+            }
+            Event::WindowEvent {
+                event:
+                    WindowEvent::KeyboardInput {
+                        input:
+                            KeyboardInput {
+                                virtual_keycode: Some(VirtualKeyCode::L),
+                                state: winit::event::ElementState::Pressed,
+                                ..
+                            },
+                        ..
+                    },
+                ..
+            } => {
+                let translation = camera.world_translation();
+                light_manager.add_point_light(
+                    PointLight {
+                        translation: translation.into(),
+                        ambient: Vec3::new(0.05, 0.05, 0.05).into(),
+                        diffuse: Vec3::new(0.8, 0.8, 0.8).into(),
+                        specular: Vec3::new(1.0, 1.0, 1.0),
+                        constant: 1.0,
+                        linear: 0.09,
+                        quadratic: 0.032,
+                        shadow_map_index: -1,
+                    },
+                    true,
+                );
+                *control_flow = ControlFlow::Poll;
+            }
+            Event::WindowEvent {
+                event:
+                    WindowEvent::KeyboardInput {
+                        input:
+                            KeyboardInput {
+                                virtual_keycode: Some(VirtualKeyCode::Back),
+                                state: winit::event::ElementState::Pressed,
+                                ..
+                            },
+                        ..
+                    },
+                ..
+            } => {
+                if let Some(last) = light_manager.point_lights().len().checked_sub(1) {
+                    light_manager.remove_point_light(last);
+                }
+                *control_flow = ControlFlow::Poll;
+            }
+            Event::WindowEvent {
+                event:
+                    WindowEvent::KeyboardInput {
+                        input:
+                            KeyboardInput {
+                                virtual_keycode: Some(VirtualKeyCode::G),
+                                state: winit::event::ElementState::Pressed,
+                                ..
+                            },
+                        ..
+                    },
+                ..
+            } => {
+                if let Some(last) = light_manager.point_lights().len().checked_sub(1) {
+                    light_manager.point_light_mut(last).translation =
+                        camera.world_translation().into();
+                }
+                *control_flow = ControlFlow::Poll;
+            }
+            Event::WindowEvent {
+                event:
+                    WindowEvent::KeyboardInput {
+                        input:
+                            KeyboardInput {
+                                virtual_keycode: Some(VirtualKeyCode::H),
+                                state: winit::event::ElementState::Pressed,
+                                ..
+                            },
+                        ..
+                    },
+                ..
+            } => {
+                if let Some(last) = light_manager.point_lights().len().checked_sub(1) {
+                    let casts_shadows = !light_manager.point_light_casts_shadows(last);
+                    light_manager.set_point_light_casts_shadows(last, casts_shadows);
+                }
+                *control_flow = ControlFlow::Poll;
+            }
+            Event::WindowEvent {
+                event:
+                    WindowEvent::KeyboardInput {
+                        input:
+                            KeyboardInput {
+                                virtual_keycode: Some(VirtualKeyCode::E),
+                                state: winit::event::ElementState::Pressed,
+                                ..
+                            },
+                        ..
+                    },
+                ..
+            } => {
+                ambient_uniforms.spherical_harmonics = 1 - ambient_uniforms.spherical_harmonics;
+                *control_flow = ControlFlow::Poll;
+            }
             Event::MainEventsCleared => {
                 window.request_redraw();
                 *control_flow = ControlFlow::Poll;
@@ -674,6 +847,9 @@ fn main() {
                 input_handler.send_frame_begin();
                 camera.update(&input_handler);
 
+                time.update();
+                light_manager.animate(time.elapsed());
+
                 let frame = swap_chain.get_next_texture();
                 let mut encoder =
                     device.create_command_encoder(&wgpu::CommandEncoderDescriptor { todo: 0 });
@@ -685,16 +861,8 @@ fn main() {
                         100.0,
                     );
                     let staging_buffer = device
-                        .create_buffer_mapped(6, wgpu::BufferUsage::COPY_SRC)
-                        .fill_from_slice(&[
-                            view_projection[0],
-                            view_projection[1],
-                            view_projection[2],
-                            view_projection[3],
-                            // TODO: Figure out why I need to invert this
-                            -camera.translation.into_homogeneous_vector(),
-                            camera.get_direction_vector().into_homogeneous_vector(),
-                        ]);
+                        .create_buffer_mapped(1, wgpu::BufferUsage::COPY_SRC)
+                        .fill_from_slice(&[view_projection]);
                     encoder.copy_buffer_to_buffer(
                         &staging_buffer,
                         0,
@@ -702,28 +870,63 @@ fn main() {
                         0,
                         std::mem::size_of::<Mat4>() as wgpu::BufferAddress,
                     );
-                    encoder.copy_buffer_to_buffer(
-                        &staging_buffer,
-                        std::mem::size_of::<Mat4>() as wgpu::BufferAddress,
-                        &fragment_uniform_buffer,
-                        0,
-                        std::mem::size_of::<Vec4>() as wgpu::BufferAddress,
-                    );
-                    encoder.copy_buffer_to_buffer(
-                        &staging_buffer,
-                        std::mem::size_of::<Mat4>() as wgpu::BufferAddress,
-                        &fragment_uniform_buffer,
-                        400,
-                        std::mem::size_of::<Vec4>() as wgpu::BufferAddress,
-                    );
-                    encoder.copy_buffer_to_buffer(
-                        &staging_buffer,
-                        (std::mem::size_of::<Mat4>() + std::mem::size_of::<Vec4>())
-                            as wgpu::BufferAddress,
-                        &fragment_uniform_buffer,
-                        400 + std::mem::size_of::<Vec4>() as wgpu::BufferAddress,
-                        std::mem::size_of::<Vec3>() as wgpu::BufferAddress,
+                    copy_to_buffer(
+                        &device,
+                        &mut encoder,
+                        &[ambient_uniforms],
+                        &ambient_uniform_buffer,
                     );
+                    light_manager.update(&device, &mut encoder, &light_bind_group_layout, &camera);
+
+                    for light in light_manager.point_lights() {
+                        if light.shadow_map_index < 0 {
+                            continue;
+                        }
+                        let index = light.shadow_map_index as usize;
+                        let face_spaces = shadow::point_light_face_spaces(
+                            light.translation.0,
+                            POINT_SHADOW_NEAR_PLANE,
+                            POINT_SHADOW_FAR_PLANE,
+                        );
+                        for (face, &light_space) in face_spaces.iter().enumerate() {
+                            let (uniform_buffer, bind_group) =
+                                &point_shadow_faces[index * 6 + face];
+                            copy_to_buffer(
+                                &device,
+                                &mut encoder,
+                                &[PointShadowUniforms {
+                                    light_space,
+                                    light_translation: light.translation.0,
+                                    far_plane: POINT_SHADOW_FAR_PLANE,
+                                }],
+                                uniform_buffer,
+                            );
+
+                            let mut shadow_pass =
+                                encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                                    color_attachments: &[],
+                                    depth_stencil_attachment: Some(
+                                        wgpu::RenderPassDepthStencilAttachmentDescriptor {
+                                            attachment: point_shadow_maps.face_view(index, face),
+                                            depth_load_op: wgpu::LoadOp::Clear,
+                                            depth_store_op: wgpu::StoreOp::Store,
+                                            clear_depth: 1.0,
+                                            stencil_load_op: wgpu::LoadOp::Clear,
+                                            stencil_store_op: wgpu::StoreOp::Store,
+                                            clear_stencil: 0,
+                                        },
+                                    ),
+                                });
+                            shadow_pass.set_pipeline(&point_shadow_pipeline);
+                            shadow_pass.set_bind_group(0, bind_group, &[]);
+                            shadow_pass.set_vertex_buffers(
+                                0,
+                                &[(&vertex_buffer, 0), (&cube_instance_buffer, 0)],
+                            );
+                            shadow_pass.set_index_buffer(&index_buffer, 0);
+                            shadow_pass.draw_indexed(0..index_count, 0, 0..cube_count);
+                        }
+                    }
 
                     let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
                         color_attachments: &[wgpu::RenderPassColorAttachmentDescriptor {
@@ -751,20 +954,31 @@ fn main() {
                         ),
                     });
                     render_pass.set_pipeline(&pipeline);
-                    render_pass.set_bind_group(0, &vertex_uniform_bind_group, &[]);
-                    render_pass.set_bind_group(1, &fragment_uniform_bind_group, &[]);
+                    render_pass.set_bind_group(0, &uniform_bind_group, &[]);
+                    render_pass.set_bind_group(1, light_manager.bind_group(), &[]);
                     render_pass.set_bind_group(2, &material_bind_group, &[]);
+                    render_pass.set_bind_group(3, &point_shadow_bind_group, &[]);
                     render_pass
                         .set_vertex_buffers(0, &[(&vertex_buffer, 0), (&cube_instance_buffer, 0)]);
-                    render_pass.draw(0..VERTICES.len() as u32, 0..CUBES.len() as u32);
+                    render_pass.set_index_buffer(&index_buffer, 0);
+                    render_pass.draw_indexed(0..index_count, 0, 0..cube_count);
 
                     render_pass.set_pipeline(&light_source_pipeline);
-                    render_pass.set_bind_group(0, &vertex_uniform_bind_group, &[]);
+                    render_pass.set_bind_group(0, &uniform_bind_group, &[]);
+                    let light_source_instance_buffer = light_manager.light_source_instance_buffer();
                     render_pass.set_vertex_buffers(
                         0,
-                        &[(&vertex_buffer, 0), (&light_source_instance_buffer, 0)],
+                        &[
+                            (&vertex_buffer, 0),
+                            (light_source_instance_buffer.buffer(), 0),
+                        ],
+                    );
+                    render_pass.set_index_buffer(&index_buffer, 0);
+                    render_pass.draw_indexed(
+                        0..index_count,
+                        0,
+                        0..light_source_instance_buffer.len() as u32,
                     );
-                    render_pass.draw(0..VERTICES.len() as u32, 0..LIGHT_SOURCES.len() as u32);
                 }
 
                 queue.submit(&[encoder.finish()]);
@@ -776,6 +990,24 @@ fn main() {
     })
 }
 
+fn copy_to_buffer<T: 'static + Copy>(
+    device: &wgpu::Device,
+    encoder: &mut wgpu::CommandEncoder,
+    data: &[T],
+    buffer: &wgpu::Buffer,
+) {
+    let staging_buffer = device
+        .create_buffer_mapped(data.len(), wgpu::BufferUsage::COPY_SRC)
+        .fill_from_slice(data);
+    encoder.copy_buffer_to_buffer(
+        &staging_buffer,
+        0,
+        buffer,
+        0,
+        (std::mem::size_of::<T>() * data.len()) as wgpu::BufferAddress,
+    );
+}
+
 pub fn prepare_shader(
     compiler: &mut Compiler,
     source_text: &str,
@@ -803,12 +1035,15 @@ pub fn create_depth_texture(
     (texture, view)
 }
 
+/// Uploads an RGBA texture. Color textures should use an sRGB `format`, so that they're decoded into linear space
+/// when sampled, while textures holding other data like specular intensities or normals have to use a linear one.
 pub fn create_sampled_texture2d(
     device: &wgpu::Device,
     encoder: &mut wgpu::CommandEncoder,
     raw_texture: &[u8],
     width: u32,
     height: u32,
+    format: wgpu::TextureFormat,
 ) -> (wgpu::Texture, wgpu::TextureView, wgpu::Sampler) {
     let texture_extent = wgpu::Extent3d {
         width,
@@ -825,7 +1060,7 @@ pub fn create_sampled_texture2d(
         mip_level_count: 0,
         sample_count: 1,
         dimension: wgpu::TextureDimension::D2,
-        format: wgpu::TextureFormat::Rgba8Unorm,
+        format,
         usage: wgpu::TextureUsage::SAMPLED | wgpu::TextureUsage::COPY_DST,
     });
 
@@ -871,66 +1106,26 @@ pub struct Vertex {
     pub translation: Vec3,
     pub normal: Vec3,
     pub uv: Vec2,
+    /// The direction of increasing `u` texture coordinates, with the handedness of the bitangent in W.
+    pub tangent: Vec4,
 }
 
-#[repr(C)]
-#[derive(Clone, Copy, Debug)]
-pub struct Instance {
-    pub model: Mat4,
-}
-
-#[repr(C)]
-#[derive(Clone, Copy, Debug)]
-pub struct FragmentUniforms {
-    pub view_translation: Vec3,
-    pub directional_light: DirectionalLight,
-    pub point_lights: [PointLight; 4],
-    pub spot_light: SpotLight,
-}
-
-#[repr(C)]
-#[derive(Clone, Copy, Debug)]
-pub struct Material {
-    pub shininess: f32,
-}
-
-#[repr(C)]
-#[derive(Clone, Copy, Debug)]
-pub struct DirectionalLight {
-    pub direction: Align16<Vec3>,
-
-    pub ambient: Align16<Vec3>,
-    pub diffuse: Align16<Vec3>,
-    pub specular: Vec3,
-}
-
-#[repr(C)]
-#[derive(Clone, Copy, Debug)]
-pub struct PointLight {
-    pub translation: Align16<Vec3>,
-
-    pub ambient: Align16<Vec3>,
-    pub diffuse: Align16<Vec3>,
-    pub specular: Vec3,
-
-    pub constant: f32,
-    pub linear: f32,
-    pub quadratic: f32,
-}
-
-#[repr(C)]
-#[derive(Clone, Copy, Debug)]
-pub struct SpotLight {
-    pub translation: Align16<Vec3>,
-    pub direction: Vec3,
-    pub cutoff: f32,
-    pub outer_cutoff: f32,
-
-    pub constant: f32,
-    pub linear: f32,
-    pub quadratic: f32,
-
-    pub ambient: Align16<Vec3>,
-    pub diffuse: Align16<Vec3>,
-    pub specular: Vec3,
+impl Vertex {
+    /// The bit patterns of every attribute, so that only exact duplicates get welded together.
+    fn weld_key(&self) -> [u32; 12] {
+        [
+            self.translation.x.to_bits(),
+            self.translation.y.to_bits(),
+            self.translation.z.to_bits(),
+            self.normal.x.to_bits(),
+            self.normal.y.to_bits(),
+            self.normal.z.to_bits(),
+            self.uv.x.to_bits(),
+            self.uv.y.to_bits(),
+            self.tangent.x.to_bits(),
+            self.tangent.y.to_bits(),
+            self.tangent.z.to_bits(),
+            self.tangent.w.to_bits(),
+        ]
+    }
 }
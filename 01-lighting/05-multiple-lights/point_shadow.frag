@@ -0,0 +1,16 @@
+#version 450
+
+layout(location = 0) in vec3 frag_translation;
+
+layout(set = 0, binding = 0) uniform Uniforms {
+    mat4 light_space;
+    vec3 light_translation;
+    float far_plane;
+};
+
+void main()
+{
+    // Store the linear distance to the light instead of the projected depth, so that every face of the cube map
+    // holds comparable values.
+    gl_FragDepth = length(frag_translation - light_translation) / far_plane;
+}
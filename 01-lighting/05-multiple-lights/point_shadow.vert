@@ -0,0 +1,19 @@
+#version 450
+
+layout(location = 0) in vec3 translation;
+
+layout(location = 3) in mat4 model;
+
+layout(location = 0) out vec3 frag_translation;
+
+layout(set = 0, binding = 0) uniform Uniforms {
+    mat4 light_space;
+    vec3 light_translation;
+    float far_plane;
+};
+
+void main()
+{
+    frag_translation = vec3(model * vec4(translation, 1.0));
+    gl_Position = light_space * vec4(frag_translation, 1.0);
+}
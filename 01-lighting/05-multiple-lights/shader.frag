@@ -1,10 +1,15 @@
 #version 450
 
-#define POINT_LIGHT_COUNT 4
+#define SPECULAR_MODEL_PHONG 0
+#define SPECULAR_MODEL_BLINN_PHONG 1
+
+// Must match `POINT_SHADOW_FAR_PLANE` in `main.rs`.
+#define POINT_SHADOW_FAR_PLANE 25.0
 
 layout(location = 0) in vec3 translation;
 layout(location = 1) in vec3 normal;
 layout(location = 2) in vec2 uv;
+layout(location = 3) in vec4 tangent;
 
 layout(location = 0) out vec4 fragment_color;
 
@@ -26,6 +31,8 @@ struct PointLight {
     float constant;
     float linear;
     float quadratic;
+
+    int shadow_map_index;
 };
 
 struct SpotLight {
@@ -41,53 +48,174 @@ struct SpotLight {
     vec3 ambient;
     vec3 diffuse;
     vec3 specular;
+
+    int cookie_index;
+    mat4 cookie_space;
+};
+
+// The spherical harmonics of the light arriving from the surroundings, convolved for diffuse surfaces. Only the first
+// three components of each coefficient are used.
+layout(set = 0, binding = 1) uniform Ambient {
+    vec4 irradiance_coefficients[9];
+    uint spherical_harmonics;
 };
 
 layout(set = 1, binding = 0) uniform Uniforms {
     vec3 view_translation;
+    uint point_light_count;
+    uint spot_light_count;
+    uint specular_model;
     DirectionalLight directional_light;
-    PointLight point_lights[POINT_LIGHT_COUNT];
-    SpotLight spot_light;
 };
+layout(set = 1, binding = 1, std430) readonly buffer PointLights {
+    PointLight point_lights[];
+};
+layout(set = 1, binding = 2, std430) readonly buffer SpotLights {
+    SpotLight spot_lights[];
+};
+layout(set = 1, binding = 3) uniform texture2DArray spot_light_cookies;
+layout(set = 1, binding = 4) uniform sampler spot_light_cookie_sampler;
 
 layout(set = 2, binding = 0) uniform Material {
+    vec3 ambient;
+    vec3 diffuse;
+    vec3 specular;
     float shininess;
 } material;
 layout(set = 2, binding = 1) uniform texture2D material_diffuse_texture;
 layout(set = 2, binding = 2) uniform sampler material_diffuse_sampler;
 layout(set = 2, binding = 3) uniform texture2D material_specular_texture;
 layout(set = 2, binding = 4) uniform sampler material_specular_sampler;
-
+layout(set = 2, binding = 5) uniform texture2D material_normal_texture;
+layout(set = 2, binding = 6) uniform sampler material_normal_sampler;
+
+layout(set = 3, binding = 0) uniform textureCubeArray point_shadow_maps;
+layout(set = 3, binding = 1) uniform sampler shadow_sampler;
+
+// Offsets spread around the lookup direction when filtering the point light shadows. Directions along the diagonals
+// are used since offsets along the lookup direction itself would make no difference.
+const vec3 point_shadow_offsets[20] = vec3[](
+    vec3(1, 1, 1), vec3(1, -1, 1), vec3(-1, -1, 1), vec3(-1, 1, 1),
+    vec3(1, 1, -1), vec3(1, -1, -1), vec3(-1, -1, -1), vec3(-1, 1, -1),
+    vec3(1, 1, 0), vec3(1, -1, 0), vec3(-1, -1, 0), vec3(-1, 1, 0),
+    vec3(1, 0, 1), vec3(-1, 0, 1), vec3(1, 0, -1), vec3(-1, 0, -1),
+    vec3(0, 1, 1), vec3(0, -1, 1), vec3(0, -1, -1), vec3(0, 1, -1)
+);
+
+vec3 calculate_normal();
+vec3 calculate_irradiance(vec3 normal);
+float calculate_specular(vec3 light_direction, vec3 normal, vec3 view_direction);
 vec3 calculate_directional_light(DirectionalLight light, vec3 normal, vec3 view_direction);
+float calculate_point_shadow(PointLight light, vec3 frag_translation);
 vec3 calculate_point_light(PointLight light, vec3 normal, vec3 frag_translation, vec3 view_direction);
+vec3 calculate_spot_light_cookie(SpotLight light, vec3 frag_translation);
 vec3 calculate_spot_light(SpotLight light, vec3 normal, vec3 frag_translation, vec3 view_direction);
 
 void main()
 {
-    vec3 normalized_normal = normalize(normal);
+    vec3 normalized_normal = calculate_normal();
     vec3 view_direction = normalize(view_translation - translation);
 
     vec3 result = calculate_directional_light(directional_light, normalized_normal, view_direction);
-    for (int i = 0; i < POINT_LIGHT_COUNT; i++) {
+    for (uint i = 0; i < point_light_count; i++) {
         result += calculate_point_light(point_lights[i], normalized_normal, translation, view_direction);
     }
-    result += calculate_spot_light(spot_light, normalized_normal, translation, view_direction);
+    for (uint i = 0; i < spot_light_count; i++) {
+        result += calculate_spot_light(spot_lights[i], normalized_normal, translation, view_direction);
+    }
 
     fragment_color = vec4(result, 1.0);
 }
 
+// Transforms the normal map's normal from tangent space into world space. A flat normal map pointing straight along
+// the Z axis reproduces the vertex normal.
+vec3 calculate_normal()
+{
+    vec3 n = normalize(normal);
+    // Interpolation leaves the tangent slightly off perpendicular to the normal, so it's straightened out again.
+    vec3 t = normalize(tangent.xyz - dot(tangent.xyz, n) * n);
+    // The sign in W flips the bitangent for mirrored texture coordinates.
+    vec3 b = tangent.w * cross(n, t);
+
+    vec3 tangent_space_normal = texture(sampler2D(material_normal_texture, material_normal_sampler), uv).rgb * 2.0 - 1.0;
+    return normalize(mat3(t, b, n) * tangent_space_normal);
+}
+
+// Returns how much of the fragment is in the light's shadow, from 0.0 (fully lit) to 1.0 (fully shadowed).
+float calculate_point_shadow(PointLight light, vec3 frag_translation)
+{
+    if (light.shadow_map_index < 0) {
+        return 0.0;
+    }
+
+    vec3 light_to_fragment = frag_translation - light.translation;
+    float current_distance = length(light_to_fragment) / POINT_SHADOW_FAR_PLANE;
+    if (current_distance > 1.0) {
+        return 0.0;
+    }
+
+    // The shadow pass writes its own depths, which the pipeline's depth bias doesn't apply to, so the bias is applied
+    // here instead.
+    float bias = 0.05 / POINT_SHADOW_FAR_PLANE;
+    // Blur the shadows more the further away they are from the viewer, where the blur is less noticeable.
+    float disk_radius = (1.0 + length(view_translation - frag_translation) / POINT_SHADOW_FAR_PLANE) / 25.0;
+
+    float lit = 0.0;
+    for (int i = 0; i < 20; i++) {
+        vec3 direction = light_to_fragment + point_shadow_offsets[i] * disk_radius;
+        lit += texture(
+            samplerCubeArrayShadow(point_shadow_maps, shadow_sampler),
+            vec4(direction, float(light.shadow_map_index)),
+            current_distance - bias
+        );
+    }
+    return 1.0 - lit / 20.0;
+}
+
+// Evaluates the spherical harmonics in the direction of the normal. The constants must match `basis` in
+// `spherical_harmonics.rs`.
+vec3 calculate_irradiance(vec3 normal)
+{
+    float x = normal.x;
+    float y = normal.y;
+    float z = normal.z;
+    return irradiance_coefficients[0].rgb * 0.282095
+        + irradiance_coefficients[1].rgb * 0.488603 * y
+        + irradiance_coefficients[2].rgb * 0.488603 * z
+        + irradiance_coefficients[3].rgb * 0.488603 * x
+        + irradiance_coefficients[4].rgb * 1.092548 * x * y
+        + irradiance_coefficients[5].rgb * 1.092548 * y * z
+        + irradiance_coefficients[6].rgb * 0.315392 * (3.0 * z * z - 1.0)
+        + irradiance_coefficients[7].rgb * 1.092548 * x * z
+        + irradiance_coefficients[8].rgb * 0.546274 * (x * x - y * y);
+}
+
+float calculate_specular(vec3 light_direction, vec3 normal, vec3 view_direction)
+{
+    if (specular_model == SPECULAR_MODEL_BLINN_PHONG) {
+        vec3 halfway_direction = normalize(light_direction + view_direction);
+        return pow(max(dot(normal, halfway_direction), 0.0), material.shininess);
+    }
+
+    vec3 reflect_direction = reflect(-light_direction, normal);
+    return pow(max(dot(view_direction, reflect_direction), 0.0), material.shininess);
+}
+
 vec3 calculate_directional_light(DirectionalLight light, vec3 normal, vec3 view_direction)
 {
     vec3 light_direction = normalize(-light.direction);
 
     float diff = max(dot(normal, light_direction), 0.0);
 
-    vec3 reflect_direction = reflect(-light_direction, normal);
-    float spec = pow(max(dot(view_direction, reflect_direction), 0.0), material.shininess);
+    float spec = calculate_specular(light_direction, normal, view_direction);
+
+    // The light from the surroundings varies with the normal, unlike the constant ambient colour. It stands in for the
+    // directional light's ambient colour since both describe light arriving from everywhere.
+    vec3 ambient_light = spherical_harmonics != 0u ? calculate_irradiance(normal) : light.ambient;
 
-    vec3 ambient = light.ambient * vec3(texture(sampler2D(material_diffuse_texture, material_diffuse_sampler), uv));
-    vec3 diffuse = light.diffuse * diff * vec3(texture(sampler2D(material_diffuse_texture, material_diffuse_sampler), uv));
-    vec3 specular = light.specular * spec * vec3(texture(sampler2D(material_specular_texture, material_specular_sampler), uv));
+    vec3 ambient = ambient_light * material.ambient * vec3(texture(sampler2D(material_diffuse_texture, material_diffuse_sampler), uv));
+    vec3 diffuse = light.diffuse * diff * material.diffuse * vec3(texture(sampler2D(material_diffuse_texture, material_diffuse_sampler), uv));
+    vec3 specular = light.specular * spec * material.specular * vec3(texture(sampler2D(material_specular_texture, material_specular_sampler), uv));
     return (ambient + diffuse + specular);
 }
 
@@ -97,19 +225,43 @@ vec3 calculate_point_light(PointLight light, vec3 normal, vec3 frag_translation,
 
     float diff = max(dot(normal, light_direction), 0.0);
 
-    vec3 reflect_direction = reflect(-light_direction, normal);
-    float spec = pow(max(dot(view_direction, reflect_direction), 0.0), material.shininess);
+    float spec = calculate_specular(light_direction, normal, view_direction);
 
     float distance = length(light.translation - frag_translation);
     float attenuation = 1.0 / (light.constant + light.linear * distance + light.quadratic * (distance * distance));
 
-    vec3 ambient = light.ambient * vec3(texture(sampler2D(material_diffuse_texture, material_diffuse_sampler), uv));
-    vec3 diffuse = light.diffuse * vec3(texture(sampler2D(material_diffuse_texture, material_diffuse_sampler), uv));
-    vec3 specular = light.specular * spec * vec3(texture(sampler2D(material_specular_texture, material_specular_sampler), uv));
+    float shadow = calculate_point_shadow(light, frag_translation);
+
+    vec3 ambient = light.ambient * material.ambient * vec3(texture(sampler2D(material_diffuse_texture, material_diffuse_sampler), uv));
+    vec3 diffuse = light.diffuse * diff * material.diffuse * vec3(texture(sampler2D(material_diffuse_texture, material_diffuse_sampler), uv));
+    vec3 specular = light.specular * spec * material.specular * vec3(texture(sampler2D(material_specular_texture, material_specular_sampler), uv));
     ambient *= attenuation;
     diffuse *= attenuation;
     specular *= attenuation;
-    return (ambient + diffuse + specular);
+    return (ambient + (1.0 - shadow) * (diffuse + specular));
+}
+
+// The color of the spot light's cookie where it's projected onto the fragment, or white if the light has none. No light
+// makes it past the edges of the cookie.
+vec3 calculate_spot_light_cookie(SpotLight light, vec3 frag_translation)
+{
+    if (light.cookie_index < 0) {
+        return vec3(1.0);
+    }
+
+    vec4 cookie_clip_translation = light.cookie_space * vec4(frag_translation, 1.0);
+    // Behind the light, the projection would show the cookie again upside down.
+    if (cookie_clip_translation.w <= 0.0) {
+        return vec3(0.0);
+    }
+    vec2 cookie_ndc = cookie_clip_translation.xy / cookie_clip_translation.w;
+    // The cookie's top row is on the light's up side.
+    vec2 cookie_uv = vec2(cookie_ndc.x * 0.5 + 0.5, 0.5 - cookie_ndc.y * 0.5);
+    if (any(lessThan(cookie_uv, vec2(0.0))) || any(greaterThan(cookie_uv, vec2(1.0)))) {
+        return vec3(0.0);
+    }
+    vec3 cookie_coordinates = vec3(cookie_uv, light.cookie_index);
+    return texture(sampler2DArray(spot_light_cookies, spot_light_cookie_sampler), cookie_coordinates).rgb;
 }
 
 vec3 calculate_spot_light(SpotLight light, vec3 normal, vec3 frag_translation, vec3 view_direction)
@@ -118,8 +270,7 @@ vec3 calculate_spot_light(SpotLight light, vec3 normal, vec3 frag_translation, v
 
     float diff = max(dot(normal, light_direction), 0.0);
 
-    vec3 reflect_direction = reflect(-light_direction, normal);
-    float spec = pow(max(dot(view_direction, reflect_direction), 0.0), material.shininess);
+    float spec = calculate_specular(light_direction, normal, view_direction);
 
     float distance = length(light.translation - frag_translation);
     float attenuation = 1.0 / (light.constant + light.linear * distance + light.quadratic * (distance * distance));
@@ -127,12 +278,13 @@ vec3 calculate_spot_light(SpotLight light, vec3 normal, vec3 frag_translation, v
     float theta = dot(light_direction, normalize(-light.direction));
     float epsilon = light.cutoff - light.outer_cutoff;
     float intensity = clamp((theta - light.outer_cutoff) / epsilon, 0.0, 1.0);
-
-    vec3 ambient = light.ambient * vec3(texture(sampler2D(material_diffuse_texture, material_diffuse_sampler), uv));
-    vec3 diffuse = light.diffuse * diff * vec3(texture(sampler2D(material_diffuse_texture, material_diffuse_sampler), uv));
-    vec3 specular = light.specular * spec * vec3(texture(sampler2D(material_specular_texture, material_specular_sampler), uv));
-    ambient *= attenuation * intensity;
-    diffuse *= attenuation * intensity;
-    specular *= attenuation * intensity;
+    vec3 cookie = calculate_spot_light_cookie(light, frag_translation);
+
+    vec3 ambient = light.ambient * material.ambient * vec3(texture(sampler2D(material_diffuse_texture, material_diffuse_sampler), uv));
+    vec3 diffuse = light.diffuse * diff * material.diffuse * vec3(texture(sampler2D(material_diffuse_texture, material_diffuse_sampler), uv));
+    vec3 specular = light.specular * spec * material.specular * vec3(texture(sampler2D(material_specular_texture, material_specular_sampler), uv));
+    ambient *= attenuation * intensity * cookie;
+    diffuse *= attenuation * intensity * cookie;
+    specular *= attenuation * intensity * cookie;
     return (ambient + diffuse + specular);
 }
@@ -3,12 +3,15 @@
 layout(location = 0) in vec3 translation;
 layout(location = 1) in vec3 normal;
 layout(location = 2) in vec2 uv;
+layout(location = 10) in vec4 tangent;
 
 layout(location = 3) in mat4 model;
+layout(location = 7) in mat3 normal_matrix;
 
 layout(location = 0) out vec3 frag_translation;
 layout(location = 1) out vec3 frag_normal;
 layout(location = 2) out vec2 frag_uv;
+layout(location = 3) out vec4 frag_tangent;
 
 layout(set = 0, binding = 0) uniform Uniforms {
     mat4 view_projection;
@@ -18,6 +21,8 @@ void main()
 {
     gl_Position = view_projection * model * vec4(translation, 1.0);
     frag_translation = vec3(model * vec4(translation, 1.0));
-    frag_normal = mat3(transpose(inverse(model))) * normal;
+    frag_normal = normal_matrix * normal;
     frag_uv = uv;
+    // Tangents lie in the surface, so they're transformed like any other direction rather than with the normal matrix.
+    frag_tangent = vec4(mat3(model) * tangent.xyz, tangent.w);
 }
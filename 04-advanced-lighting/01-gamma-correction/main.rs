@@ -0,0 +1,950 @@
+use amethyst_input::{InputHandler, StringBindings};
+use shaderc::{Compiler, ShaderKind};
+use ultraviolet::{Mat4, Vec2, Vec3, Vec4};
+use winit::{
+    event::{Event, KeyboardInput, VirtualKeyCode, WindowEvent},
+    event_loop::{ControlFlow, EventLoop},
+    window::WindowBuilder,
+};
+
+use learnopengl_but_its_wgpu::{
+    instance_buffer::InstanceBuffer,
+    scene::{self, Scene},
+    time::Time,
+    Align16, Instance,
+};
+
+const DEPTH_FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Depth32Float;
+
+/// Must match `LIGHT_COUNT` in `shader.frag`.
+const LIGHT_COUNT: usize = 4;
+
+#[rustfmt::skip]
+const VERTICES: &[Vertex] = &[
+    Vertex { translation: Vec3 { x:  0.5, y: -0.5, z: -0.5, }, normal: Vec3 { x:  0.0, y:  0.0, z: -1.0 }, uv: Vec2 { x: 1.0, y: 0.0, } },
+    Vertex { translation: Vec3 { x: -0.5, y: -0.5, z: -0.5, }, normal: Vec3 { x:  0.0, y:  0.0, z: -1.0 }, uv: Vec2 { x: 0.0, y: 0.0, } },
+    Vertex { translation: Vec3 { x:  0.5, y:  0.5, z: -0.5, }, normal: Vec3 { x:  0.0, y:  0.0, z: -1.0 }, uv: Vec2 { x: 1.0, y: 1.0, } },
+    Vertex { translation: Vec3 { x:  0.5, y:  0.5, z: -0.5, }, normal: Vec3 { x:  0.0, y:  0.0, z: -1.0 }, uv: Vec2 { x: 1.0, y: 1.0, } },
+    Vertex { translation: Vec3 { x: -0.5, y: -0.5, z: -0.5, }, normal: Vec3 { x:  0.0, y:  0.0, z: -1.0 }, uv: Vec2 { x: 0.0, y: 0.0, } },
+    Vertex { translation: Vec3 { x: -0.5, y:  0.5, z: -0.5, }, normal: Vec3 { x:  0.0, y:  0.0, z: -1.0 }, uv: Vec2 { x: 0.0, y: 1.0, } },
+
+    Vertex { translation: Vec3 { x: -0.5, y: -0.5, z:  0.5, }, normal: Vec3 { x:  0.0, y:  0.0, z:  1.0 }, uv: Vec2 { x: 0.0, y: 0.0, } },
+    Vertex { translation: Vec3 { x:  0.5, y: -0.5, z:  0.5, }, normal: Vec3 { x:  0.0, y:  0.0, z:  1.0 }, uv: Vec2 { x: 1.0, y: 0.0, } },
+    Vertex { translation: Vec3 { x:  0.5, y:  0.5, z:  0.5, }, normal: Vec3 { x:  0.0, y:  0.0, z:  1.0 }, uv: Vec2 { x: 1.0, y: 1.0, } },
+    Vertex { translation: Vec3 { x:  0.5, y:  0.5, z:  0.5, }, normal: Vec3 { x:  0.0, y:  0.0, z:  1.0 }, uv: Vec2 { x: 1.0, y: 1.0, } },
+    Vertex { translation: Vec3 { x: -0.5, y:  0.5, z:  0.5, }, normal: Vec3 { x:  0.0, y:  0.0, z:  1.0 }, uv: Vec2 { x: 0.0, y: 1.0, } },
+    Vertex { translation: Vec3 { x: -0.5, y: -0.5, z:  0.5, }, normal: Vec3 { x:  0.0, y:  0.0, z:  1.0 }, uv: Vec2 { x: 0.0, y: 0.0, } },
+
+    Vertex { translation: Vec3 { x: -0.5, y:  0.5, z:  0.5, }, normal: Vec3 { x: -1.0, y:  0.0, z:  0.0 }, uv: Vec2 { x: 1.0, y: 0.0, } },
+    Vertex { translation: Vec3 { x: -0.5, y:  0.5, z: -0.5, }, normal: Vec3 { x: -1.0, y:  0.0, z:  0.0 }, uv: Vec2 { x: 1.0, y: 1.0, } },
+    Vertex { translation: Vec3 { x: -0.5, y: -0.5, z: -0.5, }, normal: Vec3 { x: -1.0, y:  0.0, z:  0.0 }, uv: Vec2 { x: 0.0, y: 1.0, } },
+    Vertex { translation: Vec3 { x: -0.5, y: -0.5, z: -0.5, }, normal: Vec3 { x: -1.0, y:  0.0, z:  0.0 }, uv: Vec2 { x: 0.0, y: 1.0, } },
+    Vertex { translation: Vec3 { x: -0.5, y: -0.5, z:  0.5, }, normal: Vec3 { x: -1.0, y:  0.0, z:  0.0 }, uv: Vec2 { x: 0.0, y: 0.0, } },
+    Vertex { translation: Vec3 { x: -0.5, y:  0.5, z:  0.5, }, normal: Vec3 { x: -1.0, y:  0.0, z:  0.0 }, uv: Vec2 { x: 1.0, y: 0.0, } },
+
+    Vertex { translation: Vec3 { x:  0.5, y:  0.5, z:  0.5, }, normal: Vec3 { x:  1.0, y:  0.0, z:  0.0 }, uv: Vec2 { x: 1.0, y: 0.0, } },
+    Vertex { translation: Vec3 { x:  0.5, y: -0.5, z: -0.5, }, normal: Vec3 { x:  1.0, y:  0.0, z:  0.0 }, uv: Vec2 { x: 0.0, y: 1.0, } },
+    Vertex { translation: Vec3 { x:  0.5, y:  0.5, z: -0.5, }, normal: Vec3 { x:  1.0, y:  0.0, z:  0.0 }, uv: Vec2 { x: 1.0, y: 1.0, } },
+    Vertex { translation: Vec3 { x:  0.5, y: -0.5, z: -0.5, }, normal: Vec3 { x:  1.0, y:  0.0, z:  0.0 }, uv: Vec2 { x: 0.0, y: 1.0, } },
+    Vertex { translation: Vec3 { x:  0.5, y:  0.5, z:  0.5, }, normal: Vec3 { x:  1.0, y:  0.0, z:  0.0 }, uv: Vec2 { x: 1.0, y: 0.0, } },
+    Vertex { translation: Vec3 { x:  0.5, y: -0.5, z:  0.5, }, normal: Vec3 { x:  1.0, y:  0.0, z:  0.0 }, uv: Vec2 { x: 0.0, y: 0.0, } },
+
+    Vertex { translation: Vec3 { x: -0.5, y: -0.5, z: -0.5, }, normal: Vec3 { x:  0.0, y: -1.0, z:  0.0 }, uv: Vec2 { x: 0.0, y: 1.0, } },
+    Vertex { translation: Vec3 { x:  0.5, y: -0.5, z: -0.5, }, normal: Vec3 { x:  0.0, y: -1.0, z:  0.0 }, uv: Vec2 { x: 1.0, y: 1.0, } },
+    Vertex { translation: Vec3 { x:  0.5, y: -0.5, z:  0.5, }, normal: Vec3 { x:  0.0, y: -1.0, z:  0.0 }, uv: Vec2 { x: 1.0, y: 0.0, } },
+    Vertex { translation: Vec3 { x:  0.5, y: -0.5, z:  0.5, }, normal: Vec3 { x:  0.0, y: -1.0, z:  0.0 }, uv: Vec2 { x: 1.0, y: 0.0, } },
+    Vertex { translation: Vec3 { x: -0.5, y: -0.5, z:  0.5, }, normal: Vec3 { x:  0.0, y: -1.0, z:  0.0 }, uv: Vec2 { x: 0.0, y: 0.0, } },
+    Vertex { translation: Vec3 { x: -0.5, y: -0.5, z: -0.5, }, normal: Vec3 { x:  0.0, y: -1.0, z:  0.0 }, uv: Vec2 { x: 0.0, y: 1.0, } },
+
+    Vertex { translation: Vec3 { x:  0.5, y:  0.5, z: -0.5, }, normal: Vec3 { x:  0.0, y:  1.0, z:  0.0 }, uv: Vec2 { x: 1.0, y: 1.0, } },
+    Vertex { translation: Vec3 { x: -0.5, y:  0.5, z: -0.5, }, normal: Vec3 { x:  0.0, y:  1.0, z:  0.0 }, uv: Vec2 { x: 0.0, y: 1.0, } },
+    Vertex { translation: Vec3 { x:  0.5, y:  0.5, z:  0.5, }, normal: Vec3 { x:  0.0, y:  1.0, z:  0.0 }, uv: Vec2 { x: 1.0, y: 0.0, } },
+    Vertex { translation: Vec3 { x:  0.5, y:  0.5, z:  0.5, }, normal: Vec3 { x:  0.0, y:  1.0, z:  0.0 }, uv: Vec2 { x: 1.0, y: 0.0, } },
+    Vertex { translation: Vec3 { x: -0.5, y:  0.5, z: -0.5, }, normal: Vec3 { x:  0.0, y:  1.0, z:  0.0 }, uv: Vec2 { x: 0.0, y: 1.0, } },
+    Vertex { translation: Vec3 { x: -0.5, y:  0.5, z:  0.5, }, normal: Vec3 { x:  0.0, y:  1.0, z:  0.0 }, uv: Vec2 { x: 0.0, y: 0.0, } },
+
+];
+
+fn main() {
+    let scene_path = std::env::args().nth(1).unwrap_or_else(|| {
+        concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/04-advanced-lighting/01-gamma-correction/scene.ron"
+        )
+        .to_owned()
+    });
+    let scene = Scene::load(&scene_path).unwrap();
+    assert_eq!(
+        LIGHT_COUNT,
+        scene.point_lights.len(),
+        "The scene has to have exactly {} point lights",
+        LIGHT_COUNT
+    );
+
+    let (event_loop, window, mut size) = {
+        let event_loop = EventLoop::new();
+        let window = WindowBuilder::new()
+            .with_title("LearnOpenGL but it's WGPU-rs")
+            .build(&event_loop)
+            .unwrap();
+        let size = window.inner_size();
+        (event_loop, window, size)
+    };
+
+    let surface = wgpu::Surface::create(&window);
+
+    let (device, mut queue) = wgpu::Adapter::request(&wgpu::RequestAdapterOptions {
+        power_preference: wgpu::PowerPreference::HighPerformance,
+        backends: wgpu::BackendBit::all(),
+    })
+    .unwrap()
+    .request_device(&wgpu::DeviceDescriptor {
+        extensions: wgpu::Extensions {
+            anisotropic_filtering: false,
+        },
+        ..Default::default()
+    });
+
+    let mut shader_compiler = Compiler::new().expect("Could not initialize shader compiler");
+
+    let vs_module = prepare_shader(
+        &mut shader_compiler,
+        include_str!("shader.vert"),
+        ShaderKind::Vertex,
+        "shader.vert",
+        &device,
+    );
+    let fs_module = prepare_shader(
+        &mut shader_compiler,
+        include_str!("shader.frag"),
+        ShaderKind::Fragment,
+        "shader.frag",
+        &device,
+    );
+    let light_source_vs_module = prepare_shader(
+        &mut shader_compiler,
+        include_str!("light_source.vert"),
+        ShaderKind::Vertex,
+        "light_source.vert",
+        &device,
+    );
+    let light_source_fs_module = prepare_shader(
+        &mut shader_compiler,
+        include_str!("light_source.frag"),
+        ShaderKind::Fragment,
+        "light_source.frag",
+        &device,
+    );
+
+    let mut init_encoder =
+        device.create_command_encoder(&wgpu::CommandEncoderDescriptor { todo: 0 });
+
+    let vertex_buffer = device
+        .create_buffer_mapped(VERTICES.len(), wgpu::BufferUsage::VERTEX)
+        .fill_from_slice(VERTICES);
+
+    let cubes = scene.cube_instances();
+    let cube_instance_buffer = device
+        .create_buffer_mapped(cubes.len(), wgpu::BufferUsage::VERTEX)
+        .fill_from_slice(&cubes);
+
+    let mut light_source_instance_buffer =
+        InstanceBuffer::from_slice(&device, &scene.light_source_instances());
+
+    let vertex_uniform_bind_group_layout =
+        device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            bindings: &[wgpu::BindGroupLayoutBinding {
+                binding: 0,
+                visibility: wgpu::ShaderStage::VERTEX,
+                ty: wgpu::BindingType::UniformBuffer { dynamic: false },
+            }],
+        });
+
+    let fragment_uniform_bind_group_layout =
+        device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            bindings: &[wgpu::BindGroupLayoutBinding {
+                binding: 0,
+                visibility: wgpu::ShaderStage::FRAGMENT,
+                ty: wgpu::BindingType::UniformBuffer { dynamic: false },
+            }],
+        });
+
+    let material_bind_group_layout =
+        device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            bindings: &[
+                wgpu::BindGroupLayoutBinding {
+                    binding: 0,
+                    visibility: wgpu::ShaderStage::FRAGMENT,
+                    ty: wgpu::BindingType::UniformBuffer { dynamic: false },
+                },
+                wgpu::BindGroupLayoutBinding {
+                    binding: 1,
+                    visibility: wgpu::ShaderStage::FRAGMENT,
+                    ty: wgpu::BindingType::SampledTexture {
+                        multisampled: false,
+                        dimension: wgpu::TextureViewDimension::D2,
+                    },
+                },
+                wgpu::BindGroupLayoutBinding {
+                    binding: 2,
+                    visibility: wgpu::ShaderStage::FRAGMENT,
+                    ty: wgpu::BindingType::Sampler,
+                },
+                wgpu::BindGroupLayoutBinding {
+                    binding: 3,
+                    visibility: wgpu::ShaderStage::FRAGMENT,
+                    ty: wgpu::BindingType::SampledTexture {
+                        multisampled: false,
+                        dimension: wgpu::TextureViewDimension::D2,
+                    },
+                },
+                wgpu::BindGroupLayoutBinding {
+                    binding: 4,
+                    visibility: wgpu::ShaderStage::FRAGMENT,
+                    ty: wgpu::BindingType::Sampler,
+                },
+            ],
+        });
+
+    let mut event_channel = shrev::EventChannel::new();
+    let event_reader = event_channel.register_reader();
+    let mut input_handler = InputHandler::<StringBindings>::new();
+
+    let mut camera = scene.create_camera(event_reader);
+
+    let vertex_uniform_buffer = device
+        .create_buffer_mapped(1, wgpu::BufferUsage::UNIFORM | wgpu::BufferUsage::COPY_DST)
+        .fill_from_slice(&[camera.get_view_projection_matrix(
+            size.width as f32,
+            size.height as f32,
+            0.1,
+            100.0,
+        )]);
+
+    let vertex_uniform_bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+        layout: &vertex_uniform_bind_group_layout,
+        bindings: &[wgpu::Binding {
+            binding: 0,
+            resource: wgpu::BindingResource::Buffer {
+                buffer: &vertex_uniform_buffer,
+                range: 0..std::mem::size_of::<Mat4>() as wgpu::BufferAddress,
+            },
+        }],
+    });
+
+    let mut lights = [PointLight {
+        translation: Vec3::zero().into(),
+        color: Vec3::zero().into(),
+    }; LIGHT_COUNT];
+    for (light, description) in lights.iter_mut().zip(&scene.point_lights) {
+        light.translation = scene::vec3(description.translation).into();
+        light.color = scene::vec3(description.diffuse).into();
+    }
+    let mut fragment_uniforms = FragmentUniforms {
+        view_translation: camera.translation.into(),
+        lights,
+        gamma_correction: 1,
+    };
+    let fragment_uniform_buffer = device
+        .create_buffer_mapped(1, wgpu::BufferUsage::UNIFORM | wgpu::BufferUsage::COPY_DST)
+        .fill_from_slice(&[fragment_uniforms]);
+
+    let fragment_uniform_bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+        layout: &fragment_uniform_bind_group_layout,
+        bindings: &[wgpu::Binding {
+            binding: 0,
+            resource: wgpu::BindingResource::Buffer {
+                buffer: &fragment_uniform_buffer,
+                range: 0..std::mem::size_of::<FragmentUniforms>() as wgpu::BufferAddress,
+            },
+        }],
+    });
+
+    let material_uniform_buffer = device
+        .create_buffer_mapped(1, wgpu::BufferUsage::UNIFORM | wgpu::BufferUsage::COPY_DST)
+        .fill_from_slice(&[Material {
+            shininess: scene.material.shininess,
+        }]);
+
+    let material_diffuse_texture_image = image::open(
+        scene.resolve_path(
+            scene
+                .material
+                .diffuse_texture
+                .as_ref()
+                .expect("The material has no diffuse texture"),
+        ),
+    )
+    .unwrap()
+    .to_rgba();
+    let (width, height) = material_diffuse_texture_image.dimensions();
+    let material_diffuse_texture_raw = material_diffuse_texture_image.to_vec();
+    // The same texels are uploaded twice. Sampling the sRGB texture decodes them into linear space, while the other one
+    // hands them to the shader as they are stored.
+    let (_, material_diffuse_texture_view, material_diffuse_texture_sampler) =
+        create_sampled_texture2d(
+            &device,
+            &mut init_encoder,
+            &material_diffuse_texture_raw,
+            width,
+            height,
+            wgpu::TextureFormat::Rgba8UnormSrgb,
+        );
+    let (
+        _,
+        uncorrected_material_diffuse_texture_view,
+        uncorrected_material_diffuse_texture_sampler,
+    ) = create_sampled_texture2d(
+        &device,
+        &mut init_encoder,
+        &material_diffuse_texture_raw,
+        width,
+        height,
+        wgpu::TextureFormat::Rgba8Unorm,
+    );
+
+    let material_specular_texture_image = image::open(
+        scene.resolve_path(
+            scene
+                .material
+                .specular_texture
+                .as_ref()
+                .expect("The material has no specular texture"),
+        ),
+    )
+    .unwrap()
+    .to_rgba();
+    let (width, height) = material_specular_texture_image.dimensions();
+    let material_specular_texture_raw = material_specular_texture_image.to_vec();
+    let (_, material_specular_texture_view, material_specular_texture_sampler) =
+        create_sampled_texture2d(
+            &device,
+            &mut init_encoder,
+            &material_specular_texture_raw,
+            width,
+            height,
+            wgpu::TextureFormat::Rgba8Unorm,
+        );
+
+    let material_bind_group = create_material_bind_group(
+        &device,
+        &material_bind_group_layout,
+        &material_uniform_buffer,
+        (
+            &material_diffuse_texture_view,
+            &material_diffuse_texture_sampler,
+        ),
+        (
+            &material_specular_texture_view,
+            &material_specular_texture_sampler,
+        ),
+    );
+    let uncorrected_material_bind_group = create_material_bind_group(
+        &device,
+        &material_bind_group_layout,
+        &material_uniform_buffer,
+        (
+            &uncorrected_material_diffuse_texture_view,
+            &uncorrected_material_diffuse_texture_sampler,
+        ),
+        (
+            &material_specular_texture_view,
+            &material_specular_texture_sampler,
+        ),
+    );
+
+    let mut swap_chain_descriptor = wgpu::SwapChainDescriptor {
+        usage: wgpu::TextureUsage::OUTPUT_ATTACHMENT,
+        format: swap_chain_format(true),
+        width: size.width,
+        height: size.height,
+        present_mode: wgpu::PresentMode::Vsync,
+    };
+
+    let (_, mut depth_texture_view) = create_depth_texture(&device, &swap_chain_descriptor);
+
+    let render_pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+        bind_group_layouts: &[
+            &vertex_uniform_bind_group_layout,
+            &fragment_uniform_bind_group_layout,
+            &material_bind_group_layout,
+        ],
+    });
+
+    // A pipeline is tied to the format of its render target, so every pipeline exists once for each swap chain format.
+    let color_states = create_color_states(swap_chain_format(true));
+    let uncorrected_color_states = create_color_states(swap_chain_format(false));
+
+    let render_pipeline_descriptor = wgpu::RenderPipelineDescriptor {
+        layout: &render_pipeline_layout,
+        vertex_stage: wgpu::ProgrammableStageDescriptor {
+            module: &vs_module,
+            entry_point: "main",
+        },
+        fragment_stage: Some(wgpu::ProgrammableStageDescriptor {
+            module: &fs_module,
+            entry_point: "main",
+        }),
+        rasterization_state: Some(wgpu::RasterizationStateDescriptor {
+            front_face: wgpu::FrontFace::Ccw,
+            cull_mode: wgpu::CullMode::Back,
+            depth_bias: 0,
+            depth_bias_slope_scale: 0.0,
+            depth_bias_clamp: 0.0,
+        }),
+        primitive_topology: wgpu::PrimitiveTopology::TriangleList,
+        color_states: &color_states,
+        depth_stencil_state: Some(wgpu::DepthStencilStateDescriptor {
+            format: DEPTH_FORMAT,
+            depth_write_enabled: true,
+            depth_compare: wgpu::CompareFunction::Less,
+            stencil_front: wgpu::StencilStateFaceDescriptor::IGNORE,
+            stencil_back: wgpu::StencilStateFaceDescriptor::IGNORE,
+            stencil_read_mask: 0,
+            stencil_write_mask: 0,
+        }),
+        index_format: wgpu::IndexFormat::Uint16,
+        vertex_buffers: &[
+            wgpu::VertexBufferDescriptor {
+                stride: std::mem::size_of::<Vertex>() as wgpu::BufferAddress,
+                step_mode: wgpu::InputStepMode::Vertex,
+                attributes: &[
+                    wgpu::VertexAttributeDescriptor {
+                        offset: 0,
+                        format: wgpu::VertexFormat::Float3,
+                        shader_location: 0,
+                    },
+                    wgpu::VertexAttributeDescriptor {
+                        offset: std::mem::size_of::<Vec3>() as wgpu::BufferAddress,
+                        format: wgpu::VertexFormat::Float3,
+                        shader_location: 1,
+                    },
+                    wgpu::VertexAttributeDescriptor {
+                        offset: (std::mem::size_of::<Vec3>() * 2) as wgpu::BufferAddress,
+                        format: wgpu::VertexFormat::Float2,
+                        shader_location: 2,
+                    },
+                ],
+            },
+            wgpu::VertexBufferDescriptor {
+                stride: std::mem::size_of::<Instance>() as wgpu::BufferAddress,
+                step_mode: wgpu::InputStepMode::Instance,
+                attributes: &[
+                    wgpu::VertexAttributeDescriptor {
+                        offset: 0,
+                        format: wgpu::VertexFormat::Float4,
+                        shader_location: 3,
+                    },
+                    wgpu::VertexAttributeDescriptor {
+                        offset: std::mem::size_of::<Vec4>() as wgpu::BufferAddress,
+                        format: wgpu::VertexFormat::Float4,
+                        shader_location: 4,
+                    },
+                    wgpu::VertexAttributeDescriptor {
+                        offset: std::mem::size_of::<Vec4>() as wgpu::BufferAddress * 2,
+                        format: wgpu::VertexFormat::Float4,
+                        shader_location: 5,
+                    },
+                    wgpu::VertexAttributeDescriptor {
+                        offset: std::mem::size_of::<Vec4>() as wgpu::BufferAddress * 3,
+                        format: wgpu::VertexFormat::Float4,
+                        shader_location: 6,
+                    },
+                    wgpu::VertexAttributeDescriptor {
+                        offset: std::mem::size_of::<Mat4>() as wgpu::BufferAddress,
+                        format: wgpu::VertexFormat::Float3,
+                        shader_location: 7,
+                    },
+                    wgpu::VertexAttributeDescriptor {
+                        offset: (std::mem::size_of::<Mat4>() + std::mem::size_of::<Vec3>())
+                            as wgpu::BufferAddress,
+                        format: wgpu::VertexFormat::Float3,
+                        shader_location: 8,
+                    },
+                    wgpu::VertexAttributeDescriptor {
+                        offset: (std::mem::size_of::<Mat4>() + std::mem::size_of::<Vec3>() * 2)
+                            as wgpu::BufferAddress,
+                        format: wgpu::VertexFormat::Float3,
+                        shader_location: 9,
+                    },
+                ],
+            },
+        ],
+        sample_count: 1,
+        sample_mask: !0,
+        alpha_to_coverage_enabled: false,
+    };
+    let render_pipeline = device.create_render_pipeline(&render_pipeline_descriptor);
+    let uncorrected_render_pipeline =
+        device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            color_states: &uncorrected_color_states,
+            ..render_pipeline_descriptor.clone()
+        });
+
+    let light_source_pipeline_layout =
+        device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            bind_group_layouts: &[&vertex_uniform_bind_group_layout],
+        });
+
+    let light_source_pipeline_descriptor = wgpu::RenderPipelineDescriptor {
+        layout: &light_source_pipeline_layout,
+        vertex_stage: wgpu::ProgrammableStageDescriptor {
+            module: &light_source_vs_module,
+            entry_point: "main",
+        },
+        fragment_stage: Some(wgpu::ProgrammableStageDescriptor {
+            module: &light_source_fs_module,
+            entry_point: "main",
+        }),
+        rasterization_state: Some(wgpu::RasterizationStateDescriptor {
+            front_face: wgpu::FrontFace::Ccw,
+            cull_mode: wgpu::CullMode::Back,
+            depth_bias: 0,
+            depth_bias_slope_scale: 0.0,
+            depth_bias_clamp: 0.0,
+        }),
+        primitive_topology: wgpu::PrimitiveTopology::TriangleList,
+        color_states: &color_states,
+        depth_stencil_state: Some(wgpu::DepthStencilStateDescriptor {
+            format: DEPTH_FORMAT,
+            depth_write_enabled: true,
+            depth_compare: wgpu::CompareFunction::Less,
+            stencil_front: wgpu::StencilStateFaceDescriptor::IGNORE,
+            stencil_back: wgpu::StencilStateFaceDescriptor::IGNORE,
+            stencil_read_mask: 0,
+            stencil_write_mask: 0,
+        }),
+        index_format: wgpu::IndexFormat::Uint16,
+        vertex_buffers: &[
+            wgpu::VertexBufferDescriptor {
+                stride: std::mem::size_of::<Vertex>() as wgpu::BufferAddress,
+                step_mode: wgpu::InputStepMode::Vertex,
+                attributes: &[wgpu::VertexAttributeDescriptor {
+                    offset: 0,
+                    format: wgpu::VertexFormat::Float3,
+                    shader_location: 0,
+                }],
+            },
+            wgpu::VertexBufferDescriptor {
+                stride: std::mem::size_of::<Instance>() as wgpu::BufferAddress,
+                step_mode: wgpu::InputStepMode::Instance,
+                attributes: &[
+                    wgpu::VertexAttributeDescriptor {
+                        offset: 0,
+                        format: wgpu::VertexFormat::Float4,
+                        shader_location: 3,
+                    },
+                    wgpu::VertexAttributeDescriptor {
+                        offset: std::mem::size_of::<Vec4>() as wgpu::BufferAddress,
+                        format: wgpu::VertexFormat::Float4,
+                        shader_location: 4,
+                    },
+                    wgpu::VertexAttributeDescriptor {
+                        offset: std::mem::size_of::<Vec4>() as wgpu::BufferAddress * 2,
+                        format: wgpu::VertexFormat::Float4,
+                        shader_location: 5,
+                    },
+                    wgpu::VertexAttributeDescriptor {
+                        offset: std::mem::size_of::<Vec4>() as wgpu::BufferAddress * 3,
+                        format: wgpu::VertexFormat::Float4,
+                        shader_location: 6,
+                    },
+                ],
+            },
+        ],
+        sample_count: 1,
+        sample_mask: !0,
+        alpha_to_coverage_enabled: false,
+    };
+    let light_source_pipeline = device.create_render_pipeline(&light_source_pipeline_descriptor);
+    let uncorrected_light_source_pipeline =
+        device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            color_states: &uncorrected_color_states,
+            ..light_source_pipeline_descriptor.clone()
+        });
+
+    let mut swap_chain = device.create_swap_chain(&surface, &swap_chain_descriptor);
+
+    queue.submit(&[init_encoder.finish()]);
+
+    let mut time = Time::new();
+
+    event_loop.run(move |event, _, control_flow| {
+        input_handler.send_event(&event, &mut event_channel);
+
+        camera.on_event(&event_channel);
+
+        match event {
+            Event::WindowEvent {
+                event: WindowEvent::CloseRequested,
+                ..
+            } => *control_flow = ControlFlow::Exit,
+            Event::WindowEvent {
+                event: WindowEvent::Resized(new_size),
+                ..
+            } => {
+                size = new_size;
+                swap_chain_descriptor.width = size.width;
+                swap_chain_descriptor.height = size.height;
+                swap_chain = device.create_swap_chain(&surface, &swap_chain_descriptor);
+                depth_texture_view = create_depth_texture(&device, &swap_chain_descriptor).1;
+                *control_flow = ControlFlow::Poll;
+            }
+            Event::WindowEvent {
+                event:
+                    WindowEvent::MouseInput {
+                        button: winit::event::MouseButton::Left,
+                        state: winit::event::ElementState::Pressed,
+                        ..
+                    },
+                ..
+            } => {
+                camera.is_controlled = true;
+                window.set_cursor_grab(true).ok();
+                window.set_cursor_visible(false);
+                *control_flow = ControlFlow::Poll;
+            }
+            Event::WindowEvent {
+                event:
+                    WindowEvent::KeyboardInput {
+                        input:
+                            KeyboardInput {
+                                virtual_keycode: Some(VirtualKeyCode::Escape),
+                                state: winit::event::ElementState::Pressed,
+                                ..
+                            },
+                        ..
+                    },
+                ..
+            } => {
+                camera.is_controlled = false;
+                window.set_cursor_grab(false).ok();
+                window.set_cursor_visible(true);
+                *control_flow = ControlFlow::Poll;
+            }
+            Event::WindowEvent {
+                event:
+                    WindowEvent::KeyboardInput {
+                        input:
+                            KeyboardInput {
+                                virtual_keycode: Some(VirtualKeyCode::G),
+                                state: winit::event::ElementState::Pressed,
+                                ..
+                            },
+                        ..
+                    },
+                ..
+            } => {
+                fragment_uniforms.gamma_correction = 1 - fragment_uniforms.gamma_correction;
+                let gamma_correction = fragment_uniforms.gamma_correction != 0;
+                swap_chain_descriptor.format = swap_chain_format(gamma_correction);
+                swap_chain = device.create_swap_chain(&surface, &swap_chain_descriptor);
+                *control_flow = ControlFlow::Poll;
+            }
+            Event::MainEventsCleared => {
+                window.request_redraw();
+                *control_flow = ControlFlow::Poll;
+            }
+            Event::RedrawRequested(_) => {
+                input_handler.send_frame_begin();
+                camera.update(&input_handler);
+
+                time.update();
+
+                let gamma_correction = fragment_uniforms.gamma_correction != 0;
+                let (render_pipeline, light_source_pipeline, material_bind_group) =
+                    if gamma_correction {
+                        (
+                            &render_pipeline,
+                            &light_source_pipeline,
+                            &material_bind_group,
+                        )
+                    } else {
+                        (
+                            &uncorrected_render_pipeline,
+                            &uncorrected_light_source_pipeline,
+                            &uncorrected_material_bind_group,
+                        )
+                    };
+
+                let frame = swap_chain.get_next_texture();
+                let mut encoder =
+                    device.create_command_encoder(&wgpu::CommandEncoderDescriptor { todo: 0 });
+                {
+                    let staging_buffer = device
+                        .create_buffer_mapped(1, wgpu::BufferUsage::COPY_SRC)
+                        .fill_from_slice(&[camera.get_view_projection_matrix(
+                            size.width as f32,
+                            size.height as f32,
+                            0.1,
+                            100.0,
+                        )]);
+                    encoder.copy_buffer_to_buffer(
+                        &staging_buffer,
+                        0,
+                        &vertex_uniform_buffer,
+                        0,
+                        std::mem::size_of::<Mat4>() as wgpu::BufferAddress,
+                    );
+
+                    fragment_uniforms.view_translation = camera.world_translation().into();
+                    for (light, description) in
+                        fragment_uniforms.lights.iter_mut().zip(&scene.point_lights)
+                    {
+                        let animated_light = description.sample(time.elapsed());
+                        light.translation = animated_light.translation;
+                        light.color = animated_light.diffuse;
+                    }
+                    let staging_buffer = device
+                        .create_buffer_mapped(1, wgpu::BufferUsage::COPY_SRC)
+                        .fill_from_slice(&[fragment_uniforms]);
+                    encoder.copy_buffer_to_buffer(
+                        &staging_buffer,
+                        0,
+                        &fragment_uniform_buffer,
+                        0,
+                        std::mem::size_of::<FragmentUniforms>() as wgpu::BufferAddress,
+                    );
+
+                    light_source_instance_buffer.update(
+                        &device,
+                        &mut encoder,
+                        &scene.light_source_instances_at(time.elapsed()),
+                    );
+
+                    let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                        color_attachments: &[wgpu::RenderPassColorAttachmentDescriptor {
+                            attachment: &frame.view,
+                            resolve_target: None,
+                            load_op: wgpu::LoadOp::Clear,
+                            store_op: wgpu::StoreOp::Store,
+                            clear_color: wgpu::Color {
+                                r: 0.1,
+                                g: 0.1,
+                                b: 0.1,
+                                a: 1.0,
+                            },
+                        }],
+                        depth_stencil_attachment: Some(
+                            wgpu::RenderPassDepthStencilAttachmentDescriptor {
+                                attachment: &depth_texture_view,
+                                depth_load_op: wgpu::LoadOp::Clear,
+                                depth_store_op: wgpu::StoreOp::Store,
+                                clear_depth: 1.0,
+                                stencil_load_op: wgpu::LoadOp::Clear,
+                                stencil_store_op: wgpu::StoreOp::Store,
+                                clear_stencil: 0,
+                            },
+                        ),
+                    });
+                    render_pass.set_pipeline(render_pipeline);
+                    render_pass.set_bind_group(0, &vertex_uniform_bind_group, &[]);
+                    render_pass.set_bind_group(1, &fragment_uniform_bind_group, &[]);
+                    render_pass.set_bind_group(2, material_bind_group, &[]);
+                    render_pass
+                        .set_vertex_buffers(0, &[(&vertex_buffer, 0), (&cube_instance_buffer, 0)]);
+                    render_pass.draw(0..VERTICES.len() as u32, 0..cubes.len() as u32);
+
+                    render_pass.set_pipeline(light_source_pipeline);
+                    render_pass.set_bind_group(0, &vertex_uniform_bind_group, &[]);
+                    render_pass.set_vertex_buffers(
+                        0,
+                        &[
+                            (&vertex_buffer, 0),
+                            (light_source_instance_buffer.buffer(), 0),
+                        ],
+                    );
+                    render_pass.draw(
+                        0..VERTICES.len() as u32,
+                        0..light_source_instance_buffer.len() as u32,
+                    );
+                }
+
+                queue.submit(&[encoder.finish()]);
+
+                *control_flow = ControlFlow::Poll;
+            }
+            _ => {}
+        }
+    })
+}
+
+/// With gamma correction, the swap chain encodes the linear colors written to it into sRGB. Otherwise, the colors are
+/// stored as they are.
+fn swap_chain_format(gamma_correction: bool) -> wgpu::TextureFormat {
+    if gamma_correction {
+        wgpu::TextureFormat::Bgra8UnormSrgb
+    } else {
+        wgpu::TextureFormat::Bgra8Unorm
+    }
+}
+
+fn create_color_states(format: wgpu::TextureFormat) -> [wgpu::ColorStateDescriptor; 1] {
+    [wgpu::ColorStateDescriptor {
+        format,
+        alpha_blend: wgpu::BlendDescriptor::REPLACE,
+        color_blend: wgpu::BlendDescriptor::REPLACE,
+        write_mask: wgpu::ColorWrite::ALL,
+    }]
+}
+
+fn create_material_bind_group(
+    device: &wgpu::Device,
+    layout: &wgpu::BindGroupLayout,
+    uniform_buffer: &wgpu::Buffer,
+    (diffuse_texture_view, diffuse_texture_sampler): (&wgpu::TextureView, &wgpu::Sampler),
+    (specular_texture_view, specular_texture_sampler): (&wgpu::TextureView, &wgpu::Sampler),
+) -> wgpu::BindGroup {
+    device.create_bind_group(&wgpu::BindGroupDescriptor {
+        layout,
+        bindings: &[
+            wgpu::Binding {
+                binding: 0,
+                resource: wgpu::BindingResource::Buffer {
+                    buffer: uniform_buffer,
+                    range: 0..std::mem::size_of::<Material>() as wgpu::BufferAddress,
+                },
+            },
+            wgpu::Binding {
+                binding: 1,
+                resource: wgpu::BindingResource::TextureView(diffuse_texture_view),
+            },
+            wgpu::Binding {
+                binding: 2,
+                resource: wgpu::BindingResource::Sampler(diffuse_texture_sampler),
+            },
+            wgpu::Binding {
+                binding: 3,
+                resource: wgpu::BindingResource::TextureView(specular_texture_view),
+            },
+            wgpu::Binding {
+                binding: 4,
+                resource: wgpu::BindingResource::Sampler(specular_texture_sampler),
+            },
+        ],
+    })
+}
+
+pub fn prepare_shader(
+    compiler: &mut Compiler,
+    source_text: &str,
+    shader_kind: ShaderKind,
+    input_file_name: &str,
+    device: &wgpu::Device,
+) -> wgpu::ShaderModule {
+    let artifact = compiler
+        .compile_into_spirv(source_text, shader_kind, input_file_name, "main", None)
+        .unwrap();
+    device.create_shader_module(artifact.as_binary())
+}
+
+pub fn create_depth_texture(
+    device: &wgpu::Device,
+    swap_chain_desc: &wgpu::SwapChainDescriptor,
+) -> (wgpu::Texture, wgpu::TextureView) {
+    let desc = wgpu::TextureDescriptor {
+        format: DEPTH_FORMAT,
+        usage: wgpu::TextureUsage::OUTPUT_ATTACHMENT,
+        ..swap_chain_desc.to_texture_desc()
+    };
+    let texture = device.create_texture(&desc);
+    let view = texture.create_default_view();
+    (texture, view)
+}
+
+/// Uploads an RGBA texture. Color textures should use an sRGB `format`, so that they're decoded into linear space
+/// when sampled, while textures holding other data like specular intensities or normals have to use a linear one.
+pub fn create_sampled_texture2d(
+    device: &wgpu::Device,
+    encoder: &mut wgpu::CommandEncoder,
+    raw_texture: &[u8],
+    width: u32,
+    height: u32,
+    format: wgpu::TextureFormat,
+) -> (wgpu::Texture, wgpu::TextureView, wgpu::Sampler) {
+    let texture_extent = wgpu::Extent3d {
+        width,
+        height,
+        depth: 1,
+    };
+
+    let texture_buffer = device
+        .create_buffer_mapped(raw_texture.len(), wgpu::BufferUsage::COPY_SRC)
+        .fill_from_slice(&raw_texture);
+    let texture = device.create_texture(&wgpu::TextureDescriptor {
+        size: texture_extent,
+        array_layer_count: 1,
+        mip_level_count: 0,
+        sample_count: 1,
+        dimension: wgpu::TextureDimension::D2,
+        format,
+        usage: wgpu::TextureUsage::SAMPLED | wgpu::TextureUsage::COPY_DST,
+    });
+
+    encoder.copy_buffer_to_texture(
+        wgpu::BufferCopyView {
+            buffer: &texture_buffer,
+            offset: 0,
+            row_pitch: 4 * width,
+            image_height: height,
+        },
+        wgpu::TextureCopyView {
+            texture: &texture,
+            mip_level: 0,
+            array_layer: 0,
+            origin: wgpu::Origin3d {
+                x: 0.0,
+                y: 0.0,
+                z: 0.0,
+            },
+        },
+        texture_extent,
+    );
+
+    let texture_view = texture.create_default_view();
+    let texture_sampler = device.create_sampler(&wgpu::SamplerDescriptor {
+        address_mode_u: wgpu::AddressMode::ClampToEdge,
+        address_mode_v: wgpu::AddressMode::ClampToEdge,
+        address_mode_w: wgpu::AddressMode::ClampToEdge,
+        mag_filter: wgpu::FilterMode::Nearest,
+        min_filter: wgpu::FilterMode::Linear,
+        mipmap_filter: wgpu::FilterMode::Nearest,
+        lod_min_clamp: 0.0,
+        lod_max_clamp: 0.0,
+        compare_function: wgpu::CompareFunction::Never,
+    });
+
+    (texture, texture_view, texture_sampler)
+}
+
+#[repr(C)]
+#[derive(Clone, Copy, Debug)]
+pub struct Vertex {
+    pub translation: Vec3,
+    pub normal: Vec3,
+    pub uv: Vec2,
+}
+
+#[repr(C)]
+#[derive(Clone, Copy, Debug)]
+pub struct FragmentUniforms {
+    pub view_translation: Align16<Vec3>,
+    pub lights: [PointLight; LIGHT_COUNT],
+    /// Acts as a `bool`. Switches the light attenuation between the quadratic falloff, which is only correct in linear
+    /// space, and a linear one.
+    pub gamma_correction: u32,
+}
+
+/// A light without ambient or specular terms, since the chapter is only about how its color reaches the screen.
+#[repr(C)]
+#[derive(Clone, Copy, Debug)]
+pub struct PointLight {
+    pub translation: Align16<Vec3>,
+    pub color: Align16<Vec3>,
+}
+
+#[repr(C)]
+#[derive(Clone, Copy, Debug)]
+pub struct Material {
+    pub shininess: f32,
+}
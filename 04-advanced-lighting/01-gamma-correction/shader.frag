@@ -0,0 +1,66 @@
+#version 450
+
+// Must match `LIGHT_COUNT` in `main.rs`.
+#define LIGHT_COUNT 4
+
+layout(location = 0) in vec3 translation;
+layout(location = 1) in vec3 normal;
+layout(location = 2) in vec2 uv;
+
+layout(location = 0) out vec4 fragment_color;
+
+struct PointLight {
+    vec3 translation;
+    vec3 color;
+};
+
+layout(set = 1, binding = 0) uniform Uniforms {
+    vec3 view_translation;
+    PointLight lights[LIGHT_COUNT];
+    uint gamma_correction;
+};
+
+layout(set = 2, binding = 0) uniform Material {
+    float shininess;
+} material;
+// With gamma correction, the diffuse texture is an sRGB texture that's decoded into linear space when it's sampled.
+layout(set = 2, binding = 1) uniform texture2D material_diffuse_texture;
+layout(set = 2, binding = 2) uniform sampler material_diffuse_sampler;
+// The specular map holds intensities rather than colors, so it's always sampled as is.
+layout(set = 2, binding = 3) uniform texture2D material_specular_texture;
+layout(set = 2, binding = 4) uniform sampler material_specular_sampler;
+
+vec3 calculate_point_light(PointLight light, vec3 normal, vec3 view_direction);
+
+// There's no `pow(color, vec3(1.0 / 2.2))` at the end, since the sRGB swap chain encodes the linear color written to
+// it. Without gamma correction, the swap chain stores the color as is, which the monitor then darkens.
+void main()
+{
+    vec3 normalized_normal = normalize(normal);
+    vec3 view_direction = normalize(view_translation - translation);
+
+    vec3 lighting = vec3(0.0);
+    for (int i = 0; i < LIGHT_COUNT; i++) {
+        lighting += calculate_point_light(lights[i], normalized_normal, view_direction);
+    }
+
+    fragment_color = vec4(lighting, 1.0);
+}
+
+vec3 calculate_point_light(PointLight light, vec3 normal, vec3 view_direction)
+{
+    vec3 light_direction = normalize(light.translation - translation);
+    float diff = max(dot(normal, light_direction), 0.0);
+    vec3 diffuse = light.color * diff * texture(sampler2D(material_diffuse_texture, material_diffuse_sampler), uv).rgb;
+
+    vec3 halfway_direction = normalize(light_direction + view_direction);
+    float spec = pow(max(dot(normal, halfway_direction), 0.0), material.shininess);
+    vec3 specular = light.color * spec * texture(sampler2D(material_specular_texture, material_specular_sampler), uv).r;
+
+    // Light falls off with the square of the distance. That only looks right in linear space, while a linear falloff
+    // happens to look plausible once the monitor has darkened it.
+    float distance = length(light.translation - translation);
+    float attenuation = 1.0 / (gamma_correction != 0u ? distance * distance : distance);
+
+    return (diffuse + specular) * attenuation;
+}
@@ -0,0 +1,1115 @@
+use amethyst_input::{InputHandler, StringBindings};
+use shaderc::{Compiler, ShaderKind};
+use ultraviolet::{Mat4, Vec2, Vec3, Vec4};
+use winit::{
+    event::{Event, KeyboardInput, VirtualKeyCode, WindowEvent},
+    event_loop::{ControlFlow, EventLoop},
+    window::WindowBuilder,
+};
+
+use learnopengl_but_its_wgpu::{
+    lighting::{LightManager, Material, SpecularModel},
+    mesh::Mesh,
+    scene::{self, Scene, SceneBuffers},
+    shadow::{self, ShadowMap, SHADOW_MAP_FORMAT},
+    time::Time,
+    Instance,
+};
+
+const DEPTH_FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Depth32Float;
+
+const SHADOW_MAP_SIZE: u32 = 2048;
+
+const FLOOR_CENTER: [f32; 3] = [0.0, -3.5, -7.0];
+const FLOOR_HALF_EXTENT: f32 = 12.0;
+
+#[rustfmt::skip]
+const VERTICES: &[Vertex] = &[
+    Vertex { translation: Vec3 { x:  0.5, y: -0.5, z: -0.5, }, normal: Vec3 { x:  0.0, y:  0.0, z: -1.0 }, uv: Vec2 { x: 1.0, y: 0.0, } },
+    Vertex { translation: Vec3 { x: -0.5, y: -0.5, z: -0.5, }, normal: Vec3 { x:  0.0, y:  0.0, z: -1.0 }, uv: Vec2 { x: 0.0, y: 0.0, } },
+    Vertex { translation: Vec3 { x:  0.5, y:  0.5, z: -0.5, }, normal: Vec3 { x:  0.0, y:  0.0, z: -1.0 }, uv: Vec2 { x: 1.0, y: 1.0, } },
+    Vertex { translation: Vec3 { x:  0.5, y:  0.5, z: -0.5, }, normal: Vec3 { x:  0.0, y:  0.0, z: -1.0 }, uv: Vec2 { x: 1.0, y: 1.0, } },
+    Vertex { translation: Vec3 { x: -0.5, y: -0.5, z: -0.5, }, normal: Vec3 { x:  0.0, y:  0.0, z: -1.0 }, uv: Vec2 { x: 0.0, y: 0.0, } },
+    Vertex { translation: Vec3 { x: -0.5, y:  0.5, z: -0.5, }, normal: Vec3 { x:  0.0, y:  0.0, z: -1.0 }, uv: Vec2 { x: 0.0, y: 1.0, } },
+
+    Vertex { translation: Vec3 { x: -0.5, y: -0.5, z:  0.5, }, normal: Vec3 { x:  0.0, y:  0.0, z:  1.0 }, uv: Vec2 { x: 0.0, y: 0.0, } },
+    Vertex { translation: Vec3 { x:  0.5, y: -0.5, z:  0.5, }, normal: Vec3 { x:  0.0, y:  0.0, z:  1.0 }, uv: Vec2 { x: 1.0, y: 0.0, } },
+    Vertex { translation: Vec3 { x:  0.5, y:  0.5, z:  0.5, }, normal: Vec3 { x:  0.0, y:  0.0, z:  1.0 }, uv: Vec2 { x: 1.0, y: 1.0, } },
+    Vertex { translation: Vec3 { x:  0.5, y:  0.5, z:  0.5, }, normal: Vec3 { x:  0.0, y:  0.0, z:  1.0 }, uv: Vec2 { x: 1.0, y: 1.0, } },
+    Vertex { translation: Vec3 { x: -0.5, y:  0.5, z:  0.5, }, normal: Vec3 { x:  0.0, y:  0.0, z:  1.0 }, uv: Vec2 { x: 0.0, y: 1.0, } },
+    Vertex { translation: Vec3 { x: -0.5, y: -0.5, z:  0.5, }, normal: Vec3 { x:  0.0, y:  0.0, z:  1.0 }, uv: Vec2 { x: 0.0, y: 0.0, } },
+
+    Vertex { translation: Vec3 { x: -0.5, y:  0.5, z:  0.5, }, normal: Vec3 { x: -1.0, y:  0.0, z:  0.0 }, uv: Vec2 { x: 1.0, y: 0.0, } },
+    Vertex { translation: Vec3 { x: -0.5, y:  0.5, z: -0.5, }, normal: Vec3 { x: -1.0, y:  0.0, z:  0.0 }, uv: Vec2 { x: 1.0, y: 1.0, } },
+    Vertex { translation: Vec3 { x: -0.5, y: -0.5, z: -0.5, }, normal: Vec3 { x: -1.0, y:  0.0, z:  0.0 }, uv: Vec2 { x: 0.0, y: 1.0, } },
+    Vertex { translation: Vec3 { x: -0.5, y: -0.5, z: -0.5, }, normal: Vec3 { x: -1.0, y:  0.0, z:  0.0 }, uv: Vec2 { x: 0.0, y: 1.0, } },
+    Vertex { translation: Vec3 { x: -0.5, y: -0.5, z:  0.5, }, normal: Vec3 { x: -1.0, y:  0.0, z:  0.0 }, uv: Vec2 { x: 0.0, y: 0.0, } },
+    Vertex { translation: Vec3 { x: -0.5, y:  0.5, z:  0.5, }, normal: Vec3 { x: -1.0, y:  0.0, z:  0.0 }, uv: Vec2 { x: 1.0, y: 0.0, } },
+
+    Vertex { translation: Vec3 { x:  0.5, y:  0.5, z:  0.5, }, normal: Vec3 { x:  1.0, y:  0.0, z:  0.0 }, uv: Vec2 { x: 1.0, y: 0.0, } },
+    Vertex { translation: Vec3 { x:  0.5, y: -0.5, z: -0.5, }, normal: Vec3 { x:  1.0, y:  0.0, z:  0.0 }, uv: Vec2 { x: 0.0, y: 1.0, } },
+    Vertex { translation: Vec3 { x:  0.5, y:  0.5, z: -0.5, }, normal: Vec3 { x:  1.0, y:  0.0, z:  0.0 }, uv: Vec2 { x: 1.0, y: 1.0, } },
+    Vertex { translation: Vec3 { x:  0.5, y: -0.5, z: -0.5, }, normal: Vec3 { x:  1.0, y:  0.0, z:  0.0 }, uv: Vec2 { x: 0.0, y: 1.0, } },
+    Vertex { translation: Vec3 { x:  0.5, y:  0.5, z:  0.5, }, normal: Vec3 { x:  1.0, y:  0.0, z:  0.0 }, uv: Vec2 { x: 1.0, y: 0.0, } },
+    Vertex { translation: Vec3 { x:  0.5, y: -0.5, z:  0.5, }, normal: Vec3 { x:  1.0, y:  0.0, z:  0.0 }, uv: Vec2 { x: 0.0, y: 0.0, } },
+
+    Vertex { translation: Vec3 { x: -0.5, y: -0.5, z: -0.5, }, normal: Vec3 { x:  0.0, y: -1.0, z:  0.0 }, uv: Vec2 { x: 0.0, y: 1.0, } },
+    Vertex { translation: Vec3 { x:  0.5, y: -0.5, z: -0.5, }, normal: Vec3 { x:  0.0, y: -1.0, z:  0.0 }, uv: Vec2 { x: 1.0, y: 1.0, } },
+    Vertex { translation: Vec3 { x:  0.5, y: -0.5, z:  0.5, }, normal: Vec3 { x:  0.0, y: -1.0, z:  0.0 }, uv: Vec2 { x: 1.0, y: 0.0, } },
+    Vertex { translation: Vec3 { x:  0.5, y: -0.5, z:  0.5, }, normal: Vec3 { x:  0.0, y: -1.0, z:  0.0 }, uv: Vec2 { x: 1.0, y: 0.0, } },
+    Vertex { translation: Vec3 { x: -0.5, y: -0.5, z:  0.5, }, normal: Vec3 { x:  0.0, y: -1.0, z:  0.0 }, uv: Vec2 { x: 0.0, y: 0.0, } },
+    Vertex { translation: Vec3 { x: -0.5, y: -0.5, z: -0.5, }, normal: Vec3 { x:  0.0, y: -1.0, z:  0.0 }, uv: Vec2 { x: 0.0, y: 1.0, } },
+
+    Vertex { translation: Vec3 { x:  0.5, y:  0.5, z: -0.5, }, normal: Vec3 { x:  0.0, y:  1.0, z:  0.0 }, uv: Vec2 { x: 1.0, y: 1.0, } },
+    Vertex { translation: Vec3 { x: -0.5, y:  0.5, z: -0.5, }, normal: Vec3 { x:  0.0, y:  1.0, z:  0.0 }, uv: Vec2 { x: 0.0, y: 1.0, } },
+    Vertex { translation: Vec3 { x:  0.5, y:  0.5, z:  0.5, }, normal: Vec3 { x:  0.0, y:  1.0, z:  0.0 }, uv: Vec2 { x: 1.0, y: 0.0, } },
+    Vertex { translation: Vec3 { x:  0.5, y:  0.5, z:  0.5, }, normal: Vec3 { x:  0.0, y:  1.0, z:  0.0 }, uv: Vec2 { x: 1.0, y: 0.0, } },
+    Vertex { translation: Vec3 { x: -0.5, y:  0.5, z: -0.5, }, normal: Vec3 { x:  0.0, y:  1.0, z:  0.0 }, uv: Vec2 { x: 0.0, y: 1.0, } },
+    Vertex { translation: Vec3 { x: -0.5, y:  0.5, z:  0.5, }, normal: Vec3 { x:  0.0, y:  1.0, z:  0.0 }, uv: Vec2 { x: 0.0, y: 0.0, } },
+
+];
+
+/// A unit square facing up, which is scaled and moved into place by the floor's instance.
+#[rustfmt::skip]
+const FLOOR_VERTICES: &[Vertex] = &[
+    Vertex { translation: Vec3 { x: -1.0, y:  0.0, z: -1.0, }, normal: Vec3 { x:  0.0, y:  1.0, z:  0.0 }, uv: Vec2 { x: 0.0, y: 0.0, } },
+    Vertex { translation: Vec3 { x: -1.0, y:  0.0, z:  1.0, }, normal: Vec3 { x:  0.0, y:  1.0, z:  0.0 }, uv: Vec2 { x: 0.0, y: 1.0, } },
+    Vertex { translation: Vec3 { x:  1.0, y:  0.0, z:  1.0, }, normal: Vec3 { x:  0.0, y:  1.0, z:  0.0 }, uv: Vec2 { x: 1.0, y: 1.0, } },
+    Vertex { translation: Vec3 { x:  1.0, y:  0.0, z: -1.0, }, normal: Vec3 { x:  0.0, y:  1.0, z:  0.0 }, uv: Vec2 { x: 1.0, y: 0.0, } },
+];
+
+const FLOOR_INDICES: &[u16] = &[0, 1, 2, 0, 2, 3];
+
+/// The matrices transforming world space into the clip space of the lights casting shadows.
+#[repr(C)]
+#[derive(Clone, Copy, Debug)]
+struct ShadowUniforms {
+    directional_light_space: Mat4,
+    spot_light_space: Mat4,
+}
+
+fn main() {
+    let scene_path = std::env::args().nth(1).unwrap_or_else(|| {
+        concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/04-advanced-lighting/02-shadow-mapping/scene.ron"
+        )
+        .to_owned()
+    });
+    let scene = Scene::load(&scene_path).unwrap();
+
+    let (event_loop, window, mut size) = {
+        let event_loop = EventLoop::new();
+        let window = WindowBuilder::new()
+            .with_title("LearnOpenGL but it's WGPU-rs")
+            .build(&event_loop)
+            .unwrap();
+        let size = window.inner_size();
+        (event_loop, window, size)
+    };
+
+    let surface = wgpu::Surface::create(&window);
+
+    let (device, mut queue) = wgpu::Adapter::request(&wgpu::RequestAdapterOptions {
+        power_preference: wgpu::PowerPreference::HighPerformance,
+        backends: wgpu::BackendBit::all(),
+    })
+    .unwrap()
+    .request_device(&wgpu::DeviceDescriptor {
+        extensions: wgpu::Extensions {
+            anisotropic_filtering: false,
+        },
+        ..Default::default()
+    });
+
+    let mut shader_compiler = Compiler::new().expect("Could not initialize shader compiler");
+
+    let vs_module = prepare_shader(
+        &mut shader_compiler,
+        include_str!("shader.vert"),
+        ShaderKind::Vertex,
+        "shader.vert",
+        &device,
+    );
+    let fs_module = prepare_shader(
+        &mut shader_compiler,
+        include_str!("shader.frag"),
+        ShaderKind::Fragment,
+        "shader.frag",
+        &device,
+    );
+    let shadow_vs_module = prepare_shader(
+        &mut shader_compiler,
+        include_str!("shadow.vert"),
+        ShaderKind::Vertex,
+        "shadow.vert",
+        &device,
+    );
+    let light_source_vs_module = prepare_shader(
+        &mut shader_compiler,
+        include_str!("light_source.vert"),
+        ShaderKind::Vertex,
+        "light_source.vert",
+        &device,
+    );
+    let light_source_fs_module = prepare_shader(
+        &mut shader_compiler,
+        include_str!("light_source.frag"),
+        ShaderKind::Fragment,
+        "light_source.frag",
+        &device,
+    );
+
+    let mut init_encoder =
+        device.create_command_encoder(&wgpu::CommandEncoderDescriptor { todo: 0 });
+
+    let mut cube_mesh = Mesh::weld(VERTICES, Vertex::weld_key);
+    cube_mesh.optimize();
+
+    let vertex_buffer = device
+        .create_buffer_mapped(cube_mesh.vertices.len(), wgpu::BufferUsage::VERTEX)
+        .fill_from_slice(&cube_mesh.vertices);
+    // A cube has far fewer vertices than 16 bit indices can address.
+    let indices: Vec<u16> = cube_mesh
+        .indices
+        .iter()
+        .map(|&index| index as u16)
+        .collect();
+    let index_buffer = device
+        .create_buffer_mapped(indices.len(), wgpu::BufferUsage::INDEX)
+        .fill_from_slice(&indices);
+    let index_count = indices.len() as u32;
+
+    let floor_vertex_buffer = device
+        .create_buffer_mapped(FLOOR_VERTICES.len(), wgpu::BufferUsage::VERTEX)
+        .fill_from_slice(FLOOR_VERTICES);
+    let floor_index_buffer = device
+        .create_buffer_mapped(FLOOR_INDICES.len(), wgpu::BufferUsage::INDEX)
+        .fill_from_slice(FLOOR_INDICES);
+    let floor_instance_buffer = device
+        .create_buffer_mapped(1, wgpu::BufferUsage::VERTEX)
+        .fill_from_slice(&[Instance::new(
+            Mat4::from_translation(scene::vec3(FLOOR_CENTER))
+                * Mat4 {
+                    cols: [
+                        Vec4::new(FLOOR_HALF_EXTENT, 0.0, 0.0, 0.0),
+                        Vec4::new(0.0, 1.0, 0.0, 0.0),
+                        Vec4::new(0.0, 0.0, FLOOR_HALF_EXTENT, 0.0),
+                        Vec4::new(0.0, 0.0, 0.0, 1.0),
+                    ],
+                },
+        )]);
+
+    let vertex_uniform_bind_group_layout =
+        device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            bindings: &[wgpu::BindGroupLayoutBinding {
+                binding: 0,
+                visibility: wgpu::ShaderStage::VERTEX,
+                ty: wgpu::BindingType::UniformBuffer { dynamic: false },
+            }],
+        });
+
+    let light_bind_group_layout = LightManager::create_bind_group_layout(&device);
+
+    let material_bind_group_layout =
+        device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            bindings: &[
+                wgpu::BindGroupLayoutBinding {
+                    binding: 0,
+                    visibility: wgpu::ShaderStage::FRAGMENT,
+                    ty: wgpu::BindingType::UniformBuffer { dynamic: false },
+                },
+                wgpu::BindGroupLayoutBinding {
+                    binding: 1,
+                    visibility: wgpu::ShaderStage::FRAGMENT,
+                    ty: wgpu::BindingType::SampledTexture {
+                        multisampled: false,
+                        dimension: wgpu::TextureViewDimension::D2,
+                    },
+                },
+                wgpu::BindGroupLayoutBinding {
+                    binding: 2,
+                    visibility: wgpu::ShaderStage::FRAGMENT,
+                    ty: wgpu::BindingType::Sampler,
+                },
+                wgpu::BindGroupLayoutBinding {
+                    binding: 3,
+                    visibility: wgpu::ShaderStage::FRAGMENT,
+                    ty: wgpu::BindingType::SampledTexture {
+                        multisampled: false,
+                        dimension: wgpu::TextureViewDimension::D2,
+                    },
+                },
+                wgpu::BindGroupLayoutBinding {
+                    binding: 4,
+                    visibility: wgpu::ShaderStage::FRAGMENT,
+                    ty: wgpu::BindingType::Sampler,
+                },
+            ],
+        });
+
+    let shadow_bind_group_layout =
+        device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            bindings: &[
+                wgpu::BindGroupLayoutBinding {
+                    binding: 0,
+                    visibility: wgpu::ShaderStage::FRAGMENT,
+                    ty: wgpu::BindingType::UniformBuffer { dynamic: false },
+                },
+                wgpu::BindGroupLayoutBinding {
+                    binding: 1,
+                    visibility: wgpu::ShaderStage::FRAGMENT,
+                    ty: wgpu::BindingType::SampledTexture {
+                        multisampled: false,
+                        dimension: wgpu::TextureViewDimension::D2,
+                    },
+                },
+                wgpu::BindGroupLayoutBinding {
+                    binding: 2,
+                    visibility: wgpu::ShaderStage::FRAGMENT,
+                    ty: wgpu::BindingType::SampledTexture {
+                        multisampled: false,
+                        dimension: wgpu::TextureViewDimension::D2,
+                    },
+                },
+                wgpu::BindGroupLayoutBinding {
+                    binding: 3,
+                    visibility: wgpu::ShaderStage::FRAGMENT,
+                    ty: wgpu::BindingType::Sampler,
+                },
+            ],
+        });
+
+    let mut event_channel = shrev::EventChannel::new();
+    let event_reader = event_channel.register_reader();
+    let mut input_handler = InputHandler::<StringBindings>::new();
+
+    let mut camera = scene.create_camera(event_reader);
+
+    let SceneBuffers {
+        cube_instance_buffer,
+        cube_count,
+        material_uniform_buffer,
+    } = scene.create_buffers(&device);
+
+    let mut light_manager = scene
+        .create_light_manager(&device, &light_bind_group_layout, &camera)
+        .unwrap();
+
+    let vertex_uniform_buffer = device
+        .create_buffer_mapped(1, wgpu::BufferUsage::UNIFORM | wgpu::BufferUsage::COPY_DST)
+        .fill_from_slice(&[camera.get_view_projection_matrix(
+            size.width as f32,
+            size.height as f32,
+            0.1,
+            100.0,
+        )]);
+
+    let vertex_uniform_bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+        layout: &vertex_uniform_bind_group_layout,
+        bindings: &[wgpu::Binding {
+            binding: 0,
+            resource: wgpu::BindingResource::Buffer {
+                buffer: &vertex_uniform_buffer,
+                range: 0..std::mem::size_of::<Mat4>() as wgpu::BufferAddress,
+            },
+        }],
+    });
+
+    // The light spaces are recalculated every frame, since the lights can move.
+    let (scene_center, scene_radius) = scene_bounds(&scene);
+    let directional_light_space_buffer = device
+        .create_buffer_mapped(1, wgpu::BufferUsage::UNIFORM | wgpu::BufferUsage::COPY_DST)
+        .fill_from_slice(&[Mat4::identity()]);
+    let directional_light_space_bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+        layout: &vertex_uniform_bind_group_layout,
+        bindings: &[wgpu::Binding {
+            binding: 0,
+            resource: wgpu::BindingResource::Buffer {
+                buffer: &directional_light_space_buffer,
+                range: 0..std::mem::size_of::<Mat4>() as wgpu::BufferAddress,
+            },
+        }],
+    });
+    let spot_light_space_buffer = device
+        .create_buffer_mapped(1, wgpu::BufferUsage::UNIFORM | wgpu::BufferUsage::COPY_DST)
+        .fill_from_slice(&[Mat4::identity()]);
+    let spot_light_space_bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+        layout: &vertex_uniform_bind_group_layout,
+        bindings: &[wgpu::Binding {
+            binding: 0,
+            resource: wgpu::BindingResource::Buffer {
+                buffer: &spot_light_space_buffer,
+                range: 0..std::mem::size_of::<Mat4>() as wgpu::BufferAddress,
+            },
+        }],
+    });
+
+    let directional_shadow_map = ShadowMap::new(&device, SHADOW_MAP_SIZE);
+    let spot_shadow_map = ShadowMap::new(&device, SHADOW_MAP_SIZE);
+
+    let shadow_uniform_buffer = device
+        .create_buffer_mapped(1, wgpu::BufferUsage::UNIFORM | wgpu::BufferUsage::COPY_DST)
+        .fill_from_slice(&[ShadowUniforms {
+            directional_light_space: Mat4::identity(),
+            spot_light_space: Mat4::identity(),
+        }]);
+    let shadow_bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+        layout: &shadow_bind_group_layout,
+        bindings: &[
+            wgpu::Binding {
+                binding: 0,
+                resource: wgpu::BindingResource::Buffer {
+                    buffer: &shadow_uniform_buffer,
+                    range: 0..std::mem::size_of::<ShadowUniforms>() as wgpu::BufferAddress,
+                },
+            },
+            wgpu::Binding {
+                binding: 1,
+                resource: wgpu::BindingResource::TextureView(&directional_shadow_map.view),
+            },
+            wgpu::Binding {
+                binding: 2,
+                resource: wgpu::BindingResource::TextureView(&spot_shadow_map.view),
+            },
+            wgpu::Binding {
+                binding: 3,
+                // Both shadow maps share the same comparison sampler.
+                resource: wgpu::BindingResource::Sampler(&directional_shadow_map.sampler),
+            },
+        ],
+    });
+
+    let material_diffuse_texture_image = image::open(
+        scene.resolve_path(
+            scene
+                .material
+                .diffuse_texture
+                .as_ref()
+                .expect("The material has no diffuse texture"),
+        ),
+    )
+    .unwrap()
+    .to_rgba();
+    let (width, height) = material_diffuse_texture_image.dimensions();
+    let material_diffuse_texture_raw = material_diffuse_texture_image.to_vec();
+    let (_, material_diffuse_texture_view, material_diffuse_texture_sampler) =
+        create_sampled_texture2d(
+            &device,
+            &mut init_encoder,
+            &material_diffuse_texture_raw,
+            width,
+            height,
+            wgpu::TextureFormat::Rgba8UnormSrgb,
+        );
+
+    let material_specular_texture_image = image::open(
+        scene.resolve_path(
+            scene
+                .material
+                .specular_texture
+                .as_ref()
+                .expect("The material has no specular texture"),
+        ),
+    )
+    .unwrap()
+    .to_rgba();
+    let (width, height) = material_specular_texture_image.dimensions();
+    let material_specular_texture_raw = material_specular_texture_image.to_vec();
+    let (_, material_specular_texture_view, material_specular_texture_sampler) =
+        create_sampled_texture2d(
+            &device,
+            &mut init_encoder,
+            &material_specular_texture_raw,
+            width,
+            height,
+            wgpu::TextureFormat::Rgba8Unorm,
+        );
+
+    let material_bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+        layout: &material_bind_group_layout,
+        bindings: &[
+            wgpu::Binding {
+                binding: 0,
+                resource: wgpu::BindingResource::Buffer {
+                    buffer: &material_uniform_buffer,
+                    range: 0..std::mem::size_of::<Material>() as wgpu::BufferAddress,
+                },
+            },
+            wgpu::Binding {
+                binding: 1,
+                resource: wgpu::BindingResource::TextureView(&material_diffuse_texture_view),
+            },
+            wgpu::Binding {
+                binding: 2,
+                resource: wgpu::BindingResource::Sampler(&material_diffuse_texture_sampler),
+            },
+            wgpu::Binding {
+                binding: 3,
+                resource: wgpu::BindingResource::TextureView(&material_specular_texture_view),
+            },
+            wgpu::Binding {
+                binding: 4,
+                resource: wgpu::BindingResource::Sampler(&material_specular_texture_sampler),
+            },
+        ],
+    });
+
+    let floor_diffuse_texture_raw = checkerboard(256, 8, [64, 64, 64, 255], [192, 192, 192, 255]);
+    let (_, floor_diffuse_texture_view, floor_diffuse_texture_sampler) = create_sampled_texture2d(
+        &device,
+        &mut init_encoder,
+        &floor_diffuse_texture_raw,
+        256,
+        256,
+        wgpu::TextureFormat::Rgba8UnormSrgb,
+    );
+    let floor_specular_texture_raw = checkerboard(256, 8, [16, 16, 16, 255], [96, 96, 96, 255]);
+    let (_, floor_specular_texture_view, floor_specular_texture_sampler) = create_sampled_texture2d(
+        &device,
+        &mut init_encoder,
+        &floor_specular_texture_raw,
+        256,
+        256,
+        wgpu::TextureFormat::Rgba8Unorm,
+    );
+
+    let floor_material_bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+        layout: &material_bind_group_layout,
+        bindings: &[
+            wgpu::Binding {
+                binding: 0,
+                resource: wgpu::BindingResource::Buffer {
+                    buffer: &material_uniform_buffer,
+                    range: 0..std::mem::size_of::<Material>() as wgpu::BufferAddress,
+                },
+            },
+            wgpu::Binding {
+                binding: 1,
+                resource: wgpu::BindingResource::TextureView(&floor_diffuse_texture_view),
+            },
+            wgpu::Binding {
+                binding: 2,
+                resource: wgpu::BindingResource::Sampler(&floor_diffuse_texture_sampler),
+            },
+            wgpu::Binding {
+                binding: 3,
+                resource: wgpu::BindingResource::TextureView(&floor_specular_texture_view),
+            },
+            wgpu::Binding {
+                binding: 4,
+                resource: wgpu::BindingResource::Sampler(&floor_specular_texture_sampler),
+            },
+        ],
+    });
+
+    let mut swap_chain_descriptor = wgpu::SwapChainDescriptor {
+        usage: wgpu::TextureUsage::OUTPUT_ATTACHMENT,
+        format: wgpu::TextureFormat::Bgra8UnormSrgb,
+        width: size.width,
+        height: size.height,
+        present_mode: wgpu::PresentMode::Vsync,
+    };
+
+    let (mut depth_texture, mut depth_texture_view) =
+        create_depth_texture(&device, &swap_chain_descriptor);
+
+    let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+        bind_group_layouts: &[
+            &vertex_uniform_bind_group_layout,
+            &light_bind_group_layout,
+            &material_bind_group_layout,
+            &shadow_bind_group_layout,
+        ],
+    });
+
+    let render_pipeline_descriptor = wgpu::RenderPipelineDescriptor {
+        layout: &pipeline_layout,
+        vertex_stage: wgpu::ProgrammableStageDescriptor {
+            module: &vs_module,
+            entry_point: "main",
+        },
+        fragment_stage: Some(wgpu::ProgrammableStageDescriptor {
+            module: &fs_module,
+            entry_point: "main",
+        }),
+        rasterization_state: Some(wgpu::RasterizationStateDescriptor {
+            front_face: wgpu::FrontFace::Ccw,
+            cull_mode: wgpu::CullMode::Back,
+            depth_bias: 0,
+            depth_bias_slope_scale: 0.0,
+            depth_bias_clamp: 0.0,
+        }),
+        primitive_topology: wgpu::PrimitiveTopology::TriangleList,
+        color_states: &[wgpu::ColorStateDescriptor {
+            format: swap_chain_descriptor.format,
+            alpha_blend: wgpu::BlendDescriptor::REPLACE,
+            color_blend: wgpu::BlendDescriptor::REPLACE,
+            write_mask: wgpu::ColorWrite::ALL,
+        }],
+        depth_stencil_state: Some(wgpu::DepthStencilStateDescriptor {
+            format: DEPTH_FORMAT,
+            depth_write_enabled: true,
+            depth_compare: wgpu::CompareFunction::Less,
+            stencil_front: wgpu::StencilStateFaceDescriptor::IGNORE,
+            stencil_back: wgpu::StencilStateFaceDescriptor::IGNORE,
+            stencil_read_mask: 0,
+            stencil_write_mask: 0,
+        }),
+        index_format: wgpu::IndexFormat::Uint16,
+        vertex_buffers: &[
+            wgpu::VertexBufferDescriptor {
+                stride: std::mem::size_of::<Vertex>() as wgpu::BufferAddress,
+                step_mode: wgpu::InputStepMode::Vertex,
+                attributes: &[
+                    wgpu::VertexAttributeDescriptor {
+                        offset: 0,
+                        format: wgpu::VertexFormat::Float3,
+                        shader_location: 0,
+                    },
+                    wgpu::VertexAttributeDescriptor {
+                        offset: std::mem::size_of::<Vec3>() as wgpu::BufferAddress,
+                        format: wgpu::VertexFormat::Float3,
+                        shader_location: 1,
+                    },
+                    wgpu::VertexAttributeDescriptor {
+                        offset: (std::mem::size_of::<Vec3>() * 2) as wgpu::BufferAddress,
+                        format: wgpu::VertexFormat::Float2,
+                        shader_location: 2,
+                    },
+                ],
+            },
+            wgpu::VertexBufferDescriptor {
+                stride: std::mem::size_of::<Instance>() as wgpu::BufferAddress,
+                step_mode: wgpu::InputStepMode::Instance,
+                attributes: &[
+                    wgpu::VertexAttributeDescriptor {
+                        offset: 0,
+                        format: wgpu::VertexFormat::Float4,
+                        shader_location: 3,
+                    },
+                    wgpu::VertexAttributeDescriptor {
+                        offset: std::mem::size_of::<Vec4>() as wgpu::BufferAddress,
+                        format: wgpu::VertexFormat::Float4,
+                        shader_location: 4,
+                    },
+                    wgpu::VertexAttributeDescriptor {
+                        offset: std::mem::size_of::<Vec4>() as wgpu::BufferAddress * 2,
+                        format: wgpu::VertexFormat::Float4,
+                        shader_location: 5,
+                    },
+                    wgpu::VertexAttributeDescriptor {
+                        offset: std::mem::size_of::<Vec4>() as wgpu::BufferAddress * 3,
+                        format: wgpu::VertexFormat::Float4,
+                        shader_location: 6,
+                    },
+                    wgpu::VertexAttributeDescriptor {
+                        offset: std::mem::size_of::<Mat4>() as wgpu::BufferAddress,
+                        format: wgpu::VertexFormat::Float3,
+                        shader_location: 7,
+                    },
+                    wgpu::VertexAttributeDescriptor {
+                        offset: (std::mem::size_of::<Mat4>() + std::mem::size_of::<Vec3>())
+                            as wgpu::BufferAddress,
+                        format: wgpu::VertexFormat::Float3,
+                        shader_location: 8,
+                    },
+                    wgpu::VertexAttributeDescriptor {
+                        offset: (std::mem::size_of::<Mat4>() + std::mem::size_of::<Vec3>() * 2)
+                            as wgpu::BufferAddress,
+                        format: wgpu::VertexFormat::Float3,
+                        shader_location: 9,
+                    },
+                ],
+            },
+        ],
+        sample_count: 1,
+        sample_mask: !0,
+        alpha_to_coverage_enabled: false,
+    };
+    let pipeline = device.create_render_pipeline(&render_pipeline_descriptor);
+
+    let light_source_pipeline_layout =
+        device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            bind_group_layouts: &[&vertex_uniform_bind_group_layout],
+        });
+
+    let mut light_source_pipeline_descriptor = render_pipeline_descriptor.clone();
+    light_source_pipeline_descriptor.layout = &light_source_pipeline_layout;
+    light_source_pipeline_descriptor.vertex_stage = wgpu::ProgrammableStageDescriptor {
+        module: &light_source_vs_module,
+        entry_point: "main",
+    };
+    light_source_pipeline_descriptor.fragment_stage = Some(wgpu::ProgrammableStageDescriptor {
+        module: &light_source_fs_module,
+        entry_point: "main",
+    });
+    let light_source_pipeline = device.create_render_pipeline(&light_source_pipeline_descriptor);
+
+    let shadow_pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+        bind_group_layouts: &[&vertex_uniform_bind_group_layout],
+    });
+
+    // The shadow pass only writes depth, so it has no fragment stage and no color targets.
+    let mut shadow_pipeline_descriptor = render_pipeline_descriptor.clone();
+    shadow_pipeline_descriptor.layout = &shadow_pipeline_layout;
+    shadow_pipeline_descriptor.vertex_stage = wgpu::ProgrammableStageDescriptor {
+        module: &shadow_vs_module,
+        entry_point: "main",
+    };
+    shadow_pipeline_descriptor.fragment_stage = None;
+    shadow_pipeline_descriptor.color_states = &[];
+    // Pushes the stored depths away from the light to avoid shadow acne. The slope-scaled part grows with how steeply
+    // a surface is angled away from the light, which is where acne is the worst.
+    shadow_pipeline_descriptor.rasterization_state = Some(wgpu::RasterizationStateDescriptor {
+        front_face: wgpu::FrontFace::Ccw,
+        cull_mode: wgpu::CullMode::Back,
+        depth_bias: 2,
+        depth_bias_slope_scale: 2.0,
+        depth_bias_clamp: 0.0,
+    });
+    shadow_pipeline_descriptor.depth_stencil_state = Some(wgpu::DepthStencilStateDescriptor {
+        format: SHADOW_MAP_FORMAT,
+        depth_write_enabled: true,
+        depth_compare: wgpu::CompareFunction::Less,
+        stencil_front: wgpu::StencilStateFaceDescriptor::IGNORE,
+        stencil_back: wgpu::StencilStateFaceDescriptor::IGNORE,
+        stencil_read_mask: 0,
+        stencil_write_mask: 0,
+    });
+    let shadow_pipeline = device.create_render_pipeline(&shadow_pipeline_descriptor);
+
+    let mut swap_chain = device.create_swap_chain(&surface, &swap_chain_descriptor);
+
+    queue.submit(&[init_encoder.finish()]);
+
+    let mut time = Time::new();
+
+    event_loop.run(move |event, _, control_flow| {
+        input_handler.send_event(&event, &mut event_channel);
+
+        camera.on_event(&event_channel);
+
+        match event {
+            Event::WindowEvent {
+                event: WindowEvent::CloseRequested,
+                ..
+            } => *control_flow = ControlFlow::Exit,
+            Event::WindowEvent {
+                event: WindowEvent::Resized(new_size),
+                ..
+            } => {
+                size = new_size;
+                swap_chain_descriptor.width = size.width;
+                swap_chain_descriptor.height = size.height;
+                swap_chain = device.create_swap_chain(&surface, &swap_chain_descriptor);
+                let dt = create_depth_texture(&device, &swap_chain_descriptor);
+                depth_texture = dt.0;
+                depth_texture_view = dt.1;
+                *control_flow = ControlFlow::Poll;
+            }
+            Event::WindowEvent {
+                event:
+                    WindowEvent::MouseInput {
+                        button: winit::event::MouseButton::Left,
+                        state: winit::event::ElementState::Pressed,
+                        ..
+                    },
+                ..
+            } => {
+                camera.is_controlled = true;
+                window.set_cursor_grab(true).ok();
+                window.set_cursor_visible(false);
+                *control_flow = ControlFlow::Poll;
+            }
+            Event::WindowEvent {
+                event:
+                    WindowEvent::KeyboardInput {
+                        input:
+                            KeyboardInput {
+                                virtual_keycode: Some(VirtualKeyCode::Escape),
+                                state: winit::event::ElementState::Pressed,
+                                ..
+                            },
+                        ..
+                    },
+                ..
+            } => {
+                camera.is_controlled = false;
+                window.set_cursor_grab(false).ok();
+                window.set_cursor_visible(true);
+                *control_flow = ControlFlow::Poll;
+            }
+            Event::WindowEvent {
+                event:
+                    WindowEvent::KeyboardInput {
+                        input:
+                            KeyboardInput {
+                                virtual_keycode: Some(VirtualKeyCode::B),
+                                state: winit::event::ElementState::Pressed,
+                                ..
+                            },
+                        ..
+                    },
+                ..
+            } => {
+                let specular_model = match light_manager.specular_model() {
+                    SpecularModel::Phong => SpecularModel::BlinnPhong,
+                    SpecularModel::BlinnPhong => SpecularModel::Phong,
+                };
+                light_manager.set_specular_model(specular_model);
+                *control_flow = ControlFlow::Poll;
+            }
+            Event::MainEventsCleared => {
+                window.request_redraw();
+                *control_flow = ControlFlow::Poll;
+            }
+            Event::RedrawRequested(_) => {
+                input_handler.send_frame_begin();
+                camera.update(&input_handler);
+
+                time.update();
+                light_manager.animate(time.elapsed());
+
+                let frame = swap_chain.get_next_texture();
+                let mut encoder =
+                    device.create_command_encoder(&wgpu::CommandEncoderDescriptor { todo: 0 });
+                {
+                    let view_projection = camera.get_view_projection_matrix(
+                        size.width as f32,
+                        size.height as f32,
+                        0.1,
+                        100.0,
+                    );
+                    let staging_buffer = device
+                        .create_buffer_mapped(1, wgpu::BufferUsage::COPY_SRC)
+                        .fill_from_slice(&[view_projection]);
+                    encoder.copy_buffer_to_buffer(
+                        &staging_buffer,
+                        0,
+                        &vertex_uniform_buffer,
+                        0,
+                        std::mem::size_of::<Mat4>() as wgpu::BufferAddress,
+                    );
+                    light_manager.update(&device, &mut encoder, &light_bind_group_layout, &camera);
+
+                    let directional_light_space = shadow::directional_light_space(
+                        light_manager.directional_light().direction.0,
+                        scene_center,
+                        scene_radius,
+                    );
+                    let spot_light_space = light_manager
+                        .spot_lights()
+                        .first()
+                        .map(|light| {
+                            shadow::spot_light_space(
+                                light.translation.0,
+                                light.direction,
+                                light.outer_cutoff,
+                                0.1,
+                                100.0,
+                            )
+                        })
+                        .unwrap_or_else(Mat4::identity);
+                    copy_to_buffer(
+                        &device,
+                        &mut encoder,
+                        &[directional_light_space],
+                        &directional_light_space_buffer,
+                    );
+                    copy_to_buffer(
+                        &device,
+                        &mut encoder,
+                        &[spot_light_space],
+                        &spot_light_space_buffer,
+                    );
+                    copy_to_buffer(
+                        &device,
+                        &mut encoder,
+                        &[ShadowUniforms {
+                            directional_light_space,
+                            spot_light_space,
+                        }],
+                        &shadow_uniform_buffer,
+                    );
+
+                    let shadow_passes = [
+                        (&directional_shadow_map, &directional_light_space_bind_group),
+                        (&spot_shadow_map, &spot_light_space_bind_group),
+                    ];
+                    let shadow_pass_count = if light_manager.spot_lights().is_empty() {
+                        1
+                    } else {
+                        2
+                    };
+                    for (shadow_map, light_space_bind_group) in &shadow_passes[..shadow_pass_count]
+                    {
+                        let mut shadow_pass =
+                            encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                                color_attachments: &[],
+                                depth_stencil_attachment: Some(
+                                    wgpu::RenderPassDepthStencilAttachmentDescriptor {
+                                        attachment: &shadow_map.view,
+                                        depth_load_op: wgpu::LoadOp::Clear,
+                                        depth_store_op: wgpu::StoreOp::Store,
+                                        clear_depth: 1.0,
+                                        stencil_load_op: wgpu::LoadOp::Clear,
+                                        stencil_store_op: wgpu::StoreOp::Store,
+                                        clear_stencil: 0,
+                                    },
+                                ),
+                            });
+                        shadow_pass.set_pipeline(&shadow_pipeline);
+                        shadow_pass.set_bind_group(0, light_space_bind_group, &[]);
+                        shadow_pass.set_vertex_buffers(
+                            0,
+                            &[(&vertex_buffer, 0), (&cube_instance_buffer, 0)],
+                        );
+                        shadow_pass.set_index_buffer(&index_buffer, 0);
+                        shadow_pass.draw_indexed(0..index_count, 0, 0..cube_count);
+
+                        shadow_pass.set_vertex_buffers(
+                            0,
+                            &[(&floor_vertex_buffer, 0), (&floor_instance_buffer, 0)],
+                        );
+                        shadow_pass.set_index_buffer(&floor_index_buffer, 0);
+                        shadow_pass.draw_indexed(0..FLOOR_INDICES.len() as u32, 0, 0..1);
+                    }
+
+                    let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                        color_attachments: &[wgpu::RenderPassColorAttachmentDescriptor {
+                            attachment: &frame.view,
+                            resolve_target: None,
+                            load_op: wgpu::LoadOp::Clear,
+                            store_op: wgpu::StoreOp::Store,
+                            clear_color: wgpu::Color {
+                                r: 0.1,
+                                g: 0.1,
+                                b: 0.1,
+                                a: 1.0,
+                            },
+                        }],
+                        depth_stencil_attachment: Some(
+                            wgpu::RenderPassDepthStencilAttachmentDescriptor {
+                                attachment: &depth_texture_view,
+                                depth_load_op: wgpu::LoadOp::Clear,
+                                depth_store_op: wgpu::StoreOp::Store,
+                                clear_depth: 1.0,
+                                stencil_load_op: wgpu::LoadOp::Clear,
+                                stencil_store_op: wgpu::StoreOp::Store,
+                                clear_stencil: 0,
+                            },
+                        ),
+                    });
+                    render_pass.set_pipeline(&pipeline);
+                    render_pass.set_bind_group(0, &vertex_uniform_bind_group, &[]);
+                    render_pass.set_bind_group(1, light_manager.bind_group(), &[]);
+                    render_pass.set_bind_group(2, &material_bind_group, &[]);
+                    render_pass.set_bind_group(3, &shadow_bind_group, &[]);
+                    render_pass
+                        .set_vertex_buffers(0, &[(&vertex_buffer, 0), (&cube_instance_buffer, 0)]);
+                    render_pass.set_index_buffer(&index_buffer, 0);
+                    render_pass.draw_indexed(0..index_count, 0, 0..cube_count);
+
+                    render_pass.set_bind_group(2, &floor_material_bind_group, &[]);
+                    render_pass.set_vertex_buffers(
+                        0,
+                        &[(&floor_vertex_buffer, 0), (&floor_instance_buffer, 0)],
+                    );
+                    render_pass.set_index_buffer(&floor_index_buffer, 0);
+                    render_pass.draw_indexed(0..FLOOR_INDICES.len() as u32, 0, 0..1);
+
+                    render_pass.set_pipeline(&light_source_pipeline);
+                    render_pass.set_bind_group(0, &vertex_uniform_bind_group, &[]);
+                    let light_source_instance_buffer = light_manager.light_source_instance_buffer();
+                    render_pass.set_vertex_buffers(
+                        0,
+                        &[
+                            (&vertex_buffer, 0),
+                            (light_source_instance_buffer.buffer(), 0),
+                        ],
+                    );
+                    render_pass.set_index_buffer(&index_buffer, 0);
+                    render_pass.draw_indexed(
+                        0..index_count,
+                        0,
+                        0..light_source_instance_buffer.len() as u32,
+                    );
+                }
+
+                queue.submit(&[encoder.finish()]);
+
+                *control_flow = ControlFlow::Poll;
+            }
+            _ => {}
+        }
+    })
+}
+
+/// The center and radius of a sphere around the cubes and the floor, which the directional light's shadow map covers.
+fn scene_bounds(scene: &Scene) -> (Vec3, f32) {
+    let floor_center = scene::vec3(FLOOR_CENTER);
+    let floor_corner = Vec3::new(FLOOR_HALF_EXTENT, 0.0, FLOOR_HALF_EXTENT);
+    let mut min = floor_center - floor_corner;
+    let mut max = floor_center + floor_corner;
+    for object in &scene.objects {
+        let translation = scene::vec3(object.translation);
+        // Half of a unit cube's diagonal, so that rotated cubes fit too.
+        let extent = Vec3::new(0.87, 0.87, 0.87);
+        let (lower, upper) = (translation - extent, translation + extent);
+        min = Vec3::new(min.x.min(lower.x), min.y.min(lower.y), min.z.min(lower.z));
+        max = Vec3::new(max.x.max(upper.x), max.y.max(upper.y), max.z.max(upper.z));
+    }
+    ((min + max) / 2.0, (max - min).mag() / 2.0)
+}
+
+/// Generates the pixels of a square RGBA texture with `tiles` by `tiles` alternating squares.
+fn checkerboard(size: u32, tiles: u32, dark: [u8; 4], light: [u8; 4]) -> Vec<u8> {
+    let tile_size = size / tiles;
+    let mut pixels = Vec::with_capacity((size * size * 4) as usize);
+    for y in 0..size {
+        for x in 0..size {
+            let color = if (x / tile_size + y / tile_size) % 2 == 0 {
+                dark
+            } else {
+                light
+            };
+            pixels.extend_from_slice(&color);
+        }
+    }
+    pixels
+}
+
+fn copy_to_buffer<T: 'static + Copy>(
+    device: &wgpu::Device,
+    encoder: &mut wgpu::CommandEncoder,
+    data: &[T],
+    buffer: &wgpu::Buffer,
+) {
+    let staging_buffer = device
+        .create_buffer_mapped(data.len(), wgpu::BufferUsage::COPY_SRC)
+        .fill_from_slice(data);
+    encoder.copy_buffer_to_buffer(
+        &staging_buffer,
+        0,
+        buffer,
+        0,
+        (std::mem::size_of::<T>() * data.len()) as wgpu::BufferAddress,
+    );
+}
+
+pub fn prepare_shader(
+    compiler: &mut Compiler,
+    source_text: &str,
+    shader_kind: ShaderKind,
+    input_file_name: &str,
+    device: &wgpu::Device,
+) -> wgpu::ShaderModule {
+    let artifact = compiler
+        .compile_into_spirv(source_text, shader_kind, input_file_name, "main", None)
+        .unwrap();
+    device.create_shader_module(artifact.as_binary())
+}
+
+pub fn create_depth_texture(
+    device: &wgpu::Device,
+    swap_chain_desc: &wgpu::SwapChainDescriptor,
+) -> (wgpu::Texture, wgpu::TextureView) {
+    let desc = wgpu::TextureDescriptor {
+        format: DEPTH_FORMAT,
+        usage: wgpu::TextureUsage::OUTPUT_ATTACHMENT,
+        ..swap_chain_desc.to_texture_desc()
+    };
+    let texture = device.create_texture(&desc);
+    let view = texture.create_default_view();
+    (texture, view)
+}
+
+/// Uploads an RGBA texture. Color textures should use an sRGB `format`, so that they're decoded into linear space
+/// when sampled, while textures holding other data like specular intensities or normals have to use a linear one.
+pub fn create_sampled_texture2d(
+    device: &wgpu::Device,
+    encoder: &mut wgpu::CommandEncoder,
+    raw_texture: &[u8],
+    width: u32,
+    height: u32,
+    format: wgpu::TextureFormat,
+) -> (wgpu::Texture, wgpu::TextureView, wgpu::Sampler) {
+    let texture_extent = wgpu::Extent3d {
+        width,
+        height,
+        depth: 1,
+    };
+
+    let texture_buffer = device
+        .create_buffer_mapped(raw_texture.len(), wgpu::BufferUsage::COPY_SRC)
+        .fill_from_slice(&raw_texture);
+    let texture = device.create_texture(&wgpu::TextureDescriptor {
+        size: texture_extent,
+        array_layer_count: 1,
+        mip_level_count: 0,
+        sample_count: 1,
+        dimension: wgpu::TextureDimension::D2,
+        format,
+        usage: wgpu::TextureUsage::SAMPLED | wgpu::TextureUsage::COPY_DST,
+    });
+
+    encoder.copy_buffer_to_texture(
+        wgpu::BufferCopyView {
+            buffer: &texture_buffer,
+            offset: 0,
+            row_pitch: 4 * width,
+            image_height: height,
+        },
+        wgpu::TextureCopyView {
+            texture: &texture,
+            mip_level: 0,
+            array_layer: 0,
+            origin: wgpu::Origin3d {
+                x: 0.0,
+                y: 0.0,
+                z: 0.0,
+            },
+        },
+        texture_extent,
+    );
+
+    let texture_view = texture.create_default_view();
+    let texture_sampler = device.create_sampler(&wgpu::SamplerDescriptor {
+        address_mode_u: wgpu::AddressMode::ClampToEdge,
+        address_mode_v: wgpu::AddressMode::ClampToEdge,
+        address_mode_w: wgpu::AddressMode::ClampToEdge,
+        mag_filter: wgpu::FilterMode::Nearest,
+        min_filter: wgpu::FilterMode::Linear,
+        mipmap_filter: wgpu::FilterMode::Nearest,
+        lod_min_clamp: 0.0,
+        lod_max_clamp: 0.0,
+        compare_function: wgpu::CompareFunction::Never,
+    });
+
+    (texture, texture_view, texture_sampler)
+}
+
+#[repr(C)]
+#[derive(Clone, Copy, Debug)]
+pub struct Vertex {
+    pub translation: Vec3,
+    pub normal: Vec3,
+    pub uv: Vec2,
+}
+
+impl Vertex {
+    /// The bit patterns of every attribute, so that only exact duplicates get welded together.
+    fn weld_key(&self) -> [u32; 8] {
+        [
+            self.translation.x.to_bits(),
+            self.translation.y.to_bits(),
+            self.translation.z.to_bits(),
+            self.normal.x.to_bits(),
+            self.normal.y.to_bits(),
+            self.normal.z.to_bits(),
+            self.uv.x.to_bits(),
+            self.uv.y.to_bits(),
+        ]
+    }
+}
@@ -0,0 +1,352 @@
+#version 450
+
+#define SPECULAR_MODEL_PHONG 0
+#define SPECULAR_MODEL_BLINN_PHONG 1
+
+#define SHADOW_FILTER_PCF 0
+#define SHADOW_FILTER_VARIANCE 1
+#define SHADOW_FILTER_PERCENTAGE_CLOSER_SOFT 2
+
+// Must match the spot light's near and far planes in `soft-shadows.rs`.
+#define SPOT_SHADOW_NEAR_PLANE 0.1
+#define SPOT_SHADOW_FAR_PLANE 100.0
+
+layout(location = 0) in vec3 translation;
+layout(location = 1) in vec3 normal;
+layout(location = 2) in vec2 uv;
+
+layout(location = 0) out vec4 fragment_color;
+
+struct DirectionalLight {
+    vec3 direction;
+
+    vec3 ambient;
+    vec3 diffuse;
+    vec3 specular;
+};
+
+struct PointLight {
+    vec3 translation;
+
+    vec3 ambient;
+    vec3 diffuse;
+    vec3 specular;
+
+    float constant;
+    float linear;
+    float quadratic;
+
+    int shadow_map_index;
+};
+
+struct SpotLight {
+    vec3 translation;
+    vec3 direction;
+    float cutoff;
+    float outer_cutoff;
+
+    float constant;
+    float linear;
+    float quadratic;
+
+    vec3 ambient;
+    vec3 diffuse;
+    vec3 specular;
+
+    int cookie_index;
+    mat4 cookie_space;
+};
+
+layout(set = 1, binding = 0) uniform Uniforms {
+    vec3 view_translation;
+    uint point_light_count;
+    uint spot_light_count;
+    uint specular_model;
+    DirectionalLight directional_light;
+};
+layout(set = 1, binding = 1, std430) readonly buffer PointLights {
+    PointLight point_lights[];
+};
+layout(set = 1, binding = 2, std430) readonly buffer SpotLights {
+    SpotLight spot_lights[];
+};
+layout(set = 1, binding = 3) uniform texture2DArray spot_light_cookies;
+layout(set = 1, binding = 4) uniform sampler spot_light_cookie_sampler;
+
+layout(set = 2, binding = 0) uniform Material {
+    float shininess;
+} material;
+layout(set = 2, binding = 1) uniform texture2D material_diffuse_texture;
+layout(set = 2, binding = 2) uniform sampler material_diffuse_sampler;
+layout(set = 2, binding = 3) uniform texture2D material_specular_texture;
+layout(set = 2, binding = 4) uniform sampler material_specular_sampler;
+
+layout(set = 3, binding = 0) uniform ShadowUniforms {
+    mat4 directional_light_space;
+    mat4 spot_light_space;
+    uint directional_shadow_filter;
+    uint spot_shadow_filter;
+    float directional_light_size;
+    float spot_light_size;
+};
+layout(set = 3, binding = 1) uniform texture2D directional_shadow_map;
+layout(set = 3, binding = 2) uniform texture2D spot_shadow_map;
+layout(set = 3, binding = 3) uniform sampler shadow_sampler;
+layout(set = 3, binding = 4) uniform sampler depth_sampler;
+layout(set = 3, binding = 5) uniform texture2D directional_variance_shadow_map;
+layout(set = 3, binding = 6) uniform texture2D spot_variance_shadow_map;
+layout(set = 3, binding = 7) uniform sampler variance_sampler;
+
+// Sample positions spread evenly over the unit disk, used both for the blocker search and the filtering of
+// percentage-closer soft shadows.
+const vec2 poisson_disk[16] = vec2[](
+    vec2(-0.94201624, -0.39906216), vec2(0.94558609, -0.76890725),
+    vec2(-0.09418410, -0.92938870), vec2(0.34495938, 0.29387760),
+    vec2(-0.91588581, 0.45771432), vec2(-0.81544232, -0.87912464),
+    vec2(-0.38277543, 0.27676845), vec2(0.97484398, 0.75648379),
+    vec2(0.44323325, -0.97511554), vec2(0.53742981, -0.47373420),
+    vec2(-0.26496911, -0.41893023), vec2(0.79197514, 0.19090188),
+    vec2(-0.24188840, 0.99706507), vec2(-0.81409955, 0.91437590),
+    vec2(0.19984126, 0.78641367), vec2(0.14383161, -0.14100790)
+);
+
+float calculate_shadow(texture2D shadow_map, texture2D variance_shadow_map, mat4 light_space, uint filter_mode, float light_size, bool perspective, vec3 frag_translation);
+float calculate_pcf_shadow(texture2D shadow_map, vec3 shadow_coordinates);
+float calculate_variance_shadow(texture2D variance_shadow_map, vec3 shadow_coordinates);
+float calculate_percentage_closer_soft_shadow(texture2D shadow_map, vec3 shadow_coordinates, float light_size, bool perspective);
+float calculate_specular(vec3 light_direction, vec3 normal, vec3 view_direction);
+vec3 calculate_directional_light(DirectionalLight light, vec3 normal, vec3 view_direction, float shadow);
+vec3 calculate_point_light(PointLight light, vec3 normal, vec3 frag_translation, vec3 view_direction);
+vec3 calculate_spot_light_cookie(SpotLight light, vec3 frag_translation);
+vec3 calculate_spot_light(SpotLight light, vec3 normal, vec3 frag_translation, vec3 view_direction, float shadow);
+
+void main()
+{
+    vec3 normalized_normal = normalize(normal);
+    vec3 view_direction = normalize(view_translation - translation);
+
+    float directional_shadow = calculate_shadow(
+        directional_shadow_map,
+        directional_variance_shadow_map,
+        directional_light_space,
+        directional_shadow_filter,
+        directional_light_size,
+        false,
+        translation
+    );
+    vec3 result = calculate_directional_light(directional_light, normalized_normal, view_direction, directional_shadow);
+    for (uint i = 0; i < point_light_count; i++) {
+        result += calculate_point_light(point_lights[i], normalized_normal, translation, view_direction);
+    }
+    for (uint i = 0; i < spot_light_count; i++) {
+        // Only the first spot light has a shadow map.
+        float spot_shadow = i == 0
+            ? calculate_shadow(spot_shadow_map, spot_variance_shadow_map, spot_light_space, spot_shadow_filter, spot_light_size, true, translation)
+            : 0.0;
+        result += calculate_spot_light(spot_lights[i], normalized_normal, translation, view_direction, spot_shadow);
+    }
+
+    fragment_color = vec4(result, 1.0);
+}
+
+// Returns how much of the fragment is in shadow, from 0.0 (fully lit) to 1.0 (fully shadowed).
+float calculate_shadow(texture2D shadow_map, texture2D variance_shadow_map, mat4 light_space, uint filter_mode, float light_size, bool perspective, vec3 frag_translation)
+{
+    vec4 light_clip_translation = light_space * vec4(frag_translation, 1.0);
+    vec3 light_ndc = light_clip_translation.xyz / light_clip_translation.w;
+    // Both wgpu's normalized device coordinates and texture coordinates point Y down.
+    vec3 shadow_coordinates = vec3(light_ndc.xy * 0.5 + 0.5, light_ndc.z);
+
+    // Fragments outside of the light's frustum were never rendered into the shadow map.
+    if (any(lessThan(shadow_coordinates, vec3(0.0))) || any(greaterThan(shadow_coordinates, vec3(1.0)))) {
+        return 0.0;
+    }
+
+    if (filter_mode == SHADOW_FILTER_VARIANCE) {
+        return calculate_variance_shadow(variance_shadow_map, shadow_coordinates);
+    }
+    if (filter_mode == SHADOW_FILTER_PERCENTAGE_CLOSER_SOFT) {
+        return calculate_percentage_closer_soft_shadow(shadow_map, shadow_coordinates, light_size, perspective);
+    }
+    return calculate_pcf_shadow(shadow_map, shadow_coordinates);
+}
+
+// Percentage-closer filtering: average the depth comparisons of the surrounding texels.
+float calculate_pcf_shadow(texture2D shadow_map, vec3 shadow_coordinates)
+{
+    vec2 texel_size = 1.0 / vec2(textureSize(sampler2DShadow(shadow_map, shadow_sampler), 0));
+    float lit = 0.0;
+    for (int x = -1; x <= 1; x++) {
+        for (int y = -1; y <= 1; y++) {
+            vec2 offset = vec2(x, y) * texel_size;
+            lit += texture(sampler2DShadow(shadow_map, shadow_sampler), vec3(shadow_coordinates.xy + offset, shadow_coordinates.z));
+        }
+    }
+    return 1.0 - lit / 9.0;
+}
+
+float calculate_variance_shadow(texture2D variance_shadow_map, vec3 shadow_coordinates)
+{
+    vec2 moments = texture(sampler2D(variance_shadow_map, variance_sampler), shadow_coordinates.xy).rg;
+    float depth = shadow_coordinates.z;
+    if (depth <= moments.x) {
+        return 0.0;
+    }
+
+    // Chebyshev's inequality gives an upper bound for the fraction of the filtered area that is further away than
+    // the fragment, which is the fraction that's lit. A minimum variance hides precision issues on flat surfaces.
+    float variance = max(moments.y - moments.x * moments.x, 0.00002);
+    float difference = depth - moments.x;
+    float lit = variance / (variance + difference * difference);
+    // Cut off the low end of the bound, which is where light bleeds through overlapping occluders.
+    lit = clamp((lit - 0.2) / 0.8, 0.0, 1.0);
+    return 1.0 - lit;
+}
+
+// Converts a shadow map depth into a distance from the light. The orthographic projection of the directional light
+// is already linear.
+float linearize_depth(float depth, bool perspective)
+{
+    if (!perspective) {
+        return depth;
+    }
+    return SPOT_SHADOW_NEAR_PLANE * SPOT_SHADOW_FAR_PLANE / (SPOT_SHADOW_FAR_PLANE - depth * (SPOT_SHADOW_FAR_PLANE - SPOT_SHADOW_NEAR_PLANE));
+}
+
+// `light_size` is how wide the penumbrae get, in shadow map texture coordinates. For the spot light it's the width of
+// the light divided by the width of its frustum one unit away from it, and for the directional light it's the width
+// of the penumbra cast onto a fragment at the far end of the shadow map's depth range by an occluder at the near end.
+float calculate_percentage_closer_soft_shadow(texture2D shadow_map, vec3 shadow_coordinates, float light_size, bool perspective)
+{
+    float receiver_distance = linearize_depth(shadow_coordinates.z, perspective);
+
+    // Search the area that the widest penumbra falling onto the fragment could come from. For the spot light that
+    // would be an occluder right in front of it, so the search stops at occluders halfway between the light and the
+    // fragment instead to keep the area reasonably small.
+    float search_radius = perspective
+        ? light_size / receiver_distance
+        : light_size * receiver_distance;
+
+    float blocker_distance_sum = 0.0;
+    int blocker_count = 0;
+    for (int i = 0; i < 16; i++) {
+        vec2 sample_uv = shadow_coordinates.xy + poisson_disk[i] * search_radius;
+        float depth = texture(sampler2D(shadow_map, depth_sampler), sample_uv).r;
+        if (depth < shadow_coordinates.z) {
+            blocker_distance_sum += linearize_depth(depth, perspective);
+            blocker_count++;
+        }
+    }
+    if (blocker_count == 0) {
+        return 0.0;
+    }
+    float blocker_distance = blocker_distance_sum / float(blocker_count);
+
+    // Similar triangles give the width of the penumbra from the distances between the light, the occluders and the
+    // fragment.
+    float filter_radius = perspective
+        ? light_size * (receiver_distance - blocker_distance) / (blocker_distance * receiver_distance)
+        : light_size * (receiver_distance - blocker_distance);
+    vec2 texel_size = 1.0 / vec2(textureSize(sampler2DShadow(shadow_map, shadow_sampler), 0));
+    filter_radius = max(filter_radius, texel_size.x);
+
+    float lit = 0.0;
+    for (int i = 0; i < 16; i++) {
+        vec2 sample_uv = shadow_coordinates.xy + poisson_disk[i] * filter_radius;
+        lit += texture(sampler2DShadow(shadow_map, shadow_sampler), vec3(sample_uv, shadow_coordinates.z));
+    }
+    return 1.0 - lit / 16.0;
+}
+
+float calculate_specular(vec3 light_direction, vec3 normal, vec3 view_direction)
+{
+    if (specular_model == SPECULAR_MODEL_BLINN_PHONG) {
+        vec3 halfway_direction = normalize(light_direction + view_direction);
+        return pow(max(dot(normal, halfway_direction), 0.0), material.shininess);
+    }
+
+    vec3 reflect_direction = reflect(-light_direction, normal);
+    return pow(max(dot(view_direction, reflect_direction), 0.0), material.shininess);
+}
+
+vec3 calculate_directional_light(DirectionalLight light, vec3 normal, vec3 view_direction, float shadow)
+{
+    vec3 light_direction = normalize(-light.direction);
+
+    float diff = max(dot(normal, light_direction), 0.0);
+
+    float spec = calculate_specular(light_direction, normal, view_direction);
+
+    vec3 ambient = light.ambient * vec3(texture(sampler2D(material_diffuse_texture, material_diffuse_sampler), uv));
+    vec3 diffuse = light.diffuse * diff * vec3(texture(sampler2D(material_diffuse_texture, material_diffuse_sampler), uv));
+    vec3 specular = light.specular * spec * vec3(texture(sampler2D(material_specular_texture, material_specular_sampler), uv));
+    return (ambient + (1.0 - shadow) * (diffuse + specular));
+}
+
+vec3 calculate_point_light(PointLight light, vec3 normal, vec3 frag_translation, vec3 view_direction)
+{
+    vec3 light_direction = normalize(light.translation - frag_translation);
+
+    float diff = max(dot(normal, light_direction), 0.0);
+
+    float spec = calculate_specular(light_direction, normal, view_direction);
+
+    float distance = length(light.translation - frag_translation);
+    float attenuation = 1.0 / (light.constant + light.linear * distance + light.quadratic * (distance * distance));
+
+    vec3 ambient = light.ambient * vec3(texture(sampler2D(material_diffuse_texture, material_diffuse_sampler), uv));
+    vec3 diffuse = light.diffuse * vec3(texture(sampler2D(material_diffuse_texture, material_diffuse_sampler), uv));
+    vec3 specular = light.specular * spec * vec3(texture(sampler2D(material_specular_texture, material_specular_sampler), uv));
+    ambient *= attenuation;
+    diffuse *= attenuation;
+    specular *= attenuation;
+    return (ambient + diffuse + specular);
+}
+
+// The color of the spot light's cookie where it's projected onto the fragment, or white if the light has none. No light
+// makes it past the edges of the cookie.
+vec3 calculate_spot_light_cookie(SpotLight light, vec3 frag_translation)
+{
+    if (light.cookie_index < 0) {
+        return vec3(1.0);
+    }
+
+    vec4 cookie_clip_translation = light.cookie_space * vec4(frag_translation, 1.0);
+    // Behind the light, the projection would show the cookie again upside down.
+    if (cookie_clip_translation.w <= 0.0) {
+        return vec3(0.0);
+    }
+    vec2 cookie_ndc = cookie_clip_translation.xy / cookie_clip_translation.w;
+    // The cookie's top row is on the light's up side.
+    vec2 cookie_uv = vec2(cookie_ndc.x * 0.5 + 0.5, 0.5 - cookie_ndc.y * 0.5);
+    if (any(lessThan(cookie_uv, vec2(0.0))) || any(greaterThan(cookie_uv, vec2(1.0)))) {
+        return vec3(0.0);
+    }
+    vec3 cookie_coordinates = vec3(cookie_uv, light.cookie_index);
+    return texture(sampler2DArray(spot_light_cookies, spot_light_cookie_sampler), cookie_coordinates).rgb;
+}
+
+vec3 calculate_spot_light(SpotLight light, vec3 normal, vec3 frag_translation, vec3 view_direction, float shadow)
+{
+    vec3 light_direction = normalize(light.translation - frag_translation);
+
+    float diff = max(dot(normal, light_direction), 0.0);
+
+    float spec = calculate_specular(light_direction, normal, view_direction);
+
+    float distance = length(light.translation - frag_translation);
+    float attenuation = 1.0 / (light.constant + light.linear * distance + light.quadratic * (distance * distance));
+
+    float theta = dot(light_direction, normalize(-light.direction));
+    float epsilon = light.cutoff - light.outer_cutoff;
+    float intensity = clamp((theta - light.outer_cutoff) / epsilon, 0.0, 1.0);
+    vec3 cookie = calculate_spot_light_cookie(light, frag_translation);
+
+    vec3 ambient = light.ambient * vec3(texture(sampler2D(material_diffuse_texture, material_diffuse_sampler), uv));
+    vec3 diffuse = light.diffuse * diff * vec3(texture(sampler2D(material_diffuse_texture, material_diffuse_sampler), uv));
+    vec3 specular = light.specular * spec * vec3(texture(sampler2D(material_specular_texture, material_specular_sampler), uv));
+    ambient *= attenuation * intensity * cookie;
+    diffuse *= attenuation * intensity * cookie;
+    specular *= attenuation * intensity * cookie;
+    return (ambient + (1.0 - shadow) * (diffuse + specular));
+}
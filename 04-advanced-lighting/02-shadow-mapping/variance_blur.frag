@@ -0,0 +1,35 @@
+#version 450
+
+layout(location = 0) in vec2 uv;
+
+layout(location = 0) out vec2 moments;
+
+layout(set = 0, binding = 0) uniform Uniforms {
+    // The distance between two taps, in texture coordinates.
+    vec2 step_size;
+    // Whether the source is a depth texture, whose moments have to be calculated first.
+    bool from_depth;
+};
+layout(set = 0, binding = 1) uniform texture2D source_texture;
+layout(set = 0, binding = 2) uniform sampler source_sampler;
+
+// The weights of a 9 tap Gaussian kernel, starting at the center tap.
+const float weights[5] = float[](0.227027, 0.1945946, 0.1216216, 0.054054, 0.016216);
+
+vec2 sample_moments(vec2 uv)
+{
+    vec4 texel = texture(sampler2D(source_texture, source_sampler), uv);
+    if (from_depth) {
+        return vec2(texel.r, texel.r * texel.r);
+    }
+    return texel.rg;
+}
+
+void main()
+{
+    moments = sample_moments(uv) * weights[0];
+    for (int i = 1; i < 5; i++) {
+        moments += sample_moments(uv + step_size * i) * weights[i];
+        moments += sample_moments(uv - step_size * i) * weights[i];
+    }
+}
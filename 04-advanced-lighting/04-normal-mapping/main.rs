@@ -0,0 +1,880 @@
+use amethyst_input::{InputHandler, StringBindings};
+use shaderc::{Compiler, ShaderKind};
+use ultraviolet::{Mat4, Vec2, Vec3, Vec4};
+use winit::{
+    event::{Event, KeyboardInput, VirtualKeyCode, WindowEvent},
+    event_loop::{ControlFlow, EventLoop},
+    window::WindowBuilder,
+};
+
+use learnopengl_but_its_wgpu::{
+    lighting::light_source_instance,
+    scene::{self, Scene},
+    time::Time,
+    Align16, Instance,
+};
+
+const DEPTH_FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Depth32Float;
+
+/// A wall facing +Z. The texture's rows run down the wall, so the bitangent points along -Y.
+#[rustfmt::skip]
+const VERTICES: &[Vertex] = &[
+    Vertex { translation: Vec3 { x: -0.5, y:  0.5, z:  0.0, }, normal: Vec3 { x:  0.0, y:  0.0, z:  1.0 }, uv: Vec2 { x: 0.0, y: 0.0, }, tangent: Vec4 { x:  1.0, y:  0.0, z:  0.0, w: -1.0 } },
+    Vertex { translation: Vec3 { x: -0.5, y: -0.5, z:  0.0, }, normal: Vec3 { x:  0.0, y:  0.0, z:  1.0 }, uv: Vec2 { x: 0.0, y: 1.0, }, tangent: Vec4 { x:  1.0, y:  0.0, z:  0.0, w: -1.0 } },
+    Vertex { translation: Vec3 { x:  0.5, y: -0.5, z:  0.0, }, normal: Vec3 { x:  0.0, y:  0.0, z:  1.0 }, uv: Vec2 { x: 1.0, y: 1.0, }, tangent: Vec4 { x:  1.0, y:  0.0, z:  0.0, w: -1.0 } },
+    Vertex { translation: Vec3 { x: -0.5, y:  0.5, z:  0.0, }, normal: Vec3 { x:  0.0, y:  0.0, z:  1.0 }, uv: Vec2 { x: 0.0, y: 0.0, }, tangent: Vec4 { x:  1.0, y:  0.0, z:  0.0, w: -1.0 } },
+    Vertex { translation: Vec3 { x:  0.5, y: -0.5, z:  0.0, }, normal: Vec3 { x:  0.0, y:  0.0, z:  1.0 }, uv: Vec2 { x: 1.0, y: 1.0, }, tangent: Vec4 { x:  1.0, y:  0.0, z:  0.0, w: -1.0 } },
+    Vertex { translation: Vec3 { x:  0.5, y:  0.5, z:  0.0, }, normal: Vec3 { x:  0.0, y:  0.0, z:  1.0 }, uv: Vec2 { x: 1.0, y: 0.0, }, tangent: Vec4 { x:  1.0, y:  0.0, z:  0.0, w: -1.0 } },
+];
+
+fn main() {
+    let scene_path = std::env::args().nth(1).unwrap_or_else(|| {
+        concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/04-advanced-lighting/04-normal-mapping/scene.ron"
+        )
+        .to_owned()
+    });
+    let scene = Scene::load(&scene_path).unwrap();
+    let light = scene
+        .point_lights
+        .first()
+        .cloned()
+        .expect("The scene has no point light");
+
+    let (event_loop, window, mut size) = {
+        let event_loop = EventLoop::new();
+        let window = WindowBuilder::new()
+            .with_title("LearnOpenGL but it's WGPU-rs")
+            .build(&event_loop)
+            .unwrap();
+        let size = window.inner_size();
+        (event_loop, window, size)
+    };
+
+    let surface = wgpu::Surface::create(&window);
+
+    let (device, mut queue) = wgpu::Adapter::request(&wgpu::RequestAdapterOptions {
+        power_preference: wgpu::PowerPreference::HighPerformance,
+        backends: wgpu::BackendBit::all(),
+    })
+    .unwrap()
+    .request_device(&wgpu::DeviceDescriptor {
+        extensions: wgpu::Extensions {
+            anisotropic_filtering: false,
+        },
+        ..Default::default()
+    });
+
+    let mut shader_compiler = Compiler::new().expect("Could not initialize shader compiler");
+
+    let vs_module = prepare_shader(
+        &mut shader_compiler,
+        include_str!("shader.vert"),
+        ShaderKind::Vertex,
+        "shader.vert",
+        &device,
+    );
+    let fs_module = prepare_shader(
+        &mut shader_compiler,
+        include_str!("shader.frag"),
+        ShaderKind::Fragment,
+        "shader.frag",
+        &device,
+    );
+    let light_source_vs_module = prepare_shader(
+        &mut shader_compiler,
+        include_str!("light_source.vert"),
+        ShaderKind::Vertex,
+        "light_source.vert",
+        &device,
+    );
+    let light_source_fs_module = prepare_shader(
+        &mut shader_compiler,
+        include_str!("light_source.frag"),
+        ShaderKind::Fragment,
+        "light_source.frag",
+        &device,
+    );
+
+    let mut init_encoder =
+        device.create_command_encoder(&wgpu::CommandEncoderDescriptor { todo: 0 });
+
+    let vertex_buffer = device
+        .create_buffer_mapped(VERTICES.len(), wgpu::BufferUsage::VERTEX)
+        .fill_from_slice(VERTICES);
+
+    let walls = scene.cube_instances();
+    let wall_instance_buffer = device
+        .create_buffer_mapped(walls.len(), wgpu::BufferUsage::VERTEX)
+        .fill_from_slice(&walls);
+    // The light source is drawn with the wall's vertices as well, which is enough to show where it is.
+    let light_source_instance_buffer = device
+        .create_buffer_mapped(1, wgpu::BufferUsage::VERTEX | wgpu::BufferUsage::COPY_DST)
+        .fill_from_slice(&[light_source_instance(scene::vec3(light.translation))]);
+
+    let vertex_uniform_bind_group_layout =
+        device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            bindings: &[wgpu::BindGroupLayoutBinding {
+                binding: 0,
+                visibility: wgpu::ShaderStage::VERTEX,
+                ty: wgpu::BindingType::UniformBuffer { dynamic: false },
+            }],
+        });
+
+    let fragment_uniform_bind_group_layout =
+        device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            bindings: &[wgpu::BindGroupLayoutBinding {
+                binding: 0,
+                visibility: wgpu::ShaderStage::FRAGMENT,
+                ty: wgpu::BindingType::UniformBuffer { dynamic: false },
+            }],
+        });
+
+    let material_bind_group_layout =
+        device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            bindings: &[
+                wgpu::BindGroupLayoutBinding {
+                    binding: 0,
+                    visibility: wgpu::ShaderStage::FRAGMENT,
+                    ty: wgpu::BindingType::UniformBuffer { dynamic: false },
+                },
+                wgpu::BindGroupLayoutBinding {
+                    binding: 1,
+                    visibility: wgpu::ShaderStage::FRAGMENT,
+                    ty: wgpu::BindingType::SampledTexture {
+                        multisampled: false,
+                        dimension: wgpu::TextureViewDimension::D2,
+                    },
+                },
+                wgpu::BindGroupLayoutBinding {
+                    binding: 2,
+                    visibility: wgpu::ShaderStage::FRAGMENT,
+                    ty: wgpu::BindingType::Sampler,
+                },
+                wgpu::BindGroupLayoutBinding {
+                    binding: 3,
+                    visibility: wgpu::ShaderStage::FRAGMENT,
+                    ty: wgpu::BindingType::SampledTexture {
+                        multisampled: false,
+                        dimension: wgpu::TextureViewDimension::D2,
+                    },
+                },
+                wgpu::BindGroupLayoutBinding {
+                    binding: 4,
+                    visibility: wgpu::ShaderStage::FRAGMENT,
+                    ty: wgpu::BindingType::Sampler,
+                },
+                wgpu::BindGroupLayoutBinding {
+                    binding: 5,
+                    visibility: wgpu::ShaderStage::FRAGMENT,
+                    ty: wgpu::BindingType::SampledTexture {
+                        multisampled: false,
+                        dimension: wgpu::TextureViewDimension::D2,
+                    },
+                },
+                wgpu::BindGroupLayoutBinding {
+                    binding: 6,
+                    visibility: wgpu::ShaderStage::FRAGMENT,
+                    ty: wgpu::BindingType::Sampler,
+                },
+            ],
+        });
+
+    let light_uniform_bind_group_layout =
+        device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            bindings: &[wgpu::BindGroupLayoutBinding {
+                binding: 0,
+                visibility: wgpu::ShaderStage::FRAGMENT,
+                ty: wgpu::BindingType::UniformBuffer { dynamic: false },
+            }],
+        });
+
+    let mut event_channel = shrev::EventChannel::new();
+    let event_reader = event_channel.register_reader();
+    let mut input_handler = InputHandler::<StringBindings>::new();
+
+    let mut camera = scene.create_camera(event_reader);
+
+    let vertex_uniform_buffer = device
+        .create_buffer_mapped(1, wgpu::BufferUsage::UNIFORM | wgpu::BufferUsage::COPY_DST)
+        .fill_from_slice(&[camera.get_view_projection_matrix(
+            size.width as f32,
+            size.height as f32,
+            0.1,
+            100.0,
+        )]);
+
+    let vertex_uniform_bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+        layout: &vertex_uniform_bind_group_layout,
+        bindings: &[wgpu::Binding {
+            binding: 0,
+            resource: wgpu::BindingResource::Buffer {
+                buffer: &vertex_uniform_buffer,
+                range: 0..std::mem::size_of::<Mat4>() as wgpu::BufferAddress,
+            },
+        }],
+    });
+
+    let fragment_uniform_buffer = device
+        .create_buffer_mapped(1, wgpu::BufferUsage::UNIFORM | wgpu::BufferUsage::COPY_DST)
+        .fill_from_slice(&[FragmentUniforms {
+            view_translation: camera.translation.into(),
+        }]);
+
+    let fragment_uniform_bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+        layout: &fragment_uniform_bind_group_layout,
+        bindings: &[wgpu::Binding {
+            binding: 0,
+            resource: wgpu::BindingResource::Buffer {
+                buffer: &fragment_uniform_buffer,
+                range: 0..std::mem::size_of::<FragmentUniforms>() as wgpu::BufferAddress,
+            },
+        }],
+    });
+
+    let material_uniform_buffer = device
+        .create_buffer_mapped(1, wgpu::BufferUsage::UNIFORM | wgpu::BufferUsage::COPY_DST)
+        .fill_from_slice(&[Material {
+            shininess: scene.material.shininess,
+        }]);
+
+    let material_diffuse_texture_image = image::open(
+        scene.resolve_path(
+            scene
+                .material
+                .diffuse_texture
+                .as_ref()
+                .expect("The material has no diffuse texture"),
+        ),
+    )
+    .unwrap()
+    .to_rgba();
+    let (width, height) = material_diffuse_texture_image.dimensions();
+    let material_diffuse_texture_raw = material_diffuse_texture_image.to_vec();
+    let (_, material_diffuse_texture_view, material_diffuse_texture_sampler) =
+        create_sampled_texture2d(
+            &device,
+            &mut init_encoder,
+            &material_diffuse_texture_raw,
+            width,
+            height,
+            wgpu::TextureFormat::Rgba8UnormSrgb,
+        );
+
+    let material_specular_texture_image = image::open(
+        scene.resolve_path(
+            scene
+                .material
+                .specular_texture
+                .as_ref()
+                .expect("The material has no specular texture"),
+        ),
+    )
+    .unwrap()
+    .to_rgba();
+    let (width, height) = material_specular_texture_image.dimensions();
+    let material_specular_texture_raw = material_specular_texture_image.to_vec();
+    let (_, material_specular_texture_view, material_specular_texture_sampler) =
+        create_sampled_texture2d(
+            &device,
+            &mut init_encoder,
+            &material_specular_texture_raw,
+            width,
+            height,
+            wgpu::TextureFormat::Rgba8Unorm,
+        );
+
+    let material_normal_texture_image = image::open(
+        scene.resolve_path(
+            scene
+                .material
+                .normal_texture
+                .as_ref()
+                .expect("The material has no normal map"),
+        ),
+    )
+    .unwrap()
+    .to_rgba();
+    let (width, height) = material_normal_texture_image.dimensions();
+    let material_normal_texture_raw = material_normal_texture_image.to_vec();
+    let (_, material_normal_texture_view, material_normal_texture_sampler) =
+        create_sampled_texture2d(
+            &device,
+            &mut init_encoder,
+            &material_normal_texture_raw,
+            width,
+            height,
+            wgpu::TextureFormat::Rgba8Unorm,
+        );
+
+    // A flat normal map, for comparing against the vertex normals.
+    let (_, flat_normal_texture_view, flat_normal_texture_sampler) = create_sampled_texture2d(
+        &device,
+        &mut init_encoder,
+        &[128, 128, 255, 255],
+        1,
+        1,
+        wgpu::TextureFormat::Rgba8Unorm,
+    );
+
+    let create_material_bind_group =
+        |normal_texture_view: &wgpu::TextureView, normal_texture_sampler: &wgpu::Sampler| {
+            device.create_bind_group(&wgpu::BindGroupDescriptor {
+                layout: &material_bind_group_layout,
+                bindings: &[
+                    wgpu::Binding {
+                        binding: 0,
+                        resource: wgpu::BindingResource::Buffer {
+                            buffer: &material_uniform_buffer,
+                            range: 0..std::mem::size_of::<Material>() as wgpu::BufferAddress,
+                        },
+                    },
+                    wgpu::Binding {
+                        binding: 1,
+                        resource: wgpu::BindingResource::TextureView(
+                            &material_diffuse_texture_view,
+                        ),
+                    },
+                    wgpu::Binding {
+                        binding: 2,
+                        resource: wgpu::BindingResource::Sampler(&material_diffuse_texture_sampler),
+                    },
+                    wgpu::Binding {
+                        binding: 3,
+                        resource: wgpu::BindingResource::TextureView(
+                            &material_specular_texture_view,
+                        ),
+                    },
+                    wgpu::Binding {
+                        binding: 4,
+                        resource: wgpu::BindingResource::Sampler(
+                            &material_specular_texture_sampler,
+                        ),
+                    },
+                    wgpu::Binding {
+                        binding: 5,
+                        resource: wgpu::BindingResource::TextureView(normal_texture_view),
+                    },
+                    wgpu::Binding {
+                        binding: 6,
+                        resource: wgpu::BindingResource::Sampler(normal_texture_sampler),
+                    },
+                ],
+            })
+        };
+    let material_bind_groups = [
+        create_material_bind_group(
+            &material_normal_texture_view,
+            &material_normal_texture_sampler,
+        ),
+        create_material_bind_group(&flat_normal_texture_view, &flat_normal_texture_sampler),
+    ];
+    let mut normal_mapping = true;
+
+    let light_uniform_buffer = device
+        .create_buffer_mapped(1, wgpu::BufferUsage::UNIFORM | wgpu::BufferUsage::COPY_DST)
+        .fill_from_slice(&[Light {
+            translation: scene::vec3(light.translation).into(),
+            ambient: scene::vec3(light.ambient).into(),
+            diffuse: scene::vec3(light.diffuse).into(),
+            specular: scene::vec3(light.specular),
+        }]);
+
+    let light_uniform_bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+        layout: &light_uniform_bind_group_layout,
+        bindings: &[wgpu::Binding {
+            binding: 0,
+            resource: wgpu::BindingResource::Buffer {
+                buffer: &light_uniform_buffer,
+                range: 0..std::mem::size_of::<Light>() as wgpu::BufferAddress,
+            },
+        }],
+    });
+
+    let mut swap_chain_descriptor = wgpu::SwapChainDescriptor {
+        usage: wgpu::TextureUsage::OUTPUT_ATTACHMENT,
+        format: wgpu::TextureFormat::Bgra8UnormSrgb,
+        width: size.width,
+        height: size.height,
+        present_mode: wgpu::PresentMode::Vsync,
+    };
+
+    let (mut depth_texture, mut depth_texture_view) =
+        create_depth_texture(&device, &swap_chain_descriptor);
+
+    let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+        bind_group_layouts: &[
+            &vertex_uniform_bind_group_layout,
+            &fragment_uniform_bind_group_layout,
+            &material_bind_group_layout,
+            &light_uniform_bind_group_layout,
+        ],
+    });
+
+    let render_pipeline_descriptor = wgpu::RenderPipelineDescriptor {
+        layout: &pipeline_layout,
+        vertex_stage: wgpu::ProgrammableStageDescriptor {
+            module: &vs_module,
+            entry_point: "main",
+        },
+        fragment_stage: Some(wgpu::ProgrammableStageDescriptor {
+            module: &fs_module,
+            entry_point: "main",
+        }),
+        rasterization_state: Some(wgpu::RasterizationStateDescriptor {
+            front_face: wgpu::FrontFace::Ccw,
+            cull_mode: wgpu::CullMode::Back,
+            depth_bias: 0,
+            depth_bias_slope_scale: 0.0,
+            depth_bias_clamp: 0.0,
+        }),
+        primitive_topology: wgpu::PrimitiveTopology::TriangleList,
+        color_states: &[wgpu::ColorStateDescriptor {
+            format: swap_chain_descriptor.format,
+            alpha_blend: wgpu::BlendDescriptor::REPLACE,
+            color_blend: wgpu::BlendDescriptor::REPLACE,
+            write_mask: wgpu::ColorWrite::ALL,
+        }],
+        depth_stencil_state: Some(wgpu::DepthStencilStateDescriptor {
+            format: DEPTH_FORMAT,
+            depth_write_enabled: true,
+            depth_compare: wgpu::CompareFunction::Less,
+            stencil_front: wgpu::StencilStateFaceDescriptor::IGNORE,
+            stencil_back: wgpu::StencilStateFaceDescriptor::IGNORE,
+            stencil_read_mask: 0,
+            stencil_write_mask: 0,
+        }),
+        index_format: wgpu::IndexFormat::Uint16,
+        vertex_buffers: &[
+            wgpu::VertexBufferDescriptor {
+                stride: std::mem::size_of::<Vertex>() as wgpu::BufferAddress,
+                step_mode: wgpu::InputStepMode::Vertex,
+                attributes: &[
+                    wgpu::VertexAttributeDescriptor {
+                        offset: 0,
+                        format: wgpu::VertexFormat::Float3,
+                        shader_location: 0,
+                    },
+                    wgpu::VertexAttributeDescriptor {
+                        offset: std::mem::size_of::<Vec3>() as wgpu::BufferAddress,
+                        format: wgpu::VertexFormat::Float3,
+                        shader_location: 1,
+                    },
+                    wgpu::VertexAttributeDescriptor {
+                        offset: (std::mem::size_of::<Vec3>() * 2) as wgpu::BufferAddress,
+                        format: wgpu::VertexFormat::Float2,
+                        shader_location: 2,
+                    },
+                    wgpu::VertexAttributeDescriptor {
+                        offset: (std::mem::size_of::<Vec3>() * 2 + std::mem::size_of::<Vec2>())
+                            as wgpu::BufferAddress,
+                        format: wgpu::VertexFormat::Float4,
+                        shader_location: 10,
+                    },
+                ],
+            },
+            wgpu::VertexBufferDescriptor {
+                stride: std::mem::size_of::<Instance>() as wgpu::BufferAddress,
+                step_mode: wgpu::InputStepMode::Instance,
+                attributes: &[
+                    wgpu::VertexAttributeDescriptor {
+                        offset: 0,
+                        format: wgpu::VertexFormat::Float4,
+                        shader_location: 3,
+                    },
+                    wgpu::VertexAttributeDescriptor {
+                        offset: std::mem::size_of::<Vec4>() as wgpu::BufferAddress,
+                        format: wgpu::VertexFormat::Float4,
+                        shader_location: 4,
+                    },
+                    wgpu::VertexAttributeDescriptor {
+                        offset: std::mem::size_of::<Vec4>() as wgpu::BufferAddress * 2,
+                        format: wgpu::VertexFormat::Float4,
+                        shader_location: 5,
+                    },
+                    wgpu::VertexAttributeDescriptor {
+                        offset: std::mem::size_of::<Vec4>() as wgpu::BufferAddress * 3,
+                        format: wgpu::VertexFormat::Float4,
+                        shader_location: 6,
+                    },
+                    wgpu::VertexAttributeDescriptor {
+                        offset: std::mem::size_of::<Mat4>() as wgpu::BufferAddress,
+                        format: wgpu::VertexFormat::Float3,
+                        shader_location: 7,
+                    },
+                    wgpu::VertexAttributeDescriptor {
+                        offset: (std::mem::size_of::<Mat4>() + std::mem::size_of::<Vec3>())
+                            as wgpu::BufferAddress,
+                        format: wgpu::VertexFormat::Float3,
+                        shader_location: 8,
+                    },
+                    wgpu::VertexAttributeDescriptor {
+                        offset: (std::mem::size_of::<Mat4>() + std::mem::size_of::<Vec3>() * 2)
+                            as wgpu::BufferAddress,
+                        format: wgpu::VertexFormat::Float3,
+                        shader_location: 9,
+                    },
+                ],
+            },
+        ],
+        sample_count: 1,
+        sample_mask: !0,
+        alpha_to_coverage_enabled: false,
+    };
+    let pipeline = device.create_render_pipeline(&render_pipeline_descriptor);
+
+    let light_source_pipeline_layout =
+        device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            bind_group_layouts: &[&vertex_uniform_bind_group_layout],
+        });
+
+    let mut light_source_pipeline_descriptor = render_pipeline_descriptor.clone();
+    light_source_pipeline_descriptor.layout = &light_source_pipeline_layout;
+    light_source_pipeline_descriptor.vertex_stage = wgpu::ProgrammableStageDescriptor {
+        module: &light_source_vs_module,
+        entry_point: "main",
+    };
+    light_source_pipeline_descriptor.fragment_stage = Some(wgpu::ProgrammableStageDescriptor {
+        module: &light_source_fs_module,
+        entry_point: "main",
+    });
+    let light_source_pipeline = device.create_render_pipeline(&light_source_pipeline_descriptor);
+
+    let mut swap_chain = device.create_swap_chain(&surface, &swap_chain_descriptor);
+
+    queue.submit(&[init_encoder.finish()]);
+
+    let mut time = Time::new();
+
+    event_loop.run(move |event, _, control_flow| {
+        input_handler.send_event(&event, &mut event_channel);
+
+        camera.on_event(&event_channel);
+
+        match event {
+            Event::WindowEvent {
+                event: WindowEvent::CloseRequested,
+                ..
+            } => *control_flow = ControlFlow::Exit,
+            Event::WindowEvent {
+                event: WindowEvent::Resized(new_size),
+                ..
+            } => {
+                size = new_size;
+                swap_chain_descriptor.width = size.width;
+                swap_chain_descriptor.height = size.height;
+                swap_chain = device.create_swap_chain(&surface, &swap_chain_descriptor);
+                let dt = create_depth_texture(&device, &swap_chain_descriptor);
+                depth_texture = dt.0;
+                depth_texture_view = dt.1;
+                *control_flow = ControlFlow::Poll;
+            }
+            Event::WindowEvent {
+                event:
+                    WindowEvent::MouseInput {
+                        button: winit::event::MouseButton::Left,
+                        state: winit::event::ElementState::Pressed,
+                        ..
+                    },
+                ..
+            } => {
+                camera.is_controlled = true;
+                window.set_cursor_grab(true).ok();
+                window.set_cursor_visible(false);
+                *control_flow = ControlFlow::Poll;
+            }
+            Event::WindowEvent {
+                event:
+                    WindowEvent::KeyboardInput {
+                        input:
+                            KeyboardInput {
+                                virtual_keycode: Some(VirtualKeyCode::Escape),
+                                state: winit::event::ElementState::Pressed,
+                                ..
+                            },
+                        ..
+                    },
+                ..
+            } => {
+                camera.is_controlled = false;
+                window.set_cursor_grab(false).ok();
+                window.set_cursor_visible(true);
+                *control_flow = ControlFlow::Poll;
+            }
+            Event::WindowEvent {
+                event:
+                    WindowEvent::KeyboardInput {
+                        input:
+                            KeyboardInput {
+                                virtual_keycode: Some(VirtualKeyCode::N),
+                                state: winit::event::ElementState::Pressed,
+                                ..
+                            },
+                        ..
+                    },
+                ..
+            } => {
+                normal_mapping = !normal_mapping;
+                *control_flow = ControlFlow::Poll;
+            }
+            Event::MainEventsCleared => {
+                window.request_redraw();
+                *control_flow = ControlFlow::Poll;
+            }
+            Event::RedrawRequested(_) => {
+                input_handler.send_frame_begin();
+                camera.update(&input_handler);
+
+                time.update();
+                let animated_light = light.sample(time.elapsed());
+
+                let frame = swap_chain.get_next_texture();
+                let mut encoder =
+                    device.create_command_encoder(&wgpu::CommandEncoderDescriptor { todo: 0 });
+                {
+                    let view_projection = camera.get_view_projection_matrix(
+                        size.width as f32,
+                        size.height as f32,
+                        0.1,
+                        100.0,
+                    );
+                    let staging_buffer = device
+                        .create_buffer_mapped(5, wgpu::BufferUsage::COPY_SRC)
+                        .fill_from_slice(&[
+                            view_projection[0],
+                            view_projection[1],
+                            view_projection[2],
+                            view_projection[3],
+                            camera.world_translation().into_homogeneous_vector(),
+                        ]);
+                    encoder.copy_buffer_to_buffer(
+                        &staging_buffer,
+                        0,
+                        &vertex_uniform_buffer,
+                        0,
+                        std::mem::size_of::<Mat4>() as wgpu::BufferAddress,
+                    );
+                    encoder.copy_buffer_to_buffer(
+                        &staging_buffer,
+                        std::mem::size_of::<Mat4>() as wgpu::BufferAddress,
+                        &fragment_uniform_buffer,
+                        0,
+                        std::mem::size_of::<Vec4>() as wgpu::BufferAddress,
+                    );
+
+                    copy_to_buffer(
+                        &device,
+                        &mut encoder,
+                        &[Light {
+                            translation: animated_light.translation,
+                            ambient: animated_light.ambient,
+                            diffuse: animated_light.diffuse,
+                            specular: animated_light.specular,
+                        }],
+                        &light_uniform_buffer,
+                    );
+                    copy_to_buffer(
+                        &device,
+                        &mut encoder,
+                        &[light_source_instance(animated_light.translation.0)],
+                        &light_source_instance_buffer,
+                    );
+
+                    let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                        color_attachments: &[wgpu::RenderPassColorAttachmentDescriptor {
+                            attachment: &frame.view,
+                            resolve_target: None,
+                            load_op: wgpu::LoadOp::Clear,
+                            store_op: wgpu::StoreOp::Store,
+                            clear_color: wgpu::Color {
+                                r: 0.1,
+                                g: 0.1,
+                                b: 0.1,
+                                a: 1.0,
+                            },
+                        }],
+                        depth_stencil_attachment: Some(
+                            wgpu::RenderPassDepthStencilAttachmentDescriptor {
+                                attachment: &depth_texture_view,
+                                depth_load_op: wgpu::LoadOp::Clear,
+                                depth_store_op: wgpu::StoreOp::Store,
+                                clear_depth: 1.0,
+                                stencil_load_op: wgpu::LoadOp::Clear,
+                                stencil_store_op: wgpu::StoreOp::Store,
+                                clear_stencil: 0,
+                            },
+                        ),
+                    });
+                    render_pass.set_pipeline(&pipeline);
+                    render_pass.set_bind_group(0, &vertex_uniform_bind_group, &[]);
+                    render_pass.set_bind_group(1, &fragment_uniform_bind_group, &[]);
+                    let material_bind_group = if normal_mapping {
+                        &material_bind_groups[0]
+                    } else {
+                        &material_bind_groups[1]
+                    };
+                    render_pass.set_bind_group(2, material_bind_group, &[]);
+                    render_pass.set_bind_group(3, &light_uniform_bind_group, &[]);
+                    render_pass
+                        .set_vertex_buffers(0, &[(&vertex_buffer, 0), (&wall_instance_buffer, 0)]);
+                    render_pass.draw(0..VERTICES.len() as u32, 0..walls.len() as u32);
+
+                    render_pass.set_pipeline(&light_source_pipeline);
+                    render_pass.set_bind_group(0, &vertex_uniform_bind_group, &[]);
+                    render_pass.set_vertex_buffers(
+                        0,
+                        &[(&vertex_buffer, 0), (&light_source_instance_buffer, 0)],
+                    );
+                    render_pass.draw(0..VERTICES.len() as u32, 0..1);
+                }
+
+                queue.submit(&[encoder.finish()]);
+
+                *control_flow = ControlFlow::Poll;
+            }
+            _ => {}
+        }
+    })
+}
+
+fn copy_to_buffer<T: 'static + Copy>(
+    device: &wgpu::Device,
+    encoder: &mut wgpu::CommandEncoder,
+    data: &[T],
+    buffer: &wgpu::Buffer,
+) {
+    let staging_buffer = device
+        .create_buffer_mapped(data.len(), wgpu::BufferUsage::COPY_SRC)
+        .fill_from_slice(data);
+    encoder.copy_buffer_to_buffer(
+        &staging_buffer,
+        0,
+        buffer,
+        0,
+        (std::mem::size_of::<T>() * data.len()) as wgpu::BufferAddress,
+    );
+}
+
+pub fn prepare_shader(
+    compiler: &mut Compiler,
+    source_text: &str,
+    shader_kind: ShaderKind,
+    input_file_name: &str,
+    device: &wgpu::Device,
+) -> wgpu::ShaderModule {
+    let artifact = compiler
+        .compile_into_spirv(source_text, shader_kind, input_file_name, "main", None)
+        .unwrap();
+    device.create_shader_module(artifact.as_binary())
+}
+
+pub fn create_depth_texture(
+    device: &wgpu::Device,
+    swap_chain_desc: &wgpu::SwapChainDescriptor,
+) -> (wgpu::Texture, wgpu::TextureView) {
+    let desc = wgpu::TextureDescriptor {
+        format: DEPTH_FORMAT,
+        usage: wgpu::TextureUsage::OUTPUT_ATTACHMENT,
+        ..swap_chain_desc.to_texture_desc()
+    };
+    let texture = device.create_texture(&desc);
+    let view = texture.create_default_view();
+    (texture, view)
+}
+
+/// Uploads an RGBA texture. Color textures should use an sRGB `format`, so that they're decoded into linear space
+/// when sampled, while textures holding other data like specular intensities or normals have to use a linear one.
+pub fn create_sampled_texture2d(
+    device: &wgpu::Device,
+    encoder: &mut wgpu::CommandEncoder,
+    raw_texture: &[u8],
+    width: u32,
+    height: u32,
+    format: wgpu::TextureFormat,
+) -> (wgpu::Texture, wgpu::TextureView, wgpu::Sampler) {
+    let texture_extent = wgpu::Extent3d {
+        width,
+        height,
+        depth: 1,
+    };
+
+    let texture_buffer = device
+        .create_buffer_mapped(raw_texture.len(), wgpu::BufferUsage::COPY_SRC)
+        .fill_from_slice(&raw_texture);
+    let texture = device.create_texture(&wgpu::TextureDescriptor {
+        size: texture_extent,
+        array_layer_count: 1,
+        mip_level_count: 0,
+        sample_count: 1,
+        dimension: wgpu::TextureDimension::D2,
+        format,
+        usage: wgpu::TextureUsage::SAMPLED | wgpu::TextureUsage::COPY_DST,
+    });
+
+    encoder.copy_buffer_to_texture(
+        wgpu::BufferCopyView {
+            buffer: &texture_buffer,
+            offset: 0,
+            row_pitch: 4 * width,
+            image_height: height,
+        },
+        wgpu::TextureCopyView {
+            texture: &texture,
+            mip_level: 0,
+            array_layer: 0,
+            origin: wgpu::Origin3d {
+                x: 0.0,
+                y: 0.0,
+                z: 0.0,
+            },
+        },
+        texture_extent,
+    );
+
+    let texture_view = texture.create_default_view();
+    let texture_sampler = device.create_sampler(&wgpu::SamplerDescriptor {
+        address_mode_u: wgpu::AddressMode::ClampToEdge,
+        address_mode_v: wgpu::AddressMode::ClampToEdge,
+        address_mode_w: wgpu::AddressMode::ClampToEdge,
+        mag_filter: wgpu::FilterMode::Nearest,
+        min_filter: wgpu::FilterMode::Linear,
+        mipmap_filter: wgpu::FilterMode::Nearest,
+        lod_min_clamp: 0.0,
+        lod_max_clamp: 0.0,
+        compare_function: wgpu::CompareFunction::Never,
+    });
+
+    (texture, texture_view, texture_sampler)
+}
+
+#[repr(C)]
+#[derive(Clone, Copy, Debug)]
+pub struct Vertex {
+    pub translation: Vec3,
+    pub normal: Vec3,
+    pub uv: Vec2,
+    /// The direction of increasing `u` texture coordinates, with the handedness of the bitangent in W.
+    pub tangent: Vec4,
+}
+
+#[repr(C)]
+#[derive(Clone, Copy, Debug)]
+pub struct FragmentUniforms {
+    pub view_translation: Vec3,
+}
+
+#[repr(C)]
+#[derive(Clone, Copy, Debug)]
+pub struct Material {
+    pub shininess: f32,
+}
+
+#[repr(C)]
+#[derive(Clone, Copy, Debug)]
+pub struct Light {
+    pub translation: Align16<Vec3>,
+    pub ambient: Align16<Vec3>,
+    pub diffuse: Align16<Vec3>,
+    pub specular: Vec3,
+}
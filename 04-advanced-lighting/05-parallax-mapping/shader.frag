@@ -0,0 +1,170 @@
+#version 450
+
+// Must match `ParallaxMode` in `main.rs`.
+#define PARALLAX_MODE_PLAIN 0
+#define PARALLAX_MODE_NORMAL 1
+#define PARALLAX_MODE_STEEP 2
+#define PARALLAX_MODE_OCCLUSION 3
+
+layout(location = 0) in vec3 translation;
+layout(location = 1) in vec3 normal;
+layout(location = 2) in vec2 uv;
+layout(location = 3) in vec4 tangent;
+
+layout(location = 0) out vec4 fragment_color;
+
+layout(set = 1, binding = 0) uniform Uniforms {
+    vec3 view_translation;
+};
+
+layout(set = 2, binding = 0) uniform Material {
+    float shininess;
+    uint parallax_mode;
+    // The depth of the lowest point of the height map, in texture coordinates.
+    float height_scale;
+    uint min_layer_count;
+    uint max_layer_count;
+    bool self_shadowing;
+} material;
+layout(set = 2, binding = 1) uniform texture2D material_diffuse_texture;
+layout(set = 2, binding = 2) uniform sampler material_diffuse_sampler;
+layout(set = 2, binding = 3) uniform texture2D material_specular_texture;
+layout(set = 2, binding = 4) uniform sampler material_specular_sampler;
+layout(set = 2, binding = 5) uniform texture2D material_normal_texture;
+layout(set = 2, binding = 6) uniform sampler material_normal_sampler;
+layout(set = 2, binding = 7) uniform texture2D material_height_texture;
+layout(set = 2, binding = 8) uniform sampler material_height_sampler;
+
+layout(set = 3, binding = 0) uniform Light {
+    vec3 translation;
+
+    vec3 ambient;
+    vec3 diffuse;
+    vec3 specular;
+} light;
+
+float sample_depth(vec2 uv);
+float calculate_layer_count(vec3 direction);
+vec2 calculate_parallax_uv(vec3 view_direction);
+float calculate_parallax_shadow(vec2 parallax_uv, vec3 light_direction);
+
+void main()
+{
+    vec3 n = normalize(normal);
+    // Interpolation leaves the tangent slightly off perpendicular to the normal, so it's straightened out again.
+    vec3 t = normalize(tangent.xyz - dot(tangent.xyz, n) * n);
+    // The sign in W flips the bitangent for mirrored texture coordinates.
+    vec3 b = tangent.w * cross(n, t);
+    mat3 tbn = mat3(t, b, n);
+    // The basis is orthonormal, so its transpose transforms from world space into tangent space.
+    mat3 inverse_tbn = transpose(tbn);
+
+    vec3 view_direction = normalize(view_translation - translation);
+    vec3 light_direction = normalize(light.translation - translation);
+
+    vec2 parallax_uv = uv;
+    float shadow = 0.0;
+    if (material.parallax_mode == PARALLAX_MODE_STEEP || material.parallax_mode == PARALLAX_MODE_OCCLUSION) {
+        parallax_uv = calculate_parallax_uv(inverse_tbn * view_direction);
+        // Looking at the edges at a grazing angle shifts the texture coordinates off the texture entirely.
+        if (any(lessThan(parallax_uv, vec2(0.0))) || any(greaterThan(parallax_uv, vec2(1.0)))) {
+            discard;
+        }
+        if (material.self_shadowing) {
+            shadow = calculate_parallax_shadow(parallax_uv, inverse_tbn * light_direction);
+        }
+    }
+
+    vec3 normalized_normal = n;
+    if (material.parallax_mode != PARALLAX_MODE_PLAIN) {
+        vec3 tangent_space_normal = texture(sampler2D(material_normal_texture, material_normal_sampler), parallax_uv).rgb * 2.0 - 1.0;
+        normalized_normal = normalize(tbn * tangent_space_normal);
+    }
+
+    vec3 diffuse_color = vec3(texture(sampler2D(material_diffuse_texture, material_diffuse_sampler), parallax_uv));
+    vec3 specular_color = vec3(texture(sampler2D(material_specular_texture, material_specular_sampler), parallax_uv));
+
+    vec3 ambient = light.ambient * diffuse_color;
+
+    float diff = max(dot(normalized_normal, light_direction), 0.0);
+    vec3 diffuse = light.diffuse * (diff * diffuse_color);
+
+    vec3 reflect_direction = reflect(-light_direction, normalized_normal);
+    float spec = pow(max(dot(view_direction, reflect_direction), 0.0), material.shininess);
+    vec3 specular = light.specular * (spec * specular_color);
+
+    vec3 result = ambient + (1.0 - shadow) * (diffuse + specular);
+    fragment_color = vec4(result, 1.0);
+}
+
+// The depth below the surface, from 0.0 at the highest point of the height map to 1.0 at the lowest.
+//
+// The number of samples taken varies between neighbouring fragments, so the derivatives needed to pick a mip level
+// automatically are undefined. The top level is always used instead.
+float sample_depth(vec2 uv)
+{
+    return 1.0 - textureLod(sampler2D(material_height_texture, material_height_sampler), uv, 0.0).r;
+}
+
+// Looking straight at the surface needs fewer layers than looking along it, since the ray crosses less of the texture.
+float calculate_layer_count(vec3 direction)
+{
+    return mix(float(material.max_layer_count), float(material.min_layer_count), abs(direction.z));
+}
+
+// Marches the view ray through the height field one layer at a time until it ends up below the surface.
+//
+// Steep parallax mapping uses the texture coordinates of the first layer below the surface, which shows the layers as
+// steps at grazing angles. Parallax occlusion mapping interpolates between that layer and the one before it by where
+// the height field crosses the ray.
+vec2 calculate_parallax_uv(vec3 view_direction)
+{
+    float layer_count = calculate_layer_count(view_direction);
+    float layer_depth = 1.0 / layer_count;
+    // The view direction points away from the surface, so the texture coordinates move against it as the ray descends.
+    vec2 uv_step = view_direction.xy / view_direction.z * material.height_scale / layer_count;
+
+    vec2 current_uv = uv;
+    float current_layer_depth = 0.0;
+    float current_depth = sample_depth(current_uv);
+    for (uint i = 0; i < material.max_layer_count && current_layer_depth < current_depth; i++) {
+        current_uv -= uv_step;
+        current_layer_depth += layer_depth;
+        current_depth = sample_depth(current_uv);
+    }
+
+    if (material.parallax_mode == PARALLAX_MODE_STEEP) {
+        return current_uv;
+    }
+
+    vec2 previous_uv = current_uv + uv_step;
+    float after_depth = current_depth - current_layer_depth;
+    float before_depth = sample_depth(previous_uv) - (current_layer_depth - layer_depth);
+    float weight = after_depth / (after_depth - before_depth);
+    return mix(current_uv, previous_uv, weight);
+}
+
+// Marches from the point the view ray hit towards the light, returning 1.0 if the height field blocks it and 0.0
+// otherwise.
+float calculate_parallax_shadow(vec2 parallax_uv, vec3 light_direction)
+{
+    if (light_direction.z <= 0.0) {
+        return 1.0;
+    }
+
+    float start_depth = sample_depth(parallax_uv);
+    float layer_count = calculate_layer_count(light_direction);
+    float layer_depth = start_depth / layer_count;
+    vec2 uv_step = light_direction.xy / light_direction.z * material.height_scale * layer_depth;
+
+    vec2 current_uv = parallax_uv + uv_step;
+    float current_layer_depth = start_depth - layer_depth;
+    for (uint i = 0; i < material.max_layer_count && current_layer_depth > 0.0; i++) {
+        if (sample_depth(current_uv) < current_layer_depth) {
+            return 1.0;
+        }
+        current_uv += uv_step;
+        current_layer_depth -= layer_depth;
+    }
+    return 0.0;
+}
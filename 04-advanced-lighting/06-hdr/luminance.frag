@@ -0,0 +1,17 @@
+#version 450
+
+layout(location = 0) in vec2 uv;
+
+layout(location = 0) out vec4 fragment_color;
+
+layout(set = 0, binding = 0) uniform texture2D hdr_texture;
+layout(set = 0, binding = 1) uniform sampler hdr_sampler;
+
+void main()
+{
+    vec3 color = texture(sampler2D(hdr_texture, hdr_sampler), uv).rgb;
+    float luminance = dot(color, vec3(0.2126, 0.7152, 0.0722));
+    // Averaging the logarithms gives the geometric mean, which a few very bright pixels can't dominate. The small
+    // offset keeps pure black pixels from turning into negative infinity.
+    fragment_color = vec4(log(luminance + 0.0001));
+}
@@ -0,0 +1,15 @@
+#version 450
+
+layout(location = 0) in vec2 uv;
+
+layout(location = 0) out vec4 fragment_color;
+
+layout(set = 0, binding = 0) uniform texture2D source_texture;
+layout(set = 0, binding = 1) uniform sampler source_sampler;
+
+void main()
+{
+    // The source is twice the size of the target, so each pixel's center lies on the corner shared by four source
+    // texels, and a single bilinear sample averages all of them.
+    fragment_color = texture(sampler2D(source_texture, source_sampler), uv);
+}
@@ -0,0 +1,13 @@
+#version 450
+
+layout(location = 0) out vec4 fragment_color;
+
+layout(set = 0, binding = 0) uniform texture2D average_log_luminance_texture;
+layout(set = 0, binding = 1) uniform sampler average_log_luminance_sampler;
+
+void main()
+{
+    // The blend constant moves the adapted luminance part of the way towards this one.
+    float average_log_luminance = texture(sampler2D(average_log_luminance_texture, average_log_luminance_sampler), vec2(0.5)).r;
+    fragment_color = vec4(exp(average_log_luminance));
+}
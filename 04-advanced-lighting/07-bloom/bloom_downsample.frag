@@ -0,0 +1,45 @@
+#version 450
+
+layout(location = 0) in vec2 uv;
+
+layout(location = 0) out vec4 fragment_color;
+
+layout(set = 0, binding = 0) uniform Bloom {
+    float threshold;
+    float knee;
+    float intensity;
+    float filter_radius;
+} bloom;
+layout(set = 0, binding = 1) uniform texture2D source_texture;
+layout(set = 0, binding = 2) uniform sampler source_sampler;
+
+vec3 sample_source(vec2 uv, vec2 texel_size, float x, float y)
+{
+    return texture(sampler2D(source_texture, source_sampler), uv + vec2(x, y) * texel_size).rgb;
+}
+
+// The same 13 tap filter as in `bloom_prefilter.frag`, without the threshold.
+void main()
+{
+    vec2 texel_size = 1.0 / vec2(textureSize(sampler2D(source_texture, source_sampler), 0));
+
+    vec3 a = sample_source(uv, texel_size, -2.0, -2.0);
+    vec3 b = sample_source(uv, texel_size, 0.0, -2.0);
+    vec3 c = sample_source(uv, texel_size, 2.0, -2.0);
+    vec3 d = sample_source(uv, texel_size, -2.0, 0.0);
+    vec3 e = sample_source(uv, texel_size, 0.0, 0.0);
+    vec3 f = sample_source(uv, texel_size, 2.0, 0.0);
+    vec3 g = sample_source(uv, texel_size, -2.0, 2.0);
+    vec3 h = sample_source(uv, texel_size, 0.0, 2.0);
+    vec3 i = sample_source(uv, texel_size, 2.0, 2.0);
+    vec3 j = sample_source(uv, texel_size, -1.0, -1.0);
+    vec3 k = sample_source(uv, texel_size, 1.0, -1.0);
+    vec3 l = sample_source(uv, texel_size, -1.0, 1.0);
+    vec3 m = sample_source(uv, texel_size, 1.0, 1.0);
+
+    vec3 color = e * 0.125
+        + (a + c + g + i) * 0.03125
+        + (b + d + f + h) * 0.0625
+        + (j + k + l + m) * 0.125;
+    fragment_color = vec4(color, 1.0);
+}
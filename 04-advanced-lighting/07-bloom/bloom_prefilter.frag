@@ -0,0 +1,65 @@
+#version 450
+
+layout(location = 0) in vec2 uv;
+
+layout(location = 0) out vec4 fragment_color;
+
+layout(set = 0, binding = 0) uniform Bloom {
+    float threshold;
+    float knee;
+    float intensity;
+    float filter_radius;
+} bloom;
+layout(set = 0, binding = 1) uniform texture2D source_texture;
+layout(set = 0, binding = 2) uniform sampler source_sampler;
+
+vec3 downsample(vec2 uv);
+vec3 apply_threshold(vec3 color);
+
+void main()
+{
+    fragment_color = vec4(apply_threshold(downsample(uv)), 1.0);
+}
+
+vec3 sample_source(vec2 uv, vec2 texel_size, float x, float y)
+{
+    return texture(sampler2D(source_texture, source_sampler), uv + vec2(x, y) * texel_size).rgb;
+}
+
+// The 13 tap filter from Jorge Jimenez's "Next Generation Post Processing in Call of Duty: Advanced Warfare". It
+// averages overlapping blocks of four bilinear samples, which keeps small bright spots from flickering as they move
+// between the pixels of the smaller target.
+vec3 downsample(vec2 uv)
+{
+    vec2 texel_size = 1.0 / vec2(textureSize(sampler2D(source_texture, source_sampler), 0));
+
+    vec3 a = sample_source(uv, texel_size, -2.0, -2.0);
+    vec3 b = sample_source(uv, texel_size, 0.0, -2.0);
+    vec3 c = sample_source(uv, texel_size, 2.0, -2.0);
+    vec3 d = sample_source(uv, texel_size, -2.0, 0.0);
+    vec3 e = sample_source(uv, texel_size, 0.0, 0.0);
+    vec3 f = sample_source(uv, texel_size, 2.0, 0.0);
+    vec3 g = sample_source(uv, texel_size, -2.0, 2.0);
+    vec3 h = sample_source(uv, texel_size, 0.0, 2.0);
+    vec3 i = sample_source(uv, texel_size, 2.0, 2.0);
+    vec3 j = sample_source(uv, texel_size, -1.0, -1.0);
+    vec3 k = sample_source(uv, texel_size, 1.0, -1.0);
+    vec3 l = sample_source(uv, texel_size, -1.0, 1.0);
+    vec3 m = sample_source(uv, texel_size, 1.0, 1.0);
+
+    return e * 0.125
+        + (a + c + g + i) * 0.03125
+        + (b + d + f + h) * 0.0625
+        + (j + k + l + m) * 0.125;
+}
+
+// Keeps the part of the color above the threshold. Within `knee` of the threshold the contribution follows a
+// quadratic curve instead of starting abruptly.
+vec3 apply_threshold(vec3 color)
+{
+    float brightness = max(color.r, max(color.g, color.b));
+    float soft = clamp(brightness - bloom.threshold + bloom.knee, 0.0, 2.0 * bloom.knee);
+    soft = soft * soft / (4.0 * bloom.knee + 0.00001);
+    float contribution = max(soft, brightness - bloom.threshold) / max(brightness, 0.00001);
+    return color * contribution;
+}
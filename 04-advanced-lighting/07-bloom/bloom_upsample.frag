@@ -0,0 +1,36 @@
+#version 450
+
+layout(location = 0) in vec2 uv;
+
+layout(location = 0) out vec4 fragment_color;
+
+layout(set = 0, binding = 0) uniform Bloom {
+    float threshold;
+    float knee;
+    float intensity;
+    float filter_radius;
+} bloom;
+layout(set = 0, binding = 1) uniform texture2D source_texture;
+layout(set = 0, binding = 2) uniform sampler source_sampler;
+
+vec3 sample_source(float x, float y)
+{
+    return texture(sampler2D(source_texture, source_sampler), uv + vec2(x, y) * bloom.filter_radius).rgb;
+}
+
+// A 3x3 tent filter over the smaller level. The result is added onto the larger level by the blend state.
+void main()
+{
+    vec3 a = sample_source(-1.0, -1.0);
+    vec3 b = sample_source(0.0, -1.0);
+    vec3 c = sample_source(1.0, -1.0);
+    vec3 d = sample_source(-1.0, 0.0);
+    vec3 e = sample_source(0.0, 0.0);
+    vec3 f = sample_source(1.0, 0.0);
+    vec3 g = sample_source(-1.0, 1.0);
+    vec3 h = sample_source(0.0, 1.0);
+    vec3 i = sample_source(1.0, 1.0);
+
+    vec3 color = (e * 4.0 + (b + d + f + h) * 2.0 + (a + c + g + i)) / 16.0;
+    fragment_color = vec4(color, 1.0);
+}
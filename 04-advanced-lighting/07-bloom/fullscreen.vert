@@ -0,0 +1,12 @@
+#version 450
+
+layout(location = 0) out vec2 frag_uv;
+
+void main()
+{
+    // A single triangle covering the whole screen, without any vertex buffers.
+    vec2 translation = vec2(float((gl_VertexIndex << 1) & 2), float(gl_VertexIndex & 2)) * 2.0 - 1.0;
+    gl_Position = vec4(translation, 0.0, 1.0);
+    // wgpu's normalized device coordinates point Y down just like texture coordinates.
+    frag_uv = translation * 0.5 + 0.5;
+}
@@ -0,0 +1,38 @@
+#version 450
+
+layout(location = 0) flat in uint light_index;
+
+layout(location = 0) out vec4 fragment_color;
+
+struct PointLight {
+    vec3 translation;
+
+    vec3 ambient;
+    vec3 diffuse;
+    vec3 specular;
+
+    float constant;
+    float linear;
+    float quadratic;
+
+    int shadow_map_index;
+};
+
+layout(set = 1, binding = 0) uniform Uniforms {
+    vec3 view_translation;
+    uint point_light_count;
+};
+layout(set = 1, binding = 1, std430) readonly buffer PointLights {
+    PointLight point_lights[];
+};
+
+void main()
+{
+    // Point lights glow with their own color, which is well above 1.0 for bright lights so that they bloom. The
+    // remaining light sources are spot lights, which are left plain white.
+    vec3 emission = vec3(1.0);
+    if (light_index < point_light_count) {
+        emission = point_lights[light_index].diffuse;
+    }
+    fragment_color = vec4(emission, 1.0);
+}
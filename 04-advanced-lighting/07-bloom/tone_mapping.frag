@@ -0,0 +1,95 @@
+#version 450
+
+// Must match `ToneMapping` in `src/hdr.rs`.
+#define TONE_MAPPING_REINHARD 0
+#define TONE_MAPPING_ACES_FILMIC 1
+#define TONE_MAPPING_UNCHARTED_2 2
+
+layout(location = 0) in vec2 uv;
+
+layout(location = 0) out vec4 fragment_color;
+
+layout(set = 0, binding = 0) uniform Uniforms {
+    float exposure;
+    uint tone_mapping;
+    bool eye_adaptation;
+    // The luminance the adapted luminance is mapped to, before the exposure is applied.
+    float key_value;
+};
+layout(set = 0, binding = 1) uniform texture2D hdr_texture;
+layout(set = 0, binding = 2) uniform sampler hdr_sampler;
+layout(set = 0, binding = 3) uniform texture2D adapted_luminance_texture;
+layout(set = 0, binding = 4) uniform sampler adapted_luminance_sampler;
+layout(set = 0, binding = 5) uniform Bloom {
+    float threshold;
+    float knee;
+    float intensity;
+    float filter_radius;
+} bloom;
+layout(set = 0, binding = 6) uniform texture2D bloom_texture;
+layout(set = 0, binding = 7) uniform sampler bloom_sampler;
+
+vec3 reinhard(vec3 color);
+vec3 aces_filmic(vec3 color);
+vec3 uncharted_2(vec3 color);
+
+void main()
+{
+    vec3 color = texture(sampler2D(hdr_texture, hdr_sampler), uv).rgb;
+    // The bloom is added before the exposure is applied, so it adapts along with the rest of the image.
+    color += bloom.intensity * texture(sampler2D(bloom_texture, bloom_sampler), uv).rgb;
+
+    float total_exposure = exposure;
+    if (eye_adaptation) {
+        float adapted_luminance = texture(sampler2D(adapted_luminance_texture, adapted_luminance_sampler), vec2(0.5)).r;
+        total_exposure *= key_value / max(adapted_luminance, 0.0001);
+    }
+    color *= total_exposure;
+
+    if (tone_mapping == TONE_MAPPING_REINHARD) {
+        color = reinhard(color);
+    } else if (tone_mapping == TONE_MAPPING_ACES_FILMIC) {
+        color = aces_filmic(color);
+    } else {
+        color = uncharted_2(color);
+    }
+
+    fragment_color = vec4(color, 1.0);
+}
+
+vec3 reinhard(vec3 color)
+{
+    return color / (color + 1.0);
+}
+
+vec3 aces_filmic(vec3 color)
+{
+    const float a = 2.51;
+    const float b = 0.03;
+    const float c = 2.43;
+    const float d = 0.59;
+    const float e = 0.14;
+    return clamp((color * (a * color + b)) / (color * (c * color + d) + e), 0.0, 1.0);
+}
+
+vec3 uncharted_2_curve(vec3 x)
+{
+    const float shoulder_strength = 0.15;
+    const float linear_strength = 0.50;
+    const float linear_angle = 0.10;
+    const float toe_strength = 0.20;
+    const float toe_numerator = 0.02;
+    const float toe_denominator = 0.30;
+    return ((x * (shoulder_strength * x + linear_angle * linear_strength) + toe_strength * toe_numerator)
+        / (x * (shoulder_strength * x + linear_strength) + toe_strength * toe_denominator))
+        - toe_numerator / toe_denominator;
+}
+
+vec3 uncharted_2(vec3 color)
+{
+    // The curve is normalized so that this linear white point maps to 1.0.
+    const float white_point = 11.2;
+    // The curve is darker than the others at the same exposure, which Hable compensates for with this bias.
+    const float exposure_bias = 2.0;
+    return uncharted_2_curve(exposure_bias * color) / uncharted_2_curve(vec3(white_point));
+}
@@ -0,0 +1,28 @@
+#version 450
+
+layout(location = 0) in vec3 translation;
+
+layout(location = 1) in vec3 light_translation;
+layout(location = 2) in float radius;
+layout(location = 3) in vec3 diffuse;
+layout(location = 4) in vec3 specular;
+layout(location = 5) in vec3 attenuation;
+
+layout(location = 0) flat out vec3 frag_light_translation;
+layout(location = 1) flat out vec3 frag_diffuse;
+layout(location = 2) flat out vec3 frag_specular;
+layout(location = 3) flat out vec3 frag_attenuation;
+
+layout(set = 0, binding = 0) uniform Uniforms {
+    mat4 view_projection;
+};
+
+void main()
+{
+    // The sphere's vertices lie just outside of the unit sphere, so scaling it by the radius covers the whole volume.
+    gl_Position = view_projection * vec4(light_translation + translation * radius, 1.0);
+    frag_light_translation = light_translation;
+    frag_diffuse = diffuse;
+    frag_specular = specular;
+    frag_attenuation = attenuation;
+}
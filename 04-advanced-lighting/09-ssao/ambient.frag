@@ -0,0 +1,55 @@
+#version 450
+
+layout(location = 0) in vec2 uv;
+
+layout(location = 0) out vec4 fragment_color;
+
+struct DirectionalLight {
+    vec3 direction;
+
+    vec3 ambient;
+    vec3 diffuse;
+    vec3 specular;
+};
+
+layout(set = 1, binding = 0) uniform Uniforms {
+    vec3 view_translation;
+    DirectionalLight directional_light;
+};
+
+layout(set = 2, binding = 0) uniform texture2D g_position_texture;
+layout(set = 2, binding = 1) uniform texture2D g_normal_texture;
+layout(set = 2, binding = 2) uniform texture2D g_albedo_specular_texture;
+layout(set = 2, binding = 3) uniform sampler g_sampler;
+
+layout(set = 3, binding = 0) uniform texture2D occlusion_texture;
+layout(set = 3, binding = 1) uniform sampler occlusion_sampler;
+
+// Lights every pixel covered by geometry with the ambient and directional light, which reach everywhere. The point
+// lights are added on top by their light volumes.
+void main()
+{
+    ivec2 pixel = ivec2(gl_FragCoord.xy);
+    vec4 position = texelFetch(sampler2D(g_position_texture, g_sampler), pixel, 0);
+    // Nothing was drawn here, so the clear color is left alone.
+    if (position.w == 0.0) {
+        discard;
+    }
+    vec4 normal_shininess = texelFetch(sampler2D(g_normal_texture, g_sampler), pixel, 0);
+    vec4 albedo_specular = texelFetch(sampler2D(g_albedo_specular_texture, g_sampler), pixel, 0);
+
+    vec3 normal = normal_shininess.xyz;
+    float ambient_occlusion = texelFetch(sampler2D(occlusion_texture, occlusion_sampler), pixel, 0).r;
+    vec3 ambient = directional_light.ambient * albedo_specular.rgb * ambient_occlusion;
+
+    vec3 light_direction = normalize(-directional_light.direction);
+    float diff = max(dot(normal, light_direction), 0.0);
+    vec3 diffuse = directional_light.diffuse * diff * albedo_specular.rgb;
+
+    vec3 view_direction = normalize(view_translation - position.xyz);
+    vec3 reflect_direction = reflect(-light_direction, normal);
+    float spec = pow(max(dot(view_direction, reflect_direction), 0.0), normal_shininess.w);
+    vec3 specular = directional_light.specular * spec * albedo_specular.a;
+
+    fragment_color = vec4(ambient + diffuse + specular, 1.0);
+}
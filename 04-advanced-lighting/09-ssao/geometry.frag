@@ -0,0 +1,47 @@
+#version 450
+
+layout(location = 0) in vec3 translation;
+layout(location = 1) in vec3 normal;
+layout(location = 2) in vec2 uv;
+layout(location = 3) in vec4 tangent;
+
+layout(location = 0) out vec4 g_position;
+layout(location = 1) out vec4 g_normal;
+layout(location = 2) out vec4 g_albedo_specular;
+
+layout(set = 1, binding = 0) uniform Material {
+    float shininess;
+} material;
+layout(set = 1, binding = 1) uniform texture2D material_diffuse_texture;
+layout(set = 1, binding = 2) uniform sampler material_diffuse_sampler;
+layout(set = 1, binding = 3) uniform texture2D material_specular_texture;
+layout(set = 1, binding = 4) uniform sampler material_specular_sampler;
+layout(set = 1, binding = 5) uniform texture2D material_normal_texture;
+layout(set = 1, binding = 6) uniform sampler material_normal_sampler;
+
+vec3 calculate_normal();
+
+void main()
+{
+    // W marks the pixel as covered by geometry, since the position target is cleared to zero.
+    g_position = vec4(translation, 1.0);
+    g_normal = vec4(calculate_normal(), material.shininess);
+    g_albedo_specular = vec4(
+        texture(sampler2D(material_diffuse_texture, material_diffuse_sampler), uv).rgb,
+        texture(sampler2D(material_specular_texture, material_specular_sampler), uv).r
+    );
+}
+
+// Transforms the normal map's normal from tangent space into world space. A flat normal map pointing straight along
+// the Z axis reproduces the vertex normal.
+vec3 calculate_normal()
+{
+    vec3 n = normalize(normal);
+    // Interpolation leaves the tangent slightly off perpendicular to the normal, so it's straightened out again.
+    vec3 t = normalize(tangent.xyz - dot(tangent.xyz, n) * n);
+    // The sign in W flips the bitangent for mirrored texture coordinates.
+    vec3 b = tangent.w * cross(n, t);
+
+    vec3 tangent_space_normal = texture(sampler2D(material_normal_texture, material_normal_sampler), uv).rgb * 2.0 - 1.0;
+    return normalize(mat3(t, b, n) * tangent_space_normal);
+}
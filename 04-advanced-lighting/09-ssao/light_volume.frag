@@ -0,0 +1,46 @@
+#version 450
+
+layout(location = 0) flat in vec3 light_translation;
+layout(location = 1) flat in vec3 light_diffuse;
+layout(location = 2) flat in vec3 light_specular;
+// The constant, linear and quadratic attenuation factors.
+layout(location = 3) flat in vec3 light_attenuation;
+
+layout(location = 0) out vec4 fragment_color;
+
+layout(set = 1, binding = 0) uniform Uniforms {
+    vec3 view_translation;
+};
+
+layout(set = 2, binding = 0) uniform texture2D g_position_texture;
+layout(set = 2, binding = 1) uniform texture2D g_normal_texture;
+layout(set = 2, binding = 2) uniform texture2D g_albedo_specular_texture;
+layout(set = 2, binding = 3) uniform sampler g_sampler;
+
+// Adds a single point light to the pixels its volume covers. The light volume's own depth is of no use here, since the
+// surface being lit is the one in the G-buffer.
+void main()
+{
+    ivec2 pixel = ivec2(gl_FragCoord.xy);
+    vec4 position = texelFetch(sampler2D(g_position_texture, g_sampler), pixel, 0);
+    if (position.w == 0.0) {
+        discard;
+    }
+    vec4 normal_shininess = texelFetch(sampler2D(g_normal_texture, g_sampler), pixel, 0);
+    vec4 albedo_specular = texelFetch(sampler2D(g_albedo_specular_texture, g_sampler), pixel, 0);
+
+    vec3 normal = normal_shininess.xyz;
+    vec3 light_direction = normalize(light_translation - position.xyz);
+    float diff = max(dot(normal, light_direction), 0.0);
+    vec3 diffuse = light_diffuse * diff * albedo_specular.rgb;
+
+    vec3 view_direction = normalize(view_translation - position.xyz);
+    vec3 reflect_direction = reflect(-light_direction, normal);
+    float spec = pow(max(dot(view_direction, reflect_direction), 0.0), normal_shininess.w);
+    vec3 specular = light_specular * spec * albedo_specular.a;
+
+    float light_distance = length(light_translation - position.xyz);
+    float attenuation = 1.0 / (light_attenuation.x + light_attenuation.y * light_distance + light_attenuation.z * (light_distance * light_distance));
+
+    fragment_color = vec4((diffuse + specular) * attenuation, 1.0);
+}
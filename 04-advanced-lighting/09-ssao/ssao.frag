@@ -0,0 +1,74 @@
+#version 450
+
+// Must match `MAX_KERNEL_SIZE` in `src/ssao.rs`.
+#define MAX_KERNEL_SIZE 64
+// Must match `NOISE_SIZE` in `src/ssao.rs`.
+#define NOISE_SIZE 4
+
+layout(location = 0) in vec2 uv;
+
+layout(location = 0) out float occlusion;
+
+layout(set = 0, binding = 0) uniform Uniforms {
+    mat4 view;
+    mat4 projection;
+    uint sample_count;
+    float radius;
+    float bias;
+};
+layout(set = 0, binding = 1) uniform Kernel {
+    vec4 kernel[MAX_KERNEL_SIZE];
+};
+layout(set = 0, binding = 2) uniform texture2D g_position_texture;
+layout(set = 0, binding = 3) uniform texture2D g_normal_texture;
+layout(set = 0, binding = 4) uniform texture2D noise_texture;
+layout(set = 0, binding = 5) uniform sampler ssao_sampler;
+
+// The distance of a point in front of the camera, which is the W component the projection puts it at.
+float view_depth(vec3 view_position)
+{
+    return (projection * vec4(view_position, 1.0)).w;
+}
+
+void main()
+{
+    ivec2 pixel = ivec2(gl_FragCoord.xy);
+    ivec2 size = textureSize(sampler2D(g_position_texture, ssao_sampler), 0);
+    vec4 world_position = texelFetch(sampler2D(g_position_texture, ssao_sampler), pixel, 0);
+    // Nothing was drawn here, so there's nothing to occlude.
+    if (world_position.w == 0.0) {
+        occlusion = 1.0;
+        return;
+    }
+    vec3 position = (view * vec4(world_position.xyz, 1.0)).xyz;
+    // The view matrix only rotates and translates, so it doesn't need a separate normal matrix.
+    vec3 normal = normalize(mat3(view) * texelFetch(sampler2D(g_normal_texture, ssao_sampler), pixel, 0).xyz);
+    vec3 random_vector =
+        texelFetch(sampler2D(noise_texture, ssao_sampler), pixel % ivec2(NOISE_SIZE), 0).xyz * 2.0 - 1.0;
+
+    // Orients the kernel's hemisphere around the normal, rotated around it by the noise.
+    vec3 tangent = normalize(random_vector - normal * dot(random_vector, normal));
+    vec3 bitangent = cross(normal, tangent);
+    mat3 tbn = mat3(tangent, bitangent, normal);
+
+    float depth = view_depth(position);
+    uint count = min(sample_count, uint(MAX_KERNEL_SIZE));
+    float occluded = 0.0;
+    for (uint i = 0u; i < count; i++) {
+        vec3 sample_position = position + tbn * kernel[i].xyz * radius;
+        vec4 clip_position = projection * vec4(sample_position, 1.0);
+        vec2 sample_uv = clip_position.xy / clip_position.w * 0.5 + 0.5;
+        ivec2 sample_pixel = clamp(ivec2(sample_uv * vec2(size)), ivec2(0), size - 1);
+
+        vec4 scene_position = texelFetch(sampler2D(g_position_texture, ssao_sampler), sample_pixel, 0);
+        if (scene_position.w == 0.0) {
+            continue;
+        }
+        float scene_depth = view_depth((view * vec4(scene_position.xyz, 1.0)).xyz);
+        // Surfaces far in front of this pixel shouldn't darken it just because they hide some of its samples.
+        float range_check = smoothstep(0.0, 1.0, radius / abs(depth - scene_depth));
+        occluded += (scene_depth <= clip_position.w - bias ? 1.0 : 0.0) * range_check;
+    }
+
+    occlusion = 1.0 - occluded / float(count);
+}
@@ -0,0 +1,26 @@
+#version 450
+
+// Must match `NOISE_SIZE` in `src/ssao.rs`.
+#define NOISE_SIZE 4
+
+layout(location = 0) in vec2 uv;
+
+layout(location = 0) out float occlusion;
+
+layout(set = 0, binding = 0) uniform texture2D occlusion_texture;
+layout(set = 0, binding = 1) uniform sampler occlusion_sampler;
+
+// Averages a square the size of the noise texture, which evens out the pattern the noise leaves behind.
+void main()
+{
+    ivec2 pixel = ivec2(gl_FragCoord.xy);
+    ivec2 size = textureSize(sampler2D(occlusion_texture, occlusion_sampler), 0);
+    float sum = 0.0;
+    for (int x = -NOISE_SIZE / 2; x < NOISE_SIZE / 2; x++) {
+        for (int y = -NOISE_SIZE / 2; y < NOISE_SIZE / 2; y++) {
+            ivec2 offset_pixel = clamp(pixel + ivec2(x, y), ivec2(0), size - 1);
+            sum += texelFetch(sampler2D(occlusion_texture, occlusion_sampler), offset_pixel, 0).r;
+        }
+    }
+    occlusion = sum / float(NOISE_SIZE * NOISE_SIZE);
+}
@@ -0,0 +1,267 @@
+#version 450
+
+#define PI 3.14159265359
+
+layout(location = 0) in vec3 translation;
+layout(location = 1) in vec3 normal;
+layout(location = 2) in vec2 uv;
+layout(location = 3) in vec4 tangent;
+layout(location = 4) in vec2 instance_metallic_roughness;
+
+layout(location = 0) out vec4 fragment_color;
+
+struct DirectionalLight {
+    vec3 direction;
+
+    vec3 ambient;
+    vec3 diffuse;
+    vec3 specular;
+};
+
+struct PointLight {
+    vec3 translation;
+
+    vec3 ambient;
+    vec3 diffuse;
+    vec3 specular;
+
+    float constant;
+    float linear;
+    float quadratic;
+
+    int shadow_map_index;
+};
+
+struct SpotLight {
+    vec3 translation;
+    vec3 direction;
+    float cutoff;
+    float outer_cutoff;
+
+    float constant;
+    float linear;
+    float quadratic;
+
+    vec3 ambient;
+    vec3 diffuse;
+    vec3 specular;
+
+    int cookie_index;
+    mat4 cookie_space;
+};
+
+layout(set = 1, binding = 0) uniform Uniforms {
+    vec3 view_translation;
+    uint point_light_count;
+    uint spot_light_count;
+    uint specular_model;
+    DirectionalLight directional_light;
+};
+layout(set = 1, binding = 1, std430) readonly buffer PointLights {
+    PointLight point_lights[];
+};
+layout(set = 1, binding = 2, std430) readonly buffer SpotLights {
+    SpotLight spot_lights[];
+};
+layout(set = 1, binding = 3) uniform texture2DArray spot_light_cookies;
+layout(set = 1, binding = 4) uniform sampler spot_light_cookie_sampler;
+
+layout(set = 2, binding = 0) uniform Material {
+    vec3 albedo;
+    float metallic;
+    vec3 emissive;
+    float roughness;
+    float ambient_occlusion;
+} material;
+layout(set = 2, binding = 1) uniform texture2D material_albedo_texture;
+layout(set = 2, binding = 2) uniform sampler material_albedo_sampler;
+layout(set = 2, binding = 3) uniform texture2D material_metallic_roughness_texture;
+layout(set = 2, binding = 4) uniform sampler material_metallic_roughness_sampler;
+layout(set = 2, binding = 5) uniform texture2D material_ambient_occlusion_texture;
+layout(set = 2, binding = 6) uniform sampler material_ambient_occlusion_sampler;
+layout(set = 2, binding = 7) uniform texture2D material_emissive_texture;
+layout(set = 2, binding = 8) uniform sampler material_emissive_sampler;
+layout(set = 2, binding = 9) uniform texture2D material_normal_texture;
+layout(set = 2, binding = 10) uniform sampler material_normal_sampler;
+
+// The surface properties at the current fragment, with every texture applied.
+struct Surface {
+    vec3 albedo;
+    float metallic;
+    float roughness;
+    // The reflectance at normal incidence.
+    vec3 f0;
+};
+
+vec3 calculate_normal();
+float distribution_ggx(vec3 normal, vec3 halfway_direction, float roughness);
+float geometry_schlick_ggx(float n_dot_v, float roughness);
+float geometry_smith(vec3 normal, vec3 view_direction, vec3 light_direction, float roughness);
+vec3 fresnel_schlick(float cos_theta, vec3 f0);
+vec3 calculate_reflectance(Surface surface, vec3 radiance, vec3 light_direction, vec3 normal, vec3 view_direction);
+vec3 calculate_spot_light_cookie(SpotLight light, vec3 frag_translation);
+
+void main()
+{
+    vec3 normalized_normal = calculate_normal();
+    vec3 view_direction = normalize(view_translation - translation);
+
+    Surface surface;
+    // Color textures are sRGB textures, so sampling them already decodes them into linear space.
+    surface.albedo = material.albedo * texture(sampler2D(material_albedo_texture, material_albedo_sampler), uv).rgb;
+    vec4 metallic_roughness =
+        texture(sampler2D(material_metallic_roughness_texture, material_metallic_roughness_sampler), uv);
+    surface.metallic = material.metallic * instance_metallic_roughness.x * metallic_roughness.b;
+    surface.roughness = material.roughness * instance_metallic_roughness.y * metallic_roughness.g;
+    // Dielectrics all reflect about 4% of the light head on, while metals tint their reflections with their albedo.
+    surface.f0 = mix(vec3(0.04), surface.albedo, surface.metallic);
+
+    float ambient_occlusion = material.ambient_occlusion
+        * texture(sampler2D(material_ambient_occlusion_texture, material_ambient_occlusion_sampler), uv).r;
+    vec3 emissive =
+        material.emissive * texture(sampler2D(material_emissive_texture, material_emissive_sampler), uv).rgb;
+
+    // Each light's diffuse color is the radiance it emits. The ambient and specular colors of the Phong lights have no
+    // equivalent here, except for the directional light's ambient color standing in for the light from everywhere
+    // else.
+    vec3 radiance_out = calculate_reflectance(
+        surface,
+        directional_light.diffuse,
+        normalize(-directional_light.direction),
+        normalized_normal,
+        view_direction
+    );
+    for (uint i = 0; i < point_light_count; i++) {
+        PointLight light = point_lights[i];
+        float light_distance = length(light.translation - translation);
+        float attenuation = 1.0
+            / (light.constant + light.linear * light_distance + light.quadratic * (light_distance * light_distance));
+        radiance_out += calculate_reflectance(
+            surface,
+            light.diffuse * attenuation,
+            normalize(light.translation - translation),
+            normalized_normal,
+            view_direction
+        );
+    }
+    for (uint i = 0; i < spot_light_count; i++) {
+        SpotLight light = spot_lights[i];
+        vec3 light_direction = normalize(light.translation - translation);
+        float light_distance = length(light.translation - translation);
+        float attenuation = 1.0
+            / (light.constant + light.linear * light_distance + light.quadratic * (light_distance * light_distance));
+        float theta = dot(light_direction, normalize(-light.direction));
+        float intensity = clamp((theta - light.outer_cutoff) / (light.cutoff - light.outer_cutoff), 0.0, 1.0);
+        radiance_out += calculate_reflectance(
+            surface,
+            light.diffuse * attenuation * intensity * calculate_spot_light_cookie(light, translation),
+            light_direction,
+            normalized_normal,
+            view_direction
+        );
+    }
+
+    vec3 ambient = directional_light.ambient * surface.albedo * ambient_occlusion;
+    vec3 color = ambient + radiance_out + emissive;
+
+    // The lights are far brighter than the screen can show, so the result is tone mapped with Reinhard's operator.
+    // The sRGB swap chain takes care of gamma encoding it.
+    color = color / (color + vec3(1.0));
+
+    fragment_color = vec4(color, 1.0);
+}
+
+// Transforms the normal map's normal from tangent space into world space. A flat normal map pointing straight along
+// the Z axis reproduces the vertex normal.
+vec3 calculate_normal()
+{
+    vec3 n = normalize(normal);
+    // Interpolation leaves the tangent slightly off perpendicular to the normal, so it's straightened out again.
+    vec3 t = normalize(tangent.xyz - dot(tangent.xyz, n) * n);
+    // The sign in W flips the bitangent for mirrored texture coordinates.
+    vec3 b = tangent.w * cross(n, t);
+
+    vec3 tangent_space_normal = texture(sampler2D(material_normal_texture, material_normal_sampler), uv).rgb * 2.0 - 1.0;
+    return normalize(mat3(t, b, n) * tangent_space_normal);
+}
+
+// Trowbridge-Reitz GGX: the fraction of microfacets aligned with the halfway vector, and so reflecting the light
+// towards the viewer.
+float distribution_ggx(vec3 normal, vec3 halfway_direction, float roughness)
+{
+    float a = roughness * roughness;
+    float a2 = a * a;
+    float n_dot_h = max(dot(normal, halfway_direction), 0.0);
+
+    float denominator = n_dot_h * n_dot_h * (a2 - 1.0) + 1.0;
+    return a2 / (PI * denominator * denominator);
+}
+
+// Schlick-GGX: the fraction of microfacets that aren't hidden behind other microfacets from one direction.
+float geometry_schlick_ggx(float n_dot_v, float roughness)
+{
+    // The remapping for direct lighting.
+    float r = roughness + 1.0;
+    float k = (r * r) / 8.0;
+
+    return n_dot_v / (n_dot_v * (1.0 - k) + k);
+}
+
+// Smith's method: microfacets have to be visible from both the light and the viewer to reflect anything.
+float geometry_smith(vec3 normal, vec3 view_direction, vec3 light_direction, float roughness)
+{
+    float n_dot_v = max(dot(normal, view_direction), 0.0);
+    float n_dot_l = max(dot(normal, light_direction), 0.0);
+    return geometry_schlick_ggx(n_dot_v, roughness) * geometry_schlick_ggx(n_dot_l, roughness);
+}
+
+// Schlick's approximation of how much light is reflected rather than refracted, which rises towards grazing angles.
+vec3 fresnel_schlick(float cos_theta, vec3 f0)
+{
+    return f0 + (1.0 - f0) * pow(clamp(1.0 - cos_theta, 0.0, 1.0), 5.0);
+}
+
+// The Cook-Torrance BRDF times the incoming radiance and the cosine of its angle of incidence.
+vec3 calculate_reflectance(Surface surface, vec3 radiance, vec3 light_direction, vec3 normal, vec3 view_direction)
+{
+    vec3 halfway_direction = normalize(view_direction + light_direction);
+
+    float ndf = distribution_ggx(normal, halfway_direction, surface.roughness);
+    float g = geometry_smith(normal, view_direction, light_direction, surface.roughness);
+    vec3 f = fresnel_schlick(max(dot(halfway_direction, view_direction), 0.0), surface.f0);
+
+    vec3 numerator = ndf * g * f;
+    // The small constant keeps this from dividing by zero where either direction is perpendicular to the normal.
+    float denominator = 4.0 * max(dot(normal, view_direction), 0.0) * max(dot(normal, light_direction), 0.0) + 0.0001;
+    vec3 specular = numerator / denominator;
+
+    // Whatever isn't reflected is refracted and scattered back out as diffuse light, except by metals, which absorb
+    // it.
+    vec3 k_diffuse = (vec3(1.0) - f) * (1.0 - surface.metallic);
+
+    float n_dot_l = max(dot(normal, light_direction), 0.0);
+    return (k_diffuse * surface.albedo / PI + specular) * radiance * n_dot_l;
+}
+
+// The color of the spot light's cookie where it's projected onto the fragment, or white if the light has none. No light
+// makes it past the edges of the cookie.
+vec3 calculate_spot_light_cookie(SpotLight light, vec3 frag_translation)
+{
+    if (light.cookie_index < 0) {
+        return vec3(1.0);
+    }
+
+    vec4 cookie_clip_translation = light.cookie_space * vec4(frag_translation, 1.0);
+    // Behind the light, the projection would show the cookie again upside down.
+    if (cookie_clip_translation.w <= 0.0) {
+        return vec3(0.0);
+    }
+    vec2 cookie_ndc = cookie_clip_translation.xy / cookie_clip_translation.w;
+    // The cookie's top row is on the light's up side.
+    vec2 cookie_uv = vec2(cookie_ndc.x * 0.5 + 0.5, 0.5 - cookie_ndc.y * 0.5);
+    if (any(lessThan(cookie_uv, vec2(0.0))) || any(greaterThan(cookie_uv, vec2(1.0)))) {
+        return vec3(0.0);
+    }
+    vec3 cookie_coordinates = vec3(cookie_uv, light.cookie_index);
+    return texture(sampler2DArray(spot_light_cookies, spot_light_cookie_sampler), cookie_coordinates).rgb;
+}
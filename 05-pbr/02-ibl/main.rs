@@ -0,0 +1,1028 @@
+use std::path::PathBuf;
+
+use amethyst_input::{InputHandler, StringBindings};
+use shaderc::{Compiler, ShaderKind};
+use ultraviolet::{Mat4, Vec2, Vec3, Vec4};
+use winit::{
+    event::{Event, KeyboardInput, VirtualKeyCode, WindowEvent},
+    event_loop::{ControlFlow, EventLoop},
+    window::WindowBuilder,
+};
+
+use learnopengl_but_its_wgpu::{
+    ibl::{IblMaps, IblTextures, PREFILTERED_MIP_LEVELS},
+    lighting::LightManager,
+    pbr::{self, PbrInstance, PbrMaterial, Vertex},
+    scene::Scene,
+    time::Time,
+    Camera, Instance,
+};
+
+const DEPTH_FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Depth32Float;
+
+/// The spheres get more metallic from the bottom row to the top one, and rougher from the left column to the right one.
+const SPHERE_ROWS: usize = 7;
+const SPHERE_COLUMNS: usize = 7;
+const SPHERE_SPACING: f32 = 2.5;
+
+#[repr(C)]
+#[derive(Clone, Copy, Debug)]
+struct Uniforms {
+    view_projection: Mat4,
+    /// Whether the ambient light comes from the environment map rather than the directional light's ambient color.
+    image_based_lighting: u32,
+}
+
+#[repr(C)]
+#[derive(Clone, Copy, Debug)]
+struct SkyboxUniforms {
+    view: Mat4,
+    projection: Mat4,
+    source: u32,
+    lod: f32,
+}
+
+/// What the skybox shows, to see what the spheres are lit with.
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum SkyboxSource {
+    Environment,
+    Irradiance,
+    /// A mip level of the prefiltered map.
+    Prefiltered(u32),
+}
+
+fn main() {
+    let scene_path = std::env::args().nth(1).unwrap_or_else(|| {
+        concat!(env!("CARGO_MANIFEST_DIR"), "/05-pbr/02-ibl/scene.ron").to_owned()
+    });
+    let scene = Scene::load(&scene_path).unwrap();
+    let environment_path = scene.resolve_path(
+        scene
+            .environment
+            .as_ref()
+            .expect("The scene needs an environment map"),
+    );
+    // This takes a while the first time, and is cached on disk afterwards.
+    let (ibl_maps, cached) = IblMaps::load_or_compute(&environment_path).unwrap();
+    if !cached {
+        println!(
+            "Precomputed image based lighting for {} and cached it for the next run",
+            environment_path.display()
+        );
+    }
+
+    let (event_loop, window, mut size) = {
+        let event_loop = EventLoop::new();
+        let window = WindowBuilder::new()
+            .with_title("LearnOpenGL but it's WGPU-rs")
+            .build(&event_loop)
+            .unwrap();
+        let size = window.inner_size();
+        (event_loop, window, size)
+    };
+
+    let surface = wgpu::Surface::create(&window);
+
+    let (device, mut queue) = wgpu::Adapter::request(&wgpu::RequestAdapterOptions {
+        power_preference: wgpu::PowerPreference::HighPerformance,
+        backends: wgpu::BackendBit::all(),
+    })
+    .unwrap()
+    .request_device(&wgpu::DeviceDescriptor {
+        extensions: wgpu::Extensions {
+            anisotropic_filtering: false,
+        },
+        ..Default::default()
+    });
+
+    let mut shader_compiler = Compiler::new().expect("Could not initialize shader compiler");
+
+    let vs_module = prepare_shader(
+        &mut shader_compiler,
+        include_str!("pbr.vert"),
+        ShaderKind::Vertex,
+        "pbr.vert",
+        &device,
+    );
+    let fs_module = prepare_shader(
+        &mut shader_compiler,
+        include_str!("pbr.frag"),
+        ShaderKind::Fragment,
+        "pbr.frag",
+        &device,
+    );
+    let light_source_vs_module = prepare_shader(
+        &mut shader_compiler,
+        include_str!("light_source.vert"),
+        ShaderKind::Vertex,
+        "light_source.vert",
+        &device,
+    );
+    let light_source_fs_module = prepare_shader(
+        &mut shader_compiler,
+        include_str!("light_source.frag"),
+        ShaderKind::Fragment,
+        "light_source.frag",
+        &device,
+    );
+    let skybox_vs_module = prepare_shader(
+        &mut shader_compiler,
+        include_str!("skybox.vert"),
+        ShaderKind::Vertex,
+        "skybox.vert",
+        &device,
+    );
+    let skybox_fs_module = prepare_shader(
+        &mut shader_compiler,
+        include_str!("skybox.frag"),
+        ShaderKind::Fragment,
+        "skybox.frag",
+        &device,
+    );
+
+    let mut init_encoder =
+        device.create_command_encoder(&wgpu::CommandEncoderDescriptor { todo: 0 });
+
+    let mut sphere_mesh = pbr::uv_sphere(64, 32);
+    sphere_mesh.optimize();
+
+    let vertex_buffer = device
+        .create_buffer_mapped(sphere_mesh.vertices.len(), wgpu::BufferUsage::VERTEX)
+        .fill_from_slice(&sphere_mesh.vertices);
+    let index_buffer = device
+        .create_buffer_mapped(sphere_mesh.indices.len(), wgpu::BufferUsage::INDEX)
+        .fill_from_slice(&sphere_mesh.indices);
+    let index_count = sphere_mesh.indices.len() as u32;
+
+    let spheres = create_sphere_grid();
+    let sphere_instance_buffer = device
+        .create_buffer_mapped(spheres.len(), wgpu::BufferUsage::VERTEX)
+        .fill_from_slice(&spheres);
+
+    let uniform_bind_group_layout =
+        device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            bindings: &[wgpu::BindGroupLayoutBinding {
+                binding: 0,
+                visibility: wgpu::ShaderStage::VERTEX | wgpu::ShaderStage::FRAGMENT,
+                ty: wgpu::BindingType::UniformBuffer { dynamic: false },
+            }],
+        });
+
+    let light_bind_group_layout = LightManager::create_bind_group_layout(&device);
+
+    let material_bind_group_layout = pbr::create_material_bind_group_layout(&device);
+
+    let ibl_textures = IblTextures::new(&device, &mut init_encoder, &ibl_maps);
+    let ibl_bind_group_layout = IblTextures::create_bind_group_layout(&device);
+    let ibl_bind_group = ibl_textures.create_bind_group(&device, &ibl_bind_group_layout);
+
+    let mut event_channel = shrev::EventChannel::new();
+    let event_reader = event_channel.register_reader();
+    let mut input_handler = InputHandler::<StringBindings>::new();
+
+    let mut camera = scene.create_camera(event_reader);
+
+    let mut light_manager = scene
+        .create_light_manager(&device, &light_bind_group_layout, &camera)
+        .unwrap();
+
+    let mut image_based_lighting = true;
+    let mut skybox_source = SkyboxSource::Environment;
+
+    let uniform_buffer = device
+        .create_buffer_mapped(1, wgpu::BufferUsage::UNIFORM | wgpu::BufferUsage::COPY_DST)
+        .fill_from_slice(&[Uniforms {
+            view_projection: camera.get_view_projection_matrix(
+                size.width as f32,
+                size.height as f32,
+                0.1,
+                100.0,
+            ),
+            image_based_lighting: image_based_lighting as u32,
+        }]);
+
+    let uniform_bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+        layout: &uniform_bind_group_layout,
+        bindings: &[wgpu::Binding {
+            binding: 0,
+            resource: wgpu::BindingResource::Buffer {
+                buffer: &uniform_buffer,
+                range: 0..std::mem::size_of::<Uniforms>() as wgpu::BufferAddress,
+            },
+        }],
+    });
+
+    let skybox_uniform_buffer = device
+        .create_buffer_mapped(1, wgpu::BufferUsage::UNIFORM | wgpu::BufferUsage::COPY_DST)
+        .fill_from_slice(&[skybox_uniforms(
+            &camera,
+            size.width as f32,
+            size.height as f32,
+            skybox_source,
+        )]);
+
+    let skybox_uniform_bind_group_layout =
+        device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            bindings: &[wgpu::BindGroupLayoutBinding {
+                binding: 0,
+                visibility: wgpu::ShaderStage::VERTEX | wgpu::ShaderStage::FRAGMENT,
+                ty: wgpu::BindingType::UniformBuffer { dynamic: false },
+            }],
+        });
+    let skybox_uniform_bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+        layout: &skybox_uniform_bind_group_layout,
+        bindings: &[wgpu::Binding {
+            binding: 0,
+            resource: wgpu::BindingResource::Buffer {
+                buffer: &skybox_uniform_buffer,
+                range: 0..std::mem::size_of::<SkyboxUniforms>() as wgpu::BufferAddress,
+            },
+        }],
+    });
+
+    let skybox_texture_bind_group_layout =
+        device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            bindings: &[
+                wgpu::BindGroupLayoutBinding {
+                    binding: 0,
+                    visibility: wgpu::ShaderStage::FRAGMENT,
+                    ty: wgpu::BindingType::SampledTexture {
+                        multisampled: false,
+                        dimension: wgpu::TextureViewDimension::Cube,
+                    },
+                },
+                wgpu::BindGroupLayoutBinding {
+                    binding: 1,
+                    visibility: wgpu::ShaderStage::FRAGMENT,
+                    ty: wgpu::BindingType::SampledTexture {
+                        multisampled: false,
+                        dimension: wgpu::TextureViewDimension::Cube,
+                    },
+                },
+                wgpu::BindGroupLayoutBinding {
+                    binding: 2,
+                    visibility: wgpu::ShaderStage::FRAGMENT,
+                    ty: wgpu::BindingType::SampledTexture {
+                        multisampled: false,
+                        dimension: wgpu::TextureViewDimension::Cube,
+                    },
+                },
+                wgpu::BindGroupLayoutBinding {
+                    binding: 3,
+                    visibility: wgpu::ShaderStage::FRAGMENT,
+                    ty: wgpu::BindingType::Sampler,
+                },
+            ],
+        });
+    let skybox_texture_bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+        layout: &skybox_texture_bind_group_layout,
+        bindings: &[
+            wgpu::Binding {
+                binding: 0,
+                resource: wgpu::BindingResource::TextureView(&ibl_textures.environment_view),
+            },
+            wgpu::Binding {
+                binding: 1,
+                resource: wgpu::BindingResource::TextureView(&ibl_textures.irradiance_view),
+            },
+            wgpu::Binding {
+                binding: 2,
+                resource: wgpu::BindingResource::TextureView(&ibl_textures.prefiltered_view),
+            },
+            wgpu::Binding {
+                binding: 3,
+                resource: wgpu::BindingResource::Sampler(&ibl_textures.sampler),
+            },
+        ],
+    });
+
+    let material = &scene.pbr_material;
+    let material_uniform_buffer = device
+        .create_buffer_mapped(1, wgpu::BufferUsage::UNIFORM | wgpu::BufferUsage::COPY_DST)
+        .fill_from_slice(&[material.to_gpu()]);
+
+    let white = [255, 255, 255, 255];
+    let (_, material_albedo_texture_view, material_albedo_texture_sampler) = load_material_texture(
+        &device,
+        &mut init_encoder,
+        &scene,
+        material.albedo_texture.as_ref(),
+        white,
+        wgpu::TextureFormat::Rgba8UnormSrgb,
+    );
+    let (_, material_metallic_roughness_texture_view, material_metallic_roughness_texture_sampler) =
+        load_material_texture(
+            &device,
+            &mut init_encoder,
+            &scene,
+            material.metallic_roughness_texture.as_ref(),
+            white,
+            wgpu::TextureFormat::Rgba8Unorm,
+        );
+    let (_, material_ambient_occlusion_texture_view, material_ambient_occlusion_texture_sampler) =
+        load_material_texture(
+            &device,
+            &mut init_encoder,
+            &scene,
+            material.ambient_occlusion_texture.as_ref(),
+            white,
+            wgpu::TextureFormat::Rgba8Unorm,
+        );
+    let (_, material_emissive_texture_view, material_emissive_texture_sampler) =
+        load_material_texture(
+            &device,
+            &mut init_encoder,
+            &scene,
+            material.emissive_texture.as_ref(),
+            white,
+            wgpu::TextureFormat::Rgba8UnormSrgb,
+        );
+    // A flat normal map leaves the vertex normals as they are.
+    let (_, material_normal_texture_view, material_normal_texture_sampler) = load_material_texture(
+        &device,
+        &mut init_encoder,
+        &scene,
+        material.normal_texture.as_ref(),
+        [128, 128, 255, 255],
+        wgpu::TextureFormat::Rgba8Unorm,
+    );
+
+    let material_bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+        layout: &material_bind_group_layout,
+        bindings: &[
+            wgpu::Binding {
+                binding: 0,
+                resource: wgpu::BindingResource::Buffer {
+                    buffer: &material_uniform_buffer,
+                    range: 0..std::mem::size_of::<PbrMaterial>() as wgpu::BufferAddress,
+                },
+            },
+            wgpu::Binding {
+                binding: 1,
+                resource: wgpu::BindingResource::TextureView(&material_albedo_texture_view),
+            },
+            wgpu::Binding {
+                binding: 2,
+                resource: wgpu::BindingResource::Sampler(&material_albedo_texture_sampler),
+            },
+            wgpu::Binding {
+                binding: 3,
+                resource: wgpu::BindingResource::TextureView(
+                    &material_metallic_roughness_texture_view,
+                ),
+            },
+            wgpu::Binding {
+                binding: 4,
+                resource: wgpu::BindingResource::Sampler(
+                    &material_metallic_roughness_texture_sampler,
+                ),
+            },
+            wgpu::Binding {
+                binding: 5,
+                resource: wgpu::BindingResource::TextureView(
+                    &material_ambient_occlusion_texture_view,
+                ),
+            },
+            wgpu::Binding {
+                binding: 6,
+                resource: wgpu::BindingResource::Sampler(
+                    &material_ambient_occlusion_texture_sampler,
+                ),
+            },
+            wgpu::Binding {
+                binding: 7,
+                resource: wgpu::BindingResource::TextureView(&material_emissive_texture_view),
+            },
+            wgpu::Binding {
+                binding: 8,
+                resource: wgpu::BindingResource::Sampler(&material_emissive_texture_sampler),
+            },
+            wgpu::Binding {
+                binding: 9,
+                resource: wgpu::BindingResource::TextureView(&material_normal_texture_view),
+            },
+            wgpu::Binding {
+                binding: 10,
+                resource: wgpu::BindingResource::Sampler(&material_normal_texture_sampler),
+            },
+        ],
+    });
+
+    let mut swap_chain_descriptor = wgpu::SwapChainDescriptor {
+        usage: wgpu::TextureUsage::OUTPUT_ATTACHMENT,
+        format: wgpu::TextureFormat::Bgra8UnormSrgb,
+        width: size.width,
+        height: size.height,
+        present_mode: wgpu::PresentMode::Vsync,
+    };
+
+    let (mut depth_texture, mut depth_texture_view) =
+        create_depth_texture(&device, &swap_chain_descriptor);
+
+    let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+        bind_group_layouts: &[
+            &uniform_bind_group_layout,
+            &light_bind_group_layout,
+            &material_bind_group_layout,
+            &ibl_bind_group_layout,
+        ],
+    });
+
+    let vertex_buffer_descriptor = wgpu::VertexBufferDescriptor {
+        stride: std::mem::size_of::<Vertex>() as wgpu::BufferAddress,
+        step_mode: wgpu::InputStepMode::Vertex,
+        attributes: &[
+            wgpu::VertexAttributeDescriptor {
+                offset: 0,
+                format: wgpu::VertexFormat::Float3,
+                shader_location: 0,
+            },
+            wgpu::VertexAttributeDescriptor {
+                offset: std::mem::size_of::<Vec3>() as wgpu::BufferAddress,
+                format: wgpu::VertexFormat::Float3,
+                shader_location: 1,
+            },
+            wgpu::VertexAttributeDescriptor {
+                offset: (std::mem::size_of::<Vec3>() * 2) as wgpu::BufferAddress,
+                format: wgpu::VertexFormat::Float2,
+                shader_location: 2,
+            },
+            wgpu::VertexAttributeDescriptor {
+                offset: (std::mem::size_of::<Vec3>() * 2 + std::mem::size_of::<Vec2>())
+                    as wgpu::BufferAddress,
+                format: wgpu::VertexFormat::Float4,
+                shader_location: 10,
+            },
+        ],
+    };
+
+    let render_pipeline_descriptor = wgpu::RenderPipelineDescriptor {
+        layout: &pipeline_layout,
+        vertex_stage: wgpu::ProgrammableStageDescriptor {
+            module: &vs_module,
+            entry_point: "main",
+        },
+        fragment_stage: Some(wgpu::ProgrammableStageDescriptor {
+            module: &fs_module,
+            entry_point: "main",
+        }),
+        rasterization_state: Some(wgpu::RasterizationStateDescriptor {
+            front_face: wgpu::FrontFace::Ccw,
+            cull_mode: wgpu::CullMode::Back,
+            depth_bias: 0,
+            depth_bias_slope_scale: 0.0,
+            depth_bias_clamp: 0.0,
+        }),
+        primitive_topology: wgpu::PrimitiveTopology::TriangleList,
+        color_states: &[wgpu::ColorStateDescriptor {
+            format: swap_chain_descriptor.format,
+            alpha_blend: wgpu::BlendDescriptor::REPLACE,
+            color_blend: wgpu::BlendDescriptor::REPLACE,
+            write_mask: wgpu::ColorWrite::ALL,
+        }],
+        depth_stencil_state: Some(wgpu::DepthStencilStateDescriptor {
+            format: DEPTH_FORMAT,
+            depth_write_enabled: true,
+            depth_compare: wgpu::CompareFunction::Less,
+            stencil_front: wgpu::StencilStateFaceDescriptor::IGNORE,
+            stencil_back: wgpu::StencilStateFaceDescriptor::IGNORE,
+            stencil_read_mask: 0,
+            stencil_write_mask: 0,
+        }),
+        index_format: wgpu::IndexFormat::Uint32,
+        vertex_buffers: &[
+            vertex_buffer_descriptor.clone(),
+            wgpu::VertexBufferDescriptor {
+                stride: std::mem::size_of::<PbrInstance>() as wgpu::BufferAddress,
+                step_mode: wgpu::InputStepMode::Instance,
+                attributes: &[
+                    wgpu::VertexAttributeDescriptor {
+                        offset: 0,
+                        format: wgpu::VertexFormat::Float4,
+                        shader_location: 3,
+                    },
+                    wgpu::VertexAttributeDescriptor {
+                        offset: std::mem::size_of::<Vec4>() as wgpu::BufferAddress,
+                        format: wgpu::VertexFormat::Float4,
+                        shader_location: 4,
+                    },
+                    wgpu::VertexAttributeDescriptor {
+                        offset: std::mem::size_of::<Vec4>() as wgpu::BufferAddress * 2,
+                        format: wgpu::VertexFormat::Float4,
+                        shader_location: 5,
+                    },
+                    wgpu::VertexAttributeDescriptor {
+                        offset: std::mem::size_of::<Vec4>() as wgpu::BufferAddress * 3,
+                        format: wgpu::VertexFormat::Float4,
+                        shader_location: 6,
+                    },
+                    wgpu::VertexAttributeDescriptor {
+                        offset: std::mem::size_of::<Mat4>() as wgpu::BufferAddress,
+                        format: wgpu::VertexFormat::Float3,
+                        shader_location: 7,
+                    },
+                    wgpu::VertexAttributeDescriptor {
+                        offset: (std::mem::size_of::<Mat4>() + std::mem::size_of::<Vec3>())
+                            as wgpu::BufferAddress,
+                        format: wgpu::VertexFormat::Float3,
+                        shader_location: 8,
+                    },
+                    wgpu::VertexAttributeDescriptor {
+                        offset: (std::mem::size_of::<Mat4>() + std::mem::size_of::<Vec3>() * 2)
+                            as wgpu::BufferAddress,
+                        format: wgpu::VertexFormat::Float3,
+                        shader_location: 9,
+                    },
+                    wgpu::VertexAttributeDescriptor {
+                        offset: std::mem::size_of::<Instance>() as wgpu::BufferAddress,
+                        format: wgpu::VertexFormat::Float2,
+                        shader_location: 11,
+                    },
+                ],
+            },
+        ],
+        sample_count: 1,
+        sample_mask: !0,
+        alpha_to_coverage_enabled: false,
+    };
+    let pipeline = device.create_render_pipeline(&render_pipeline_descriptor);
+
+    let light_source_pipeline_layout =
+        device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            bind_group_layouts: &[&uniform_bind_group_layout],
+        });
+
+    let mut light_source_pipeline_descriptor = render_pipeline_descriptor.clone();
+    light_source_pipeline_descriptor.layout = &light_source_pipeline_layout;
+    light_source_pipeline_descriptor.vertex_stage = wgpu::ProgrammableStageDescriptor {
+        module: &light_source_vs_module,
+        entry_point: "main",
+    };
+    light_source_pipeline_descriptor.fragment_stage = Some(wgpu::ProgrammableStageDescriptor {
+        module: &light_source_fs_module,
+        entry_point: "main",
+    });
+    // The light sources are plain instances without any material factors.
+    let light_source_vertex_buffers = [
+        vertex_buffer_descriptor,
+        wgpu::VertexBufferDescriptor {
+            stride: std::mem::size_of::<Instance>() as wgpu::BufferAddress,
+            step_mode: wgpu::InputStepMode::Instance,
+            attributes: &[
+                wgpu::VertexAttributeDescriptor {
+                    offset: 0,
+                    format: wgpu::VertexFormat::Float4,
+                    shader_location: 3,
+                },
+                wgpu::VertexAttributeDescriptor {
+                    offset: std::mem::size_of::<Vec4>() as wgpu::BufferAddress,
+                    format: wgpu::VertexFormat::Float4,
+                    shader_location: 4,
+                },
+                wgpu::VertexAttributeDescriptor {
+                    offset: std::mem::size_of::<Vec4>() as wgpu::BufferAddress * 2,
+                    format: wgpu::VertexFormat::Float4,
+                    shader_location: 5,
+                },
+                wgpu::VertexAttributeDescriptor {
+                    offset: std::mem::size_of::<Vec4>() as wgpu::BufferAddress * 3,
+                    format: wgpu::VertexFormat::Float4,
+                    shader_location: 6,
+                },
+            ],
+        },
+    ];
+    light_source_pipeline_descriptor.vertex_buffers = &light_source_vertex_buffers;
+    let light_source_pipeline = device.create_render_pipeline(&light_source_pipeline_descriptor);
+
+    let skybox_pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+        bind_group_layouts: &[
+            &skybox_uniform_bind_group_layout,
+            &skybox_texture_bind_group_layout,
+        ],
+    });
+    let skybox_pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+        layout: &skybox_pipeline_layout,
+        vertex_stage: wgpu::ProgrammableStageDescriptor {
+            module: &skybox_vs_module,
+            entry_point: "main",
+        },
+        fragment_stage: Some(wgpu::ProgrammableStageDescriptor {
+            module: &skybox_fs_module,
+            entry_point: "main",
+        }),
+        rasterization_state: Some(wgpu::RasterizationStateDescriptor {
+            front_face: wgpu::FrontFace::Ccw,
+            cull_mode: wgpu::CullMode::None,
+            depth_bias: 0,
+            depth_bias_slope_scale: 0.0,
+            depth_bias_clamp: 0.0,
+        }),
+        primitive_topology: wgpu::PrimitiveTopology::TriangleList,
+        color_states: &[wgpu::ColorStateDescriptor {
+            format: swap_chain_descriptor.format,
+            alpha_blend: wgpu::BlendDescriptor::REPLACE,
+            color_blend: wgpu::BlendDescriptor::REPLACE,
+            write_mask: wgpu::ColorWrite::ALL,
+        }],
+        // The skybox lies exactly on the far plane the depth buffer is cleared to, so it has to pass for equal depths.
+        depth_stencil_state: Some(wgpu::DepthStencilStateDescriptor {
+            format: DEPTH_FORMAT,
+            depth_write_enabled: false,
+            depth_compare: wgpu::CompareFunction::LessEqual,
+            stencil_front: wgpu::StencilStateFaceDescriptor::IGNORE,
+            stencil_back: wgpu::StencilStateFaceDescriptor::IGNORE,
+            stencil_read_mask: 0,
+            stencil_write_mask: 0,
+        }),
+        index_format: wgpu::IndexFormat::Uint32,
+        vertex_buffers: &[],
+        sample_count: 1,
+        sample_mask: !0,
+        alpha_to_coverage_enabled: false,
+    });
+
+    let mut swap_chain = device.create_swap_chain(&surface, &swap_chain_descriptor);
+
+    queue.submit(&[init_encoder.finish()]);
+
+    let mut time = Time::new();
+
+    event_loop.run(move |event, _, control_flow| {
+        input_handler.send_event(&event, &mut event_channel);
+
+        camera.on_event(&event_channel);
+
+        match event {
+            Event::WindowEvent {
+                event: WindowEvent::CloseRequested,
+                ..
+            } => *control_flow = ControlFlow::Exit,
+            Event::WindowEvent {
+                event: WindowEvent::Resized(new_size),
+                ..
+            } => {
+                size = new_size;
+                swap_chain_descriptor.width = size.width;
+                swap_chain_descriptor.height = size.height;
+                swap_chain = device.create_swap_chain(&surface, &swap_chain_descriptor);
+                let dt = create_depth_texture(&device, &swap_chain_descriptor);
+                depth_texture = dt.0;
+                depth_texture_view = dt.1;
+                *control_flow = ControlFlow::Poll;
+            }
+            Event::WindowEvent {
+                event:
+                    WindowEvent::MouseInput {
+                        button: winit::event::MouseButton::Left,
+                        state: winit::event::ElementState::Pressed,
+                        ..
+                    },
+                ..
+            } => {
+                camera.is_controlled = true;
+                window.set_cursor_grab(true).ok();
+                window.set_cursor_visible(false);
+                *control_flow = ControlFlow::Poll;
+            }
+            Event::WindowEvent {
+                event:
+                    WindowEvent::KeyboardInput {
+                        input:
+                            KeyboardInput {
+                                virtual_keycode: Some(VirtualKeyCode::Escape),
+                                state: winit::event::ElementState::Pressed,
+                                ..
+                            },
+                        ..
+                    },
+                ..
+            } => {
+                camera.is_controlled = false;
+                window.set_cursor_grab(false).ok();
+                window.set_cursor_visible(true);
+                *control_flow = ControlFlow::Poll;
+            }
+            Event::WindowEvent {
+                event:
+                    WindowEvent::KeyboardInput {
+                        input:
+                            KeyboardInput {
+                                virtual_keycode: Some(VirtualKeyCode::I),
+                                state: winit::event::ElementState::Pressed,
+                                ..
+                            },
+                        ..
+                    },
+                ..
+            } => {
+                image_based_lighting = !image_based_lighting;
+                *control_flow = ControlFlow::Poll;
+            }
+            Event::WindowEvent {
+                event:
+                    WindowEvent::KeyboardInput {
+                        input:
+                            KeyboardInput {
+                                virtual_keycode: Some(VirtualKeyCode::B),
+                                state: winit::event::ElementState::Pressed,
+                                ..
+                            },
+                        ..
+                    },
+                ..
+            } => {
+                skybox_source = skybox_source.next();
+                *control_flow = ControlFlow::Poll;
+            }
+            Event::MainEventsCleared => {
+                window.request_redraw();
+                *control_flow = ControlFlow::Poll;
+            }
+            Event::RedrawRequested(_) => {
+                input_handler.send_frame_begin();
+                camera.update(&input_handler);
+
+                time.update();
+                light_manager.animate(time.elapsed());
+
+                let frame = swap_chain.get_next_texture();
+                let mut encoder =
+                    device.create_command_encoder(&wgpu::CommandEncoderDescriptor { todo: 0 });
+                {
+                    let uniforms = Uniforms {
+                        view_projection: camera.get_view_projection_matrix(
+                            size.width as f32,
+                            size.height as f32,
+                            0.1,
+                            100.0,
+                        ),
+                        image_based_lighting: image_based_lighting as u32,
+                    };
+                    copy_to_buffer(&device, &mut encoder, &[uniforms], &uniform_buffer);
+                    copy_to_buffer(
+                        &device,
+                        &mut encoder,
+                        &[skybox_uniforms(
+                            &camera,
+                            size.width as f32,
+                            size.height as f32,
+                            skybox_source,
+                        )],
+                        &skybox_uniform_buffer,
+                    );
+                    light_manager.update(&device, &mut encoder, &light_bind_group_layout, &camera);
+
+                    let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                        color_attachments: &[wgpu::RenderPassColorAttachmentDescriptor {
+                            attachment: &frame.view,
+                            resolve_target: None,
+                            load_op: wgpu::LoadOp::Clear,
+                            store_op: wgpu::StoreOp::Store,
+                            clear_color: wgpu::Color {
+                                r: 0.1,
+                                g: 0.1,
+                                b: 0.1,
+                                a: 1.0,
+                            },
+                        }],
+                        depth_stencil_attachment: Some(
+                            wgpu::RenderPassDepthStencilAttachmentDescriptor {
+                                attachment: &depth_texture_view,
+                                depth_load_op: wgpu::LoadOp::Clear,
+                                depth_store_op: wgpu::StoreOp::Store,
+                                clear_depth: 1.0,
+                                stencil_load_op: wgpu::LoadOp::Clear,
+                                stencil_store_op: wgpu::StoreOp::Store,
+                                clear_stencil: 0,
+                            },
+                        ),
+                    });
+                    render_pass.set_pipeline(&pipeline);
+                    render_pass.set_bind_group(0, &uniform_bind_group, &[]);
+                    render_pass.set_bind_group(1, light_manager.bind_group(), &[]);
+                    render_pass.set_bind_group(2, &material_bind_group, &[]);
+                    render_pass.set_bind_group(3, &ibl_bind_group, &[]);
+                    render_pass.set_vertex_buffers(
+                        0,
+                        &[(&vertex_buffer, 0), (&sphere_instance_buffer, 0)],
+                    );
+                    render_pass.set_index_buffer(&index_buffer, 0);
+                    render_pass.draw_indexed(0..index_count, 0, 0..spheres.len() as u32);
+
+                    render_pass.set_pipeline(&light_source_pipeline);
+                    render_pass.set_bind_group(0, &uniform_bind_group, &[]);
+                    let light_source_instance_buffer = light_manager.light_source_instance_buffer();
+                    render_pass.set_vertex_buffers(
+                        0,
+                        &[
+                            (&vertex_buffer, 0),
+                            (light_source_instance_buffer.buffer(), 0),
+                        ],
+                    );
+                    render_pass.set_index_buffer(&index_buffer, 0);
+                    render_pass.draw_indexed(
+                        0..index_count,
+                        0,
+                        0..light_source_instance_buffer.len() as u32,
+                    );
+
+                    // Drawn last, so the depth test skips every pixel already covered by something else.
+                    render_pass.set_pipeline(&skybox_pipeline);
+                    render_pass.set_bind_group(0, &skybox_uniform_bind_group, &[]);
+                    render_pass.set_bind_group(1, &skybox_texture_bind_group, &[]);
+                    render_pass.draw(0..3, 0..1);
+                }
+
+                queue.submit(&[encoder.finish()]);
+
+                *control_flow = ControlFlow::Poll;
+            }
+            _ => {}
+        }
+    })
+}
+
+impl SkyboxSource {
+    fn next(self) -> Self {
+        match self {
+            SkyboxSource::Environment => SkyboxSource::Irradiance,
+            SkyboxSource::Irradiance => SkyboxSource::Prefiltered(0),
+            SkyboxSource::Prefiltered(level) if level + 1 < PREFILTERED_MIP_LEVELS => {
+                SkyboxSource::Prefiltered(level + 1)
+            }
+            SkyboxSource::Prefiltered(_) => SkyboxSource::Environment,
+        }
+    }
+}
+
+fn skybox_uniforms(
+    camera: &Camera,
+    width: f32,
+    height: f32,
+    source: SkyboxSource,
+) -> SkyboxUniforms {
+    // Must match the `SOURCE_*` constants in `skybox.frag`.
+    let (source, lod) = match source {
+        SkyboxSource::Environment => (0, 0.0),
+        SkyboxSource::Irradiance => (1, 0.0),
+        SkyboxSource::Prefiltered(level) => (2, level as f32),
+    };
+    SkyboxUniforms {
+        view: camera.get_view_matrix(),
+        projection: camera.get_projection_matrix(width, height, 0.1, 100.0),
+        source,
+        lod,
+    }
+}
+
+/// Lays the spheres out in a grid around the origin, facing the camera.
+fn create_sphere_grid() -> Vec<PbrInstance> {
+    let mut spheres = Vec::with_capacity(SPHERE_ROWS * SPHERE_COLUMNS);
+    for row in 0..SPHERE_ROWS {
+        let metallic = row as f32 / (SPHERE_ROWS - 1) as f32;
+        for column in 0..SPHERE_COLUMNS {
+            // Perfectly smooth surfaces reflect the point lights as infinitely small highlights, which the GGX
+            // distribution can't represent.
+            let roughness = (column as f32 / (SPHERE_COLUMNS - 1) as f32).max(0.05);
+            let translation = Vec3::new(
+                (column as f32 - (SPHERE_COLUMNS - 1) as f32 / 2.0) * SPHERE_SPACING,
+                (row as f32 - (SPHERE_ROWS - 1) as f32 / 2.0) * SPHERE_SPACING,
+                0.0,
+            );
+            spheres.push(PbrInstance::new(
+                Mat4::from_translation(translation),
+                metallic,
+                roughness,
+            ));
+        }
+    }
+    spheres
+}
+
+/// Loads one of the material's textures, or a 1x1 texture of `fallback` if it has none.
+fn load_material_texture(
+    device: &wgpu::Device,
+    encoder: &mut wgpu::CommandEncoder,
+    scene: &Scene,
+    path: Option<&PathBuf>,
+    fallback: [u8; 4],
+    format: wgpu::TextureFormat,
+) -> (wgpu::Texture, wgpu::TextureView, wgpu::Sampler) {
+    let image = match path {
+        Some(path) => image::open(scene.resolve_path(path)).unwrap().to_rgba(),
+        None => image::RgbaImage::from_pixel(1, 1, image::Rgba(fallback)),
+    };
+    let (width, height) = image.dimensions();
+    create_sampled_texture2d(device, encoder, &image.to_vec(), width, height, format)
+}
+
+fn copy_to_buffer<T: 'static + Copy>(
+    device: &wgpu::Device,
+    encoder: &mut wgpu::CommandEncoder,
+    data: &[T],
+    buffer: &wgpu::Buffer,
+) {
+    let staging_buffer = device
+        .create_buffer_mapped(data.len(), wgpu::BufferUsage::COPY_SRC)
+        .fill_from_slice(data);
+    encoder.copy_buffer_to_buffer(
+        &staging_buffer,
+        0,
+        buffer,
+        0,
+        (std::mem::size_of::<T>() * data.len()) as wgpu::BufferAddress,
+    );
+}
+
+pub fn prepare_shader(
+    compiler: &mut Compiler,
+    source_text: &str,
+    shader_kind: ShaderKind,
+    input_file_name: &str,
+    device: &wgpu::Device,
+) -> wgpu::ShaderModule {
+    let artifact = compiler
+        .compile_into_spirv(source_text, shader_kind, input_file_name, "main", None)
+        .unwrap();
+    device.create_shader_module(artifact.as_binary())
+}
+
+pub fn create_depth_texture(
+    device: &wgpu::Device,
+    swap_chain_desc: &wgpu::SwapChainDescriptor,
+) -> (wgpu::Texture, wgpu::TextureView) {
+    let desc = wgpu::TextureDescriptor {
+        format: DEPTH_FORMAT,
+        usage: wgpu::TextureUsage::OUTPUT_ATTACHMENT,
+        ..swap_chain_desc.to_texture_desc()
+    };
+    let texture = device.create_texture(&desc);
+    let view = texture.create_default_view();
+    (texture, view)
+}
+
+/// Uploads an RGBA texture. Color textures should use an sRGB `format`, so that they're decoded into linear space
+/// when sampled, while textures holding other data like specular intensities or normals have to use a linear one.
+pub fn create_sampled_texture2d(
+    device: &wgpu::Device,
+    encoder: &mut wgpu::CommandEncoder,
+    raw_texture: &[u8],
+    width: u32,
+    height: u32,
+    format: wgpu::TextureFormat,
+) -> (wgpu::Texture, wgpu::TextureView, wgpu::Sampler) {
+    let texture_extent = wgpu::Extent3d {
+        width,
+        height,
+        depth: 1,
+    };
+
+    let texture_buffer = device
+        .create_buffer_mapped(raw_texture.len(), wgpu::BufferUsage::COPY_SRC)
+        .fill_from_slice(&raw_texture);
+    let texture = device.create_texture(&wgpu::TextureDescriptor {
+        size: texture_extent,
+        array_layer_count: 1,
+        mip_level_count: 0,
+        sample_count: 1,
+        dimension: wgpu::TextureDimension::D2,
+        format,
+        usage: wgpu::TextureUsage::SAMPLED | wgpu::TextureUsage::COPY_DST,
+    });
+
+    encoder.copy_buffer_to_texture(
+        wgpu::BufferCopyView {
+            buffer: &texture_buffer,
+            offset: 0,
+            row_pitch: 4 * width,
+            image_height: height,
+        },
+        wgpu::TextureCopyView {
+            texture: &texture,
+            mip_level: 0,
+            array_layer: 0,
+            origin: wgpu::Origin3d {
+                x: 0.0,
+                y: 0.0,
+                z: 0.0,
+            },
+        },
+        texture_extent,
+    );
+
+    let texture_view = texture.create_default_view();
+    let texture_sampler = device.create_sampler(&wgpu::SamplerDescriptor {
+        address_mode_u: wgpu::AddressMode::Repeat,
+        address_mode_v: wgpu::AddressMode::Repeat,
+        address_mode_w: wgpu::AddressMode::Repeat,
+        mag_filter: wgpu::FilterMode::Linear,
+        min_filter: wgpu::FilterMode::Linear,
+        mipmap_filter: wgpu::FilterMode::Nearest,
+        lod_min_clamp: 0.0,
+        lod_max_clamp: 0.0,
+        compare_function: wgpu::CompareFunction::Never,
+    });
+
+    (texture, texture_view, texture_sampler)
+}
@@ -0,0 +1,31 @@
+#version 450
+
+layout(location = 0) in vec3 translation;
+layout(location = 1) in vec3 normal;
+layout(location = 2) in vec2 uv;
+layout(location = 10) in vec4 tangent;
+
+layout(location = 3) in mat4 model;
+layout(location = 7) in mat3 normal_matrix;
+layout(location = 11) in vec2 metallic_roughness;
+
+layout(location = 0) out vec3 frag_translation;
+layout(location = 1) out vec3 frag_normal;
+layout(location = 2) out vec2 frag_uv;
+layout(location = 3) out vec4 frag_tangent;
+layout(location = 4) out vec2 frag_metallic_roughness;
+
+layout(set = 0, binding = 0) uniform Uniforms {
+    mat4 view_projection;
+};
+
+void main()
+{
+    gl_Position = view_projection * model * vec4(translation, 1.0);
+    frag_translation = vec3(model * vec4(translation, 1.0));
+    frag_normal = normal_matrix * normal;
+    frag_uv = uv;
+    // Tangents lie in the surface, so they're transformed like any other direction rather than with the normal matrix.
+    frag_tangent = vec4(mat3(model) * tangent.xyz, tangent.w);
+    frag_metallic_roughness = metallic_roughness;
+}
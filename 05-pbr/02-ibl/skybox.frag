@@ -0,0 +1,39 @@
+#version 450
+
+// Must match `skybox_uniforms` in `main.rs`.
+#define SOURCE_ENVIRONMENT 0u
+#define SOURCE_IRRADIANCE 1u
+#define SOURCE_PREFILTERED 2u
+
+layout(location = 0) in vec3 direction;
+
+layout(location = 0) out vec4 fragment_color;
+
+layout(set = 0, binding = 0) uniform Uniforms {
+    mat4 view;
+    mat4 projection;
+    uint source;
+    float lod;
+};
+
+layout(set = 1, binding = 0) uniform textureCube environment_map;
+layout(set = 1, binding = 1) uniform textureCube irradiance_map;
+layout(set = 1, binding = 2) uniform textureCube prefiltered_map;
+layout(set = 1, binding = 3) uniform sampler ibl_sampler;
+
+void main()
+{
+    vec3 color;
+    if (source == SOURCE_IRRADIANCE) {
+        color = texture(samplerCube(irradiance_map, ibl_sampler), direction).rgb;
+    } else if (source == SOURCE_PREFILTERED) {
+        color = textureLod(samplerCube(prefiltered_map, ibl_sampler), direction, lod).rgb;
+    } else {
+        color = texture(samplerCube(environment_map, ibl_sampler), direction).rgb;
+    }
+
+    // Tone mapped just like the spheres.
+    color = color / (color + vec3(1.0));
+
+    fragment_color = vec4(color, 1.0);
+}
@@ -0,0 +1,23 @@
+#version 450
+
+layout(location = 0) out vec3 frag_direction;
+
+layout(set = 0, binding = 0) uniform Uniforms {
+    mat4 view;
+    mat4 projection;
+    uint source;
+    float lod;
+};
+
+void main()
+{
+    // A single triangle covering the whole screen, without any vertex buffers. It lies on the far plane, so the depth
+    // test only lets it through where nothing else has been drawn.
+    vec2 translation = vec2(float((gl_VertexIndex << 1) & 2), float(gl_VertexIndex & 2)) * 2.0 - 1.0;
+    gl_Position = vec4(translation, 1.0, 1.0);
+
+    // Unprojecting the corner gives the direction towards it in view space, which is rotated back into world space.
+    // The inverse of a rotation is its transpose, and the view matrix's translation doesn't apply to directions.
+    vec4 view_direction = inverse(projection) * vec4(translation, 1.0, 1.0);
+    frag_direction = transpose(mat3(view)) * (view_direction.xyz / view_direction.w);
+}
@@ -0,0 +1,192 @@
+#version 450
+
+#define SPECULAR_MODEL_PHONG 0
+#define SPECULAR_MODEL_BLINN_PHONG 1
+
+layout(location = 0) in vec3 translation;
+layout(location = 1) in vec3 normal;
+layout(location = 2) in vec2 uv;
+
+layout(location = 0) out vec4 fragment_color;
+
+struct DirectionalLight {
+    vec3 direction;
+
+    vec3 ambient;
+    vec3 diffuse;
+    vec3 specular;
+};
+
+struct PointLight {
+    vec3 translation;
+
+    vec3 ambient;
+    vec3 diffuse;
+    vec3 specular;
+
+    float constant;
+    float linear;
+    float quadratic;
+
+    int shadow_map_index;
+};
+
+struct SpotLight {
+    vec3 translation;
+    vec3 direction;
+    float cutoff;
+    float outer_cutoff;
+
+    float constant;
+    float linear;
+    float quadratic;
+
+    vec3 ambient;
+    vec3 diffuse;
+    vec3 specular;
+
+    int cookie_index;
+    mat4 cookie_space;
+};
+
+layout(set = 1, binding = 0) uniform Uniforms {
+    vec3 view_translation;
+    uint point_light_count;
+    uint spot_light_count;
+    uint specular_model;
+    DirectionalLight directional_light;
+};
+layout(set = 1, binding = 1, std430) readonly buffer PointLights {
+    PointLight point_lights[];
+};
+layout(set = 1, binding = 2, std430) readonly buffer SpotLights {
+    SpotLight spot_lights[];
+};
+layout(set = 1, binding = 3) uniform texture2DArray spot_light_cookies;
+layout(set = 1, binding = 4) uniform sampler spot_light_cookie_sampler;
+
+layout(set = 2, binding = 0) uniform Material {
+    vec3 ambient;
+    vec3 diffuse;
+    vec3 specular;
+    float shininess;
+} material;
+layout(set = 2, binding = 1) uniform texture2D material_diffuse_texture;
+layout(set = 2, binding = 2) uniform sampler material_diffuse_sampler;
+layout(set = 2, binding = 3) uniform texture2D material_specular_texture;
+layout(set = 2, binding = 4) uniform sampler material_specular_sampler;
+
+float calculate_specular(vec3 light_direction, vec3 normal, vec3 view_direction);
+vec3 calculate_directional_light(DirectionalLight light, vec3 normal, vec3 view_direction);
+vec3 calculate_point_light(PointLight light, vec3 normal, vec3 frag_translation, vec3 view_direction);
+vec3 calculate_spot_light_cookie(SpotLight light, vec3 frag_translation);
+vec3 calculate_spot_light(SpotLight light, vec3 normal, vec3 frag_translation, vec3 view_direction);
+
+void main()
+{
+    vec3 normalized_normal = normalize(normal);
+    vec3 view_direction = normalize(view_translation - translation);
+
+    vec3 result = calculate_directional_light(directional_light, normalized_normal, view_direction);
+    for (uint i = 0; i < point_light_count; i++) {
+        result += calculate_point_light(point_lights[i], normalized_normal, translation, view_direction);
+    }
+    for (uint i = 0; i < spot_light_count; i++) {
+        result += calculate_spot_light(spot_lights[i], normalized_normal, translation, view_direction);
+    }
+
+    fragment_color = vec4(result, 1.0);
+}
+
+float calculate_specular(vec3 light_direction, vec3 normal, vec3 view_direction)
+{
+    if (specular_model == SPECULAR_MODEL_BLINN_PHONG) {
+        vec3 halfway_direction = normalize(light_direction + view_direction);
+        return pow(max(dot(normal, halfway_direction), 0.0), material.shininess);
+    }
+
+    vec3 reflect_direction = reflect(-light_direction, normal);
+    return pow(max(dot(view_direction, reflect_direction), 0.0), material.shininess);
+}
+
+vec3 calculate_directional_light(DirectionalLight light, vec3 normal, vec3 view_direction)
+{
+    vec3 light_direction = normalize(-light.direction);
+
+    float diff = max(dot(normal, light_direction), 0.0);
+
+    float spec = calculate_specular(light_direction, normal, view_direction);
+
+    vec3 ambient = light.ambient * material.ambient * vec3(texture(sampler2D(material_diffuse_texture, material_diffuse_sampler), uv));
+    vec3 diffuse = light.diffuse * diff * material.diffuse * vec3(texture(sampler2D(material_diffuse_texture, material_diffuse_sampler), uv));
+    vec3 specular = light.specular * spec * material.specular * vec3(texture(sampler2D(material_specular_texture, material_specular_sampler), uv));
+    return (ambient + diffuse + specular);
+}
+
+vec3 calculate_point_light(PointLight light, vec3 normal, vec3 frag_translation, vec3 view_direction)
+{
+    vec3 light_direction = normalize(light.translation - frag_translation);
+
+    float diff = max(dot(normal, light_direction), 0.0);
+
+    float spec = calculate_specular(light_direction, normal, view_direction);
+
+    float distance = length(light.translation - frag_translation);
+    float attenuation = 1.0 / (light.constant + light.linear * distance + light.quadratic * (distance * distance));
+
+    vec3 ambient = light.ambient * material.ambient * vec3(texture(sampler2D(material_diffuse_texture, material_diffuse_sampler), uv));
+    vec3 diffuse = light.diffuse * material.diffuse * vec3(texture(sampler2D(material_diffuse_texture, material_diffuse_sampler), uv));
+    vec3 specular = light.specular * spec * material.specular * vec3(texture(sampler2D(material_specular_texture, material_specular_sampler), uv));
+    ambient *= attenuation;
+    diffuse *= attenuation;
+    specular *= attenuation;
+    return (ambient + diffuse + specular);
+}
+
+// The color of the spot light's cookie where it's projected onto the fragment, or white if the light has none. No light
+// makes it past the edges of the cookie.
+vec3 calculate_spot_light_cookie(SpotLight light, vec3 frag_translation)
+{
+    if (light.cookie_index < 0) {
+        return vec3(1.0);
+    }
+
+    vec4 cookie_clip_translation = light.cookie_space * vec4(frag_translation, 1.0);
+    // Behind the light, the projection would show the cookie again upside down.
+    if (cookie_clip_translation.w <= 0.0) {
+        return vec3(0.0);
+    }
+    vec2 cookie_ndc = cookie_clip_translation.xy / cookie_clip_translation.w;
+    // The cookie's top row is on the light's up side.
+    vec2 cookie_uv = vec2(cookie_ndc.x * 0.5 + 0.5, 0.5 - cookie_ndc.y * 0.5);
+    if (any(lessThan(cookie_uv, vec2(0.0))) || any(greaterThan(cookie_uv, vec2(1.0)))) {
+        return vec3(0.0);
+    }
+    vec3 cookie_coordinates = vec3(cookie_uv, light.cookie_index);
+    return texture(sampler2DArray(spot_light_cookies, spot_light_cookie_sampler), cookie_coordinates).rgb;
+}
+
+vec3 calculate_spot_light(SpotLight light, vec3 normal, vec3 frag_translation, vec3 view_direction)
+{
+    vec3 light_direction = normalize(light.translation - frag_translation);
+
+    float diff = max(dot(normal, light_direction), 0.0);
+
+    float spec = calculate_specular(light_direction, normal, view_direction);
+
+    float distance = length(light.translation - frag_translation);
+    float attenuation = 1.0 / (light.constant + light.linear * distance + light.quadratic * (distance * distance));
+
+    float theta = dot(light_direction, normalize(-light.direction));
+    float epsilon = light.cutoff - light.outer_cutoff;
+    float intensity = clamp((theta - light.outer_cutoff) / epsilon, 0.0, 1.0);
+    vec3 cookie = calculate_spot_light_cookie(light, frag_translation);
+
+    vec3 ambient = light.ambient * material.ambient * vec3(texture(sampler2D(material_diffuse_texture, material_diffuse_sampler), uv));
+    vec3 diffuse = light.diffuse * diff * material.diffuse * vec3(texture(sampler2D(material_diffuse_texture, material_diffuse_sampler), uv));
+    vec3 specular = light.specular * spec * material.specular * vec3(texture(sampler2D(material_specular_texture, material_specular_sampler), uv));
+    ambient *= attenuation * intensity * cookie;
+    diffuse *= attenuation * intensity * cookie;
+    specular *= attenuation * intensity * cookie;
+    return (ambient + diffuse + specular);
+}
@@ -0,0 +1,38 @@
+#version 450
+
+const int MAX_JOINTS = 64;
+
+layout(location = 0) in vec3 translation;
+layout(location = 1) in vec3 normal;
+layout(location = 2) in vec2 uv;
+layout(location = 3) in uvec4 joints;
+layout(location = 4) in vec4 weights;
+
+layout(location = 5) in mat4 model;
+layout(location = 9) in mat3 normal_matrix;
+
+layout(location = 0) out vec3 frag_translation;
+layout(location = 1) out vec3 frag_normal;
+layout(location = 2) out vec2 frag_uv;
+
+layout(set = 0, binding = 0) uniform Uniforms {
+    mat4 view_projection;
+};
+
+layout(set = 3, binding = 0) uniform Joints {
+    mat4 joint_matrices[MAX_JOINTS];
+};
+
+void main()
+{
+    mat4 skin = weights.x * joint_matrices[joints.x]
+              + weights.y * joint_matrices[joints.y]
+              + weights.z * joint_matrices[joints.z]
+              + weights.w * joint_matrices[joints.w];
+    vec4 skinned_translation = skin * vec4(translation, 1.0);
+
+    gl_Position = view_projection * model * skinned_translation;
+    frag_translation = vec3(model * skinned_translation);
+    frag_normal = normal_matrix * mat3(skin) * normal;
+    frag_uv = uv;
+}
@@ -0,0 +1,276 @@
+#version 450
+
+#define SPECULAR_MODEL_PHONG 0
+#define SPECULAR_MODEL_BLINN_PHONG 1
+
+// Must match `MAX_CASCADES` in `main.rs`.
+#define MAX_CASCADES 4
+// The fraction at the far end of each cascade over which it's blended with the next one.
+#define CASCADE_BLEND_FRACTION 0.1
+
+layout(location = 0) in vec3 translation;
+layout(location = 1) in vec3 normal;
+layout(location = 2) in vec2 uv;
+
+layout(location = 0) out vec4 fragment_color;
+
+struct DirectionalLight {
+    vec3 direction;
+
+    vec3 ambient;
+    vec3 diffuse;
+    vec3 specular;
+};
+
+struct PointLight {
+    vec3 translation;
+
+    vec3 ambient;
+    vec3 diffuse;
+    vec3 specular;
+
+    float constant;
+    float linear;
+    float quadratic;
+
+    int shadow_map_index;
+};
+
+struct SpotLight {
+    vec3 translation;
+    vec3 direction;
+    float cutoff;
+    float outer_cutoff;
+
+    float constant;
+    float linear;
+    float quadratic;
+
+    vec3 ambient;
+    vec3 diffuse;
+    vec3 specular;
+
+    int cookie_index;
+    mat4 cookie_space;
+};
+
+layout(set = 1, binding = 0) uniform Uniforms {
+    vec3 view_translation;
+    uint point_light_count;
+    uint spot_light_count;
+    uint specular_model;
+    DirectionalLight directional_light;
+};
+layout(set = 1, binding = 1, std430) readonly buffer PointLights {
+    PointLight point_lights[];
+};
+layout(set = 1, binding = 2, std430) readonly buffer SpotLights {
+    SpotLight spot_lights[];
+};
+layout(set = 1, binding = 3) uniform texture2DArray spot_light_cookies;
+layout(set = 1, binding = 4) uniform sampler spot_light_cookie_sampler;
+
+layout(set = 2, binding = 0) uniform Material {
+    vec3 ambient;
+    vec3 diffuse;
+    vec3 specular;
+    float shininess;
+} material;
+layout(set = 2, binding = 1) uniform texture2D material_diffuse_texture;
+layout(set = 2, binding = 2) uniform sampler material_diffuse_sampler;
+layout(set = 2, binding = 3) uniform texture2D material_specular_texture;
+layout(set = 2, binding = 4) uniform sampler material_specular_sampler;
+
+layout(set = 3, binding = 0) uniform CascadeUniforms {
+    mat4 cascade_light_spaces[MAX_CASCADES];
+    // The distance along the camera's forward direction at which each cascade ends.
+    vec4 cascade_splits;
+    vec3 camera_forward;
+    uint cascade_count;
+    bool show_cascades;
+};
+layout(set = 3, binding = 1) uniform texture2DArray cascaded_shadow_map;
+layout(set = 3, binding = 2) uniform sampler shadow_sampler;
+
+const vec3 cascade_colors[MAX_CASCADES] = vec3[](
+    vec3(1.0, 0.25, 0.25),
+    vec3(0.25, 1.0, 0.25),
+    vec3(0.25, 0.25, 1.0),
+    vec3(1.0, 1.0, 0.25)
+);
+
+float calculate_cascade_shadow(uint cascade, vec3 frag_translation);
+float calculate_directional_shadow(vec3 frag_translation, out uint cascade);
+float calculate_specular(vec3 light_direction, vec3 normal, vec3 view_direction);
+vec3 calculate_directional_light(DirectionalLight light, vec3 normal, vec3 view_direction, float shadow);
+vec3 calculate_point_light(PointLight light, vec3 normal, vec3 frag_translation, vec3 view_direction);
+vec3 calculate_spot_light_cookie(SpotLight light, vec3 frag_translation);
+vec3 calculate_spot_light(SpotLight light, vec3 normal, vec3 frag_translation, vec3 view_direction);
+
+void main()
+{
+    vec3 normalized_normal = normalize(normal);
+    vec3 view_direction = normalize(view_translation - translation);
+
+    uint cascade;
+    float directional_shadow = calculate_directional_shadow(translation, cascade);
+    vec3 result = calculate_directional_light(directional_light, normalized_normal, view_direction, directional_shadow);
+    for (uint i = 0; i < point_light_count; i++) {
+        result += calculate_point_light(point_lights[i], normalized_normal, translation, view_direction);
+    }
+    for (uint i = 0; i < spot_light_count; i++) {
+        result += calculate_spot_light(spot_lights[i], normalized_normal, translation, view_direction);
+    }
+
+    if (show_cascades && cascade < cascade_count) {
+        result *= cascade_colors[cascade];
+    }
+
+    fragment_color = vec4(result, 1.0);
+}
+
+// Returns how much of the fragment is in the shadow of one cascade, from 0.0 (fully lit) to 1.0 (fully shadowed).
+float calculate_cascade_shadow(uint cascade, vec3 frag_translation)
+{
+    vec4 light_clip_translation = cascade_light_spaces[cascade] * vec4(frag_translation, 1.0);
+    vec3 light_ndc = light_clip_translation.xyz / light_clip_translation.w;
+    // Both wgpu's normalized device coordinates and texture coordinates point Y down.
+    vec2 shadow_uv = light_ndc.xy * 0.5 + 0.5;
+
+    if (any(lessThan(shadow_uv, vec2(0.0))) || any(greaterThan(shadow_uv, vec2(1.0))) || light_ndc.z < 0.0 || light_ndc.z > 1.0) {
+        return 0.0;
+    }
+
+    // Percentage-closer filtering: average the depth comparisons of the surrounding texels.
+    vec2 texel_size = 1.0 / vec2(textureSize(sampler2DArrayShadow(cascaded_shadow_map, shadow_sampler), 0).xy);
+    float lit = 0.0;
+    for (int x = -1; x <= 1; x++) {
+        for (int y = -1; y <= 1; y++) {
+            vec2 offset = vec2(x, y) * texel_size;
+            lit += texture(sampler2DArrayShadow(cascaded_shadow_map, shadow_sampler), vec4(shadow_uv + offset, float(cascade), light_ndc.z));
+        }
+    }
+    return 1.0 - lit / 9.0;
+}
+
+// Picks the cascade covering the fragment and returns how much of the fragment is in shadow. Near the end of a
+// cascade the shadow is blended with the next cascade's, hiding the seam where the resolution changes.
+float calculate_directional_shadow(vec3 frag_translation, out uint cascade)
+{
+    float distance = dot(frag_translation - view_translation, normalize(camera_forward));
+
+    cascade = 0;
+    while (cascade < cascade_count && distance > cascade_splits[cascade]) {
+        cascade++;
+    }
+    if (cascade == cascade_count) {
+        return 0.0;
+    }
+
+    float shadow = calculate_cascade_shadow(cascade, frag_translation);
+
+    if (cascade + 1 < cascade_count) {
+        float cascade_start = cascade == 0 ? 0.0 : cascade_splits[cascade - 1];
+        float cascade_end = cascade_splits[cascade];
+        float blend_start = cascade_end - (cascade_end - cascade_start) * CASCADE_BLEND_FRACTION;
+        if (distance > blend_start) {
+            float next_shadow = calculate_cascade_shadow(cascade + 1, frag_translation);
+            shadow = mix(shadow, next_shadow, (distance - blend_start) / (cascade_end - blend_start));
+        }
+    }
+
+    return shadow;
+}
+
+float calculate_specular(vec3 light_direction, vec3 normal, vec3 view_direction)
+{
+    if (specular_model == SPECULAR_MODEL_BLINN_PHONG) {
+        vec3 halfway_direction = normalize(light_direction + view_direction);
+        return pow(max(dot(normal, halfway_direction), 0.0), material.shininess);
+    }
+
+    vec3 reflect_direction = reflect(-light_direction, normal);
+    return pow(max(dot(view_direction, reflect_direction), 0.0), material.shininess);
+}
+
+vec3 calculate_directional_light(DirectionalLight light, vec3 normal, vec3 view_direction, float shadow)
+{
+    vec3 light_direction = normalize(-light.direction);
+
+    float diff = max(dot(normal, light_direction), 0.0);
+
+    float spec = calculate_specular(light_direction, normal, view_direction);
+
+    vec3 ambient = light.ambient * material.ambient * vec3(texture(sampler2D(material_diffuse_texture, material_diffuse_sampler), uv));
+    vec3 diffuse = light.diffuse * diff * material.diffuse * vec3(texture(sampler2D(material_diffuse_texture, material_diffuse_sampler), uv));
+    vec3 specular = light.specular * spec * material.specular * vec3(texture(sampler2D(material_specular_texture, material_specular_sampler), uv));
+    return (ambient + (1.0 - shadow) * (diffuse + specular));
+}
+
+vec3 calculate_point_light(PointLight light, vec3 normal, vec3 frag_translation, vec3 view_direction)
+{
+    vec3 light_direction = normalize(light.translation - frag_translation);
+
+    float diff = max(dot(normal, light_direction), 0.0);
+
+    float spec = calculate_specular(light_direction, normal, view_direction);
+
+    float distance = length(light.translation - frag_translation);
+    float attenuation = 1.0 / (light.constant + light.linear * distance + light.quadratic * (distance * distance));
+
+    vec3 ambient = light.ambient * material.ambient * vec3(texture(sampler2D(material_diffuse_texture, material_diffuse_sampler), uv));
+    vec3 diffuse = light.diffuse * material.diffuse * vec3(texture(sampler2D(material_diffuse_texture, material_diffuse_sampler), uv));
+    vec3 specular = light.specular * spec * material.specular * vec3(texture(sampler2D(material_specular_texture, material_specular_sampler), uv));
+    ambient *= attenuation;
+    diffuse *= attenuation;
+    specular *= attenuation;
+    return (ambient + diffuse + specular);
+}
+
+// The color of the spot light's cookie where it's projected onto the fragment, or white if the light has none. No light
+// makes it past the edges of the cookie.
+vec3 calculate_spot_light_cookie(SpotLight light, vec3 frag_translation)
+{
+    if (light.cookie_index < 0) {
+        return vec3(1.0);
+    }
+
+    vec4 cookie_clip_translation = light.cookie_space * vec4(frag_translation, 1.0);
+    // Behind the light, the projection would show the cookie again upside down.
+    if (cookie_clip_translation.w <= 0.0) {
+        return vec3(0.0);
+    }
+    vec2 cookie_ndc = cookie_clip_translation.xy / cookie_clip_translation.w;
+    // The cookie's top row is on the light's up side.
+    vec2 cookie_uv = vec2(cookie_ndc.x * 0.5 + 0.5, 0.5 - cookie_ndc.y * 0.5);
+    if (any(lessThan(cookie_uv, vec2(0.0))) || any(greaterThan(cookie_uv, vec2(1.0)))) {
+        return vec3(0.0);
+    }
+    vec3 cookie_coordinates = vec3(cookie_uv, light.cookie_index);
+    return texture(sampler2DArray(spot_light_cookies, spot_light_cookie_sampler), cookie_coordinates).rgb;
+}
+
+vec3 calculate_spot_light(SpotLight light, vec3 normal, vec3 frag_translation, vec3 view_direction)
+{
+    vec3 light_direction = normalize(light.translation - frag_translation);
+
+    float diff = max(dot(normal, light_direction), 0.0);
+
+    float spec = calculate_specular(light_direction, normal, view_direction);
+
+    float distance = length(light.translation - frag_translation);
+    float attenuation = 1.0 / (light.constant + light.linear * distance + light.quadratic * (distance * distance));
+
+    float theta = dot(light_direction, normalize(-light.direction));
+    float epsilon = light.cutoff - light.outer_cutoff;
+    float intensity = clamp((theta - light.outer_cutoff) / epsilon, 0.0, 1.0);
+    vec3 cookie = calculate_spot_light_cookie(light, frag_translation);
+
+    vec3 ambient = light.ambient * material.ambient * vec3(texture(sampler2D(material_diffuse_texture, material_diffuse_sampler), uv));
+    vec3 diffuse = light.diffuse * diff * material.diffuse * vec3(texture(sampler2D(material_diffuse_texture, material_diffuse_sampler), uv));
+    vec3 specular = light.specular * spec * material.specular * vec3(texture(sampler2D(material_specular_texture, material_specular_sampler), uv));
+    ambient *= attenuation * intensity * cookie;
+    diffuse *= attenuation * intensity * cookie;
+    specular *= attenuation * intensity * cookie;
+    return (ambient + diffuse + specular);
+}
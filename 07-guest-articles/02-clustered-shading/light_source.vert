@@ -0,0 +1,21 @@
+#version 450
+
+// Must match `LIGHT_SOURCE_SCALE` in `src/lighting.rs`.
+#define LIGHT_SOURCE_SCALE 0.2
+
+layout(location = 0) in vec3 translation;
+
+layout(location = 1) in vec3 light_translation;
+layout(location = 3) in vec3 diffuse;
+
+layout(location = 0) flat out vec3 color;
+
+layout(set = 0, binding = 0) uniform Uniforms {
+    mat4 view_projection;
+};
+
+void main()
+{
+    gl_Position = view_projection * vec4(light_translation + translation * LIGHT_SOURCE_SCALE, 1.0);
+    color = diffuse;
+}
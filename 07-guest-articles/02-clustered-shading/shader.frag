@@ -0,0 +1,152 @@
+#version 450
+
+// Must match `CLUSTER_COUNT_X`, `CLUSTER_COUNT_Y` and `CLUSTER_COUNT_Z` in `src/cluster.rs`.
+#define CLUSTER_COUNT_X 16u
+#define CLUSTER_COUNT_Y 9u
+#define CLUSTER_COUNT_Z 24u
+// The number of lights in a cluster shown as the hottest color of the heat map.
+#define HEAT_MAP_MAX_LIGHTS 32.0
+
+layout(location = 0) in vec3 translation;
+layout(location = 1) in vec3 normal;
+layout(location = 2) in vec2 uv;
+
+layout(location = 0) out vec4 fragment_color;
+
+struct DirectionalLight {
+    vec3 direction;
+
+    vec3 ambient;
+    vec3 diffuse;
+    vec3 specular;
+};
+
+// Must match `PointLight` in `main.rs`.
+struct PointLight {
+    vec3 translation;
+    float radius;
+    vec3 diffuse;
+    vec3 specular;
+
+    float constant;
+    float linear;
+    float quadratic;
+};
+
+layout(set = 1, binding = 0) uniform Uniforms {
+    vec3 view_translation;
+    DirectionalLight directional_light;
+    uint heat_map;
+};
+layout(set = 1, binding = 1, std430) readonly buffer PointLights {
+    PointLight point_lights[];
+};
+
+layout(set = 2, binding = 0) uniform Material {
+    float shininess;
+} material;
+layout(set = 2, binding = 1) uniform texture2D material_diffuse_texture;
+layout(set = 2, binding = 2) uniform sampler material_diffuse_sampler;
+layout(set = 2, binding = 3) uniform texture2D material_specular_texture;
+layout(set = 2, binding = 4) uniform sampler material_specular_sampler;
+
+// Must match `ClusterUniforms` in `src/cluster.rs`.
+layout(set = 3, binding = 0) uniform Clusters {
+    mat4 view;
+    vec2 projection_scale;
+    float slice_scale;
+    float slice_bias;
+};
+// The offset and number of every cluster's lights in `light_indices`.
+layout(set = 3, binding = 1, std430) readonly buffer ClusterList {
+    uvec2 clusters[];
+};
+layout(set = 3, binding = 2, std430) readonly buffer LightIndexList {
+    uint light_indices[];
+};
+
+uint find_cluster();
+vec3 heat_map_color(float heat);
+vec3 calculate_directional_light(DirectionalLight light, vec3 normal, vec3 view_direction);
+vec3 calculate_point_light(PointLight light, vec3 normal, vec3 frag_translation, vec3 view_direction);
+
+void main()
+{
+    uvec2 cluster = clusters[find_cluster()];
+    if (heat_map != 0u) {
+        fragment_color = vec4(heat_map_color(float(cluster.y) / HEAT_MAP_MAX_LIGHTS), 1.0);
+        return;
+    }
+
+    vec3 normalized_normal = normalize(normal);
+    vec3 view_direction = normalize(view_translation - translation);
+
+    vec3 result = calculate_directional_light(directional_light, normalized_normal, view_direction);
+    // Only the lights that can reach this cluster are considered, rather than every light in the scene.
+    for (uint i = cluster.x; i < cluster.x + cluster.y; i++) {
+        result += calculate_point_light(point_lights[light_indices[i]], normalized_normal, translation, view_direction);
+    }
+
+    fragment_color = vec4(result, 1.0);
+}
+
+// Finds the fragment's cluster the same way as `ClusterGrid::cluster_at` does. The tile follows from the projected
+// position, and the slice from the distance along the view direction.
+uint find_cluster()
+{
+    vec3 view_position = vec3(view * vec4(translation, 1.0));
+    float depth = -view_position.z;
+    vec2 ndc = clamp(view_position.xy * projection_scale / depth, -1.0, 1.0);
+    // Tiles are counted from the top left, just like `ClusterGrid::cluster_index` does.
+    uvec2 tile = min(
+        uvec2(vec2(ndc.x + 1.0, 1.0 - ndc.y) * 0.5 * vec2(CLUSTER_COUNT_X, CLUSTER_COUNT_Y)),
+        uvec2(CLUSTER_COUNT_X - 1u, CLUSTER_COUNT_Y - 1u)
+    );
+    uint slice = min(uint(max(log(depth) * slice_scale + slice_bias, 0.0)), CLUSTER_COUNT_Z - 1u);
+    return (slice * CLUSTER_COUNT_Y + tile.y) * CLUSTER_COUNT_X + tile.x;
+}
+
+// Goes from black through blue, green and yellow to red as the heat rises from 0 to 1.
+vec3 heat_map_color(float heat)
+{
+    if (heat <= 0.0) {
+        return vec3(0.0);
+    }
+    vec3 colors[5] = vec3[](vec3(0.0, 0.0, 0.5), vec3(0.0, 0.5, 1.0), vec3(0.0, 1.0, 0.0), vec3(1.0, 1.0, 0.0), vec3(1.0, 0.0, 0.0));
+    float position = clamp(heat, 0.0, 1.0) * 4.0;
+    uint index = min(uint(position), 3u);
+    return mix(colors[index], colors[index + 1], position - float(index));
+}
+
+vec3 calculate_directional_light(DirectionalLight light, vec3 normal, vec3 view_direction)
+{
+    vec3 light_direction = normalize(-light.direction);
+
+    float diff = max(dot(normal, light_direction), 0.0);
+
+    vec3 reflect_direction = reflect(-light_direction, normal);
+    float spec = pow(max(dot(view_direction, reflect_direction), 0.0), material.shininess);
+
+    vec3 diffuse_color = texture(sampler2D(material_diffuse_texture, material_diffuse_sampler), uv).rgb;
+    vec3 ambient = light.ambient * diffuse_color;
+    vec3 diffuse = light.diffuse * diff * diffuse_color;
+    vec3 specular = light.specular * spec * texture(sampler2D(material_specular_texture, material_specular_sampler), uv).rgb;
+    return ambient + diffuse + specular;
+}
+
+vec3 calculate_point_light(PointLight light, vec3 normal, vec3 frag_translation, vec3 view_direction)
+{
+    vec3 light_direction = normalize(light.translation - frag_translation);
+
+    float diff = max(dot(normal, light_direction), 0.0);
+
+    vec3 reflect_direction = reflect(-light_direction, normal);
+    float spec = pow(max(dot(view_direction, reflect_direction), 0.0), material.shininess);
+
+    float distance = length(light.translation - frag_translation);
+    float attenuation = 1.0 / (light.constant + light.linear * distance + light.quadratic * (distance * distance));
+
+    vec3 diffuse = light.diffuse * diff * texture(sampler2D(material_diffuse_texture, material_diffuse_sampler), uv).rgb;
+    vec3 specular = light.specular * spec * texture(sampler2D(material_specular_texture, material_specular_sampler), uv).rgb;
+    return (diffuse + specular) * attenuation;
+}
@@ -0,0 +1,622 @@
+//! Skeletal animation: skins and animation clips imported from glTF, and a CPU sampler that turns a clip and a point
+//! in time into the joint matrices used for skinning on the GPU.
+//!
+//! Rotations are kept as glTF stores them, as quaternions in a [`Vec4`] laid out as `(x, y, z, w)`.
+
+use std::{
+    collections::HashMap,
+    error::Error,
+    fmt,
+    ops::{Add, Mul},
+    path::Path,
+};
+
+use gltf::animation::{util::ReadOutputs, Interpolation as GltfInterpolation};
+use ultraviolet::{Mat4, Vec2, Vec3, Vec4};
+
+use crate::{mesh::Mesh, scene::vec3};
+
+/// The number of joint matrices the skinning shaders have room for.
+pub const MAX_JOINTS: usize = 64;
+
+/// A vertex influenced by up to four joints.
+#[repr(C)]
+#[derive(Clone, Copy, Debug)]
+pub struct SkinnedVertex {
+    pub translation: Vec3,
+    pub normal: Vec3,
+    pub uv: Vec2,
+    pub joints: [u32; 4],
+    pub weights: [f32; 4],
+}
+
+/// The joint matrices of a skeleton, laid out as the skinning shaders' uniform block.
+#[repr(C)]
+#[derive(Clone, Copy)]
+pub struct JointUniforms {
+    pub joint_matrices: [Mat4; MAX_JOINTS],
+}
+
+/// The local transform of a joint relative to its parent.
+#[derive(Clone, Copy, Debug)]
+pub struct JointTransform {
+    pub translation: Vec3,
+    pub rotation: Vec4,
+    pub scale: Vec3,
+}
+
+#[derive(Clone, Debug)]
+pub struct Joint {
+    pub name: Option<String>,
+    pub parent: Option<usize>,
+    /// Transforms from model space into the joint's space in the bind pose.
+    pub inverse_bind_matrix: Mat4,
+    pub rest_pose: JointTransform,
+}
+
+/// The joints of a skin, in the order the [`SkinnedVertex::joints`] indices refer to them.
+#[derive(Clone, Debug)]
+pub struct Skeleton {
+    pub joints: Vec<Joint>,
+    /// The transform of the nodes above the root joints that aren't joints themselves.
+    pub root_transform: Mat4,
+    /// The joint indices ordered so that every parent comes before its children.
+    evaluation_order: Vec<usize>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Interpolation {
+    Step,
+    Linear,
+    /// Hermite splines, with an in-tangent and an out-tangent stored around every keyframe value.
+    CubicSpline,
+}
+
+#[derive(Clone, Debug)]
+pub enum ChannelValues {
+    Translations(Vec<Vec3>),
+    Rotations(Vec<Vec4>),
+    Scales(Vec<Vec3>),
+}
+
+/// The keyframes animating one property of one joint.
+#[derive(Clone, Debug)]
+pub struct Channel {
+    pub joint: usize,
+    pub interpolation: Interpolation,
+    pub times: Vec<f32>,
+    pub values: ChannelValues,
+}
+
+#[derive(Clone, Debug)]
+pub struct AnimationClip {
+    pub name: Option<String>,
+    pub duration: f32,
+    pub channels: Vec<Channel>,
+}
+
+/// The first skinned mesh of a glTF file, together with its skeleton and every animation targeting it.
+pub struct SkinnedModel {
+    pub mesh: Mesh<SkinnedVertex>,
+    pub skeleton: Skeleton,
+    pub clips: Vec<AnimationClip>,
+}
+
+#[derive(Debug)]
+pub enum AnimationError {
+    Gltf(gltf::Error),
+    NoSkinnedMesh,
+    MissingAttribute(&'static str),
+    TooManyJoints(usize),
+}
+
+impl JointTransform {
+    pub fn identity() -> Self {
+        Self {
+            translation: Vec3::zero(),
+            rotation: Vec4::new(0.0, 0.0, 0.0, 1.0),
+            scale: Vec3::new(1.0, 1.0, 1.0),
+        }
+    }
+
+    /// The matrix applying the scale, then the rotation, then the translation.
+    pub fn matrix(&self) -> Mat4 {
+        let rotation = rotation_matrix(self.rotation);
+        Mat4 {
+            cols: [
+                rotation.cols[0] * self.scale.x,
+                rotation.cols[1] * self.scale.y,
+                rotation.cols[2] * self.scale.z,
+                Vec4::new(
+                    self.translation.x,
+                    self.translation.y,
+                    self.translation.z,
+                    1.0,
+                ),
+            ],
+        }
+    }
+}
+
+impl Skeleton {
+    pub fn new(joints: Vec<Joint>, root_transform: Mat4) -> Self {
+        let depth = |mut joint: usize| {
+            let mut depth = 0;
+            while let Some(parent) = joints[joint].parent {
+                joint = parent;
+                depth += 1;
+            }
+            depth
+        };
+        let mut evaluation_order: Vec<usize> = (0..joints.len()).collect();
+        evaluation_order.sort_by_key(|&joint| depth(joint));
+
+        Self {
+            joints,
+            root_transform,
+            evaluation_order,
+        }
+    }
+
+    pub fn rest_pose(&self) -> Vec<JointTransform> {
+        self.joints.iter().map(|joint| joint.rest_pose).collect()
+    }
+
+    /// Calculates the matrices that move each vertex from its bind pose into `pose`, one per joint.
+    ///
+    /// ```
+    /// # use ultraviolet::{Mat4, Vec3, Vec4};
+    /// #
+    /// # use learnopengl_but_its_wgpu::animation::{Joint, JointTransform, Skeleton};
+    /// #
+    /// let bind_pose = |y| JointTransform {
+    ///     translation: Vec3::new(0.0, y, 0.0),
+    ///     ..JointTransform::identity()
+    /// };
+    /// let skeleton = Skeleton::new(
+    ///     vec![
+    ///         Joint {
+    ///             name: None,
+    ///             parent: None,
+    ///             inverse_bind_matrix: Mat4::identity(),
+    ///             rest_pose: bind_pose(0.0),
+    ///         },
+    ///         Joint {
+    ///             name: None,
+    ///             parent: Some(0),
+    ///             inverse_bind_matrix: Mat4::from_translation(Vec3::new(0.0, -1.0, 0.0)),
+    ///             rest_pose: bind_pose(1.0),
+    ///         },
+    ///     ],
+    ///     Mat4::identity(),
+    /// );
+    ///
+    /// // The rest pose matches the bind pose, so nothing moves.
+    /// for matrix in skeleton.joint_matrices(&skeleton.rest_pose()) {
+    ///     for (column, identity_column) in matrix.cols.iter().zip(&Mat4::identity().cols) {
+    ///         assert!((*column - *identity_column).mag() < 1e-6);
+    ///     }
+    /// }
+    ///
+    /// // Bending the root by 90 degrees around Z swings the tip of the child joint from (0, 2) over to (-2, 0).
+    /// let half_sqrt_2 = std::f32::consts::FRAC_1_SQRT_2;
+    /// let mut pose = skeleton.rest_pose();
+    /// pose[0].rotation = Vec4::new(0.0, 0.0, half_sqrt_2, half_sqrt_2);
+    /// let tip = skeleton.joint_matrices(&pose)[1] * Vec4::new(0.0, 2.0, 0.0, 1.0);
+    /// assert!((tip - Vec4::new(-2.0, 0.0, 0.0, 1.0)).mag() < 1e-6);
+    /// ```
+    pub fn joint_matrices(&self, pose: &[JointTransform]) -> Vec<Mat4> {
+        let mut global_transforms = vec![Mat4::identity(); self.joints.len()];
+        for &joint in &self.evaluation_order {
+            let parent_transform = match self.joints[joint].parent {
+                Some(parent) => global_transforms[parent],
+                None => self.root_transform,
+            };
+            global_transforms[joint] = parent_transform * pose[joint].matrix();
+        }
+
+        global_transforms
+            .iter()
+            .zip(&self.joints)
+            .map(|(&global_transform, joint)| global_transform * joint.inverse_bind_matrix)
+            .collect()
+    }
+}
+
+impl AnimationClip {
+    /// Samples every channel of the clip at `time` seconds, starting from the rest pose of `skeleton`.
+    ///
+    /// The clip loops, so `time` may go past the end of it.
+    ///
+    /// ```
+    /// # use ultraviolet::{Mat4, Vec3};
+    /// #
+    /// # use learnopengl_but_its_wgpu::animation::{
+    /// #     AnimationClip, Channel, ChannelValues, Interpolation, Joint, JointTransform, Skeleton,
+    /// # };
+    /// #
+    /// let skeleton = Skeleton::new(
+    ///     vec![Joint {
+    ///         name: None,
+    ///         parent: None,
+    ///         inverse_bind_matrix: Mat4::identity(),
+    ///         rest_pose: JointTransform::identity(),
+    ///     }],
+    ///     Mat4::identity(),
+    /// );
+    /// let clip = AnimationClip {
+    ///     name: None,
+    ///     duration: 2.0,
+    ///     channels: vec![Channel {
+    ///         joint: 0,
+    ///         interpolation: Interpolation::Linear,
+    ///         times: vec![0.0, 2.0],
+    ///         values: ChannelValues::Translations(vec![Vec3::zero(), Vec3::new(4.0, 0.0, 0.0)]),
+    ///     }],
+    /// };
+    ///
+    /// assert!((clip.sample(&skeleton, 0.5)[0].translation - Vec3::new(1.0, 0.0, 0.0)).mag() < 1e-6);
+    /// assert!((clip.sample(&skeleton, 3.5)[0].translation - Vec3::new(3.0, 0.0, 0.0)).mag() < 1e-6);
+    /// ```
+    pub fn sample(&self, skeleton: &Skeleton, time: f32) -> Vec<JointTransform> {
+        let time = if self.duration > 0.0 {
+            time.rem_euclid(self.duration)
+        } else {
+            0.0
+        };
+
+        let mut pose = skeleton.rest_pose();
+        for channel in &self.channels {
+            let transform = &mut pose[channel.joint];
+            match &channel.values {
+                ChannelValues::Translations(values) => {
+                    transform.translation = channel.sample(values, time, lerp)
+                }
+                ChannelValues::Rotations(values) => {
+                    transform.rotation = channel.sample(values, time, slerp).normalized()
+                }
+                ChannelValues::Scales(values) => {
+                    transform.scale = channel.sample(values, time, lerp)
+                }
+            }
+        }
+
+        pose
+    }
+}
+
+impl Channel {
+    fn sample<T>(&self, values: &[T], time: f32, interpolate: impl Fn(T, T, f32) -> T) -> T
+    where
+        T: Copy + Add<Output = T> + Mul<f32, Output = T>,
+    {
+        // Cubic spline channels store an in-tangent, a value and an out-tangent per keyframe.
+        let value = |keyframe: usize| match self.interpolation {
+            Interpolation::CubicSpline => values[keyframe * 3 + 1],
+            _ => values[keyframe],
+        };
+
+        let next = self.times.iter().position(|&t| t > time);
+        let (previous, next) = match next {
+            Some(0) => return value(0),
+            Some(next) => (next - 1, next),
+            None => return value(self.times.len() - 1),
+        };
+
+        let delta = self.times[next] - self.times[previous];
+        let t = (time - self.times[previous]) / delta;
+        match self.interpolation {
+            Interpolation::Step => value(previous),
+            Interpolation::Linear => interpolate(value(previous), value(next), t),
+            Interpolation::CubicSpline => {
+                let out_tangent = values[previous * 3 + 2];
+                let in_tangent = values[next * 3];
+                let t2 = t * t;
+                let t3 = t2 * t;
+                value(previous) * (2.0 * t3 - 3.0 * t2 + 1.0)
+                    + out_tangent * (delta * (t3 - 2.0 * t2 + t))
+                    + value(next) * (-2.0 * t3 + 3.0 * t2)
+                    + in_tangent * (delta * (t3 - t2))
+            }
+        }
+    }
+}
+
+impl SkinnedModel {
+    /// Loads the first mesh instantiated with a skin from a glTF file, along with all of its animations.
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, AnimationError> {
+        let (document, buffers, _) = gltf::import(path)?;
+        let get_buffer = |buffer: gltf::Buffer| Some(&*buffers[buffer.index()]);
+
+        let (mesh_node, skin) = document
+            .nodes()
+            .find_map(|node| {
+                node.mesh()?;
+                let skin = node.skin()?;
+                Some((node, skin))
+            })
+            .ok_or(AnimationError::NoSkinnedMesh)?;
+
+        let joint_nodes: Vec<_> = skin.joints().collect();
+        if joint_nodes.len() > MAX_JOINTS {
+            return Err(AnimationError::TooManyJoints(joint_nodes.len()));
+        }
+        let joint_indices: HashMap<usize, usize> = joint_nodes
+            .iter()
+            .enumerate()
+            .map(|(joint, node)| (node.index(), joint))
+            .collect();
+
+        let mut node_parents = HashMap::new();
+        for node in document.nodes() {
+            for child in node.children() {
+                node_parents.insert(child.index(), node.index());
+            }
+        }
+
+        let inverse_bind_matrices: Vec<Mat4> =
+            match skin.reader(get_buffer).read_inverse_bind_matrices() {
+                Some(matrices) => matrices.map(mat4).collect(),
+                None => vec![Mat4::identity(); joint_nodes.len()],
+            };
+
+        let joints = joint_nodes
+            .iter()
+            .zip(inverse_bind_matrices)
+            .map(|(node, inverse_bind_matrix)| Joint {
+                name: node.name().map(str::to_owned),
+                parent: node_parents
+                    .get(&node.index())
+                    .and_then(|parent| joint_indices.get(parent))
+                    .copied(),
+                inverse_bind_matrix,
+                rest_pose: node_transform(node),
+            })
+            .collect::<Vec<_>>();
+
+        // Everything above the root joints moves the skeleton as a whole.
+        let mut root_transform = Mat4::identity();
+        let root_node = joint_nodes
+            .iter()
+            .zip(&joints)
+            .find(|(_, joint)| joint.parent.is_none())
+            .map(|(node, _)| node.index());
+        let mut ancestor = root_node.and_then(|node| node_parents.get(&node));
+        while let Some(&node) = ancestor {
+            let node_matrix = node_transform(&document.nodes().nth(node).unwrap()).matrix();
+            root_transform = node_matrix * root_transform;
+            ancestor = node_parents.get(&node);
+        }
+
+        let skeleton = Skeleton::new(joints, root_transform);
+
+        let mut mesh = Mesh {
+            vertices: Vec::new(),
+            indices: Vec::new(),
+        };
+        for primitive in mesh_node.mesh().unwrap().primitives() {
+            let reader = primitive.reader(get_buffer);
+            let base_index = mesh.vertices.len() as u32;
+
+            let translations = reader
+                .read_positions()
+                .ok_or(AnimationError::MissingAttribute("POSITION"))?;
+            let mut normals = reader
+                .read_normals()
+                .ok_or(AnimationError::MissingAttribute("NORMAL"))?;
+            let mut uvs = reader.read_tex_coords(0).map(|uvs| uvs.into_f32());
+            let mut joints = reader
+                .read_joints(0)
+                .ok_or(AnimationError::MissingAttribute("JOINTS_0"))?
+                .into_u16();
+            let mut weights = reader
+                .read_weights(0)
+                .ok_or(AnimationError::MissingAttribute("WEIGHTS_0"))?
+                .into_f32();
+
+            for translation in translations {
+                let joints = joints.next().unwrap_or_default();
+                mesh.vertices.push(SkinnedVertex {
+                    translation: vec3(translation),
+                    normal: vec3(normals.next().unwrap_or_default()),
+                    uv: vec2(uvs.as_mut().and_then(Iterator::next).unwrap_or_default()),
+                    joints: [
+                        joints[0].into(),
+                        joints[1].into(),
+                        joints[2].into(),
+                        joints[3].into(),
+                    ],
+                    weights: weights.next().unwrap_or_default(),
+                });
+            }
+
+            let vertex_count = mesh.vertices.len() as u32 - base_index;
+            match reader.read_indices() {
+                Some(indices) => mesh
+                    .indices
+                    .extend(indices.into_u32().map(|index| base_index + index)),
+                None => mesh.indices.extend(base_index..base_index + vertex_count),
+            }
+        }
+
+        let clips = document
+            .animations()
+            .map(|animation| {
+                let channels: Vec<Channel> = animation
+                    .channels()
+                    .filter_map(|channel| {
+                        let joint = *joint_indices.get(&channel.target().node().index())?;
+                        let reader = channel.reader(get_buffer);
+                        let times: Vec<f32> = reader.read_inputs()?.collect();
+                        let values = match reader.read_outputs()? {
+                            ReadOutputs::Translations(values) => {
+                                ChannelValues::Translations(values.map(vec3).collect())
+                            }
+                            ReadOutputs::Rotations(values) => {
+                                ChannelValues::Rotations(values.into_f32().map(vec4).collect())
+                            }
+                            ReadOutputs::Scales(values) => {
+                                ChannelValues::Scales(values.map(vec3).collect())
+                            }
+                            ReadOutputs::MorphTargetWeights(_) => return None,
+                        };
+
+                        Some(Channel {
+                            joint,
+                            interpolation: match channel.sampler().interpolation() {
+                                GltfInterpolation::Step => Interpolation::Step,
+                                GltfInterpolation::Linear => Interpolation::Linear,
+                                GltfInterpolation::CubicSpline => Interpolation::CubicSpline,
+                            },
+                            times,
+                            values,
+                        })
+                    })
+                    .collect();
+
+                AnimationClip {
+                    name: animation.name().map(str::to_owned),
+                    duration: channels
+                        .iter()
+                        .filter_map(|channel| channel.times.last().copied())
+                        .fold(0.0, f32::max),
+                    channels,
+                }
+            })
+            .collect();
+
+        Ok(Self {
+            mesh,
+            skeleton,
+            clips,
+        })
+    }
+}
+
+impl JointUniforms {
+    /// Packs `joint_matrices` into the uniform block, leaving the unused slots as the identity.
+    pub fn new(joint_matrices: &[Mat4]) -> Self {
+        let mut uniforms = Self {
+            joint_matrices: [Mat4::identity(); MAX_JOINTS],
+        };
+        uniforms.joint_matrices[..joint_matrices.len()].copy_from_slice(joint_matrices);
+        uniforms
+    }
+}
+
+impl From<gltf::Error> for AnimationError {
+    fn from(err: gltf::Error) -> Self {
+        AnimationError::Gltf(err)
+    }
+}
+
+impl fmt::Display for AnimationError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            AnimationError::Gltf(err) => write!(f, "could not load glTF file: {}", err),
+            AnimationError::NoSkinnedMesh => write!(f, "the glTF file has no skinned mesh"),
+            AnimationError::MissingAttribute(attribute) => {
+                write!(f, "the skinned mesh has no {} attribute", attribute)
+            }
+            AnimationError::TooManyJoints(count) => write!(
+                f,
+                "the skin has {} joints, but at most {} are supported",
+                count, MAX_JOINTS
+            ),
+        }
+    }
+}
+
+impl Error for AnimationError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            AnimationError::Gltf(err) => Some(err),
+            AnimationError::NoSkinnedMesh
+            | AnimationError::MissingAttribute(_)
+            | AnimationError::TooManyJoints(_) => None,
+        }
+    }
+}
+
+/// Spherically interpolates between two unit quaternions along the shorter arc.
+fn slerp(from: Vec4, to: Vec4, t: f32) -> Vec4 {
+    let mut cos_theta = from.dot(to);
+    let to = if cos_theta < 0.0 {
+        cos_theta = -cos_theta;
+        -to
+    } else {
+        to
+    };
+
+    // Nearly parallel quaternions make the sine below vanish, and a linear blend is indistinguishable anyway.
+    if cos_theta > 0.9995 {
+        return lerp(from, to, t).normalized();
+    }
+
+    let theta = cos_theta.acos();
+    let sin_theta = theta.sin();
+    from * (((1.0 - t) * theta).sin() / sin_theta) + to * ((t * theta).sin() / sin_theta)
+}
+
+/// Converts a unit quaternion into a rotation matrix.
+fn rotation_matrix(rotation: Vec4) -> Mat4 {
+    let Vec4 { x, y, z, w } = rotation;
+    Mat4 {
+        cols: [
+            Vec4::new(
+                1.0 - 2.0 * (y * y + z * z),
+                2.0 * (x * y + z * w),
+                2.0 * (x * z - y * w),
+                0.0,
+            ),
+            Vec4::new(
+                2.0 * (x * y - z * w),
+                1.0 - 2.0 * (x * x + z * z),
+                2.0 * (y * z + x * w),
+                0.0,
+            ),
+            Vec4::new(
+                2.0 * (x * z + y * w),
+                2.0 * (y * z - x * w),
+                1.0 - 2.0 * (x * x + y * y),
+                0.0,
+            ),
+            Vec4::new(0.0, 0.0, 0.0, 1.0),
+        ],
+    }
+}
+
+fn node_transform(node: &gltf::Node) -> JointTransform {
+    let (translation, rotation, scale) = node.transform().decomposed();
+    JointTransform {
+        translation: vec3(translation),
+        rotation: vec4(rotation),
+        scale: vec3(scale),
+    }
+}
+
+fn lerp<T>(from: T, to: T, t: f32) -> T
+where
+    T: Add<Output = T> + Mul<f32, Output = T>,
+{
+    from * (1.0 - t) + to * t
+}
+
+fn vec2(array: [f32; 2]) -> Vec2 {
+    Vec2::new(array[0], array[1])
+}
+
+fn vec4(array: [f32; 4]) -> Vec4 {
+    Vec4::new(array[0], array[1], array[2], array[3])
+}
+
+fn mat4(columns: [[f32; 4]; 4]) -> Mat4 {
+    Mat4 {
+        cols: [
+            vec4(columns[0]),
+            vec4(columns[1]),
+            vec4(columns[2]),
+            vec4(columns[3]),
+        ],
+    }
+}
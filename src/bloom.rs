@@ -0,0 +1,115 @@
+//! Bloom: the light from the brightest parts of an [HDR](crate::hdr) image bleeding into their surroundings, like it
+//! would in a camera lens or an eye.
+//!
+//! The parts of the image above a brightness threshold are extracted into the first of a chain of [`BloomTargets`],
+//! each half the size of the one before it. Every level is then blurred upwards into the next larger one with additive
+//! blending, so the first level ends up holding a wide, smooth glow that's added back onto the image before tone
+//! mapping.
+
+use crate::hdr::HDR_FORMAT;
+
+/// The most levels a bloom chain has. More levels spread the glow further.
+pub const MAX_BLOOM_LEVELS: usize = 6;
+
+/// The parameters of the bloom, laid out to be uploaded as a uniform buffer.
+#[repr(C)]
+#[derive(Clone, Copy, Debug)]
+pub struct BloomSettings {
+    /// The brightness above which colors start to bloom.
+    pub threshold: f32,
+    /// How far below the threshold the bloom fades in, to avoid a hard edge around the blooming areas. `0.0` is a
+    /// hard cutoff.
+    pub knee: f32,
+    /// How much of the bloom is added onto the image.
+    pub intensity: f32,
+    /// The radius of the filter used to blur each level into the next larger one, in texture coordinates.
+    pub filter_radius: f32,
+}
+
+impl Default for BloomSettings {
+    fn default() -> Self {
+        Self {
+            threshold: 1.0,
+            knee: 0.5,
+            intensity: 0.08,
+            filter_radius: 0.005,
+        }
+    }
+}
+
+/// The chain of targets the bloom is downsampled and then upsampled through. It has to be recreated whenever the
+/// window is resized.
+pub struct BloomTargets {
+    /// The levels from half the size of the window downwards. They're separate textures rather than the mip levels of
+    /// a single one, so no texture is ever sampled and rendered to in the same pass.
+    levels: Vec<(wgpu::Texture, wgpu::TextureView)>,
+    /// A bilinear sampler, so that each sample averages four texels of the level it's taken from.
+    pub sampler: wgpu::Sampler,
+}
+
+impl BloomTargets {
+    pub fn new(device: &wgpu::Device, width: u32, height: u32) -> Self {
+        let levels = level_sizes(width, height, MAX_BLOOM_LEVELS)
+            .into_iter()
+            .map(|(width, height)| {
+                let texture = device.create_texture(&wgpu::TextureDescriptor {
+                    size: wgpu::Extent3d {
+                        width,
+                        height,
+                        depth: 1,
+                    },
+                    array_layer_count: 1,
+                    mip_level_count: 1,
+                    sample_count: 1,
+                    dimension: wgpu::TextureDimension::D2,
+                    format: HDR_FORMAT,
+                    usage: wgpu::TextureUsage::OUTPUT_ATTACHMENT | wgpu::TextureUsage::SAMPLED,
+                });
+                let view = texture.create_default_view();
+                (texture, view)
+            })
+            .collect();
+        let sampler = device.create_sampler(&wgpu::SamplerDescriptor {
+            address_mode_u: wgpu::AddressMode::ClampToEdge,
+            address_mode_v: wgpu::AddressMode::ClampToEdge,
+            address_mode_w: wgpu::AddressMode::ClampToEdge,
+            mag_filter: wgpu::FilterMode::Linear,
+            min_filter: wgpu::FilterMode::Linear,
+            mipmap_filter: wgpu::FilterMode::Nearest,
+            lod_min_clamp: 0.0,
+            lod_max_clamp: 0.0,
+            compare_function: wgpu::CompareFunction::Never,
+        });
+
+        Self { levels, sampler }
+    }
+
+    pub fn level_count(&self) -> usize {
+        self.levels.len()
+    }
+
+    /// The view of the level `level` halvings below half the size of the window. Level `0` holds the finished bloom.
+    pub fn level_view(&self, level: usize) -> &wgpu::TextureView {
+        &self.levels[level].1
+    }
+}
+
+/// The sizes of the levels of a bloom chain for a `width` by `height` image, starting at half its size. The chain
+/// stops early once a level would be smaller than a single pixel along either axis.
+///
+/// ```
+/// # use learnopengl_but_its_wgpu::bloom::level_sizes;
+/// #
+/// assert_eq!(
+///     vec![(640, 360), (320, 180), (160, 90), (80, 45), (40, 22), (20, 11)],
+///     level_sizes(1280, 720, 6)
+/// );
+/// assert_eq!(vec![(4, 1)], level_sizes(8, 3, 6));
+/// assert!(level_sizes(1, 1, 6).is_empty());
+/// ```
+pub fn level_sizes(width: u32, height: u32, max_level_count: usize) -> Vec<(u32, u32)> {
+    (1..=max_level_count)
+        .map(|level| (width >> level, height >> level))
+        .take_while(|&(width, height)| width > 0 && height > 0)
+        .collect()
+}
@@ -0,0 +1,472 @@
+//! Clustered shading: the view frustum is divided into a grid of clusters, and every cluster gets a list of the lights
+//! that can reach it. A fragment then only has to loop over the lights of the cluster it lies in, which keeps forward
+//! rendering fast with hundreds of lights while still supporting transparency and MSAA.
+//!
+//! Clusters are screen space tiles split into slices along the view direction. The slices grow exponentially with
+//! their distance from the camera, so that clusters far away aren't much longer than they are wide.
+//!
+//! The lights are assigned on the CPU every frame. The result is uploaded as a list of light indices, which holds the
+//! lights of every cluster one after the other, along with the range of that list belonging to each cluster.
+
+use std::mem;
+
+use ultraviolet::{Mat4, Vec2, Vec3};
+
+use crate::instance_buffer::{InstanceBuffer, InstanceData};
+
+/// The number of tiles across the screen. Must match `CLUSTER_COUNT_X` in the shaders.
+pub const CLUSTER_COUNT_X: u32 = 16;
+/// The number of tiles down the screen. Must match `CLUSTER_COUNT_Y` in the shaders.
+pub const CLUSTER_COUNT_Y: u32 = 9;
+/// The number of depth slices. Must match `CLUSTER_COUNT_Z` in the shaders.
+pub const CLUSTER_COUNT_Z: u32 = 24;
+pub const CLUSTER_COUNT: usize = (CLUSTER_COUNT_X * CLUSTER_COUNT_Y * CLUSTER_COUNT_Z) as usize;
+
+/// The range of the light index list holding the lights of a cluster.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Cluster {
+    pub offset: u32,
+    pub count: u32,
+}
+
+impl InstanceData for Cluster {
+    fn same_as(&self, other: &Self) -> bool {
+        self == other
+    }
+}
+
+/// Everything a fragment shader needs to find the cluster it lies in.
+#[repr(C)]
+#[derive(Clone, Copy, Debug)]
+pub struct ClusterUniforms {
+    /// Transforms world space into view space.
+    pub view: Mat4,
+    /// Scales view space X and Y divided by the depth into normalized device coordinates, with Y pointing up.
+    pub projection_scale: Vec2,
+    /// The slice at a view space depth `d` is `log(d) * slice_scale + slice_bias`.
+    pub slice_scale: f32,
+    pub slice_bias: f32,
+}
+
+/// The frustum of a camera divided into clusters.
+#[derive(Clone, Copy, Debug)]
+pub struct ClusterGrid {
+    /// The size of the viewport in pixels.
+    pub width: f32,
+    pub height: f32,
+    /// The vertical field of view in radians.
+    pub vertical_fov: f32,
+    pub near: f32,
+    pub far: f32,
+}
+
+/// The lights of every cluster, ready to be uploaded.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct LightAssignment {
+    /// Indexed by [`ClusterGrid::cluster_index`].
+    pub clusters: Vec<Cluster>,
+    pub light_indices: Vec<u32>,
+}
+
+/// Owns the buffers and bind group exposing the lights of every cluster to the fragment shader.
+pub struct ClusterBuffers {
+    uniform_buffer: wgpu::Buffer,
+    cluster_buffer: InstanceBuffer<Cluster>,
+    light_index_buffer: InstanceBuffer<u32>,
+    bind_group: wgpu::BindGroup,
+}
+
+impl ClusterGrid {
+    /// The index of the cluster in the given tile and slice. Tiles are counted from the top left of the screen.
+    pub fn cluster_index(x: u32, y: u32, z: u32) -> usize {
+        ((z * CLUSTER_COUNT_Y + y) * CLUSTER_COUNT_X + x) as usize
+    }
+
+    /// The distance from the camera at which `slice` starts. Slice [`CLUSTER_COUNT_Z`] starts at the far plane.
+    pub fn slice_depth(&self, slice: u32) -> f32 {
+        self.near * (self.far / self.near).powf(slice as f32 / CLUSTER_COUNT_Z as f32)
+    }
+
+    /// The slice containing the view space `depth`, which has to lie between the near and far planes.
+    pub fn slice(&self, depth: f32) -> u32 {
+        let uniforms = self.uniforms(Mat4::identity());
+        let slice = depth.ln() * uniforms.slice_scale + uniforms.slice_bias;
+        (slice.max(0.0) as u32).min(CLUSTER_COUNT_Z - 1)
+    }
+
+    /// The index of the cluster containing a position in view space, where the camera looks down the negative Z axis,
+    /// or `None` if it lies outside of the frustum.
+    ///
+    /// ```
+    /// # use ultraviolet::Vec3;
+    /// #
+    /// # use learnopengl_but_its_wgpu::cluster::{ClusterGrid, CLUSTER_COUNT_X, CLUSTER_COUNT_Y, CLUSTER_COUNT_Z};
+    /// #
+    /// let grid = ClusterGrid {
+    ///     width: 1600.0,
+    ///     height: 900.0,
+    ///     vertical_fov: std::f32::consts::FRAC_PI_2,
+    ///     near: 0.1,
+    ///     far: 100.0,
+    /// };
+    ///
+    /// // Straight ahead is in the middle of the screen, and the top left corner is the first tile.
+    /// let center = grid.cluster_at(Vec3::new(0.01, 0.01, -1.0)).unwrap();
+    /// assert_eq!(center, ClusterGrid::cluster_index(CLUSTER_COUNT_X / 2, CLUSTER_COUNT_Y / 2, grid.slice(1.0)));
+    /// let top_left = grid.cluster_at(Vec3::new(-15.9, 8.9, -10.0)).unwrap();
+    /// assert_eq!(top_left, ClusterGrid::cluster_index(0, 0, grid.slice(10.0)));
+    ///
+    /// // The slices cover the whole range between the near and far planes.
+    /// assert_eq!(0, grid.slice(0.1));
+    /// assert_eq!(CLUSTER_COUNT_Z - 1, grid.slice(99.9));
+    /// assert!((grid.slice_depth(CLUSTER_COUNT_Z) - 100.0).abs() < 1e-3);
+    ///
+    /// assert_eq!(None, grid.cluster_at(Vec3::new(0.0, 0.0, 1.0)));
+    /// assert_eq!(None, grid.cluster_at(Vec3::new(0.0, 2.0, -1.0)));
+    /// ```
+    pub fn cluster_at(&self, position: Vec3) -> Option<usize> {
+        let depth = -position.z;
+        if depth < self.near || depth >= self.far {
+            return None;
+        }
+        let (x, y) = self.normalized_device_coordinates(position.x, position.y, depth);
+        if !(-1.0..1.0).contains(&x) || y <= -1.0 || y > 1.0 {
+            return None;
+        }
+        let (tile_x, tile_y) = tile(x, y);
+        Some(Self::cluster_index(tile_x, tile_y, self.slice(depth)))
+    }
+
+    pub fn uniforms(&self, view: Mat4) -> ClusterUniforms {
+        let slice_scale = CLUSTER_COUNT_Z as f32 / (self.far / self.near).ln();
+        let tan_half_fov = (self.vertical_fov / 2.0).tan();
+        ClusterUniforms {
+            view,
+            projection_scale: Vec2::new(
+                1.0 / (tan_half_fov * self.width / self.height),
+                1.0 / tan_half_fov,
+            ),
+            slice_scale,
+            slice_bias: -self.near.ln() * slice_scale,
+        }
+    }
+
+    /// Assigns spherical lights, given as their center in view space and their radius, to every cluster they overlap.
+    /// The lights are referred to by their index in `lights`.
+    ///
+    /// The assignment is conservative, so every point lit by a light lies in a cluster it's assigned to:
+    ///
+    /// ```
+    /// # use ultraviolet::Vec3;
+    /// #
+    /// # use learnopengl_but_its_wgpu::cluster::{ClusterGrid, CLUSTER_COUNT};
+    /// #
+    /// let grid = ClusterGrid {
+    ///     width: 1280.0,
+    ///     height: 720.0,
+    ///     vertical_fov: 1.0,
+    ///     near: 0.1,
+    ///     far: 100.0,
+    /// };
+    /// let lights = [
+    ///     (Vec3::new(0.0, 0.0, -5.0), 1.0),
+    ///     (Vec3::new(2.0, -1.0, -8.0), 3.0),
+    ///     // Reaching past the near plane and out of the sides of the frustum.
+    ///     (Vec3::new(-1.0, 0.5, 0.0), 2.0),
+    ///     // Entirely behind the camera.
+    ///     (Vec3::new(0.0, 0.0, 5.0), 1.0),
+    /// ];
+    /// let assignment = grid.assign_lights(&lights);
+    /// assert_eq!(CLUSTER_COUNT, assignment.clusters.len());
+    ///
+    /// for (i, &(center, radius)) in lights.iter().enumerate() {
+    ///     for step in 0..1000 {
+    ///         // Points scattered through the light's sphere.
+    ///         let t = step as f32;
+    ///         let offset = Vec3::new((t * 0.37).sin(), (t * 0.71).cos(), (t * 0.13).sin());
+    ///         let point = center + offset * (radius * (t / 1000.0).sqrt() / offset.mag().max(1.0));
+    ///         if let Some(index) = grid.cluster_at(point) {
+    ///             let cluster = assignment.clusters[index];
+    ///             let range = cluster.offset as usize..(cluster.offset + cluster.count) as usize;
+    ///             assert!(assignment.light_indices[range].contains(&(i as u32)));
+    ///         }
+    ///     }
+    /// }
+    ///
+    /// // Clusters far away from every light are left empty, and lights behind the camera aren't assigned anywhere.
+    /// let far_away = grid.cluster_at(Vec3::new(0.0, 0.0, -90.0)).unwrap();
+    /// assert_eq!(0, assignment.clusters[far_away].count);
+    /// assert!(!assignment.light_indices.contains(&3));
+    /// ```
+    pub fn assign_lights(&self, lights: &[(Vec3, f32)]) -> LightAssignment {
+        // Every overlapping pair of a cluster and a light, which are then sorted into the lists of each cluster.
+        let mut pairs = Vec::new();
+        for (light_index, &(center, radius)) in lights.iter().enumerate() {
+            let closest_depth = (-center.z - radius).max(self.near);
+            let furthest_depth = (-center.z + radius).min(self.far);
+            if closest_depth >= furthest_depth {
+                continue;
+            }
+
+            for z in self.slice(closest_depth)..=self.slice(furthest_depth) {
+                // Only the part of the light's bounding box within the slice can overlap its clusters.
+                let near_depth = closest_depth.max(self.slice_depth(z));
+                let far_depth = furthest_depth.min(self.slice_depth(z + 1));
+                if near_depth > far_depth {
+                    continue;
+                }
+
+                // Positions are divided by their depth when projected, so the corners of the box are projected at
+                // both of its depths to find the range of tiles it covers.
+                let mut min = Vec2::new(f32::INFINITY, f32::INFINITY);
+                let mut max = Vec2::new(-f32::INFINITY, -f32::INFINITY);
+                for &depth in &[near_depth, far_depth] {
+                    for &x in &[center.x - radius, center.x + radius] {
+                        for &y in &[center.y - radius, center.y + radius] {
+                            let projected = self.normalized_device_coordinates(x, y, depth);
+                            let projected = Vec2::new(projected.0, projected.1);
+                            min = min.min_by_component(projected);
+                            max = max.max_by_component(projected);
+                        }
+                    }
+                }
+                if min.x >= 1.0 || max.x < -1.0 || min.y > 1.0 || max.y <= -1.0 {
+                    continue;
+                }
+                // The top of the screen is the first row of tiles.
+                let (min_x, min_y) = tile(min.x.max(-1.0), max.y.min(1.0));
+                let (max_x, max_y) = tile(max.x.min(1.0 - 1e-6), min.y.max(-1.0 + 1e-6));
+
+                for y in min_y..=max_y {
+                    for x in min_x..=max_x {
+                        if self.sphere_overlaps_cluster(center, radius, x, y, z) {
+                            pairs.push((Self::cluster_index(x, y, z), light_index as u32));
+                        }
+                    }
+                }
+            }
+        }
+
+        let mut clusters = vec![Cluster::default(); CLUSTER_COUNT];
+        for &(cluster, _) in &pairs {
+            clusters[cluster].count += 1;
+        }
+        let mut offset = 0;
+        for cluster in &mut clusters {
+            cluster.offset = offset;
+            offset += cluster.count;
+        }
+
+        let mut light_indices = vec![0; pairs.len()];
+        let mut filled = vec![0; CLUSTER_COUNT];
+        for &(cluster, light_index) in &pairs {
+            light_indices[(clusters[cluster].offset + filled[cluster]) as usize] = light_index;
+            filled[cluster] += 1;
+        }
+
+        LightAssignment {
+            clusters,
+            light_indices,
+        }
+    }
+
+    /// Projects a position in view space, given by its depth instead of its Z coordinate, without flipping Y.
+    fn normalized_device_coordinates(&self, x: f32, y: f32, depth: f32) -> (f32, f32) {
+        let tan_half_fov = (self.vertical_fov / 2.0).tan();
+        let aspect_ratio = self.width / self.height;
+        (
+            x / (depth * tan_half_fov * aspect_ratio),
+            y / (depth * tan_half_fov),
+        )
+    }
+
+    /// Tests a light's sphere against the bounding box of a cluster, which is a little larger than the cluster itself.
+    fn sphere_overlaps_cluster(&self, center: Vec3, radius: f32, x: u32, y: u32, z: u32) -> bool {
+        let tan_half_fov = (self.vertical_fov / 2.0).tan();
+        let aspect_ratio = self.width / self.height;
+        let left = (x as f32 / CLUSTER_COUNT_X as f32) * 2.0 - 1.0;
+        let right = ((x + 1) as f32 / CLUSTER_COUNT_X as f32) * 2.0 - 1.0;
+        let top = 1.0 - (y as f32 / CLUSTER_COUNT_Y as f32) * 2.0;
+        let bottom = 1.0 - ((y + 1) as f32 / CLUSTER_COUNT_Y as f32) * 2.0;
+
+        let mut min = Vec3::new(f32::INFINITY, f32::INFINITY, f32::INFINITY);
+        let mut max = -min;
+        for &depth in &[self.slice_depth(z), self.slice_depth(z + 1)] {
+            for &ndc_x in &[left, right] {
+                for &ndc_y in &[bottom, top] {
+                    let corner = Vec3::new(
+                        ndc_x * depth * tan_half_fov * aspect_ratio,
+                        ndc_y * depth * tan_half_fov,
+                        -depth,
+                    );
+                    min = min.min_by_component(corner);
+                    max = max.max_by_component(corner);
+                }
+            }
+        }
+
+        let closest = center.max_by_component(min).min_by_component(max);
+        (closest - center).mag_sq() <= radius * radius
+    }
+}
+
+impl ClusterBuffers {
+    /// The layout of the bind group holding the [`ClusterUniforms`], the ranges of every cluster and the light index
+    /// list.
+    pub fn create_bind_group_layout(device: &wgpu::Device) -> wgpu::BindGroupLayout {
+        device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            bindings: &[
+                wgpu::BindGroupLayoutBinding {
+                    binding: 0,
+                    visibility: wgpu::ShaderStage::FRAGMENT,
+                    ty: wgpu::BindingType::UniformBuffer { dynamic: false },
+                },
+                wgpu::BindGroupLayoutBinding {
+                    binding: 1,
+                    visibility: wgpu::ShaderStage::FRAGMENT,
+                    ty: wgpu::BindingType::StorageBuffer {
+                        dynamic: false,
+                        readonly: true,
+                    },
+                },
+                wgpu::BindGroupLayoutBinding {
+                    binding: 2,
+                    visibility: wgpu::ShaderStage::FRAGMENT,
+                    ty: wgpu::BindingType::StorageBuffer {
+                        dynamic: false,
+                        readonly: true,
+                    },
+                },
+            ],
+        })
+    }
+
+    /// Creates buffers with every cluster empty.
+    pub fn new(
+        device: &wgpu::Device,
+        bind_group_layout: &wgpu::BindGroupLayout,
+        grid: &ClusterGrid,
+    ) -> Self {
+        let uniform_buffer = device
+            .create_buffer_mapped(1, wgpu::BufferUsage::UNIFORM | wgpu::BufferUsage::COPY_DST)
+            .fill_from_slice(&[grid.uniforms(Mat4::identity())]);
+        let cluster_buffer = InstanceBuffer::from_slice_with_usage(
+            device,
+            &[Cluster::default(); CLUSTER_COUNT],
+            wgpu::BufferUsage::STORAGE,
+        );
+        let light_index_buffer = InstanceBuffer::with_usage(device, 1, wgpu::BufferUsage::STORAGE);
+        let bind_group = Self::create_bind_group(
+            device,
+            bind_group_layout,
+            &uniform_buffer,
+            &cluster_buffer,
+            &light_index_buffer,
+        );
+
+        Self {
+            uniform_buffer,
+            cluster_buffer,
+            light_index_buffer,
+            bind_group,
+        }
+    }
+
+    /// Assigns the lights to the clusters of `grid` and uploads the result, recording the necessary copies into
+    /// `encoder`. The lights are given in world space as their center and radius, and `view` transforms them into
+    /// view space.
+    pub fn update(
+        &mut self,
+        device: &wgpu::Device,
+        encoder: &mut wgpu::CommandEncoder,
+        bind_group_layout: &wgpu::BindGroupLayout,
+        grid: &ClusterGrid,
+        view: Mat4,
+        lights: &[(Vec3, f32)],
+    ) {
+        let view_space_lights: Vec<(Vec3, f32)> = lights
+            .iter()
+            .map(|&(center, radius)| {
+                let center = view * center.into_homogeneous_point();
+                (Vec3::new(center.x, center.y, center.z), radius)
+            })
+            .collect();
+        let assignment = grid.assign_lights(&view_space_lights);
+
+        let staging_buffer = device
+            .create_buffer_mapped(1, wgpu::BufferUsage::COPY_SRC)
+            .fill_from_slice(&[grid.uniforms(view)]);
+        encoder.copy_buffer_to_buffer(
+            &staging_buffer,
+            0,
+            &self.uniform_buffer,
+            0,
+            mem::size_of::<ClusterUniforms>() as wgpu::BufferAddress,
+        );
+
+        self.cluster_buffer
+            .update(device, encoder, &assignment.clusters);
+        if self
+            .light_index_buffer
+            .update(device, encoder, &assignment.light_indices)
+        {
+            self.bind_group = Self::create_bind_group(
+                device,
+                bind_group_layout,
+                &self.uniform_buffer,
+                &self.cluster_buffer,
+                &self.light_index_buffer,
+            );
+        }
+    }
+
+    pub fn bind_group(&self) -> &wgpu::BindGroup {
+        &self.bind_group
+    }
+
+    fn create_bind_group(
+        device: &wgpu::Device,
+        bind_group_layout: &wgpu::BindGroupLayout,
+        uniform_buffer: &wgpu::Buffer,
+        cluster_buffer: &InstanceBuffer<Cluster>,
+        light_index_buffer: &InstanceBuffer<u32>,
+    ) -> wgpu::BindGroup {
+        device.create_bind_group(&wgpu::BindGroupDescriptor {
+            layout: bind_group_layout,
+            bindings: &[
+                wgpu::Binding {
+                    binding: 0,
+                    resource: wgpu::BindingResource::Buffer {
+                        buffer: uniform_buffer,
+                        range: 0..mem::size_of::<ClusterUniforms>() as wgpu::BufferAddress,
+                    },
+                },
+                wgpu::Binding {
+                    binding: 1,
+                    resource: wgpu::BindingResource::Buffer {
+                        buffer: cluster_buffer.buffer(),
+                        range: 0..cluster_buffer.size(),
+                    },
+                },
+                wgpu::Binding {
+                    binding: 2,
+                    resource: wgpu::BindingResource::Buffer {
+                        buffer: light_index_buffer.buffer(),
+                        range: 0..light_index_buffer.size(),
+                    },
+                },
+            ],
+        })
+    }
+}
+
+/// The tile containing a point in normalized device coordinates with Y pointing up.
+fn tile(x: f32, y: f32) -> (u32, u32) {
+    let tile_x = ((x + 1.0) / 2.0 * CLUSTER_COUNT_X as f32) as u32;
+    let tile_y = ((1.0 - y) / 2.0 * CLUSTER_COUNT_Y as f32) as u32;
+    (
+        tile_x.min(CLUSTER_COUNT_X - 1),
+        tile_y.min(CLUSTER_COUNT_Y - 1),
+    )
+}
@@ -0,0 +1,194 @@
+//! Deferred shading: the scene's geometry is rendered once into a G-buffer holding everything the lighting needs, and
+//! each light is then only evaluated for the pixels its light volume covers.
+//!
+//! A light volume is a sphere around a point light whose radius is where the light's attenuation has made it too dim
+//! to notice. Drawing its back faces with the depth test reversed covers exactly the pixels whose surface lies in front
+//! of the back of the sphere, which still works with the camera inside of it.
+
+use std::f32::consts::PI;
+
+use ultraviolet::Vec3;
+
+/// World space positions. Half floats lose too much precision away from the origin.
+pub const POSITION_FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Rgba32Float;
+/// World space normals in RGB and the material's shininess in A.
+pub const NORMAL_FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Rgba16Float;
+/// The diffuse color in RGB and the specular intensity in A. Only RGB is sRGB encoded, which spends the few bits on the
+/// dark colors the eye tells apart best, while the specular intensity is stored as is.
+pub const ALBEDO_SPECULAR_FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Rgba8UnormSrgb;
+pub const DEPTH_FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Depth32Float;
+
+/// The brightness below which a light is considered to contribute nothing, relative to its brightest color component.
+/// This is a little over one step of an 8 bit color channel.
+pub const LIGHT_VOLUME_CUTOFF: f32 = 5.0 / 256.0;
+
+/// The render targets of the geometry pass. They have to be recreated whenever the window is resized.
+///
+/// The depth texture is kept around after the geometry pass, so that anything drawn with forward rendering afterwards
+/// is still hidden behind the scene.
+pub struct GBuffer {
+    pub position_texture: wgpu::Texture,
+    pub position_view: wgpu::TextureView,
+    pub normal_texture: wgpu::Texture,
+    pub normal_view: wgpu::TextureView,
+    pub albedo_specular_texture: wgpu::Texture,
+    pub albedo_specular_view: wgpu::TextureView,
+    pub depth_texture: wgpu::Texture,
+    pub depth_view: wgpu::TextureView,
+    /// The targets are read one texel per pixel, so there's nothing to filter.
+    pub sampler: wgpu::Sampler,
+}
+
+impl GBuffer {
+    pub fn new(device: &wgpu::Device, width: u32, height: u32) -> Self {
+        let (position_texture, position_view) =
+            create_target(device, width, height, POSITION_FORMAT);
+        let (normal_texture, normal_view) = create_target(device, width, height, NORMAL_FORMAT);
+        let (albedo_specular_texture, albedo_specular_view) =
+            create_target(device, width, height, ALBEDO_SPECULAR_FORMAT);
+        let (depth_texture, depth_view) = create_target(device, width, height, DEPTH_FORMAT);
+        let sampler = device.create_sampler(&wgpu::SamplerDescriptor {
+            address_mode_u: wgpu::AddressMode::ClampToEdge,
+            address_mode_v: wgpu::AddressMode::ClampToEdge,
+            address_mode_w: wgpu::AddressMode::ClampToEdge,
+            mag_filter: wgpu::FilterMode::Nearest,
+            min_filter: wgpu::FilterMode::Nearest,
+            mipmap_filter: wgpu::FilterMode::Nearest,
+            lod_min_clamp: 0.0,
+            lod_max_clamp: 0.0,
+            compare_function: wgpu::CompareFunction::Never,
+        });
+
+        Self {
+            position_texture,
+            position_view,
+            normal_texture,
+            normal_view,
+            albedo_specular_texture,
+            albedo_specular_view,
+            depth_texture,
+            depth_view,
+            sampler,
+        }
+    }
+}
+
+fn create_target(
+    device: &wgpu::Device,
+    width: u32,
+    height: u32,
+    format: wgpu::TextureFormat,
+) -> (wgpu::Texture, wgpu::TextureView) {
+    let texture = device.create_texture(&wgpu::TextureDescriptor {
+        size: wgpu::Extent3d {
+            width,
+            height,
+            depth: 1,
+        },
+        array_layer_count: 1,
+        mip_level_count: 1,
+        sample_count: 1,
+        dimension: wgpu::TextureDimension::D2,
+        format,
+        usage: wgpu::TextureUsage::OUTPUT_ATTACHMENT | wgpu::TextureUsage::SAMPLED,
+    });
+    let view = texture.create_default_view();
+    (texture, view)
+}
+
+/// The distance at which a point light with the given attenuation factors fades below [`LIGHT_VOLUME_CUTOFF`] of
+/// `brightness`, which should be the light's brightest color component.
+///
+/// ```
+/// # use learnopengl_but_its_wgpu::deferred::{light_volume_radius, LIGHT_VOLUME_CUTOFF};
+/// #
+/// let (constant, linear, quadratic) = (1.0, 0.7, 1.8);
+/// let radius = light_volume_radius(constant, linear, quadratic, 1.0);
+/// let attenuation = 1.0 / (constant + linear * radius + quadratic * radius * radius);
+/// assert!((attenuation - LIGHT_VOLUME_CUTOFF).abs() < 1e-6);
+///
+/// // Brighter lights reach further.
+/// assert!(light_volume_radius(constant, linear, quadratic, 4.0) > radius);
+/// // A light that's too dim to notice even up close doesn't need a volume at all.
+/// assert_eq!(0.0, light_volume_radius(constant, linear, quadratic, 0.01));
+/// // Lights without any attenuation reach everywhere.
+/// assert_eq!(f32::INFINITY, light_volume_radius(1.0, 0.0, 0.0, 1.0));
+/// ```
+pub fn light_volume_radius(constant: f32, linear: f32, quadratic: f32, brightness: f32) -> f32 {
+    // Solves `brightness / (constant + linear * d + quadratic * d^2) = LIGHT_VOLUME_CUTOFF` for `d`.
+    let c = constant - brightness / LIGHT_VOLUME_CUTOFF;
+    if c >= 0.0 {
+        0.0
+    } else if quadratic > 0.0 {
+        (-linear + (linear * linear - 4.0 * quadratic * c).sqrt()) / (2.0 * quadratic)
+    } else if linear > 0.0 {
+        -c / linear
+    } else {
+        f32::INFINITY
+    }
+}
+
+/// A sphere of latitude and longitude lines to draw light volumes with, as positions and triangle indices. The
+/// triangles face outwards with the same winding as the cubes.
+///
+/// The flat triangles of a sphere lie partly inside of the round sphere through its vertices, which would cut off the
+/// edges of the light. The vertices are pushed out just far enough for every triangle to lie outside of the unit
+/// sphere instead.
+///
+/// ```
+/// # use learnopengl_but_its_wgpu::deferred::light_volume_sphere;
+/// #
+/// let (vertices, indices) = light_volume_sphere(16, 8);
+/// for triangle in indices.chunks(3) {
+///     let a = vertices[triangle[0] as usize];
+///     let b = vertices[triangle[1] as usize];
+///     let c = vertices[triangle[2] as usize];
+///     let normal = (b - a).cross(c - a).normalized();
+///     // The distance from the center to the triangle's plane.
+///     assert!(normal.dot(a) >= 1.0 - 1e-5);
+/// }
+/// ```
+pub fn light_volume_sphere(sectors: u16, stacks: u16) -> (Vec<Vec3>, Vec<u16>) {
+    let mut vertices = Vec::with_capacity(((sectors + 1) * (stacks + 1)) as usize);
+    for stack in 0..=stacks {
+        let polar = PI * stack as f32 / stacks as f32;
+        for sector in 0..=sectors {
+            let azimuth = 2.0 * PI * sector as f32 / sectors as f32;
+            vertices.push(Vec3::new(
+                polar.sin() * azimuth.cos(),
+                polar.cos(),
+                polar.sin() * azimuth.sin(),
+            ));
+        }
+    }
+
+    let mut indices = Vec::with_capacity((6 * sectors * stacks) as usize);
+    for stack in 0..stacks {
+        for sector in 0..sectors {
+            let top_left = stack * (sectors + 1) + sector;
+            let bottom_left = top_left + sectors + 1;
+            // The quads around the poles degenerate into a single triangle.
+            if stack != 0 {
+                indices.extend_from_slice(&[top_left, top_left + 1, bottom_left]);
+            }
+            if stack != stacks - 1 {
+                indices.extend_from_slice(&[top_left + 1, bottom_left + 1, bottom_left]);
+            }
+        }
+    }
+
+    let closest_distance = indices
+        .chunks(3)
+        .map(|triangle| {
+            let a = vertices[triangle[0] as usize];
+            let b = vertices[triangle[1] as usize];
+            let c = vertices[triangle[2] as usize];
+            (b - a).cross(c - a).normalized().dot(a)
+        })
+        .fold(1.0, f32::min);
+    for vertex in &mut vertices {
+        *vertex *= 1.0 / closest_distance;
+    }
+
+    (vertices, indices)
+}
@@ -0,0 +1,186 @@
+//! High dynamic range rendering: a floating point color target that lighting can exceed 1.0 in, and the luminance
+//! targets used to adapt the exposure to how bright the scene is.
+//!
+//! The scene's average luminance is found by rendering the logarithm of each pixel's luminance into a
+//! [`LUMINANCE_SIZE`] square target, then halving it with bilinear samples until a single texel is left. That texel
+//! holds the logarithmic average, which is blended into the adapted luminance every frame so the exposure changes
+//! gradually like an eye adjusting to the dark.
+
+pub const HDR_FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Rgba16Float;
+/// The format of the luminance targets. Half floats can be blended, unlike full ones on some hardware.
+pub const LUMINANCE_FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Rgba16Float;
+/// The size of the first luminance target. This is a power of two so every level halves exactly down to 1x1.
+pub const LUMINANCE_SIZE: u32 = 256;
+
+/// How the HDR colors are mapped into the `0.0..=1.0` range of the swap chain.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ToneMapping {
+    /// `color / (color + 1.0)`, which never clips but washes out bright colors.
+    Reinhard = 0,
+    /// Krzysztof Narkowicz's fit of the ACES reference rendering transform, with a filmic toe and shoulder.
+    AcesFilmic = 1,
+    /// John Hable's filmic curve from Uncharted 2, which keeps more contrast in the shadows.
+    Uncharted2 = 2,
+}
+
+impl ToneMapping {
+    /// The next operator, for cycling through all of them.
+    pub fn next(self) -> Self {
+        match self {
+            ToneMapping::Reinhard => ToneMapping::AcesFilmic,
+            ToneMapping::AcesFilmic => ToneMapping::Uncharted2,
+            ToneMapping::Uncharted2 => ToneMapping::Reinhard,
+        }
+    }
+}
+
+/// A window sized [`HDR_FORMAT`] color target. It has to be recreated whenever the window is resized.
+pub struct HdrTarget {
+    pub texture: wgpu::Texture,
+    pub view: wgpu::TextureView,
+    pub sampler: wgpu::Sampler,
+    pub width: u32,
+    pub height: u32,
+}
+
+impl HdrTarget {
+    pub fn new(device: &wgpu::Device, width: u32, height: u32) -> Self {
+        let texture = device.create_texture(&wgpu::TextureDescriptor {
+            size: wgpu::Extent3d {
+                width,
+                height,
+                depth: 1,
+            },
+            array_layer_count: 1,
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: HDR_FORMAT,
+            usage: wgpu::TextureUsage::OUTPUT_ATTACHMENT | wgpu::TextureUsage::SAMPLED,
+        });
+        let view = texture.create_default_view();
+
+        Self {
+            texture,
+            view,
+            sampler: create_linear_sampler(device),
+            width,
+            height,
+        }
+    }
+}
+
+/// The chain of targets the scene's average luminance is reduced through, and the adapted luminance.
+pub struct LuminanceTargets {
+    /// The levels from [`LUMINANCE_SIZE`] down to 1x1. They're separate textures rather than the mip levels of a
+    /// single one, so no texture is ever sampled and rendered to in the same pass.
+    levels: Vec<(wgpu::Texture, wgpu::TextureView)>,
+    /// A 1x1 target holding the luminance the eye has adapted to so far.
+    pub adapted_texture: wgpu::Texture,
+    pub adapted_view: wgpu::TextureView,
+    /// A bilinear sampler, so that each sample of a level averages four texels of the one before it.
+    pub sampler: wgpu::Sampler,
+}
+
+impl LuminanceTargets {
+    /// Creates the targets, clearing the adapted luminance to `initial_luminance` with `encoder`.
+    pub fn new(
+        device: &wgpu::Device,
+        encoder: &mut wgpu::CommandEncoder,
+        initial_luminance: f32,
+    ) -> Self {
+        let levels = (0..)
+            .map(|level| LUMINANCE_SIZE >> level)
+            .take_while(|&size| size > 0)
+            .map(|size| create_luminance_target(device, size))
+            .collect();
+        let (adapted_texture, adapted_view) = create_luminance_target(device, 1);
+
+        encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+            color_attachments: &[wgpu::RenderPassColorAttachmentDescriptor {
+                attachment: &adapted_view,
+                resolve_target: None,
+                load_op: wgpu::LoadOp::Clear,
+                store_op: wgpu::StoreOp::Store,
+                clear_color: wgpu::Color {
+                    r: initial_luminance as f64,
+                    g: initial_luminance as f64,
+                    b: initial_luminance as f64,
+                    a: 1.0,
+                },
+            }],
+            depth_stencil_attachment: None,
+        });
+
+        Self {
+            levels,
+            adapted_texture,
+            adapted_view,
+            sampler: create_linear_sampler(device),
+        }
+    }
+
+    /// The number of levels, including the first [`LUMINANCE_SIZE`] one and the last 1x1 one.
+    pub fn level_count(&self) -> usize {
+        self.levels.len()
+    }
+
+    /// The view of the level `level` halvings below [`LUMINANCE_SIZE`].
+    pub fn level_view(&self, level: usize) -> &wgpu::TextureView {
+        &self.levels[level].1
+    }
+}
+
+/// How much of the way to the scene's current luminance the adapted luminance moves in `delta_time` seconds, when
+/// adapting at `speed`. Used as the blend constant when blending the current luminance into the adapted one.
+///
+/// Adapting over two half steps ends up in the same place as adapting over a whole step, so the result doesn't
+/// depend on the frame rate.
+///
+/// ```
+/// # use learnopengl_but_its_wgpu::hdr::adaptation_blend_factor;
+/// #
+/// assert_eq!(0.0, adaptation_blend_factor(0.0, 1.5));
+/// assert!(adaptation_blend_factor(100.0, 1.5) > 0.999);
+///
+/// let adapt = |adapted: f32, current: f32, factor: f32| adapted + (current - adapted) * factor;
+/// let half_step = adaptation_blend_factor(0.05, 1.5);
+/// let whole_step = adaptation_blend_factor(0.1, 1.5);
+/// let two_half_steps = adapt(adapt(1.0, 5.0, half_step), 5.0, half_step);
+/// assert!((two_half_steps - adapt(1.0, 5.0, whole_step)).abs() < 1e-5);
+/// ```
+pub fn adaptation_blend_factor(delta_time: f32, speed: f32) -> f32 {
+    1.0 - (-delta_time * speed).exp()
+}
+
+fn create_luminance_target(device: &wgpu::Device, size: u32) -> (wgpu::Texture, wgpu::TextureView) {
+    let texture = device.create_texture(&wgpu::TextureDescriptor {
+        size: wgpu::Extent3d {
+            width: size,
+            height: size,
+            depth: 1,
+        },
+        array_layer_count: 1,
+        mip_level_count: 1,
+        sample_count: 1,
+        dimension: wgpu::TextureDimension::D2,
+        format: LUMINANCE_FORMAT,
+        usage: wgpu::TextureUsage::OUTPUT_ATTACHMENT | wgpu::TextureUsage::SAMPLED,
+    });
+    let view = texture.create_default_view();
+    (texture, view)
+}
+
+fn create_linear_sampler(device: &wgpu::Device) -> wgpu::Sampler {
+    device.create_sampler(&wgpu::SamplerDescriptor {
+        address_mode_u: wgpu::AddressMode::ClampToEdge,
+        address_mode_v: wgpu::AddressMode::ClampToEdge,
+        address_mode_w: wgpu::AddressMode::ClampToEdge,
+        mag_filter: wgpu::FilterMode::Linear,
+        min_filter: wgpu::FilterMode::Linear,
+        mipmap_filter: wgpu::FilterMode::Nearest,
+        lod_min_clamp: 0.0,
+        lod_max_clamp: 0.0,
+        compare_function: wgpu::CompareFunction::Never,
+    })
+}
@@ -0,0 +1,866 @@
+//! Image based lighting: the whole environment around a scene as one big light source, captured in an equirectangular
+//! high dynamic range image.
+//!
+//! Integrating the environment for every fragment would be far too slow, so the integrals are precomputed into
+//! textures instead. The diffuse term only depends on the normal, and becomes an irradiance cube map. The specular
+//! term is split into two parts following the split sum approximation: the environment convolved with the GGX
+//! distribution for increasing roughness, stored in the mip levels of a cube map, and the rest of the BRDF as a
+//! function of the viewing angle and the roughness, stored in a 2D lookup texture.
+//!
+//! The precomputation runs on the CPU and takes a while, so [`IblMaps::load_or_compute`] caches the results as
+//! Radiance images in a directory next to the environment map.
+//!
+//! Cube map faces are stored and addressed like the GPU does: in the order +X, -X, +Y, -Y, +Z, -Z, each with its top
+//! row first.
+
+use std::{
+    f32::consts::PI,
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+use ultraviolet::{Vec2, Vec3};
+
+use crate::radiance::{HdrError, HdrImage};
+
+/// The format of every texture created by [`IblTextures`].
+pub const IBL_FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Rgba16Float;
+/// The width and height of a face of the environment cube map the skybox is drawn with.
+pub const ENVIRONMENT_SIZE: u32 = 512;
+/// The width and height of a face of the irradiance map. Irradiance changes slowly with the normal, so this can be tiny.
+pub const IRRADIANCE_SIZE: u32 = 32;
+/// The width and height of a face of the first mip level of the prefiltered map.
+pub const PREFILTERED_SIZE: u32 = 128;
+/// The number of mip levels of the prefiltered map, from a roughness of `0.0` at the first to `1.0` at the last.
+pub const PREFILTERED_MIP_LEVELS: u32 = 5;
+/// The width and height of the BRDF lookup texture.
+pub const BRDF_LUT_SIZE: u32 = 128;
+
+const IRRADIANCE_SAMPLE_COUNT: u32 = 1024;
+const PREFILTER_SAMPLE_COUNT: u32 = 512;
+const BRDF_SAMPLE_COUNT: u32 = 512;
+
+/// A cube map of radiance values in linear space.
+#[derive(Clone, Debug)]
+pub struct CubeMap {
+    /// The width and height of each face.
+    pub size: u32,
+    /// The texels of every face in turn, row by row.
+    pub texels: Vec<Vec3>,
+}
+
+/// Everything image based lighting needs, ready to be uploaded with [`IblTextures::new`].
+pub struct IblMaps {
+    pub environment: CubeMap,
+    pub irradiance: CubeMap,
+    /// One cube map per mip level, each half the size of the previous one.
+    pub prefiltered: Vec<CubeMap>,
+    /// The scale (X) and bias (Y) applied to the reflectance at normal incidence, for the viewing angle's cosine along
+    /// the rows and the roughness down the columns.
+    pub brdf_lut: Vec<Vec2>,
+}
+
+/// The textures created from [`IblMaps`], along with the sampler they're all sampled with.
+pub struct IblTextures {
+    pub environment_texture: wgpu::Texture,
+    pub environment_view: wgpu::TextureView,
+    pub irradiance_texture: wgpu::Texture,
+    pub irradiance_view: wgpu::TextureView,
+    pub prefiltered_texture: wgpu::Texture,
+    pub prefiltered_view: wgpu::TextureView,
+    pub brdf_lut_texture: wgpu::Texture,
+    pub brdf_lut_view: wgpu::TextureView,
+    pub sampler: wgpu::Sampler,
+}
+
+impl CubeMap {
+    /// Creates a cube map by evaluating `radiance` for the direction through the center of each texel.
+    pub fn from_fn<F: FnMut(Vec3) -> Vec3>(size: u32, mut radiance: F) -> Self {
+        let mut texels = Vec::with_capacity((6 * size * size) as usize);
+        for face in 0..6 {
+            for y in 0..size {
+                for x in 0..size {
+                    let u = (x as f32 + 0.5) / size as f32;
+                    let v = (y as f32 + 0.5) / size as f32;
+                    texels.push(radiance(face_direction(face, u, v)));
+                }
+            }
+        }
+        Self { size, texels }
+    }
+
+    /// Projects an equirectangular image onto the faces of a cube map.
+    pub fn from_equirectangular(image: &HdrImage, size: u32) -> Self {
+        Self::from_fn(size, |direction| sample_equirectangular(image, direction))
+    }
+
+    pub fn texel(&self, face: usize, x: u32, y: u32) -> Vec3 {
+        self.texels[(face as u32 * self.size * self.size + y * self.size + x) as usize]
+    }
+
+    /// Bilinearly samples the cube map in `direction`, which doesn't have to be normalized. Samples don't blend across
+    /// the edges of a face, which is only noticeable on the smallest mip levels.
+    ///
+    /// ```
+    /// # use ultraviolet::Vec3;
+    /// #
+    /// # use learnopengl_but_its_wgpu::ibl::CubeMap;
+    /// #
+    /// let cube_map = CubeMap::from_fn(16, |direction| direction);
+    /// for &direction in &[Vec3::new(1.0, 0.2, -0.3), Vec3::new(0.1, -1.0, 0.4), Vec3::new(-0.5, 0.3, -1.0)] {
+    ///     let direction = direction.normalized();
+    ///     assert!((cube_map.sample(direction) - direction).mag() < 0.05);
+    /// }
+    /// ```
+    pub fn sample(&self, direction: Vec3) -> Vec3 {
+        let (face, u, v) = face_coordinates(direction);
+        let size = self.size as f32;
+        // Texel centers lie at half texel offsets.
+        let x = (u * size - 0.5).max(0.0).min(size - 1.0);
+        let y = (v * size - 0.5).max(0.0).min(size - 1.0);
+        let (x0, y0) = (x.floor() as u32, y.floor() as u32);
+        let (x1, y1) = ((x0 + 1).min(self.size - 1), (y0 + 1).min(self.size - 1));
+        let (tx, ty) = (x - x0 as f32, y - y0 as f32);
+
+        let top = self.texel(face, x0, y0) * (1.0 - tx) + self.texel(face, x1, y0) * tx;
+        let bottom = self.texel(face, x0, y1) * (1.0 - tx) + self.texel(face, x1, y1) * tx;
+        top * (1.0 - ty) + bottom * ty
+    }
+
+    /// Averages each square of 2x2 texels into the next mip level.
+    pub fn downsampled(&self) -> Self {
+        let size = (self.size / 2).max(1);
+        let mut texels = Vec::with_capacity((6 * size * size) as usize);
+        for face in 0..6 {
+            for y in 0..size {
+                for x in 0..size {
+                    let (x0, y0) = ((x * 2).min(self.size - 1), (y * 2).min(self.size - 1));
+                    let (x1, y1) = ((x0 + 1).min(self.size - 1), (y0 + 1).min(self.size - 1));
+                    texels.push(
+                        (self.texel(face, x0, y0)
+                            + self.texel(face, x1, y0)
+                            + self.texel(face, x0, y1)
+                            + self.texel(face, x1, y1))
+                            * 0.25,
+                    );
+                }
+            }
+        }
+        Self { size, texels }
+    }
+
+    /// Creates every mip level down to 1x1 faces, starting with this cube map itself.
+    pub fn mip_chain(&self) -> Vec<Self> {
+        let mut levels = vec![self.clone()];
+        while levels[levels.len() - 1].size > 1 {
+            let next = levels[levels.len() - 1].downsampled();
+            levels.push(next);
+        }
+        levels
+    }
+
+    /// Stacks the faces on top of each other into a single image, to be cached on disk.
+    fn to_image(&self) -> HdrImage {
+        HdrImage {
+            width: self.size,
+            height: self.size * 6,
+            pixels: self.texels.clone(),
+        }
+    }
+
+    /// Reverses [`to_image`](Self::to_image), checking that the faces have the expected size.
+    fn from_image(image: HdrImage, size: u32) -> Option<Self> {
+        if image.width == size && image.height == size * 6 {
+            Some(Self {
+                size,
+                texels: image.pixels,
+            })
+        } else {
+            None
+        }
+    }
+}
+
+impl IblMaps {
+    /// Precomputes everything from an equirectangular environment map.
+    pub fn compute(image: &HdrImage) -> Self {
+        let environment = CubeMap::from_equirectangular(image, ENVIRONMENT_SIZE);
+        let environment_mips = environment.mip_chain();
+
+        let irradiance = irradiance_map(&environment_mips, IRRADIANCE_SIZE);
+        let prefiltered = (0..PREFILTERED_MIP_LEVELS)
+            .map(|level| {
+                let roughness = level as f32 / (PREFILTERED_MIP_LEVELS - 1) as f32;
+                prefiltered_map(&environment_mips, PREFILTERED_SIZE >> level, roughness)
+            })
+            .collect();
+        let brdf_lut = brdf_lut(BRDF_LUT_SIZE);
+
+        Self {
+            environment,
+            irradiance,
+            prefiltered,
+            brdf_lut,
+        }
+    }
+
+    /// Loads the maps for the environment map at `path` from its cache directory, or computes them and fills the
+    /// cache if any of them are missing or were computed with different sizes. The cache has to be deleted by hand when
+    /// the environment map changes.
+    ///
+    /// The maps are returned along with whether they came from the cache.
+    pub fn load_or_compute<P: AsRef<Path>>(path: P) -> Result<(Self, bool), HdrError> {
+        let path = path.as_ref();
+        let directory = cache_directory(path);
+        if let Some(maps) = Self::load_cache(&directory) {
+            return Ok((maps, true));
+        }
+
+        let maps = Self::compute(&HdrImage::load(path)?);
+        maps.save_cache(&directory).map_err(HdrError::Io)?;
+        Ok((maps, false))
+    }
+
+    fn load_cache(directory: &Path) -> Option<Self> {
+        let load = |name: &str| HdrImage::load(directory.join(name)).ok();
+
+        let environment = CubeMap::from_image(load("environment.hdr")?, ENVIRONMENT_SIZE)?;
+        let irradiance = CubeMap::from_image(load("irradiance.hdr")?, IRRADIANCE_SIZE)?;
+        let mut prefiltered = Vec::with_capacity(PREFILTERED_MIP_LEVELS as usize);
+        for level in 0..PREFILTERED_MIP_LEVELS {
+            let image = load(&format!("prefiltered_{}.hdr", level))?;
+            prefiltered.push(CubeMap::from_image(image, PREFILTERED_SIZE >> level)?);
+        }
+        let brdf_lut = load("brdf_lut.hdr")?;
+        if brdf_lut.width != BRDF_LUT_SIZE || brdf_lut.height != BRDF_LUT_SIZE {
+            return None;
+        }
+        let brdf_lut = brdf_lut
+            .pixels
+            .iter()
+            .map(|pixel| Vec2::new(pixel.x, pixel.y))
+            .collect();
+
+        Some(Self {
+            environment,
+            irradiance,
+            prefiltered,
+            brdf_lut,
+        })
+    }
+
+    fn save_cache(&self, directory: &Path) -> io::Result<()> {
+        fs::create_dir_all(directory)?;
+        self.environment
+            .to_image()
+            .save(directory.join("environment.hdr"))?;
+        self.irradiance
+            .to_image()
+            .save(directory.join("irradiance.hdr"))?;
+        for (level, cube_map) in self.prefiltered.iter().enumerate() {
+            cube_map
+                .to_image()
+                .save(directory.join(format!("prefiltered_{}.hdr", level)))?;
+        }
+        HdrImage {
+            width: BRDF_LUT_SIZE,
+            height: BRDF_LUT_SIZE,
+            pixels: self
+                .brdf_lut
+                .iter()
+                .map(|texel| Vec3::new(texel.x, texel.y, 0.0))
+                .collect(),
+        }
+        .save(directory.join("brdf_lut.hdr"))
+    }
+}
+
+impl IblTextures {
+    pub fn new(device: &wgpu::Device, encoder: &mut wgpu::CommandEncoder, maps: &IblMaps) -> Self {
+        let (environment_texture, environment_view) =
+            create_cube_texture(device, encoder, &[&maps.environment]);
+        let (irradiance_texture, irradiance_view) =
+            create_cube_texture(device, encoder, &[&maps.irradiance]);
+        let prefiltered_levels: Vec<&CubeMap> = maps.prefiltered.iter().collect();
+        let (prefiltered_texture, prefiltered_view) =
+            create_cube_texture(device, encoder, &prefiltered_levels);
+
+        let brdf_lut_extent = wgpu::Extent3d {
+            width: BRDF_LUT_SIZE,
+            height: BRDF_LUT_SIZE,
+            depth: 1,
+        };
+        let brdf_lut_texture = device.create_texture(&wgpu::TextureDescriptor {
+            size: brdf_lut_extent,
+            array_layer_count: 1,
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: IBL_FORMAT,
+            usage: wgpu::TextureUsage::SAMPLED | wgpu::TextureUsage::COPY_DST,
+        });
+        let brdf_lut_texels: Vec<u16> = maps
+            .brdf_lut
+            .iter()
+            .flat_map(|texel| half_texel(Vec3::new(texel.x, texel.y, 0.0)).to_vec())
+            .collect();
+        let brdf_lut_buffer = device
+            .create_buffer_mapped(brdf_lut_texels.len(), wgpu::BufferUsage::COPY_SRC)
+            .fill_from_slice(&brdf_lut_texels);
+        encoder.copy_buffer_to_texture(
+            wgpu::BufferCopyView {
+                buffer: &brdf_lut_buffer,
+                offset: 0,
+                row_pitch: 8 * BRDF_LUT_SIZE,
+                image_height: BRDF_LUT_SIZE,
+            },
+            wgpu::TextureCopyView {
+                texture: &brdf_lut_texture,
+                mip_level: 0,
+                array_layer: 0,
+                origin: wgpu::Origin3d {
+                    x: 0.0,
+                    y: 0.0,
+                    z: 0.0,
+                },
+            },
+            brdf_lut_extent,
+        );
+        let brdf_lut_view = brdf_lut_texture.create_default_view();
+
+        // The prefiltered map is sampled at a level of detail picked by the shader, which the clamp mustn't limit.
+        let sampler = device.create_sampler(&wgpu::SamplerDescriptor {
+            address_mode_u: wgpu::AddressMode::ClampToEdge,
+            address_mode_v: wgpu::AddressMode::ClampToEdge,
+            address_mode_w: wgpu::AddressMode::ClampToEdge,
+            mag_filter: wgpu::FilterMode::Linear,
+            min_filter: wgpu::FilterMode::Linear,
+            mipmap_filter: wgpu::FilterMode::Linear,
+            lod_min_clamp: 0.0,
+            lod_max_clamp: PREFILTERED_MIP_LEVELS as f32,
+            compare_function: wgpu::CompareFunction::Never,
+        });
+
+        Self {
+            environment_texture,
+            environment_view,
+            irradiance_texture,
+            irradiance_view,
+            prefiltered_texture,
+            prefiltered_view,
+            brdf_lut_texture,
+            brdf_lut_view,
+            sampler,
+        }
+    }
+
+    /// The layout of the bind group created by [`create_bind_group`](Self::create_bind_group): the irradiance map at
+    /// binding `0`, the prefiltered map at `1`, the BRDF lookup texture at `2` and the sampler at `3`.
+    pub fn create_bind_group_layout(device: &wgpu::Device) -> wgpu::BindGroupLayout {
+        device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            bindings: &[
+                wgpu::BindGroupLayoutBinding {
+                    binding: 0,
+                    visibility: wgpu::ShaderStage::FRAGMENT,
+                    ty: wgpu::BindingType::SampledTexture {
+                        multisampled: false,
+                        dimension: wgpu::TextureViewDimension::Cube,
+                    },
+                },
+                wgpu::BindGroupLayoutBinding {
+                    binding: 1,
+                    visibility: wgpu::ShaderStage::FRAGMENT,
+                    ty: wgpu::BindingType::SampledTexture {
+                        multisampled: false,
+                        dimension: wgpu::TextureViewDimension::Cube,
+                    },
+                },
+                wgpu::BindGroupLayoutBinding {
+                    binding: 2,
+                    visibility: wgpu::ShaderStage::FRAGMENT,
+                    ty: wgpu::BindingType::SampledTexture {
+                        multisampled: false,
+                        dimension: wgpu::TextureViewDimension::D2,
+                    },
+                },
+                wgpu::BindGroupLayoutBinding {
+                    binding: 3,
+                    visibility: wgpu::ShaderStage::FRAGMENT,
+                    ty: wgpu::BindingType::Sampler,
+                },
+            ],
+        })
+    }
+
+    pub fn create_bind_group(
+        &self,
+        device: &wgpu::Device,
+        layout: &wgpu::BindGroupLayout,
+    ) -> wgpu::BindGroup {
+        device.create_bind_group(&wgpu::BindGroupDescriptor {
+            layout,
+            bindings: &[
+                wgpu::Binding {
+                    binding: 0,
+                    resource: wgpu::BindingResource::TextureView(&self.irradiance_view),
+                },
+                wgpu::Binding {
+                    binding: 1,
+                    resource: wgpu::BindingResource::TextureView(&self.prefiltered_view),
+                },
+                wgpu::Binding {
+                    binding: 2,
+                    resource: wgpu::BindingResource::TextureView(&self.brdf_lut_view),
+                },
+                wgpu::Binding {
+                    binding: 3,
+                    resource: wgpu::BindingResource::Sampler(&self.sampler),
+                },
+            ],
+        })
+    }
+}
+
+/// The directory the precomputed maps for the environment map at `path` are cached in.
+pub fn cache_directory<P: AsRef<Path>>(path: P) -> PathBuf {
+    path.as_ref().with_extension("ibl")
+}
+
+/// The direction through the point at `u` and `v` in `0.0..=1.0` on a face of a cube map, normalized.
+pub fn face_direction(face: usize, u: f32, v: f32) -> Vec3 {
+    let s = u * 2.0 - 1.0;
+    let t = v * 2.0 - 1.0;
+    match face {
+        0 => Vec3::new(1.0, -t, -s),
+        1 => Vec3::new(-1.0, -t, s),
+        2 => Vec3::new(s, 1.0, t),
+        3 => Vec3::new(s, -1.0, -t),
+        4 => Vec3::new(s, -t, 1.0),
+        _ => Vec3::new(-s, -t, -1.0),
+    }
+    .normalized()
+}
+
+/// The face of a cube map `direction` points at and the coordinates on that face, the inverse of [`face_direction`].
+///
+/// ```
+/// # use ultraviolet::Vec3;
+/// #
+/// # use learnopengl_but_its_wgpu::ibl::{face_coordinates, face_direction};
+/// #
+/// assert_eq!((0, 0.5, 0.5), face_coordinates(Vec3::new(2.0, 0.0, 0.0)));
+/// assert_eq!((3, 0.5, 0.5), face_coordinates(Vec3::new(0.0, -1.0, 0.0)));
+/// for face in 0..6 {
+///     let (u, v) = (0.2, 0.7);
+///     let (face_again, u_again, v_again) = face_coordinates(face_direction(face, u, v));
+///     assert_eq!(face, face_again);
+///     assert!((u - u_again).abs() < 1e-5 && (v - v_again).abs() < 1e-5);
+/// }
+/// ```
+pub fn face_coordinates(direction: Vec3) -> (usize, f32, f32) {
+    let Vec3 { x, y, z } = direction;
+    let (ax, ay, az) = (x.abs(), y.abs(), z.abs());
+    // The axis with the largest magnitude picks the face, and the other two are projected onto it.
+    let (face, s, t, major) = if ax >= ay && ax >= az {
+        if x > 0.0 {
+            (0, -z, -y, ax)
+        } else {
+            (1, z, -y, ax)
+        }
+    } else if ay >= az {
+        if y > 0.0 {
+            (2, x, z, ay)
+        } else {
+            (3, x, -z, ay)
+        }
+    } else if z > 0.0 {
+        (4, x, -y, az)
+    } else {
+        (5, -x, -y, az)
+    };
+    (face, (s / major + 1.0) * 0.5, (t / major + 1.0) * 0.5)
+}
+
+/// Bilinearly samples an equirectangular image in `direction`, which has to be normalized. The top row of the image is
+/// straight up, and the center looks along +X.
+pub fn sample_equirectangular(image: &HdrImage, direction: Vec3) -> Vec3 {
+    let u = direction.z.atan2(direction.x) / (2.0 * PI) + 0.5;
+    let v = direction.y.clamp(-1.0, 1.0).acos() / PI;
+
+    let (width, height) = (image.width as f32, image.height as f32);
+    // The image wraps around horizontally, but not vertically.
+    let x = (u * width - 0.5).rem_euclid(width);
+    let y = (v * height - 0.5).max(0.0).min(height - 1.0);
+    let (x0, y0) = (x.floor() as u32 % image.width, y.floor() as u32);
+    let (x1, y1) = ((x0 + 1) % image.width, (y0 + 1).min(image.height - 1));
+    let (tx, ty) = (x - x.floor(), y - y0 as f32);
+
+    let top = image.pixel(x0, y0) * (1.0 - tx) + image.pixel(x1, y0) * tx;
+    let bottom = image.pixel(x0, y1) * (1.0 - tx) + image.pixel(x1, y1) * tx;
+    top * (1.0 - ty) + bottom * ty
+}
+
+/// The direction through the point at `u` and `v` in `0.0..=1.0` on an equirectangular image, the inverse of the
+/// mapping [`sample_equirectangular`] uses.
+pub fn equirectangular_direction(u: f32, v: f32) -> Vec3 {
+    let azimuth = (u - 0.5) * 2.0 * PI;
+    let polar = v * PI;
+    Vec3::new(
+        polar.sin() * azimuth.cos(),
+        polar.cos(),
+        polar.sin() * azimuth.sin(),
+    )
+}
+
+/// Convolves the environment with a cosine lobe around each normal, which is the light a diffuse surface receives.
+/// The result is divided by pi like the Lambertian BRDF, so it just has to be multiplied with the albedo.
+///
+/// `environment` has to hold every mip level of the environment map, since each sample reads from the level whose
+/// texels cover about as much of the sphere as the sample stands for. That's far less noisy than using just the first
+/// level with the same number of samples.
+///
+/// ```
+/// # use ultraviolet::Vec3;
+/// #
+/// # use learnopengl_but_its_wgpu::ibl::{irradiance_map, CubeMap};
+/// #
+/// // A uniformly white environment lights a diffuse surface with pi, which is divided by pi again.
+/// let white = CubeMap::from_fn(16, |_| Vec3::new(1.0, 1.0, 1.0));
+/// let irradiance = irradiance_map(&white.mip_chain(), 4);
+/// for &texel in &irradiance.texels {
+///     assert!((texel - Vec3::new(1.0, 1.0, 1.0)).mag() < 1e-3);
+/// }
+///
+/// // Light only coming from above reaches surfaces facing up the most, and none facing down.
+/// let sky = CubeMap::from_fn(16, |direction| Vec3::new(1.0, 1.0, 1.0) * direction.y.max(0.0));
+/// let irradiance = irradiance_map(&sky.mip_chain(), 4);
+/// let up = irradiance.sample(Vec3::new(0.0, 1.0, 0.0)).x;
+/// let side = irradiance.sample(Vec3::new(1.0, 0.0, 0.0)).x;
+/// let down = irradiance.sample(Vec3::new(0.0, -1.0, 0.0)).x;
+/// assert!(up > side && side > down);
+/// assert!(down < 0.05);
+/// ```
+pub fn irradiance_map(environment: &[CubeMap], size: u32) -> CubeMap {
+    let texel_solid_angle = texel_solid_angle(environment[0].size);
+    CubeMap::from_fn(size, |normal| {
+        let (tangent, bitangent) = tangent_basis(normal);
+        let mut irradiance = Vec3::zero();
+        for i in 0..IRRADIANCE_SAMPLE_COUNT {
+            // Cosine weighted samples of the hemisphere cancel out the cosine term, leaving the average radiance.
+            let xi = hammersley(i, IRRADIANCE_SAMPLE_COUNT);
+            let phi = 2.0 * PI * xi.x;
+            let cos_theta = (1.0 - xi.y).sqrt();
+            let sin_theta = xi.y.sqrt();
+            let direction = tangent * (phi.cos() * sin_theta)
+                + bitangent * (phi.sin() * sin_theta)
+                + normal * cos_theta;
+
+            let pdf = cos_theta / PI;
+            let sample_solid_angle = 1.0 / (IRRADIANCE_SAMPLE_COUNT as f32 * pdf + 1e-4);
+            let lod = 0.5 * (sample_solid_angle / texel_solid_angle).log2() + 1.0;
+            irradiance += sample_lod(environment, direction, lod);
+        }
+        irradiance / IRRADIANCE_SAMPLE_COUNT as f32
+    })
+}
+
+/// Convolves the environment with the GGX distribution of the given roughness, assuming the viewer looks straight
+/// along the normal. `environment` has to hold every mip level, like for [`irradiance_map`].
+///
+/// A roughness of `0.0` is a perfect mirror, which just downsamples the environment to `size`.
+///
+/// ```
+/// # use ultraviolet::Vec3;
+/// #
+/// # use learnopengl_but_its_wgpu::ibl::{prefiltered_map, CubeMap};
+/// #
+/// let environment = CubeMap::from_fn(16, |direction| Vec3::new(1.0, 1.0, 1.0) * direction.y.max(0.0));
+/// let mips = environment.mip_chain();
+/// let up = Vec3::new(0.0, 1.0, 0.0);
+///
+/// let mirror = prefiltered_map(&mips, 8, 0.0);
+/// let rough = prefiltered_map(&mips, 8, 1.0);
+/// assert!((mirror.sample(up).x - 1.0).abs() < 0.05);
+/// // Rough reflections blur in the dimmer light from around the reflected direction.
+/// assert!(rough.sample(up).x < mirror.sample(up).x - 0.1);
+/// assert!(rough.sample(Vec3::new(1.0, 0.0, 0.0)).x > 0.1);
+/// ```
+pub fn prefiltered_map(environment: &[CubeMap], size: u32, roughness: f32) -> CubeMap {
+    let texel_solid_angle = texel_solid_angle(environment[0].size);
+    if roughness == 0.0 {
+        let lod = (environment[0].size as f32 / size as f32).log2();
+        return CubeMap::from_fn(size, |direction| sample_lod(environment, direction, lod));
+    }
+
+    CubeMap::from_fn(size, |normal| {
+        let mut radiance = Vec3::zero();
+        let mut total_weight = 0.0;
+        for i in 0..PREFILTER_SAMPLE_COUNT {
+            let halfway =
+                importance_sample_ggx(hammersley(i, PREFILTER_SAMPLE_COUNT), normal, roughness);
+            let light = halfway * (2.0 * normal.dot(halfway)) - normal;
+            let n_dot_l = normal.dot(light);
+            if n_dot_l <= 0.0 {
+                continue;
+            }
+
+            // With the view direction along the normal, the probability of the reflected direction simplifies to the
+            // distribution over four.
+            let n_dot_h = normal.dot(halfway).max(0.0);
+            let pdf = distribution_ggx(n_dot_h, roughness) / 4.0 + 1e-4;
+            let sample_solid_angle = 1.0 / (PREFILTER_SAMPLE_COUNT as f32 * pdf + 1e-4);
+            let lod = 0.5 * (sample_solid_angle / texel_solid_angle).log2();
+
+            radiance += sample_lod(environment, light, lod) * n_dot_l;
+            total_weight += n_dot_l;
+        }
+        radiance / total_weight
+    })
+}
+
+/// Integrates the specular BRDF over the hemisphere for each combination of the cosine of the viewing angle (along the
+/// rows) and the roughness (down the columns), at the centers of the texels of a `size` by `size` texture. The Fresnel
+/// term is factored out into a scale and a bias for the reflectance at normal incidence.
+///
+/// ```
+/// # use learnopengl_but_its_wgpu::ibl::brdf_lut;
+/// #
+/// let size = 16;
+/// let lut = brdf_lut(size);
+/// assert_eq!((size * size) as usize, lut.len());
+/// for texel in &lut {
+///     assert!(texel.x >= 0.0 && texel.y >= 0.0 && texel.x + texel.y <= 1.0 + 1e-3);
+/// }
+/// // A smooth surface seen head on reflects exactly the reflectance at normal incidence.
+/// let smooth_head_on = lut[(size - 1) as usize];
+/// assert!((smooth_head_on.x - 1.0).abs() < 0.05 && smooth_head_on.y < 0.05);
+/// // Rough surfaces lose energy to masking and shadowing between their microfacets.
+/// let rough_head_on = lut[(size * size - 1) as usize];
+/// assert!(rough_head_on.x + rough_head_on.y < smooth_head_on.x + smooth_head_on.y);
+/// ```
+pub fn brdf_lut(size: u32) -> Vec<Vec2> {
+    let mut texels = Vec::with_capacity((size * size) as usize);
+    for y in 0..size {
+        let roughness = (y as f32 + 0.5) / size as f32;
+        for x in 0..size {
+            let n_dot_v = (x as f32 + 0.5) / size as f32;
+            texels.push(integrate_brdf(n_dot_v, roughness));
+        }
+    }
+    texels
+}
+
+fn integrate_brdf(n_dot_v: f32, roughness: f32) -> Vec2 {
+    // The integral only depends on the angle between the view direction and the normal, so the normal is fixed.
+    let normal = Vec3::new(0.0, 0.0, 1.0);
+    let view = Vec3::new((1.0 - n_dot_v * n_dot_v).sqrt(), 0.0, n_dot_v);
+
+    let mut scale = 0.0;
+    let mut bias = 0.0;
+    for i in 0..BRDF_SAMPLE_COUNT {
+        let halfway = importance_sample_ggx(hammersley(i, BRDF_SAMPLE_COUNT), normal, roughness);
+        let light = halfway * (2.0 * view.dot(halfway)) - view;
+
+        let n_dot_l = light.z.max(0.0);
+        let n_dot_h = halfway.z.max(0.0);
+        let v_dot_h = view.dot(halfway).max(0.0);
+        if n_dot_l > 0.0 {
+            let g = geometry_smith_ibl(n_dot_v, n_dot_l, roughness);
+            // The BRDF times the cosine term divided by the probability of the sample, without the Fresnel term.
+            let g_visibility = g * v_dot_h / (n_dot_h * n_dot_v);
+            let fresnel = (1.0 - v_dot_h).powi(5);
+            scale += (1.0 - fresnel) * g_visibility;
+            bias += fresnel * g_visibility;
+        }
+    }
+    Vec2::new(scale, bias) / BRDF_SAMPLE_COUNT as f32
+}
+
+/// The `i`th of `count` points of the Hammersley sequence, which covers the unit square more evenly than random points.
+pub fn hammersley(i: u32, count: u32) -> Vec2 {
+    // The radical inverse in base 2 mirrors the bits of `i` around the binary point.
+    Vec2::new(
+        i as f32 / count as f32,
+        i.reverse_bits() as f32 / 4_294_967_296.0,
+    )
+}
+
+/// Turns a point of the unit square into a halfway vector around `normal`, distributed like the microfacets of a GGX
+/// surface with the given roughness.
+pub fn importance_sample_ggx(xi: Vec2, normal: Vec3, roughness: f32) -> Vec3 {
+    let a = roughness * roughness;
+    let phi = 2.0 * PI * xi.x;
+    let cos_theta = ((1.0 - xi.y) / (1.0 + (a * a - 1.0) * xi.y)).sqrt();
+    let sin_theta = (1.0 - cos_theta * cos_theta).max(0.0).sqrt();
+
+    let (tangent, bitangent) = tangent_basis(normal);
+    (tangent * (phi.cos() * sin_theta) + bitangent * (phi.sin() * sin_theta) + normal * cos_theta)
+        .normalized()
+}
+
+/// Converts a 32 bit float into the bits of the closest 16 bit float, as used by [`IBL_FORMAT`]. Values too large to
+/// be represented become infinity.
+///
+/// ```
+/// # use learnopengl_but_its_wgpu::ibl::f16_bits;
+/// #
+/// assert_eq!(0x0000, f16_bits(0.0));
+/// assert_eq!(0x3c00, f16_bits(1.0));
+/// assert_eq!(0x3800, f16_bits(0.5));
+/// assert_eq!(0xc000, f16_bits(-2.0));
+/// assert_eq!(0x3555, f16_bits(1.0 / 3.0));
+/// assert_eq!(0x7bff, f16_bits(65504.0));
+/// assert_eq!(0x7c00, f16_bits(1e6));
+/// assert_eq!(0x0001, f16_bits(2f32.powi(-24)));
+/// ```
+pub fn f16_bits(value: f32) -> u16 {
+    let bits = value.to_bits();
+    let sign = ((bits >> 16) & 0x8000) as u16;
+    let exponent = ((bits >> 23) & 0xff) as i32;
+    let mantissa = bits & 0x7f_ffff;
+
+    if exponent == 0xff {
+        // Infinity stays infinity, and NaN stays NaN.
+        return sign | 0x7c00 | if mantissa != 0 { 0x200 } else { 0 };
+    }
+    // Rebias the exponent from 127 to 15.
+    let exponent = exponent - 127 + 15;
+    if exponent >= 0x1f {
+        return sign | 0x7c00;
+    }
+    if exponent <= 0 {
+        // Too small for a normal half float, so it becomes subnormal with the implicit leading one made explicit.
+        if exponent < -10 {
+            return sign;
+        }
+        let mantissa = mantissa | 0x80_0000;
+        let shift = (14 - exponent) as u32;
+        return sign | ((mantissa + (1 << (shift - 1))) >> shift) as u16;
+    }
+
+    // Rounding up can carry into the exponent, which still gives the right result.
+    let half = (exponent as u32) << 10 | mantissa >> 13;
+    let round = (mantissa >> 12) & 1;
+    sign | (half + round) as u16
+}
+
+fn create_cube_texture(
+    device: &wgpu::Device,
+    encoder: &mut wgpu::CommandEncoder,
+    levels: &[&CubeMap],
+) -> (wgpu::Texture, wgpu::TextureView) {
+    let size = levels[0].size;
+    let texture = device.create_texture(&wgpu::TextureDescriptor {
+        size: wgpu::Extent3d {
+            width: size,
+            height: size,
+            depth: 1,
+        },
+        array_layer_count: 6,
+        mip_level_count: levels.len() as u32,
+        sample_count: 1,
+        dimension: wgpu::TextureDimension::D2,
+        format: IBL_FORMAT,
+        usage: wgpu::TextureUsage::SAMPLED | wgpu::TextureUsage::COPY_DST,
+    });
+
+    for (level, cube_map) in levels.iter().enumerate() {
+        let texels: Vec<u16> = cube_map
+            .texels
+            .iter()
+            .flat_map(|&texel| half_texel(texel).to_vec())
+            .collect();
+        let buffer = device
+            .create_buffer_mapped(texels.len(), wgpu::BufferUsage::COPY_SRC)
+            .fill_from_slice(&texels);
+
+        // Each face is an array layer of its own.
+        let face_size = 8 * cube_map.size * cube_map.size;
+        for face in 0..6 {
+            encoder.copy_buffer_to_texture(
+                wgpu::BufferCopyView {
+                    buffer: &buffer,
+                    offset: (face * face_size) as wgpu::BufferAddress,
+                    row_pitch: 8 * cube_map.size,
+                    image_height: cube_map.size,
+                },
+                wgpu::TextureCopyView {
+                    texture: &texture,
+                    mip_level: level as u32,
+                    array_layer: face,
+                    origin: wgpu::Origin3d {
+                        x: 0.0,
+                        y: 0.0,
+                        z: 0.0,
+                    },
+                },
+                wgpu::Extent3d {
+                    width: cube_map.size,
+                    height: cube_map.size,
+                    depth: 1,
+                },
+            );
+        }
+    }
+
+    let view = texture.create_view(&wgpu::TextureViewDescriptor {
+        format: IBL_FORMAT,
+        dimension: wgpu::TextureViewDimension::Cube,
+        aspect: wgpu::TextureAspect::All,
+        base_mip_level: 0,
+        level_count: levels.len() as u32,
+        base_array_layer: 0,
+        array_layer_count: 6,
+    });
+    (texture, view)
+}
+
+fn half_texel(texel: Vec3) -> [u16; 4] {
+    [
+        f16_bits(texel.x),
+        f16_bits(texel.y),
+        f16_bits(texel.z),
+        f16_bits(1.0),
+    ]
+}
+
+/// Trilinearly samples a mip chain, clamping `lod` to the available levels.
+fn sample_lod(levels: &[CubeMap], direction: Vec3, lod: f32) -> Vec3 {
+    let lod = lod.max(0.0).min((levels.len() - 1) as f32);
+    let lower = lod.floor() as usize;
+    let upper = (lower + 1).min(levels.len() - 1);
+    let t = lod - lower as f32;
+    levels[lower].sample(direction) * (1.0 - t) + levels[upper].sample(direction) * t
+}
+
+/// The solid angle covered by a texel of a cube map with faces of the given size, on average.
+fn texel_solid_angle(size: u32) -> f32 {
+    4.0 * PI / (6 * size * size) as f32
+}
+
+/// Two directions perpendicular to `normal` and to each other.
+fn tangent_basis(normal: Vec3) -> (Vec3, Vec3) {
+    let up = if normal.z.abs() < 0.999 {
+        Vec3::new(0.0, 0.0, 1.0)
+    } else {
+        Vec3::new(1.0, 0.0, 0.0)
+    };
+    let tangent = up.cross(normal).normalized();
+    let bitangent = normal.cross(tangent);
+    (tangent, bitangent)
+}
+
+/// Trowbridge-Reitz GGX, like in the shaders.
+fn distribution_ggx(n_dot_h: f32, roughness: f32) -> f32 {
+    let a = roughness * roughness;
+    let a2 = a * a;
+    let denominator = n_dot_h * n_dot_h * (a2 - 1.0) + 1.0;
+    a2 / (PI * denominator * denominator)
+}
+
+/// Smith's method with Schlick-GGX, using the remapping of the roughness for image based lighting.
+fn geometry_smith_ibl(n_dot_v: f32, n_dot_l: f32, roughness: f32) -> f32 {
+    let k = roughness * roughness / 2.0;
+    let schlick_ggx = |n_dot: f32| n_dot / (n_dot * (1.0 - k) + k);
+    schlick_ggx(n_dot_v) * schlick_ggx(n_dot_l)
+}
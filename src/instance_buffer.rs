@@ -0,0 +1,250 @@
+use std::{fmt::Debug, mem, ops::Range};
+
+use ultraviolet::{Mat3, Mat4, Vec2, Vec3, Vec4};
+
+use crate::Align16;
+
+/// Changed instances separated by at most this many unchanged ones are uploaded with a single copy, since issuing a
+/// copy per tiny range tends to cost more than re-uploading a few instances that didn't change.
+const MERGE_DISTANCE: usize = 16;
+
+/// Data that can be stored in an [`InstanceBuffer`].
+///
+/// The buffer has to know which instances changed since the last upload. The vector and matrix types instances are made
+/// of don't implement `PartialEq`, and comparing their memory would read the padding between fields, so every instance
+/// type compares its fields itself.
+pub trait InstanceData: Copy + 'static {
+    /// Whether `self` and `other` hold the same values, and so would look the same to a shader.
+    fn same_as(&self, other: &Self) -> bool;
+}
+
+/// A GPU array of per-instance data that can be replaced every frame.
+///
+/// This is a vertex buffer unless created with another usage, such as a storage buffer of lights.
+///
+/// The buffer keeps a copy of what it last uploaded so that [`update`](InstanceBuffer::update) only has to copy the
+/// instances that actually changed. When more instances are passed than fit, the GPU buffer is recreated with its
+/// capacity doubled until they fit.
+pub struct InstanceBuffer<T> {
+    buffer: wgpu::Buffer,
+    usage: wgpu::BufferUsage,
+    capacity: usize,
+    instances: Vec<T>,
+}
+
+impl<T> InstanceBuffer<T>
+where
+    T: InstanceData,
+{
+    /// Creates an empty vertex buffer with room for `capacity` instances.
+    pub fn new(device: &wgpu::Device, capacity: usize) -> Self {
+        Self::with_usage(device, capacity, wgpu::BufferUsage::VERTEX)
+    }
+
+    /// Creates an empty buffer with room for `capacity` instances that can be used as `usage`.
+    pub fn with_usage(device: &wgpu::Device, capacity: usize, usage: wgpu::BufferUsage) -> Self {
+        let capacity = capacity.max(1);
+        let usage = usage | wgpu::BufferUsage::COPY_DST;
+        Self {
+            buffer: Self::create_buffer(device, capacity, usage),
+            usage,
+            capacity,
+            instances: Vec::with_capacity(capacity),
+        }
+    }
+
+    /// Creates a vertex buffer filled with `instances`.
+    pub fn from_slice(device: &wgpu::Device, instances: &[T]) -> Self {
+        Self::from_slice_with_usage(device, instances, wgpu::BufferUsage::VERTEX)
+    }
+
+    /// Creates a buffer filled with `instances` that can be used as `usage`.
+    pub fn from_slice_with_usage(
+        device: &wgpu::Device,
+        instances: &[T],
+        usage: wgpu::BufferUsage,
+    ) -> Self {
+        if instances.is_empty() {
+            return Self::with_usage(device, 1, usage);
+        }
+
+        let usage = usage | wgpu::BufferUsage::COPY_DST;
+        let buffer = device
+            .create_buffer_mapped(instances.len(), usage)
+            .fill_from_slice(instances);
+        Self {
+            buffer,
+            usage,
+            capacity: instances.len(),
+            instances: instances.to_vec(),
+        }
+    }
+
+    /// Replaces the contents of the buffer with `instances`, recording the necessary copies into `encoder`.
+    ///
+    /// Returns `true` if the GPU buffer had to be recreated, in which case any bind group referring to it has to be
+    /// recreated too.
+    pub fn update(
+        &mut self,
+        device: &wgpu::Device,
+        encoder: &mut wgpu::CommandEncoder,
+        instances: &[T],
+    ) -> bool {
+        let reallocated = instances.len() > self.capacity;
+        let dirty_ranges = if reallocated {
+            let mut capacity = self.capacity;
+            while capacity < instances.len() {
+                capacity *= 2;
+            }
+            self.buffer = Self::create_buffer(device, capacity, self.usage);
+            self.capacity = capacity;
+
+            vec![(0..instances.len())]
+        } else {
+            changed_ranges(&self.instances, instances)
+        };
+
+        self.instances.clear();
+        self.instances.extend_from_slice(instances);
+
+        if dirty_ranges.is_empty() {
+            return reallocated;
+        }
+
+        let dirty_count = dirty_ranges.iter().map(|range| range.len()).sum();
+        let staging_buffer = device.create_buffer_mapped(dirty_count, wgpu::BufferUsage::COPY_SRC);
+        let mut staging_offset = 0;
+        for range in &dirty_ranges {
+            staging_buffer.data[staging_offset..staging_offset + range.len()]
+                .copy_from_slice(&instances[range.clone()]);
+            staging_offset += range.len();
+        }
+        let staging_buffer = staging_buffer.finish();
+
+        let stride = mem::size_of::<T>() as wgpu::BufferAddress;
+        let mut staging_offset = 0;
+        for range in dirty_ranges {
+            encoder.copy_buffer_to_buffer(
+                &staging_buffer,
+                staging_offset * stride,
+                &self.buffer,
+                range.start as wgpu::BufferAddress * stride,
+                range.len() as wgpu::BufferAddress * stride,
+            );
+            staging_offset += range.len() as wgpu::BufferAddress;
+        }
+
+        reallocated
+    }
+
+    pub fn buffer(&self) -> &wgpu::Buffer {
+        &self.buffer
+    }
+
+    pub fn instances(&self) -> &[T] {
+        &self.instances
+    }
+
+    pub fn len(&self) -> usize {
+        self.instances.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.instances.is_empty()
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// The size of the whole GPU buffer in bytes, including the unused capacity.
+    pub fn size(&self) -> wgpu::BufferAddress {
+        (self.capacity * mem::size_of::<T>()) as wgpu::BufferAddress
+    }
+
+    fn create_buffer(
+        device: &wgpu::Device,
+        capacity: usize,
+        usage: wgpu::BufferUsage,
+    ) -> wgpu::Buffer {
+        device.create_buffer(&wgpu::BufferDescriptor {
+            size: (capacity * mem::size_of::<T>()) as wgpu::BufferAddress,
+            usage,
+        })
+    }
+}
+
+/// Finds the ranges of `new` that differ from `old`, merging ranges that are close to each other.
+fn changed_ranges<T: InstanceData>(old: &[T], new: &[T]) -> Vec<Range<usize>> {
+    let mut ranges: Vec<Range<usize>> = Vec::new();
+
+    let changed = new
+        .iter()
+        .enumerate()
+        .filter(|&(i, instance)| match old.get(i) {
+            Some(old) => !old.same_as(instance),
+            None => true,
+        })
+        .map(|(i, _)| i);
+    for i in changed {
+        match ranges.last_mut() {
+            Some(last) if i - last.end <= MERGE_DISTANCE => last.end = i + 1,
+            _ => ranges.push(i..i + 1),
+        }
+    }
+
+    ranges
+}
+
+macro_rules! impl_instance_data_for_primitives {
+    ($($primitive:ty),*) => {
+        $(
+            impl InstanceData for $primitive {
+                fn same_as(&self, other: &Self) -> bool {
+                    self == other
+                }
+            }
+        )*
+    };
+}
+
+impl_instance_data_for_primitives!(f32, i32, u32);
+
+macro_rules! impl_instance_data_for_vectors {
+    ($($vector:ty => $($component:ident),*;)*) => {
+        $(
+            impl InstanceData for $vector {
+                fn same_as(&self, other: &Self) -> bool {
+                    $(self.$component == other.$component)&&*
+                }
+            }
+        )*
+    };
+}
+
+impl_instance_data_for_vectors! {
+    Vec2 => x, y;
+    Vec3 => x, y, z;
+    Vec4 => x, y, z, w;
+}
+
+impl InstanceData for Mat3 {
+    fn same_as(&self, other: &Self) -> bool {
+        self.cols.iter().zip(&other.cols).all(|(a, b)| a.same_as(b))
+    }
+}
+
+impl InstanceData for Mat4 {
+    fn same_as(&self, other: &Self) -> bool {
+        self.cols.iter().zip(&other.cols).all(|(a, b)| a.same_as(b))
+    }
+}
+
+impl<T> InstanceData for Align16<T>
+where
+    T: InstanceData + Debug,
+{
+    fn same_as(&self, other: &Self) -> bool {
+        self.0.same_as(&other.0)
+    }
+}
@@ -2,9 +2,31 @@ use std::fmt::Debug;
 
 use amethyst_input::{Button, InputEvent, InputHandler, ScrollDirection, StringBindings};
 use shrev::{EventChannel, ReaderId};
-use ultraviolet::{Mat4, Rotor3, Vec3};
+use ultraviolet::{Mat3, Mat4, Rotor3, Vec3};
+
+use crate::instance_buffer::InstanceData;
+
+pub mod animation;
+pub mod bloom;
+pub mod cluster;
+pub mod deferred;
+pub mod hdr;
+pub mod ibl;
+pub mod instance_buffer;
+pub mod light_animation;
+pub mod lighting;
+pub mod mesh;
+pub mod pbr;
+pub mod radiance;
+pub mod scene;
+pub mod shadow;
+pub mod spherical_harmonics;
+pub mod ssao;
+pub mod time;
 
 pub struct Camera {
+    /// The translation the view matrix applies to the world to move the camera to the origin. This is the negation of
+    /// where the camera is; see [`world_translation`](Camera::world_translation).
     pub translation: Vec3,
     pub pitch: f32,
     pub yaw: f32,
@@ -19,15 +41,29 @@ pub struct Camera {
 
 impl Camera {
     pub fn get_view_projection_matrix(&self, width: f32, height: f32, near: f32, far: f32) -> Mat4 {
-        ultraviolet::projection::perspective_vk(self.zoom, width / height, near, far)
-            * (Rotor3::from_rotation_xy(self.roll)
-                * Rotor3::from_rotation_yz(self.pitch)
-                * Rotor3::from_rotation_xz(self.yaw))
-            .into_matrix()
-            .into_homogeneous()
+        self.get_projection_matrix(width, height, near, far) * self.get_view_matrix()
+    }
+
+    /// Transforms world space into view space, where the camera sits at the origin.
+    pub fn get_view_matrix(&self) -> Mat4 {
+        (Rotor3::from_rotation_xy(self.roll)
+            * Rotor3::from_rotation_yz(self.pitch)
+            * Rotor3::from_rotation_xz(self.yaw))
+        .into_matrix()
+        .into_homogeneous()
             * Mat4::from_translation(self.translation)
     }
 
+    /// Where the camera is in world space. The view matrix translates the world by `translation` before rotating it,
+    /// which brings the point at `-translation` to the origin.
+    pub fn world_translation(&self) -> Vec3 {
+        -self.translation
+    }
+
+    pub fn get_projection_matrix(&self, width: f32, height: f32, near: f32, far: f32) -> Mat4 {
+        ultraviolet::projection::perspective_vk(self.zoom, width / height, near, far)
+    }
+
     pub fn get_direction_vector(&self) -> Vec3 {
         Vec3 {
             x: -self.yaw.sin() * self.pitch.cos(),
@@ -147,3 +183,89 @@ where
         Self(from)
     }
 }
+
+#[repr(C)]
+#[derive(Clone, Copy, Debug)]
+pub struct Instance {
+    pub model: Mat4,
+    /// The inverse transpose of the upper-left 3x3 part of `model`, used to transform normals.
+    pub normal_matrix: Mat3,
+}
+
+impl InstanceData for Instance {
+    fn same_as(&self, other: &Self) -> bool {
+        self.model.same_as(&other.model) && self.normal_matrix.same_as(&other.normal_matrix)
+    }
+}
+
+impl Instance {
+    /// Creates an instance with the normal matrix derived from `model`.
+    ///
+    /// Transforming normals with `model` itself skews them under non-uniform scaling, while the normal matrix keeps
+    /// them perpendicular to the transformed surface:
+    ///
+    /// ```
+    /// # use ultraviolet::{Mat4, Vec3, Vec4};
+    /// #
+    /// # use learnopengl_but_its_wgpu::Instance;
+    /// #
+    /// let model = Mat4 {
+    ///     cols: [
+    ///         Vec4::new(4.0, 0.0, 0.0, 0.0),
+    ///         Vec4::new(0.0, 0.5, 0.0, 0.0),
+    ///         Vec4::new(0.0, 0.0, 1.0, 0.0),
+    ///         Vec4::new(1.0, 2.0, 3.0, 1.0),
+    ///     ],
+    /// };
+    /// let instance = Instance::new(model);
+    ///
+    /// // A surface spanned by these tangents has the normal `(1, 1, 0)`.
+    /// let tangents = [Vec3::new(1.0, -1.0, 0.0), Vec3::new(0.0, 0.0, 1.0)];
+    /// let normal = Vec3::new(1.0, 1.0, 0.0).normalized();
+    ///
+    /// let transform = |vector: Vec3| {
+    ///     let vector = model * vector.into_homogeneous_vector();
+    ///     Vec3::new(vector.x, vector.y, vector.z)
+    /// };
+    /// let transformed_normal = (instance.normal_matrix * normal).normalized();
+    /// let naive_normal = transform(normal).normalized();
+    ///
+    /// for &tangent in &tangents {
+    ///     let transformed_tangent = transform(tangent).normalized();
+    ///     assert!(transformed_tangent.dot(transformed_normal).abs() < 1e-6);
+    /// }
+    /// assert!(transform(tangents[0]).normalized().dot(naive_normal).abs() > 0.5);
+    /// ```
+    pub fn new(model: Mat4) -> Self {
+        Self {
+            model,
+            normal_matrix: normal_matrix(&model),
+        }
+    }
+}
+
+/// Calculates the inverse transpose of the upper-left 3x3 part of `model`.
+///
+/// The columns of the cofactor matrix of a 3x3 matrix are the cross products of its other two columns, and the inverse
+/// transpose is just the cofactor matrix divided by the determinant.
+pub fn normal_matrix(model: &Mat4) -> Mat3 {
+    let columns = [
+        Vec3::new(model.cols[0].x, model.cols[0].y, model.cols[0].z),
+        Vec3::new(model.cols[1].x, model.cols[1].y, model.cols[1].z),
+        Vec3::new(model.cols[2].x, model.cols[2].y, model.cols[2].z),
+    ];
+    let cofactors = [
+        columns[1].cross(columns[2]),
+        columns[2].cross(columns[0]),
+        columns[0].cross(columns[1]),
+    ];
+    let determinant = columns[0].dot(cofactors[0]);
+
+    Mat3 {
+        cols: [
+            cofactors[0] / determinant,
+            cofactors[1] / determinant,
+            cofactors[2] / determinant,
+        ],
+    }
+}
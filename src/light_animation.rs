@@ -0,0 +1,203 @@
+//! Time driven animations for point lights: orbiting, bobbing, color cycling and flickering.
+//!
+//! An animated light keeps the light it started out as and applies its animations to that on every frame, in the order
+//! they're listed. Each animation only depends on the time, so a light looks the same at the same point in time no
+//! matter how irregularly it's sampled.
+//!
+//! In a scene file, the animations are listed with the point light they belong to:
+//!
+//! ```ron
+//! (
+//!     translation: (0.0, 1.0, 0.0),
+//!     ambient: (0.05, 0.05, 0.05),
+//!     diffuse: (0.8, 0.8, 0.8),
+//!     specular: (1.0, 1.0, 1.0),
+//!     animations: [
+//!         Orbit(center: (0.0, 1.0, 0.0), radius: 2.0, angular_speed: 45.0),
+//!         Bob(amplitude: 0.25, frequency: 0.5),
+//!         Flicker(intensity: 0.3, rate: 8.0),
+//!     ],
+//! )
+//! ```
+
+use std::f32::consts::PI;
+
+use serde::Deserialize;
+use ultraviolet::Vec3;
+
+use crate::{lighting::PointLight, scene::vec3};
+
+#[derive(Clone, Debug, Deserialize, PartialEq)]
+pub enum LightAnimation {
+    /// Circles around `center` in the plane perpendicular to `axis`, replacing the light's translation. Angles are in
+    /// degrees.
+    Orbit {
+        center: [f32; 3],
+        /// Straight up by default, which makes for a horizontal orbit.
+        #[serde(default = "default_axis")]
+        axis: [f32; 3],
+        radius: f32,
+        /// In degrees per second. Negative speeds orbit the other way around.
+        angular_speed: f32,
+        /// How far around the orbit the light starts.
+        #[serde(default)]
+        phase: f32,
+    },
+    /// Moves the light up and down by up to `amplitude`, `frequency` times per second.
+    Bob {
+        amplitude: f32,
+        frequency: f32,
+        /// How far into a bob the light starts, in degrees.
+        #[serde(default)]
+        phase: f32,
+    },
+    /// Tints the ambient and diffuse colors with a color whose channels are sine waves of their own `frequencies`, in
+    /// cycles per second, like in the Materials chapter's exercise. The exercise's waves come to about
+    /// `(0.32, 0.11, 0.21)`. Unlike there, the waves are shifted into `0.0..1.0`, so that the light never goes negative.
+    ColorCycle { frequencies: [f32; 3] },
+    /// Dims the light by a random amount of up to `intensity`, which changes smoothly `rate` times per second. Lights
+    /// flickering with different `seed`s flicker differently.
+    Flicker {
+        intensity: f32,
+        rate: f32,
+        #[serde(default)]
+        seed: u32,
+    },
+}
+
+/// A point light's animations, along with the light they start from.
+#[derive(Clone, Debug)]
+pub struct PointLightAnimation {
+    pub rest: PointLight,
+    pub animations: Vec<LightAnimation>,
+}
+
+impl LightAnimation {
+    /// Applies the animation at `time` seconds to `light`.
+    pub fn apply(&self, light: &mut PointLight, time: f32) {
+        match *self {
+            LightAnimation::Orbit {
+                center,
+                axis,
+                radius,
+                angular_speed,
+                phase,
+            } => {
+                let angle = (phase + angular_speed * time).to_radians();
+                let (tangent, bitangent) = orbit_plane(vec3(axis).normalized());
+                let offset = tangent * angle.cos() + bitangent * angle.sin();
+                light.translation = (vec3(center) + offset * radius).into();
+            }
+            LightAnimation::Bob {
+                amplitude,
+                frequency,
+                phase,
+            } => {
+                let angle = 2.0 * PI * frequency * time + phase.to_radians();
+                light.translation.0.y += amplitude * angle.sin();
+            }
+            LightAnimation::ColorCycle { frequencies } => {
+                let wave = |frequency: f32| 0.5 + 0.5 * (2.0 * PI * frequency * time).sin();
+                let color = Vec3::new(
+                    wave(frequencies[0]),
+                    wave(frequencies[1]),
+                    wave(frequencies[2]),
+                );
+                light.ambient.0 *= color;
+                light.diffuse.0 *= color;
+            }
+            LightAnimation::Flicker {
+                intensity,
+                rate,
+                seed,
+            } => {
+                let brightness = 1.0 - intensity * smooth_noise(seed, time * rate);
+                light.ambient.0 *= brightness;
+                light.diffuse.0 *= brightness;
+                light.specular *= brightness;
+            }
+        }
+    }
+}
+
+impl PointLightAnimation {
+    /// The light as its animations leave it at `time` seconds.
+    ///
+    /// ```
+    /// use learnopengl_but_its_wgpu::{
+    ///     light_animation::{LightAnimation, PointLightAnimation},
+    ///     lighting::PointLight,
+    /// };
+    /// use ultraviolet::Vec3;
+    ///
+    /// let rest = PointLight {
+    ///     translation: Vec3::zero().into(),
+    ///     ambient: Vec3::zero().into(),
+    ///     diffuse: Vec3::one().into(),
+    ///     specular: Vec3::one(),
+    ///     constant: 1.0,
+    ///     linear: 0.09,
+    ///     quadratic: 0.032,
+    ///     shadow_map_index: -1,
+    /// };
+    /// let animation = PointLightAnimation {
+    ///     rest,
+    ///     animations: vec![LightAnimation::Orbit {
+    ///         center: [0.0, 1.0, 0.0],
+    ///         axis: [0.0, 1.0, 0.0],
+    ///         radius: 2.0,
+    ///         angular_speed: 90.0,
+    ///         phase: 0.0,
+    ///     }],
+    /// };
+    ///
+    /// // A quarter of the way around after a second.
+    /// let translation = animation.sample(1.0).translation.0;
+    /// assert!((translation - Vec3::new(0.0, 1.0, 2.0)).mag() < 1e-5);
+    /// // The rest of the light is left alone.
+    /// assert!((animation.sample(1.0).diffuse.0 - rest.diffuse.0).mag() < 1e-6);
+    /// ```
+    pub fn sample(&self, time: f32) -> PointLight {
+        let mut light = self.rest;
+        for animation in &self.animations {
+            animation.apply(&mut light, time);
+        }
+        light
+    }
+}
+
+/// Two directions spanning the plane perpendicular to `axis`, where an orbit starts out along the first one and heads
+/// towards the second one. Around the up axis, these are +X and +Z.
+fn orbit_plane(axis: Vec3) -> (Vec3, Vec3) {
+    let reference = if axis.x.abs() < 0.999 {
+        Vec3::new(1.0, 0.0, 0.0)
+    } else {
+        Vec3::new(0.0, 1.0, 0.0)
+    };
+    let tangent = (reference - axis * reference.dot(axis)).normalized();
+    (tangent, tangent.cross(axis))
+}
+
+fn default_axis() -> [f32; 3] {
+    [0.0, 1.0, 0.0]
+}
+
+/// Noise in `0.0..1.0` that smoothly blends between a random value at every whole number `x`.
+fn smooth_noise(seed: u32, x: f32) -> f32 {
+    let cell = x.floor();
+    let t = x - cell;
+    let previous = hash(seed, cell as i32 as u32);
+    let next = hash(seed, (cell as i32).wrapping_add(1) as u32);
+    previous + (next - previous) * t * t * (3.0 - 2.0 * t)
+}
+
+/// A random number in `0.0..1.0` for every `seed` and `x`.
+fn hash(seed: u32, x: u32) -> f32 {
+    let mut hash = x.wrapping_mul(0x9e37_79b9) ^ seed.wrapping_mul(0x85eb_ca6b);
+    hash ^= hash >> 16;
+    hash = hash.wrapping_mul(0x7feb_352d);
+    hash ^= hash >> 15;
+    hash = hash.wrapping_mul(0x846c_a68b);
+    hash ^= hash >> 16;
+    (hash >> 8) as f32 / (1 << 24) as f32
+}
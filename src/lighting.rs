@@ -0,0 +1,662 @@
+//! GPU-side layouts of the uniforms used by the lighting chapters, and a manager for the lights of a scene.
+//!
+//! These mirror the blocks declared in `01-lighting/05-multiple-lights/shader.frag`, so any change to one has to be
+//! reflected in the other. The fragment uniforms are a `std140` uniform block, while the point and spot lights live
+//! in `std430` storage buffers so that a scene can have any number of them. Spot light cookies share a single texture
+//! array, with one layer per cookie.
+
+use std::mem;
+
+use image::{imageops, RgbaImage};
+use ultraviolet::{Mat4, Vec3, Vec4};
+
+use crate::{
+    instance_buffer::{InstanceBuffer, InstanceData},
+    light_animation::{LightAnimation, PointLightAnimation},
+    shadow, Align16, Camera, Instance,
+};
+
+/// The uniform scale applied to the cubes drawn at each light's position.
+pub const LIGHT_SOURCE_SCALE: f32 = 0.2;
+
+/// The number of point lights that can cast shadows at once. Further shadow casting point lights are lit as if
+/// nothing occluded them.
+pub const MAX_POINT_LIGHT_SHADOWS: usize = 4;
+
+/// The width and height of every spot light cookie. Cookies of other sizes are resized to fit.
+pub const COOKIE_SIZE: u32 = 256;
+
+/// The number of different cookies the spot lights of a scene can share between them.
+pub const MAX_COOKIES: u32 = 8;
+
+/// Cookies tint the light like any other color texture, so they're stored in sRGB and sampled in linear space.
+pub const COOKIE_FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Rgba8UnormSrgb;
+
+/// The depth range of the projection a cookie is projected with. Only its field of view matters, since cookies are
+/// sampled by their position on the projection's near plane.
+const COOKIE_NEAR: f32 = 0.1;
+const COOKIE_FAR: f32 = 100.0;
+
+#[repr(C)]
+#[derive(Clone, Copy, Debug)]
+pub struct FragmentUniforms {
+    pub view_translation: Vec3,
+    pub point_light_count: u32,
+    pub spot_light_count: u32,
+    /// A [`SpecularModel`] cast to an integer.
+    pub specular_model: u32,
+    pub directional_light: DirectionalLight,
+}
+
+/// How the specular highlights are calculated.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SpecularModel {
+    /// Phong reflects the light direction around the normal and compares it with the view direction. The angle
+    /// between the two exceeds 90 degrees at grazing angles, cutting the highlight off abruptly.
+    Phong = 0,
+    /// Blinn-Phong compares the normal with the halfway vector between the light and view directions instead, which
+    /// never exceeds 90 degrees. It needs a higher shininess than Phong for highlights of a similar size.
+    BlinnPhong = 1,
+}
+
+#[repr(C)]
+#[derive(Clone, Copy, Debug)]
+pub struct Material {
+    pub ambient: Align16<Vec3>,
+    pub diffuse: Align16<Vec3>,
+    pub specular: Vec3,
+    pub shininess: f32,
+}
+
+#[repr(C)]
+#[derive(Clone, Copy, Debug)]
+pub struct DirectionalLight {
+    pub direction: Align16<Vec3>,
+
+    pub ambient: Align16<Vec3>,
+    pub diffuse: Align16<Vec3>,
+    pub specular: Vec3,
+}
+
+#[repr(C)]
+#[derive(Clone, Copy, Debug)]
+pub struct PointLight {
+    pub translation: Align16<Vec3>,
+
+    pub ambient: Align16<Vec3>,
+    pub diffuse: Align16<Vec3>,
+    pub specular: Vec3,
+
+    pub constant: f32,
+    pub linear: f32,
+    pub quadratic: f32,
+
+    /// The index of the light's cube map in the point light shadow maps, or `-1` if it casts no shadows. This is
+    /// assigned by [`LightManager::update`] for the lights added as shadow casters.
+    pub shadow_map_index: i32,
+}
+
+impl InstanceData for PointLight {
+    fn same_as(&self, other: &Self) -> bool {
+        self.translation.same_as(&other.translation)
+            && self.ambient.same_as(&other.ambient)
+            && self.diffuse.same_as(&other.diffuse)
+            && self.specular.same_as(&other.specular)
+            && self.constant == other.constant
+            && self.linear == other.linear
+            && self.quadratic == other.quadratic
+            && self.shadow_map_index == other.shadow_map_index
+    }
+}
+
+#[repr(C)]
+#[derive(Clone, Copy, Debug)]
+pub struct SpotLight {
+    pub translation: Align16<Vec3>,
+    pub direction: Vec3,
+    pub cutoff: f32,
+    pub outer_cutoff: f32,
+
+    pub constant: f32,
+    pub linear: f32,
+    pub quadratic: f32,
+
+    pub ambient: Align16<Vec3>,
+    pub diffuse: Align16<Vec3>,
+    pub specular: Vec3,
+
+    /// The layer of the light's cookie in the cookie texture array, or `-1` if it has none. This is assigned by
+    /// [`LightManager::update`] for the lights given a cookie.
+    pub cookie_index: i32,
+    /// Projects world space onto the light's cookie, as derived by [`LightManager::update`] from the light's
+    /// translation and direction.
+    pub cookie_space: Mat4,
+}
+
+impl InstanceData for SpotLight {
+    fn same_as(&self, other: &Self) -> bool {
+        self.translation.same_as(&other.translation)
+            && self.direction.same_as(&other.direction)
+            && self.cutoff == other.cutoff
+            && self.outer_cutoff == other.outer_cutoff
+            && self.constant == other.constant
+            && self.linear == other.linear
+            && self.quadratic == other.quadratic
+            && self.ambient.same_as(&other.ambient)
+            && self.diffuse.same_as(&other.diffuse)
+            && self.specular.same_as(&other.specular)
+            && self.cookie_index == other.cookie_index
+            && self.cookie_space.same_as(&other.cookie_space)
+    }
+}
+
+/// A texture projected from a spot light, tinting the light it casts like a lens pattern or a stained glass window.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct SpotLightCookie {
+    /// The cookie's layer, as returned by [`LightManager::add_cookie`].
+    pub layer: u32,
+    /// The full angle of the cone the cookie is stretched over, in radians. This is independent of the light's
+    /// cutoffs, and no light makes it past the edges of the cookie.
+    pub cone_angle: f32,
+}
+
+/// Owns the lights of a scene along with the buffers and bind group exposing them to the fragment shader.
+///
+/// Lights can be added, removed and edited at any time. The changes are uploaded by the next call to
+/// [`update`](LightManager::update), which also keeps the light source gizmos in sync with the light list.
+pub struct LightManager {
+    directional_light: DirectionalLight,
+    point_lights: Vec<PointLight>,
+    /// Whether each point light renders a shadow cube map.
+    shadow_casting_point_lights: Vec<bool>,
+    /// The animations of each point light that has any.
+    point_light_animations: Vec<Option<PointLightAnimation>>,
+    spot_lights: Vec<SpotLight>,
+    /// Whether each spot light follows the camera around like a flashlight.
+    attached_spot_lights: Vec<bool>,
+    /// The cookie projected from each spot light that has one.
+    spot_light_cookies: Vec<Option<SpotLightCookie>>,
+    specular_model: SpecularModel,
+
+    uniform_buffer: wgpu::Buffer,
+    point_light_buffer: InstanceBuffer<PointLight>,
+    spot_light_buffer: InstanceBuffer<SpotLight>,
+    light_source_instance_buffer: InstanceBuffer<Instance>,
+    cookie_texture: wgpu::Texture,
+    cookie_view: wgpu::TextureView,
+    cookie_sampler: wgpu::Sampler,
+    cookie_count: u32,
+    /// The cookies added since the last update, waiting to be copied into their layers.
+    pending_cookies: Vec<(u32, wgpu::Buffer)>,
+    bind_group: wgpu::BindGroup,
+}
+
+impl DirectionalLight {
+    /// A light that contributes nothing to the scene.
+    pub fn disabled() -> Self {
+        Self {
+            direction: Vec3::new(0.0, -1.0, 0.0).into(),
+            ambient: Vec3::zero().into(),
+            diffuse: Vec3::zero().into(),
+            specular: Vec3::zero(),
+        }
+    }
+}
+
+impl LightManager {
+    /// The layout of the bind group holding the fragment uniforms, the light storage buffers and the cookie textures.
+    pub fn create_bind_group_layout(device: &wgpu::Device) -> wgpu::BindGroupLayout {
+        device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            bindings: &[
+                wgpu::BindGroupLayoutBinding {
+                    binding: 0,
+                    visibility: wgpu::ShaderStage::FRAGMENT,
+                    ty: wgpu::BindingType::UniformBuffer { dynamic: false },
+                },
+                wgpu::BindGroupLayoutBinding {
+                    binding: 1,
+                    visibility: wgpu::ShaderStage::FRAGMENT,
+                    ty: wgpu::BindingType::StorageBuffer {
+                        dynamic: false,
+                        readonly: true,
+                    },
+                },
+                wgpu::BindGroupLayoutBinding {
+                    binding: 2,
+                    visibility: wgpu::ShaderStage::FRAGMENT,
+                    ty: wgpu::BindingType::StorageBuffer {
+                        dynamic: false,
+                        readonly: true,
+                    },
+                },
+                wgpu::BindGroupLayoutBinding {
+                    binding: 3,
+                    visibility: wgpu::ShaderStage::FRAGMENT,
+                    ty: wgpu::BindingType::SampledTexture {
+                        multisampled: false,
+                        dimension: wgpu::TextureViewDimension::D2Array,
+                    },
+                },
+                wgpu::BindGroupLayoutBinding {
+                    binding: 4,
+                    visibility: wgpu::ShaderStage::FRAGMENT,
+                    ty: wgpu::BindingType::Sampler,
+                },
+            ],
+        })
+    }
+
+    /// Creates a manager holding no point or spot lights.
+    pub fn new(
+        device: &wgpu::Device,
+        bind_group_layout: &wgpu::BindGroupLayout,
+        directional_light: DirectionalLight,
+    ) -> Self {
+        let uniform_buffer = device
+            .create_buffer_mapped(1, wgpu::BufferUsage::UNIFORM | wgpu::BufferUsage::COPY_DST)
+            .fill_from_slice(&[FragmentUniforms {
+                view_translation: Vec3::zero(),
+                point_light_count: 0,
+                spot_light_count: 0,
+                specular_model: SpecularModel::Phong as u32,
+                directional_light,
+            }]);
+        let point_light_buffer = InstanceBuffer::with_usage(device, 1, wgpu::BufferUsage::STORAGE);
+        let spot_light_buffer = InstanceBuffer::with_usage(device, 1, wgpu::BufferUsage::STORAGE);
+        let light_source_instance_buffer = InstanceBuffer::new(device, 1);
+        let cookie_texture = device.create_texture(&wgpu::TextureDescriptor {
+            size: wgpu::Extent3d {
+                width: COOKIE_SIZE,
+                height: COOKIE_SIZE,
+                depth: 1,
+            },
+            array_layer_count: MAX_COOKIES,
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: COOKIE_FORMAT,
+            usage: wgpu::TextureUsage::SAMPLED | wgpu::TextureUsage::COPY_DST,
+        });
+        let cookie_view = cookie_texture.create_view(&wgpu::TextureViewDescriptor {
+            format: COOKIE_FORMAT,
+            dimension: wgpu::TextureViewDimension::D2Array,
+            aspect: wgpu::TextureAspect::All,
+            base_mip_level: 0,
+            level_count: 1,
+            base_array_layer: 0,
+            array_layer_count: MAX_COOKIES,
+        });
+        let cookie_sampler = device.create_sampler(&wgpu::SamplerDescriptor {
+            address_mode_u: wgpu::AddressMode::ClampToEdge,
+            address_mode_v: wgpu::AddressMode::ClampToEdge,
+            address_mode_w: wgpu::AddressMode::ClampToEdge,
+            mag_filter: wgpu::FilterMode::Linear,
+            min_filter: wgpu::FilterMode::Linear,
+            mipmap_filter: wgpu::FilterMode::Nearest,
+            lod_min_clamp: 0.0,
+            lod_max_clamp: 0.0,
+            compare_function: wgpu::CompareFunction::Never,
+        });
+        let bind_group = Self::create_bind_group(
+            device,
+            bind_group_layout,
+            &uniform_buffer,
+            &point_light_buffer,
+            &spot_light_buffer,
+            &cookie_view,
+            &cookie_sampler,
+        );
+
+        Self {
+            directional_light,
+            point_lights: Vec::new(),
+            shadow_casting_point_lights: Vec::new(),
+            point_light_animations: Vec::new(),
+            spot_lights: Vec::new(),
+            attached_spot_lights: Vec::new(),
+            spot_light_cookies: Vec::new(),
+            specular_model: SpecularModel::Phong,
+            uniform_buffer,
+            point_light_buffer,
+            spot_light_buffer,
+            light_source_instance_buffer,
+            cookie_texture,
+            cookie_view,
+            cookie_sampler,
+            cookie_count: 0,
+            pending_cookies: Vec::new(),
+            bind_group,
+        }
+    }
+
+    pub fn directional_light(&self) -> &DirectionalLight {
+        &self.directional_light
+    }
+
+    pub fn directional_light_mut(&mut self) -> &mut DirectionalLight {
+        &mut self.directional_light
+    }
+
+    pub fn point_lights(&self) -> &[PointLight] {
+        &self.point_lights
+    }
+
+    pub fn point_light_mut(&mut self, index: usize) -> &mut PointLight {
+        &mut self.point_lights[index]
+    }
+
+    /// Adds a point light, returning its index. Only the first [`MAX_POINT_LIGHT_SHADOWS`] shadow casting lights get a
+    /// shadow map.
+    pub fn add_point_light(&mut self, light: PointLight, casts_shadows: bool) -> usize {
+        self.point_lights.push(light);
+        self.shadow_casting_point_lights.push(casts_shadows);
+        self.point_light_animations.push(None);
+        self.point_lights.len() - 1
+    }
+
+    /// Removes the point light at `index`, shifting the indices of the lights after it down by one.
+    pub fn remove_point_light(&mut self, index: usize) -> PointLight {
+        self.shadow_casting_point_lights.remove(index);
+        self.point_light_animations.remove(index);
+        self.point_lights.remove(index)
+    }
+
+    pub fn point_light_casts_shadows(&self, index: usize) -> bool {
+        self.shadow_casting_point_lights[index]
+    }
+
+    pub fn set_point_light_casts_shadows(&mut self, index: usize, casts_shadows: bool) {
+        self.shadow_casting_point_lights[index] = casts_shadows;
+    }
+
+    /// Animates the point light at `index`, starting from the light as it is now. Without any animations, the light
+    /// stays wherever it was last animated to.
+    pub fn set_point_light_animations(&mut self, index: usize, animations: Vec<LightAnimation>) {
+        self.point_light_animations[index] = if animations.is_empty() {
+            None
+        } else {
+            Some(PointLightAnimation {
+                rest: self.point_lights[index],
+                animations,
+            })
+        };
+    }
+
+    /// Moves every animated point light to where its animations put it at `time` seconds. Like any other edit, this
+    /// is uploaded by the next [`update`](LightManager::update).
+    pub fn animate(&mut self, time: f32) {
+        for (light, animation) in self
+            .point_lights
+            .iter_mut()
+            .zip(&self.point_light_animations)
+        {
+            if let Some(animation) = animation {
+                *light = animation.sample(time);
+            }
+        }
+    }
+
+    pub fn spot_lights(&self) -> &[SpotLight] {
+        &self.spot_lights
+    }
+
+    pub fn spot_light_mut(&mut self, index: usize) -> &mut SpotLight {
+        &mut self.spot_lights[index]
+    }
+
+    /// Adds a spot light, returning its index. Lights attached to the camera are moved along with it on every update.
+    pub fn add_spot_light(&mut self, light: SpotLight, attached_to_camera: bool) -> usize {
+        self.spot_lights.push(light);
+        self.attached_spot_lights.push(attached_to_camera);
+        self.spot_light_cookies.push(None);
+        self.spot_lights.len() - 1
+    }
+
+    /// Removes the spot light at `index`, shifting the indices of the lights after it down by one.
+    pub fn remove_spot_light(&mut self, index: usize) -> SpotLight {
+        self.attached_spot_lights.remove(index);
+        self.spot_light_cookies.remove(index);
+        self.spot_lights.remove(index)
+    }
+
+    pub fn spot_light_cookie(&self, index: usize) -> Option<SpotLightCookie> {
+        self.spot_light_cookies[index]
+    }
+
+    /// Projects `cookie` from the spot light at `index`, or stops projecting one if it's `None`.
+    pub fn set_spot_light_cookie(&mut self, index: usize, cookie: Option<SpotLightCookie>) {
+        self.spot_light_cookies[index] = cookie;
+    }
+
+    /// Adds a cookie that any number of spot lights can project, returning its layer, or `None` once all
+    /// [`MAX_COOKIES`] layers are taken. The image is resized to [`COOKIE_SIZE`] and uploaded by the next
+    /// [`update`](LightManager::update). Its top row ends up on the light's up side.
+    pub fn add_cookie(&mut self, device: &wgpu::Device, image: &RgbaImage) -> Option<u32> {
+        if self.cookie_count >= MAX_COOKIES {
+            return None;
+        }
+
+        let resized;
+        let image = if image.dimensions() == (COOKIE_SIZE, COOKIE_SIZE) {
+            image
+        } else {
+            resized = imageops::resize(
+                image,
+                COOKIE_SIZE,
+                COOKIE_SIZE,
+                imageops::FilterType::Triangle,
+            );
+            &resized
+        };
+        let pixels: &[u8] = image;
+        let buffer = device
+            .create_buffer_mapped(pixels.len(), wgpu::BufferUsage::COPY_SRC)
+            .fill_from_slice(pixels);
+
+        let layer = self.cookie_count;
+        self.cookie_count += 1;
+        self.pending_cookies.push((layer, buffer));
+        Some(layer)
+    }
+
+    pub fn specular_model(&self) -> SpecularModel {
+        self.specular_model
+    }
+
+    pub fn set_specular_model(&mut self, specular_model: SpecularModel) {
+        self.specular_model = specular_model;
+    }
+
+    /// Uploads the current state of the lights, recording the necessary copies into `encoder`.
+    pub fn update(
+        &mut self,
+        device: &wgpu::Device,
+        encoder: &mut wgpu::CommandEncoder,
+        bind_group_layout: &wgpu::BindGroupLayout,
+        camera: &Camera,
+    ) {
+        let camera_translation = camera.world_translation();
+        for (light, _) in self
+            .spot_lights
+            .iter_mut()
+            .zip(&self.attached_spot_lights)
+            .filter(|(_, &attached)| attached)
+        {
+            light.translation = camera_translation.into();
+            light.direction = camera.get_direction_vector();
+        }
+
+        for (light, cookie) in self.spot_lights.iter_mut().zip(&self.spot_light_cookies) {
+            if let Some(cookie) = cookie {
+                light.cookie_index = cookie.layer as i32;
+                light.cookie_space = shadow::spot_light_space(
+                    light.translation.0,
+                    light.direction,
+                    (cookie.cone_angle / 2.0).cos(),
+                    COOKIE_NEAR,
+                    COOKIE_FAR,
+                );
+            } else {
+                light.cookie_index = -1;
+            }
+        }
+
+        for (layer, buffer) in self.pending_cookies.drain(..) {
+            encoder.copy_buffer_to_texture(
+                wgpu::BufferCopyView {
+                    buffer: &buffer,
+                    offset: 0,
+                    row_pitch: 4 * COOKIE_SIZE,
+                    image_height: COOKIE_SIZE,
+                },
+                wgpu::TextureCopyView {
+                    texture: &self.cookie_texture,
+                    mip_level: 0,
+                    array_layer: layer,
+                    origin: wgpu::Origin3d {
+                        x: 0.0,
+                        y: 0.0,
+                        z: 0.0,
+                    },
+                },
+                wgpu::Extent3d {
+                    width: COOKIE_SIZE,
+                    height: COOKIE_SIZE,
+                    depth: 1,
+                },
+            );
+        }
+
+        let mut shadow_map_count = 0;
+        for (light, &casts_shadows) in self
+            .point_lights
+            .iter_mut()
+            .zip(&self.shadow_casting_point_lights)
+        {
+            if casts_shadows && shadow_map_count < MAX_POINT_LIGHT_SHADOWS {
+                light.shadow_map_index = shadow_map_count as i32;
+                shadow_map_count += 1;
+            } else {
+                light.shadow_map_index = -1;
+            }
+        }
+
+        let uniforms = FragmentUniforms {
+            view_translation: camera_translation,
+            point_light_count: self.point_lights.len() as u32,
+            spot_light_count: self.spot_lights.len() as u32,
+            specular_model: self.specular_model as u32,
+            directional_light: self.directional_light,
+        };
+        let staging_buffer = device
+            .create_buffer_mapped(1, wgpu::BufferUsage::COPY_SRC)
+            .fill_from_slice(&[uniforms]);
+        encoder.copy_buffer_to_buffer(
+            &staging_buffer,
+            0,
+            &self.uniform_buffer,
+            0,
+            mem::size_of::<FragmentUniforms>() as wgpu::BufferAddress,
+        );
+
+        let point_lights_reallocated =
+            self.point_light_buffer
+                .update(device, encoder, &self.point_lights);
+        let spot_lights_reallocated =
+            self.spot_light_buffer
+                .update(device, encoder, &self.spot_lights);
+        if point_lights_reallocated || spot_lights_reallocated {
+            self.bind_group = Self::create_bind_group(
+                device,
+                bind_group_layout,
+                &self.uniform_buffer,
+                &self.point_light_buffer,
+                &self.spot_light_buffer,
+                &self.cookie_view,
+                &self.cookie_sampler,
+            );
+        }
+
+        let light_sources: Vec<Instance> = self
+            .point_lights
+            .iter()
+            .map(|light| light.translation.0)
+            .chain(
+                self.spot_lights
+                    .iter()
+                    .zip(&self.attached_spot_lights)
+                    .filter(|(_, &attached)| !attached)
+                    .map(|(light, _)| light.translation.0),
+            )
+            .map(light_source_instance)
+            .collect();
+        self.light_source_instance_buffer
+            .update(device, encoder, &light_sources);
+    }
+
+    pub fn bind_group(&self) -> &wgpu::BindGroup {
+        &self.bind_group
+    }
+
+    /// The gizmos drawn at the position of every light that isn't attached to the camera.
+    pub fn light_source_instance_buffer(&self) -> &InstanceBuffer<Instance> {
+        &self.light_source_instance_buffer
+    }
+
+    fn create_bind_group(
+        device: &wgpu::Device,
+        bind_group_layout: &wgpu::BindGroupLayout,
+        uniform_buffer: &wgpu::Buffer,
+        point_light_buffer: &InstanceBuffer<PointLight>,
+        spot_light_buffer: &InstanceBuffer<SpotLight>,
+        cookie_view: &wgpu::TextureView,
+        cookie_sampler: &wgpu::Sampler,
+    ) -> wgpu::BindGroup {
+        device.create_bind_group(&wgpu::BindGroupDescriptor {
+            layout: bind_group_layout,
+            bindings: &[
+                wgpu::Binding {
+                    binding: 0,
+                    resource: wgpu::BindingResource::Buffer {
+                        buffer: uniform_buffer,
+                        range: 0..mem::size_of::<FragmentUniforms>() as wgpu::BufferAddress,
+                    },
+                },
+                wgpu::Binding {
+                    binding: 1,
+                    resource: wgpu::BindingResource::Buffer {
+                        buffer: point_light_buffer.buffer(),
+                        range: 0..point_light_buffer.size(),
+                    },
+                },
+                wgpu::Binding {
+                    binding: 2,
+                    resource: wgpu::BindingResource::Buffer {
+                        buffer: spot_light_buffer.buffer(),
+                        range: 0..spot_light_buffer.size(),
+                    },
+                },
+                wgpu::Binding {
+                    binding: 3,
+                    resource: wgpu::BindingResource::TextureView(cookie_view),
+                },
+                wgpu::Binding {
+                    binding: 4,
+                    resource: wgpu::BindingResource::Sampler(cookie_sampler),
+                },
+            ],
+        })
+    }
+}
+
+/// The model matrix of the small cube drawn at a light's position.
+pub fn light_source_instance(translation: Vec3) -> Instance {
+    Instance::new(Mat4 {
+        cols: [
+            Vec4::new(LIGHT_SOURCE_SCALE, 0.0, 0.0, 0.0),
+            Vec4::new(0.0, LIGHT_SOURCE_SCALE, 0.0, 0.0),
+            Vec4::new(0.0, 0.0, LIGHT_SOURCE_SCALE, 0.0),
+            Vec4::new(translation.x, translation.y, translation.z, 1.0),
+        ],
+    })
+}
@@ -0,0 +1,151 @@
+//! Physically based rendering with the metallic-roughness material model.
+//!
+//! Surfaces are described by their albedo, how metallic they are and how rough they are, which the Cook-Torrance BRDF
+//! turns into a diffuse and a specular term that together never reflect more light than they receive. Every input is
+//! a constant factor multiplied with a texture, so a material without a texture just uses a 1x1 white one.
+
+use std::f32::consts::PI;
+
+use ultraviolet::{Mat4, Vec2, Vec3, Vec4};
+
+use crate::{mesh::Mesh, Instance};
+
+/// The factors of a PBR material, laid out to be uploaded as a uniform buffer.
+#[repr(C)]
+#[derive(Clone, Copy, Debug)]
+pub struct PbrMaterial {
+    /// The base color, in linear space.
+    pub albedo: Vec3,
+    /// `0.0` for dielectrics and `1.0` for metals. Anything in between is only useful for blending the two.
+    pub metallic: f32,
+    /// Light emitted by the surface itself, which isn't affected by any of the lights.
+    pub emissive: Vec3,
+    /// From `0.0` for a perfect mirror to `1.0` for a completely diffuse surface.
+    pub roughness: f32,
+    /// How much of the ambient light reaches the surface, from `0.0` to `1.0`.
+    pub ambient_occlusion: f32,
+}
+
+/// An instance of a PBR mesh. The metallic and roughness factors scale the material's, so instances sharing a
+/// material can still look different.
+#[repr(C)]
+#[derive(Clone, Copy, Debug)]
+pub struct PbrInstance {
+    pub instance: Instance,
+    pub metallic: f32,
+    pub roughness: f32,
+}
+
+/// The vertex layout of the meshes created by this module, matching the one used by the lighting chapters.
+#[repr(C)]
+#[derive(Clone, Copy, Debug)]
+pub struct Vertex {
+    pub translation: Vec3,
+    pub normal: Vec3,
+    pub uv: Vec2,
+    /// The direction of increasing `u` texture coordinates, with the handedness of the bitangent in W.
+    pub tangent: Vec4,
+}
+
+impl PbrInstance {
+    pub fn new(model: Mat4, metallic: f32, roughness: f32) -> Self {
+        Self {
+            instance: Instance::new(model),
+            metallic,
+            roughness,
+        }
+    }
+}
+
+/// The layout of the material bind group: the [`PbrMaterial`] uniform at binding `0`, followed by a texture and a
+/// sampler each for the albedo, the metallic-roughness map (roughness in G and metallic in B, like glTF), the ambient
+/// occlusion map (in R), the emissive map and the tangent space normal map.
+pub fn create_material_bind_group_layout(device: &wgpu::Device) -> wgpu::BindGroupLayout {
+    let mut bindings = vec![wgpu::BindGroupLayoutBinding {
+        binding: 0,
+        visibility: wgpu::ShaderStage::FRAGMENT,
+        ty: wgpu::BindingType::UniformBuffer { dynamic: false },
+    }];
+    for texture in 0..5 {
+        bindings.push(wgpu::BindGroupLayoutBinding {
+            binding: 1 + texture * 2,
+            visibility: wgpu::ShaderStage::FRAGMENT,
+            ty: wgpu::BindingType::SampledTexture {
+                multisampled: false,
+                dimension: wgpu::TextureViewDimension::D2,
+            },
+        });
+        bindings.push(wgpu::BindGroupLayoutBinding {
+            binding: 2 + texture * 2,
+            visibility: wgpu::ShaderStage::FRAGMENT,
+            ty: wgpu::BindingType::Sampler,
+        });
+    }
+
+    device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+        bindings: &bindings,
+    })
+}
+
+/// A unit sphere of latitude and longitude lines around the origin, with its triangles facing outwards with the same
+/// winding as the cubes. The texture wraps around it once, with `v` increasing from the top pole to the bottom one.
+///
+/// ```
+/// # use learnopengl_but_its_wgpu::pbr::uv_sphere;
+/// #
+/// let sphere = uv_sphere(32, 16);
+/// assert_eq!(32 * 16 * 6 - 32 * 2 * 3, sphere.indices.len());
+/// for vertex in &sphere.vertices {
+///     assert!((vertex.translation.mag() - 1.0).abs() < 1e-5);
+///     // The normal of a unit sphere is its position.
+///     assert!((vertex.normal - vertex.translation).mag() < 1e-5);
+///     assert!((vertex.tangent.x * vertex.normal.x + vertex.tangent.z * vertex.normal.z).abs() < 1e-5);
+/// }
+/// for triangle in sphere.indices.chunks(3) {
+///     let a = sphere.vertices[triangle[0] as usize].translation;
+///     let b = sphere.vertices[triangle[1] as usize].translation;
+///     let c = sphere.vertices[triangle[2] as usize].translation;
+///     assert!((b - a).cross(c - a).dot(a) > 0.0);
+/// }
+/// ```
+pub fn uv_sphere(sectors: u32, stacks: u32) -> Mesh<Vertex> {
+    let mut vertices = Vec::with_capacity(((sectors + 1) * (stacks + 1)) as usize);
+    for stack in 0..=stacks {
+        let v = stack as f32 / stacks as f32;
+        let polar = PI * v;
+        for sector in 0..=sectors {
+            let u = sector as f32 / sectors as f32;
+            let azimuth = 2.0 * PI * u;
+            let translation = Vec3::new(
+                polar.sin() * azimuth.cos(),
+                polar.cos(),
+                polar.sin() * azimuth.sin(),
+            );
+            vertices.push(Vertex {
+                translation,
+                normal: translation,
+                uv: Vec2::new(u, v),
+                // The derivative along the azimuth, which stays well defined at the poles. The bitangent, the
+                // derivative along the polar angle, is the cross product of the normal and the tangent.
+                tangent: Vec4::new(-azimuth.sin(), 0.0, azimuth.cos(), 1.0),
+            });
+        }
+    }
+
+    let mut indices = Vec::with_capacity((6 * sectors * stacks) as usize);
+    for stack in 0..stacks {
+        for sector in 0..sectors {
+            let top_left = stack * (sectors + 1) + sector;
+            let bottom_left = top_left + sectors + 1;
+            // The quads around the poles degenerate into a single triangle.
+            if stack != 0 {
+                indices.extend_from_slice(&[top_left, top_left + 1, bottom_left]);
+            }
+            if stack != stacks - 1 {
+                indices.extend_from_slice(&[top_left + 1, bottom_left + 1, bottom_left]);
+            }
+        }
+    }
+
+    Mesh { vertices, indices }
+}
@@ -0,0 +1,242 @@
+//! Reading and writing Radiance `.hdr` images, the usual format for high dynamic range environment maps.
+//!
+//! Each pixel is stored as RGBE: an 8 bit mantissa per channel sharing an 8 bit exponent, which covers a huge range of
+//! intensities at a fixed relative precision. Scanlines are either stored as is or run length encoded one channel at
+//! a time. Only the standard `-Y height +X width` orientation is supported, with the top scanline first.
+
+use std::{
+    error::Error,
+    fmt, fs,
+    io::{self, BufRead, BufReader, BufWriter, Read, Write},
+    path::Path,
+};
+
+use ultraviolet::Vec3;
+
+/// A high dynamic range image in linear space, row by row from the top.
+#[derive(Clone, Debug)]
+pub struct HdrImage {
+    pub width: u32,
+    pub height: u32,
+    pub pixels: Vec<Vec3>,
+}
+
+#[derive(Debug)]
+pub enum HdrError {
+    Io(io::Error),
+    /// The file isn't a Radiance image, or uses a feature that isn't supported.
+    Format(String),
+}
+
+impl HdrImage {
+    pub fn pixel(&self, x: u32, y: u32) -> Vec3 {
+        self.pixels[(y * self.width + x) as usize]
+    }
+
+    /// Reads the Radiance image at `path`.
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, HdrError> {
+        let file = fs::File::open(path).map_err(HdrError::Io)?;
+        Self::read(BufReader::new(file))
+    }
+
+    /// Writes the image to `path` as an uncompressed Radiance image.
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
+        let file = fs::File::create(path)?;
+        let mut writer = BufWriter::new(file);
+        self.write(&mut writer)?;
+        writer.flush()
+    }
+
+    /// Reads a Radiance image.
+    ///
+    /// ```
+    /// # use ultraviolet::Vec3;
+    /// #
+    /// # use learnopengl_but_its_wgpu::radiance::HdrImage;
+    /// #
+    /// let image = HdrImage {
+    ///     width: 2,
+    ///     height: 1,
+    ///     pixels: vec![Vec3::new(0.5, 1.0, 2.0), Vec3::new(1000.0, 0.0, 0.001)],
+    /// };
+    /// let mut file = Vec::new();
+    /// image.write(&mut file).unwrap();
+    ///
+    /// let read = HdrImage::read(&file[..]).unwrap();
+    /// assert_eq!((2, 1), (read.width, read.height));
+    /// // Every channel shares the exponent of the brightest one, so dimmer channels lose precision.
+    /// let pixel = read.pixel(0, 0);
+    /// assert_eq!((0.5, 1.0, 2.0), (pixel.x, pixel.y, pixel.z));
+    /// assert!((read.pixel(1, 0).x - 1000.0).abs() < 1000.0 / 128.0);
+    /// assert!(read.pixel(1, 0).z < 1000.0 / 128.0);
+    /// ```
+    pub fn read<R: BufRead>(mut reader: R) -> Result<Self, HdrError> {
+        let mut line = String::new();
+        read_line(&mut reader, &mut line)?;
+        if line != "#?RADIANCE" && line != "#?RGBE" {
+            return Err(HdrError::Format("missing Radiance signature".to_owned()));
+        }
+        // The header is a list of variables ending with an empty line.
+        loop {
+            read_line(&mut reader, &mut line)?;
+            if line.is_empty() {
+                break;
+            }
+            if line.starts_with("FORMAT=") && line != "FORMAT=32-bit_rle_rgbe" {
+                return Err(HdrError::Format(format!("unsupported {}", line)));
+            }
+        }
+
+        read_line(&mut reader, &mut line)?;
+        let (width, height) = match line.split_whitespace().collect::<Vec<_>>()[..] {
+            ["-Y", height, "+X", width] => (width.parse::<u32>().ok(), height.parse::<u32>().ok()),
+            _ => (None, None),
+        };
+        let (width, height) = match (width, height) {
+            (Some(width), Some(height)) if width > 0 && height > 0 => (width, height),
+            _ => {
+                return Err(HdrError::Format(format!(
+                    "unsupported resolution `{}`",
+                    line
+                )))
+            }
+        };
+
+        let mut pixels = Vec::with_capacity((width * height) as usize);
+        let mut scanline = vec![[0; 4]; width as usize];
+        for _ in 0..height {
+            read_scanline(&mut reader, &mut scanline)?;
+            pixels.extend(scanline.iter().map(|&rgbe| decode(rgbe)));
+        }
+
+        Ok(Self {
+            width,
+            height,
+            pixels,
+        })
+    }
+
+    /// Writes the image without any compression.
+    pub fn write<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+        write!(
+            writer,
+            "#?RADIANCE\nFORMAT=32-bit_rle_rgbe\n\n-Y {} +X {}\n",
+            self.height, self.width
+        )?;
+        for &pixel in &self.pixels {
+            writer.write_all(&encode(pixel))?;
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for HdrError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            HdrError::Io(err) => write!(f, "could not read Radiance image: {}", err),
+            HdrError::Format(message) => write!(f, "invalid Radiance image: {}", message),
+        }
+    }
+}
+
+impl Error for HdrError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            HdrError::Io(err) => Some(err),
+            HdrError::Format(_) => None,
+        }
+    }
+}
+
+/// Reads a line of the header into `line`, without the line break.
+fn read_line<R: BufRead>(reader: &mut R, line: &mut String) -> Result<(), HdrError> {
+    line.clear();
+    if reader.read_line(line).map_err(HdrError::Io)? == 0 {
+        return Err(HdrError::Format("unexpected end of header".to_owned()));
+    }
+    let trimmed = line.trim_end_matches(['\n', '\r']).len();
+    line.truncate(trimmed);
+    Ok(())
+}
+
+fn read_scanline<R: Read>(reader: &mut R, scanline: &mut [[u8; 4]]) -> Result<(), HdrError> {
+    let mut start = [0; 4];
+    reader.read_exact(&mut start).map_err(HdrError::Io)?;
+    let width = scanline.len();
+
+    // Run length encoded scanlines start with two 2s and their width, which can't be a valid pixel since its
+    // mantissas would have to be normalized. Scanlines too short or long to be encoded are stored as is.
+    let encoded = start[0] == 2 && start[1] == 2 && start[2] & 0x80 == 0;
+    if !encoded || !(8..=0x7fff).contains(&width) {
+        scanline[0] = start;
+        for pixel in &mut scanline[1..] {
+            reader.read_exact(pixel).map_err(HdrError::Io)?;
+        }
+        return Ok(());
+    }
+    if ((start[2] as usize) << 8 | start[3] as usize) != width {
+        return Err(HdrError::Format("wrong scanline width".to_owned()));
+    }
+
+    // Each channel is stored separately, as runs of a repeated byte and literal bytes.
+    for channel in 0..4 {
+        let mut x = 0;
+        while x < width {
+            let mut count = [0; 1];
+            reader.read_exact(&mut count).map_err(HdrError::Io)?;
+            let count = count[0] as usize;
+            let (length, run) = if count > 128 {
+                (count - 128, true)
+            } else {
+                (count, false)
+            };
+            if length == 0 || x + length > width {
+                return Err(HdrError::Format("invalid run length".to_owned()));
+            }
+
+            if run {
+                let mut value = [0; 1];
+                reader.read_exact(&mut value).map_err(HdrError::Io)?;
+                for pixel in &mut scanline[x..x + length] {
+                    pixel[channel] = value[0];
+                }
+            } else {
+                let mut values = [0; 128];
+                reader
+                    .read_exact(&mut values[..length])
+                    .map_err(HdrError::Io)?;
+                for (pixel, &value) in scanline[x..x + length].iter_mut().zip(&values[..length]) {
+                    pixel[channel] = value;
+                }
+            }
+            x += length;
+        }
+    }
+    Ok(())
+}
+
+fn decode(rgbe: [u8; 4]) -> Vec3 {
+    if rgbe[3] == 0 {
+        return Vec3::zero();
+    }
+    // The mantissas are fractions of 256 scaled by 2 to the power of the exponent, which is biased by 128.
+    let scale = 2f32.powi(rgbe[3] as i32 - (128 + 8));
+    Vec3::new(rgbe[0] as f32, rgbe[1] as f32, rgbe[2] as f32) * scale
+}
+
+fn encode(color: Vec3) -> [u8; 4] {
+    let max = color.x.max(color.y).max(color.z);
+    if max < 1e-32 {
+        return [0; 4];
+    }
+    // The exponent is chosen for the brightest channel to land in `128..256`.
+    let exponent = max.log2().floor() as i32 + 1;
+    let scale = 256.0 / 2f32.powi(exponent);
+    let mantissa = |value: f32| (value.max(0.0) * scale).min(255.0) as u8;
+    [
+        mantissa(color.x),
+        mantissa(color.y),
+        mantissa(color.z),
+        (exponent + 128) as u8,
+    ]
+}
@@ -0,0 +1,708 @@
+//! Data-driven scene descriptions for the lighting chapters.
+//!
+//! A scene file is a [RON](https://github.com/ron-rs/ron) document describing where the camera starts, which cubes
+//! are placed where, the material they share and the lights illuminating them. Angles are written in degrees and
+//! colours as `(r, g, b)` tuples, and texture paths are resolved relative to the scene file.
+//!
+//! ```ron
+//! Scene(
+//!     camera: (translation: (0.0, 0.0, 3.0), yaw: 180.0),
+//!     material: (shininess: 32.0, diffuse: (1.0, 0.5, 0.31)),
+//!     objects: [
+//!         (translation: (0.0, 0.0, 0.0)),
+//!         (translation: (2.0, 5.0, -15.0), rotation: Some((axis: (1.0, 0.3, 0.5), angle: 20.0))),
+//!     ],
+//!     point_lights: [
+//!         (
+//!             translation: (1.2, 1.0, 2.0),
+//!             ambient: (0.2, 0.2, 0.2),
+//!             diffuse: (0.5, 0.5, 0.5),
+//!             specular: (1.0, 1.0, 1.0),
+//!         ),
+//!     ],
+//! )
+//! ```
+
+use std::{
+    error::Error,
+    fmt, fs, io,
+    path::{Path, PathBuf},
+    str::FromStr,
+};
+
+use amethyst_input::{InputEvent, StringBindings};
+use serde::Deserialize;
+use shrev::ReaderId;
+use ultraviolet::{Mat4, Vec3, Vec4};
+
+use crate::{
+    light_animation::LightAnimation,
+    lighting::{
+        light_source_instance, DirectionalLight, LightManager, Material, PointLight, SpotLight,
+        SpotLightCookie,
+    },
+    pbr::PbrMaterial,
+    radiance::{HdrError, HdrImage},
+    spherical_harmonics::SphericalHarmonics,
+    Camera, Instance,
+};
+
+/// The size of the faces of the cube map the sky is integrated over for spherical harmonics. The sky is smooth, so this
+/// can be small.
+const SPHERICAL_HARMONICS_SIZE: u32 = 16;
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct Scene {
+    pub camera: CameraDescription,
+    #[serde(default)]
+    pub material: MaterialDescription,
+    /// The material used by the PBR chapters instead of `material`.
+    #[serde(default)]
+    pub pbr_material: PbrMaterialDescription,
+    #[serde(default)]
+    pub objects: Vec<ObjectDescription>,
+    #[serde(default)]
+    pub directional_light: Option<DirectionalLightDescription>,
+    #[serde(default)]
+    pub point_lights: Vec<PointLightDescription>,
+    #[serde(default)]
+    pub spot_lights: Vec<SpotLightDescription>,
+    /// An equirectangular Radiance image of the surroundings, lighting the scene in the image based lighting
+    /// chapters.
+    #[serde(default)]
+    pub environment: Option<PathBuf>,
+    /// The surroundings used for spherical harmonics ambient lighting when there's no `environment`.
+    #[serde(default)]
+    pub sky: Option<SkyDescription>,
+
+    /// The directory the scene was loaded from, used to resolve relative paths.
+    #[serde(skip)]
+    pub directory: PathBuf,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct CameraDescription {
+    pub translation: [f32; 3],
+    #[serde(default)]
+    pub pitch: f32,
+    #[serde(default)]
+    pub yaw: f32,
+    #[serde(default)]
+    pub roll: f32,
+    #[serde(default = "default_fov")]
+    pub fov: f32,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct MaterialDescription {
+    /// The colours of the surface. The chapters without textures use them as is, while the ones built on
+    /// [`Scene::material`] multiply the texture maps with them.
+    #[serde(default = "default_ambient")]
+    pub ambient: [f32; 3],
+    #[serde(default = "default_diffuse")]
+    pub diffuse: [f32; 3],
+    #[serde(default = "default_specular")]
+    pub specular: [f32; 3],
+    #[serde(default = "default_shininess")]
+    pub shininess: f32,
+    #[serde(default)]
+    pub diffuse_texture: Option<PathBuf>,
+    #[serde(default)]
+    pub specular_texture: Option<PathBuf>,
+    /// A tangent space normal map, whose green channel points along increasing `v` texture coordinates. The vertex
+    /// normals are used as is without one.
+    #[serde(default)]
+    pub normal_texture: Option<PathBuf>,
+    /// A height map for parallax mapping, white where the surface is highest.
+    #[serde(default)]
+    pub height_texture: Option<PathBuf>,
+    /// Light the surface gives off by itself, regardless of any light sources. It's multiplied with `emissive_texture`
+    /// if there is one.
+    #[serde(default)]
+    pub emissive: [f32; 3],
+    #[serde(default)]
+    pub emissive_texture: Option<PathBuf>,
+    /// How far the emissive texture moves across the surface per second, in texture coordinates.
+    #[serde(default)]
+    pub emissive_scroll: [f32; 2],
+}
+
+/// A metallic-roughness material. Each texture is multiplied with the matching constant, which is used as is when there
+/// is no texture.
+#[derive(Clone, Debug, Deserialize)]
+pub struct PbrMaterialDescription {
+    #[serde(default = "default_albedo")]
+    pub albedo: [f32; 3],
+    #[serde(default)]
+    pub metallic: f32,
+    #[serde(default = "default_roughness")]
+    pub roughness: f32,
+    #[serde(default = "default_ambient_occlusion")]
+    pub ambient_occlusion: f32,
+    #[serde(default)]
+    pub emissive: [f32; 3],
+    #[serde(default)]
+    pub albedo_texture: Option<PathBuf>,
+    /// Roughness in the green channel and metallic in the blue channel, like glTF.
+    #[serde(default)]
+    pub metallic_roughness_texture: Option<PathBuf>,
+    #[serde(default)]
+    pub ambient_occlusion_texture: Option<PathBuf>,
+    #[serde(default)]
+    pub emissive_texture: Option<PathBuf>,
+    /// A tangent space normal map, whose green channel points along increasing `v` texture coordinates.
+    #[serde(default)]
+    pub normal_texture: Option<PathBuf>,
+}
+
+/// A sky fading from `horizon` up to `zenith`, over a ground of a single colour.
+#[derive(Clone, Debug, Deserialize)]
+pub struct SkyDescription {
+    pub zenith: [f32; 3],
+    pub horizon: [f32; 3],
+    pub ground: [f32; 3],
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct ObjectDescription {
+    pub translation: [f32; 3],
+    #[serde(default)]
+    pub rotation: Option<RotationDescription>,
+    #[serde(default = "default_scale")]
+    pub scale: [f32; 3],
+}
+
+/// A rotation of `angle` degrees around `axis`.
+#[derive(Clone, Copy, Debug, Deserialize)]
+pub struct RotationDescription {
+    pub axis: [f32; 3],
+    pub angle: f32,
+}
+
+#[derive(Clone, Copy, Debug, Deserialize)]
+pub struct Attenuation {
+    pub constant: f32,
+    pub linear: f32,
+    pub quadratic: f32,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct DirectionalLightDescription {
+    pub direction: [f32; 3],
+    pub ambient: [f32; 3],
+    pub diffuse: [f32; 3],
+    pub specular: [f32; 3],
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct PointLightDescription {
+    pub translation: [f32; 3],
+    pub ambient: [f32; 3],
+    pub diffuse: [f32; 3],
+    pub specular: [f32; 3],
+    #[serde(default)]
+    pub attenuation: Attenuation,
+    /// Whether the light renders a shadow cube map. Each one costs six extra depth passes per frame.
+    #[serde(default)]
+    pub casts_shadows: bool,
+    /// Applied in order on every frame, starting from the light as described here.
+    #[serde(default)]
+    pub animations: Vec<LightAnimation>,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct SpotLightDescription {
+    /// Whether the light should follow the camera around like a flashlight, in which case `translation` and
+    /// `direction` only describe its initial state.
+    #[serde(default)]
+    pub attached_to_camera: bool,
+    #[serde(default)]
+    pub translation: [f32; 3],
+    #[serde(default = "default_direction")]
+    pub direction: [f32; 3],
+    pub cutoff: f32,
+    pub outer_cutoff: f32,
+    #[serde(default)]
+    pub attenuation: Attenuation,
+    pub ambient: [f32; 3],
+    pub diffuse: [f32; 3],
+    pub specular: [f32; 3],
+    /// An image projected from the light to tint it. Lights sharing an image share a cookie, and lights beyond the
+    /// first [`MAX_COOKIES`](crate::lighting::MAX_COOKIES) images project none.
+    #[serde(default)]
+    pub cookie_texture: Option<PathBuf>,
+    /// The full angle of the cone the cookie is projected into, in degrees. Defaults to just fitting the outer cone.
+    #[serde(default)]
+    pub cookie_angle: Option<f32>,
+}
+
+/// The GPU buffers needed to draw a scene with the multiple lights pipeline, apart from the lights themselves.
+pub struct SceneBuffers {
+    pub cube_instance_buffer: wgpu::Buffer,
+    pub cube_count: u32,
+    pub material_uniform_buffer: wgpu::Buffer,
+}
+
+#[derive(Debug)]
+pub enum SceneError {
+    Io(io::Error),
+    Parse(ron::de::Error),
+}
+
+impl Scene {
+    /// Reads and parses the scene file at `path`.
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, SceneError> {
+        let path = path.as_ref();
+        let source = fs::read_to_string(path).map_err(SceneError::Io)?;
+        let mut scene: Self = source.parse()?;
+        scene.directory = path
+            .parent()
+            .map(Path::to_path_buf)
+            .unwrap_or_else(PathBuf::new);
+        Ok(scene)
+    }
+
+    /// Resolves a path found in the scene file relative to the scene file's directory.
+    pub fn resolve_path<P: AsRef<Path>>(&self, path: P) -> PathBuf {
+        self.directory.join(path)
+    }
+
+    pub fn create_camera(&self, event_reader: ReaderId<InputEvent<StringBindings>>) -> Camera {
+        Camera {
+            translation: vec3(self.camera.translation),
+            pitch: self.camera.pitch.to_radians(),
+            yaw: self.camera.yaw.to_radians(),
+            roll: self.camera.roll.to_radians(),
+            movement_speed: 0.1,
+            mouse_sensitivity: 0.0025,
+            zoom: self.camera.fov.to_radians(),
+            is_controlled: false,
+            event_reader,
+        }
+    }
+
+    pub fn cube_instances(&self) -> Vec<Instance> {
+        self.objects
+            .iter()
+            .map(|object| Instance::new(object.model_matrix()))
+            .collect()
+    }
+
+    pub fn light_source_instances(&self) -> Vec<Instance> {
+        self.point_lights
+            .iter()
+            .map(|light| light_source_instance(vec3(light.translation)))
+            .collect()
+    }
+
+    /// The cubes drawn at the point lights' positions, moved to where their animations put them at `time` seconds.
+    pub fn light_source_instances_at(&self, time: f32) -> Vec<Instance> {
+        self.point_lights
+            .iter()
+            .map(|light| light_source_instance(light.sample(time).translation.0))
+            .collect()
+    }
+
+    /// Creates a light manager holding every light of the scene, loading the spot lights' cookies.
+    pub fn create_light_manager(
+        &self,
+        device: &wgpu::Device,
+        bind_group_layout: &wgpu::BindGroupLayout,
+        camera: &Camera,
+    ) -> Result<LightManager, image::ImageError> {
+        let directional_light = self
+            .directional_light
+            .as_ref()
+            .map(DirectionalLightDescription::to_gpu)
+            .unwrap_or_else(DirectionalLight::disabled);
+
+        let mut light_manager = LightManager::new(device, bind_group_layout, directional_light);
+        for light in &self.point_lights {
+            let index = light_manager.add_point_light(light.to_gpu(), light.casts_shadows);
+            light_manager.set_point_light_animations(index, light.animations.clone());
+        }
+        let mut cookie_layers: Vec<(&Path, Option<u32>)> = Vec::new();
+        for light in &self.spot_lights {
+            let index =
+                light_manager.add_spot_light(light.to_gpu(camera), light.attached_to_camera);
+            let path = match &light.cookie_texture {
+                Some(path) => path.as_path(),
+                None => continue,
+            };
+            let layer = match cookie_layers.iter().find(|(loaded, _)| *loaded == path) {
+                Some(&(_, layer)) => layer,
+                None => {
+                    let image = image::open(self.resolve_path(path))?.to_rgba();
+                    let layer = light_manager.add_cookie(device, &image);
+                    cookie_layers.push((path, layer));
+                    layer
+                }
+            };
+            if let Some(layer) = layer {
+                light_manager.set_spot_light_cookie(
+                    index,
+                    Some(SpotLightCookie {
+                        layer,
+                        cone_angle: light.cookie_angle(),
+                    }),
+                );
+            }
+        }
+        Ok(light_manager)
+    }
+
+    /// Projects the light arriving from the scene's surroundings onto spherical harmonics: the environment map if
+    /// there is one, or else the sky. Without either, the directional light's ambient colour arrives from everywhere.
+    pub fn ambient_spherical_harmonics(&self) -> Result<SphericalHarmonics, HdrError> {
+        if let Some(path) = &self.environment {
+            let image = HdrImage::load(self.resolve_path(path))?;
+            return Ok(SphericalHarmonics::from_equirectangular(&image));
+        }
+
+        let sky = self.sky.clone().unwrap_or_else(|| {
+            let ambient = self
+                .directional_light
+                .as_ref()
+                .map(|light| light.ambient)
+                .unwrap_or([0.0; 3]);
+            SkyDescription {
+                zenith: ambient,
+                horizon: ambient,
+                ground: ambient,
+            }
+        });
+        Ok(SphericalHarmonics::project(
+            SPHERICAL_HARMONICS_SIZE,
+            |direction| sky.radiance(direction),
+        ))
+    }
+
+    /// The material the textured chapters multiply their diffuse and specular maps with.
+    ///
+    /// ```
+    /// # use learnopengl_but_its_wgpu::scene::Scene;
+    /// #
+    /// let scene: Scene = "Scene(
+    ///     camera: (translation: (0.0, 0.0, 3.0)),
+    ///     material: (diffuse: (1.0, 0.5, 0.31), shininess: 64.0),
+    /// )"
+    /// .parse()
+    /// .unwrap();
+    /// let material = scene.material();
+    /// let diffuse = material.diffuse.0;
+    /// assert_eq!((1.0, 0.5, 0.31), (diffuse.x, diffuse.y, diffuse.z));
+    /// assert_eq!(64.0, material.shininess);
+    /// // Colours that aren't given leave the maps as they are.
+    /// assert_eq!((1.0, 1.0, 1.0), (material.specular.x, material.specular.y, material.specular.z));
+    /// ```
+    pub fn material(&self) -> Material {
+        Material {
+            ambient: vec3(self.material.ambient).into(),
+            diffuse: vec3(self.material.diffuse).into(),
+            specular: vec3(self.material.specular),
+            shininess: self.material.shininess,
+        }
+    }
+
+    /// Creates the instance and material buffers for the multiple lights pipeline.
+    pub fn create_buffers(&self, device: &wgpu::Device) -> SceneBuffers {
+        let cubes = self.cube_instances();
+        let cube_instance_buffer = device
+            .create_buffer_mapped(cubes.len(), wgpu::BufferUsage::VERTEX)
+            .fill_from_slice(&cubes);
+
+        let material_uniform_buffer = device
+            .create_buffer_mapped(1, wgpu::BufferUsage::UNIFORM | wgpu::BufferUsage::COPY_DST)
+            .fill_from_slice(&[self.material()]);
+
+        SceneBuffers {
+            cube_instance_buffer,
+            cube_count: cubes.len() as u32,
+            material_uniform_buffer,
+        }
+    }
+}
+
+impl FromStr for Scene {
+    type Err = SceneError;
+
+    /// Parses a scene from RON source. Relative paths will be resolved against the current working directory.
+    fn from_str(source: &str) -> Result<Self, Self::Err> {
+        ron::de::from_str(source).map_err(SceneError::Parse)
+    }
+}
+
+impl ObjectDescription {
+    pub fn model_matrix(&self) -> Mat4 {
+        let rotation = self
+            .rotation
+            .map(|rotation| rotation.matrix())
+            .unwrap_or_else(Mat4::identity);
+        Mat4::from_translation(vec3(self.translation)) * rotation * scale_matrix(self.scale)
+    }
+}
+
+impl RotationDescription {
+    /// Builds the rotation matrix using Rodrigues' rotation formula.
+    ///
+    /// ```
+    /// # use ultraviolet::Vec4;
+    /// #
+    /// # use learnopengl_but_its_wgpu::scene::Scene;
+    /// #
+    /// let scene: Scene = "Scene(
+    ///     camera: (translation: (0.0, 0.0, 3.0)),
+    ///     objects: [(translation: (0.0, 0.0, 0.0), rotation: Some((axis: (0.0, 2.0, 0.0), angle: 90.0)))],
+    /// )"
+    /// .parse()
+    /// .unwrap();
+    /// let rotation = scene.objects[0].rotation.unwrap().matrix();
+    ///
+    /// // A quarter turn around Y takes X over to -Z, even though the axis isn't normalized.
+    /// let rotated = rotation * Vec4::new(1.0, 0.0, 0.0, 1.0);
+    /// assert!((rotated - Vec4::new(0.0, 0.0, -1.0, 1.0)).mag() < 1e-6);
+    /// // Points on the axis stay where they are.
+    /// let rotated = rotation * Vec4::new(0.0, 3.0, 0.0, 1.0);
+    /// assert!((rotated - Vec4::new(0.0, 3.0, 0.0, 1.0)).mag() < 1e-6);
+    /// ```
+    pub fn matrix(&self) -> Mat4 {
+        let axis = vec3(self.axis).normalized();
+        let (sin, cos) = self.angle.to_radians().sin_cos();
+        let t = 1.0 - cos;
+        let Vec3 { x, y, z } = axis;
+
+        Mat4 {
+            cols: [
+                Vec4::new(
+                    t * x * x + cos,
+                    t * x * y + sin * z,
+                    t * x * z - sin * y,
+                    0.0,
+                ),
+                Vec4::new(
+                    t * x * y - sin * z,
+                    t * y * y + cos,
+                    t * y * z + sin * x,
+                    0.0,
+                ),
+                Vec4::new(
+                    t * x * z + sin * y,
+                    t * y * z - sin * x,
+                    t * z * z + cos,
+                    0.0,
+                ),
+                Vec4::new(0.0, 0.0, 0.0, 1.0),
+            ],
+        }
+    }
+}
+
+impl PbrMaterialDescription {
+    pub fn to_gpu(&self) -> PbrMaterial {
+        PbrMaterial {
+            albedo: vec3(self.albedo),
+            metallic: self.metallic,
+            emissive: vec3(self.emissive),
+            roughness: self.roughness,
+            ambient_occlusion: self.ambient_occlusion,
+        }
+    }
+}
+
+impl SkyDescription {
+    pub fn radiance(&self, direction: Vec3) -> Vec3 {
+        if direction.y < 0.0 {
+            return vec3(self.ground);
+        }
+        // Most of the sky is close to the zenith's colour, with the horizon's only showing up close to it.
+        let t = direction.y.sqrt();
+        vec3(self.horizon) * (1.0 - t) + vec3(self.zenith) * t
+    }
+}
+
+impl DirectionalLightDescription {
+    pub fn to_gpu(&self) -> DirectionalLight {
+        DirectionalLight {
+            direction: vec3(self.direction).into(),
+            ambient: vec3(self.ambient).into(),
+            diffuse: vec3(self.diffuse).into(),
+            specular: vec3(self.specular),
+        }
+    }
+}
+
+impl PointLightDescription {
+    pub fn to_gpu(&self) -> PointLight {
+        PointLight {
+            translation: vec3(self.translation).into(),
+            ambient: vec3(self.ambient).into(),
+            diffuse: vec3(self.diffuse).into(),
+            specular: vec3(self.specular),
+            constant: self.attenuation.constant,
+            linear: self.attenuation.linear,
+            quadratic: self.attenuation.quadratic,
+            shadow_map_index: -1,
+        }
+    }
+
+    /// The light as its animations leave it at `time` seconds. This is what the examples that don't use a
+    /// [`LightManager`] upload every frame.
+    pub fn sample(&self, time: f32) -> PointLight {
+        let mut light = self.to_gpu();
+        for animation in &self.animations {
+            animation.apply(&mut light, time);
+        }
+        light
+    }
+}
+
+impl SpotLightDescription {
+    pub fn to_gpu(&self, camera: &Camera) -> SpotLight {
+        let (translation, direction) = if self.attached_to_camera {
+            (camera.world_translation(), camera.get_direction_vector())
+        } else {
+            (vec3(self.translation), vec3(self.direction).normalized())
+        };
+
+        SpotLight {
+            translation: translation.into(),
+            direction,
+            cutoff: self.cutoff.to_radians().cos(),
+            outer_cutoff: self.outer_cutoff.to_radians().cos(),
+            constant: self.attenuation.constant,
+            linear: self.attenuation.linear,
+            quadratic: self.attenuation.quadratic,
+            ambient: vec3(self.ambient).into(),
+            diffuse: vec3(self.diffuse).into(),
+            specular: vec3(self.specular),
+            cookie_index: -1,
+            cookie_space: Mat4::identity(),
+        }
+    }
+
+    /// The full angle of the cone the light's cookie is projected into, in radians.
+    pub fn cookie_angle(&self) -> f32 {
+        self.cookie_angle
+            .unwrap_or(self.outer_cutoff * 2.0)
+            .to_radians()
+    }
+}
+
+impl Default for MaterialDescription {
+    fn default() -> Self {
+        Self {
+            ambient: default_ambient(),
+            diffuse: default_diffuse(),
+            specular: default_specular(),
+            shininess: default_shininess(),
+            diffuse_texture: None,
+            specular_texture: None,
+            normal_texture: None,
+            height_texture: None,
+            emissive: [0.0, 0.0, 0.0],
+            emissive_texture: None,
+            emissive_scroll: [0.0, 0.0],
+        }
+    }
+}
+
+impl Default for PbrMaterialDescription {
+    fn default() -> Self {
+        Self {
+            albedo: default_albedo(),
+            metallic: 0.0,
+            roughness: default_roughness(),
+            ambient_occlusion: default_ambient_occlusion(),
+            emissive: [0.0, 0.0, 0.0],
+            albedo_texture: None,
+            metallic_roughness_texture: None,
+            ambient_occlusion_texture: None,
+            emissive_texture: None,
+            normal_texture: None,
+        }
+    }
+}
+
+impl Default for Attenuation {
+    /// No attenuation at all.
+    fn default() -> Self {
+        Self {
+            constant: 1.0,
+            linear: 0.0,
+            quadratic: 0.0,
+        }
+    }
+}
+
+impl fmt::Display for SceneError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SceneError::Io(err) => write!(f, "could not read scene file: {}", err),
+            SceneError::Parse(err) => write!(f, "could not parse scene file: {}", err),
+        }
+    }
+}
+
+impl Error for SceneError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            SceneError::Io(err) => Some(err),
+            SceneError::Parse(err) => Some(err),
+        }
+    }
+}
+
+pub fn vec3(array: [f32; 3]) -> Vec3 {
+    Vec3::new(array[0], array[1], array[2])
+}
+
+fn scale_matrix(scale: [f32; 3]) -> Mat4 {
+    Mat4 {
+        cols: [
+            Vec4::new(scale[0], 0.0, 0.0, 0.0),
+            Vec4::new(0.0, scale[1], 0.0, 0.0),
+            Vec4::new(0.0, 0.0, scale[2], 0.0),
+            Vec4::new(0.0, 0.0, 0.0, 1.0),
+        ],
+    }
+}
+
+fn default_fov() -> f32 {
+    60.0
+}
+
+fn default_ambient() -> [f32; 3] {
+    [1.0, 1.0, 1.0]
+}
+
+fn default_diffuse() -> [f32; 3] {
+    [1.0, 1.0, 1.0]
+}
+
+fn default_specular() -> [f32; 3] {
+    [1.0, 1.0, 1.0]
+}
+
+fn default_shininess() -> f32 {
+    32.0
+}
+
+fn default_albedo() -> [f32; 3] {
+    [1.0, 1.0, 1.0]
+}
+
+fn default_roughness() -> f32 {
+    0.5
+}
+
+fn default_ambient_occlusion() -> f32 {
+    1.0
+}
+
+fn default_scale() -> [f32; 3] {
+    [1.0, 1.0, 1.0]
+}
+
+fn default_direction() -> [f32; 3] {
+    [0.0, 0.0, -1.0]
+}
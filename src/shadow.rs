@@ -0,0 +1,576 @@
+//! Shadow maps: depth textures rendered from a light's point of view, and the matrices transforming world space into
+//! the clip space of a light.
+//!
+//! The light matrices produce depths in `0.0..=1.0` like the camera's projection does. wgpu's normalized device
+//! coordinates point Y down like Vulkan's, which matches texture coordinates, so a shadow map is sampled at
+//! `ndc.xy * 0.5 + 0.5` without any flipping.
+
+use ultraviolet::{Mat4, Vec3, Vec4};
+
+pub const SHADOW_MAP_FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Depth32Float;
+/// The format of variance shadow maps, which store the mean depth and the mean squared depth.
+pub const VARIANCE_SHADOW_MAP_FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Rg32Float;
+
+/// How a light's shadow map is filtered when shading.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ShadowFilter {
+    /// Percentage-closer filtering averages the depth comparisons of a fixed number of nearby texels, which only
+    /// softens the edges of the shadows by a constant amount.
+    Pcf = 0,
+    /// Variance shadow maps store the first two moments of the depths, which can be blurred like any other texture.
+    /// Chebyshev's inequality then bounds how much of the blurred area is lit. Overlapping occluders can make light
+    /// bleed through the shadows.
+    Variance = 1,
+    /// Percentage-closer soft shadows search the shadow map for the occluders above a fragment, and widen the PCF
+    /// filter by how far the fragment is from them. Contact shadows stay sharp while distant ones get blurry, like
+    /// with a light source that has a size.
+    PercentageCloserSoft = 2,
+}
+
+impl ShadowFilter {
+    /// The next filter, for cycling through all of them.
+    pub fn next(self) -> Self {
+        match self {
+            ShadowFilter::Pcf => ShadowFilter::Variance,
+            ShadowFilter::Variance => ShadowFilter::PercentageCloserSoft,
+            ShadowFilter::PercentageCloserSoft => ShadowFilter::Pcf,
+        }
+    }
+}
+
+/// A depth texture that can be rendered into and then sampled with a comparison sampler.
+pub struct ShadowMap {
+    pub texture: wgpu::Texture,
+    pub view: wgpu::TextureView,
+    pub sampler: wgpu::Sampler,
+    /// A sampler returning the stored depths themselves, for searching for occluders or blurring the depths.
+    pub depth_sampler: wgpu::Sampler,
+    pub size: u32,
+}
+
+impl ShadowMap {
+    pub fn new(device: &wgpu::Device, size: u32) -> Self {
+        let texture = device.create_texture(&wgpu::TextureDescriptor {
+            size: wgpu::Extent3d {
+                width: size,
+                height: size,
+                depth: 1,
+            },
+            array_layer_count: 1,
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: SHADOW_MAP_FORMAT,
+            usage: wgpu::TextureUsage::OUTPUT_ATTACHMENT | wgpu::TextureUsage::SAMPLED,
+        });
+        let view = texture.create_default_view();
+
+        Self {
+            texture,
+            view,
+            sampler: create_comparison_sampler(device),
+            depth_sampler: device.create_sampler(&wgpu::SamplerDescriptor {
+                address_mode_u: wgpu::AddressMode::ClampToEdge,
+                address_mode_v: wgpu::AddressMode::ClampToEdge,
+                address_mode_w: wgpu::AddressMode::ClampToEdge,
+                mag_filter: wgpu::FilterMode::Nearest,
+                min_filter: wgpu::FilterMode::Nearest,
+                mipmap_filter: wgpu::FilterMode::Nearest,
+                lod_min_clamp: 0.0,
+                lod_max_clamp: 0.0,
+                compare_function: wgpu::CompareFunction::Never,
+            }),
+            size,
+        }
+    }
+}
+
+/// The moments of a [`ShadowMap`]'s depths, blurred so that they can be filtered with Chebyshev's inequality.
+///
+/// The moments are derived from the depth texture in a separate pass rather than rendered directly, so the shadow
+/// pass itself stays the same no matter how the shadows are filtered.
+pub struct VarianceShadowMap {
+    pub texture: wgpu::Texture,
+    pub view: wgpu::TextureView,
+    /// A linearly filtering sampler, which blurs the moments a little further.
+    pub sampler: wgpu::Sampler,
+    pub size: u32,
+}
+
+impl VarianceShadowMap {
+    pub fn new(device: &wgpu::Device, size: u32) -> Self {
+        let texture = device.create_texture(&wgpu::TextureDescriptor {
+            size: wgpu::Extent3d {
+                width: size,
+                height: size,
+                depth: 1,
+            },
+            array_layer_count: 1,
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: VARIANCE_SHADOW_MAP_FORMAT,
+            usage: wgpu::TextureUsage::OUTPUT_ATTACHMENT | wgpu::TextureUsage::SAMPLED,
+        });
+        let view = texture.create_default_view();
+        let sampler = device.create_sampler(&wgpu::SamplerDescriptor {
+            address_mode_u: wgpu::AddressMode::ClampToEdge,
+            address_mode_v: wgpu::AddressMode::ClampToEdge,
+            address_mode_w: wgpu::AddressMode::ClampToEdge,
+            mag_filter: wgpu::FilterMode::Linear,
+            min_filter: wgpu::FilterMode::Linear,
+            mipmap_filter: wgpu::FilterMode::Nearest,
+            lod_min_clamp: 0.0,
+            lod_max_clamp: 0.0,
+            compare_function: wgpu::CompareFunction::Never,
+        });
+
+        Self {
+            texture,
+            view,
+            sampler,
+            size,
+        }
+    }
+}
+
+/// The shadow cube maps of several point lights, stored as the faces of a cube map array.
+///
+/// Each face stores the distance from the light divided by the far plane rather than the projected depth, so that the
+/// whole cube map can be compared against a single distance no matter which face a direction lands on.
+pub struct PointShadowMaps {
+    pub texture: wgpu::Texture,
+    /// A cube map array view of every light's shadow map, for sampling.
+    pub view: wgpu::TextureView,
+    /// A 2D view of every face, for rendering into. The faces of light `i` are at `6 * i..6 * i + 6`.
+    face_views: Vec<wgpu::TextureView>,
+    pub sampler: wgpu::Sampler,
+    pub size: u32,
+    pub count: u32,
+}
+
+impl PointShadowMaps {
+    pub fn new(device: &wgpu::Device, size: u32, count: u32) -> Self {
+        let texture = device.create_texture(&wgpu::TextureDescriptor {
+            size: wgpu::Extent3d {
+                width: size,
+                height: size,
+                depth: 1,
+            },
+            array_layer_count: count * 6,
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: SHADOW_MAP_FORMAT,
+            usage: wgpu::TextureUsage::OUTPUT_ATTACHMENT | wgpu::TextureUsage::SAMPLED,
+        });
+        let view = texture.create_view(&wgpu::TextureViewDescriptor {
+            format: SHADOW_MAP_FORMAT,
+            dimension: wgpu::TextureViewDimension::CubeArray,
+            aspect: wgpu::TextureAspect::All,
+            base_mip_level: 0,
+            level_count: 1,
+            base_array_layer: 0,
+            array_layer_count: count * 6,
+        });
+        let face_views = (0..count * 6)
+            .map(|layer| {
+                texture.create_view(&wgpu::TextureViewDescriptor {
+                    format: SHADOW_MAP_FORMAT,
+                    dimension: wgpu::TextureViewDimension::D2,
+                    aspect: wgpu::TextureAspect::All,
+                    base_mip_level: 0,
+                    level_count: 1,
+                    base_array_layer: layer,
+                    array_layer_count: 1,
+                })
+            })
+            .collect();
+
+        Self {
+            texture,
+            view,
+            face_views,
+            sampler: create_comparison_sampler(device),
+            size,
+            count,
+        }
+    }
+
+    /// The view of one face of a light's cube map, in the order of [`point_light_face_spaces`].
+    pub fn face_view(&self, light: usize, face: usize) -> &wgpu::TextureView {
+        &self.face_views[light * 6 + face]
+    }
+}
+
+/// The shadow maps of a directional light's cascades, stored as the layers of a 2D array texture.
+pub struct CascadedShadowMap {
+    pub texture: wgpu::Texture,
+    /// A 2D array view of every cascade, for sampling.
+    pub view: wgpu::TextureView,
+    /// A 2D view of every cascade, for rendering into.
+    cascade_views: Vec<wgpu::TextureView>,
+    pub sampler: wgpu::Sampler,
+    pub size: u32,
+    pub cascade_count: u32,
+}
+
+impl CascadedShadowMap {
+    pub fn new(device: &wgpu::Device, size: u32, cascade_count: u32) -> Self {
+        let texture = device.create_texture(&wgpu::TextureDescriptor {
+            size: wgpu::Extent3d {
+                width: size,
+                height: size,
+                depth: 1,
+            },
+            array_layer_count: cascade_count,
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: SHADOW_MAP_FORMAT,
+            usage: wgpu::TextureUsage::OUTPUT_ATTACHMENT | wgpu::TextureUsage::SAMPLED,
+        });
+        let view = texture.create_view(&wgpu::TextureViewDescriptor {
+            format: SHADOW_MAP_FORMAT,
+            dimension: wgpu::TextureViewDimension::D2Array,
+            aspect: wgpu::TextureAspect::All,
+            base_mip_level: 0,
+            level_count: 1,
+            base_array_layer: 0,
+            array_layer_count: cascade_count,
+        });
+        let cascade_views = (0..cascade_count)
+            .map(|layer| {
+                texture.create_view(&wgpu::TextureViewDescriptor {
+                    format: SHADOW_MAP_FORMAT,
+                    dimension: wgpu::TextureViewDimension::D2,
+                    aspect: wgpu::TextureAspect::All,
+                    base_mip_level: 0,
+                    level_count: 1,
+                    base_array_layer: layer,
+                    array_layer_count: 1,
+                })
+            })
+            .collect();
+
+        Self {
+            texture,
+            view,
+            cascade_views,
+            sampler: create_comparison_sampler(device),
+            size,
+            cascade_count,
+        }
+    }
+
+    pub fn cascade_view(&self, cascade: usize) -> &wgpu::TextureView {
+        &self.cascade_views[cascade]
+    }
+}
+
+/// Creates a sampler that compares the depth it's given with the stored depth instead of returning the depth itself.
+///
+/// With linear filtering, the hardware compares the four nearest texels and blends the results, which smooths the
+/// shadow's edges a little even before any PCF is done in the shader.
+pub fn create_comparison_sampler(device: &wgpu::Device) -> wgpu::Sampler {
+    device.create_sampler(&wgpu::SamplerDescriptor {
+        address_mode_u: wgpu::AddressMode::ClampToEdge,
+        address_mode_v: wgpu::AddressMode::ClampToEdge,
+        address_mode_w: wgpu::AddressMode::ClampToEdge,
+        mag_filter: wgpu::FilterMode::Linear,
+        min_filter: wgpu::FilterMode::Linear,
+        mipmap_filter: wgpu::FilterMode::Nearest,
+        lod_min_clamp: 0.0,
+        lod_max_clamp: 0.0,
+        compare_function: wgpu::CompareFunction::LessEqual,
+    })
+}
+
+/// A right-handed view matrix looking from `eye` towards `target`.
+pub fn look_at(eye: Vec3, target: Vec3, up: Vec3) -> Mat4 {
+    let forward = (target - eye).normalized();
+    // Fall back to another up vector when looking straight along the given one.
+    let up = if forward.cross(up).mag_sq() < 1e-6 {
+        Vec3::new(1.0, 0.0, 0.0)
+    } else {
+        up
+    };
+    let right = forward.cross(up).normalized();
+    let up = right.cross(forward);
+
+    Mat4 {
+        cols: [
+            Vec4::new(right.x, up.x, -forward.x, 0.0),
+            Vec4::new(right.y, up.y, -forward.y, 0.0),
+            Vec4::new(right.z, up.z, -forward.z, 0.0),
+            Vec4::new(-right.dot(eye), -up.dot(eye), forward.dot(eye), 1.0),
+        ],
+    }
+}
+
+/// An orthographic projection of the box in front of a right-handed view, with depths from `0.0` at `near` to `1.0`
+/// at `far`.
+pub fn orthographic(left: f32, right: f32, bottom: f32, top: f32, near: f32, far: f32) -> Mat4 {
+    Mat4 {
+        cols: [
+            Vec4::new(2.0 / (right - left), 0.0, 0.0, 0.0),
+            Vec4::new(0.0, 2.0 / (top - bottom), 0.0, 0.0),
+            Vec4::new(0.0, 0.0, -1.0 / (far - near), 0.0),
+            Vec4::new(
+                -(right + left) / (right - left),
+                -(top + bottom) / (top - bottom),
+                -near / (far - near),
+                1.0,
+            ),
+        ],
+    }
+}
+
+/// A perspective projection for a right-handed view, with depths from `0.0` at `near` to `1.0` at `far`.
+pub fn perspective(vertical_fov: f32, aspect_ratio: f32, near: f32, far: f32) -> Mat4 {
+    let y_scale = 1.0 / (vertical_fov / 2.0).tan();
+    Mat4 {
+        cols: [
+            Vec4::new(y_scale / aspect_ratio, 0.0, 0.0, 0.0),
+            Vec4::new(0.0, y_scale, 0.0, 0.0),
+            Vec4::new(0.0, 0.0, far / (near - far), -1.0),
+            Vec4::new(0.0, 0.0, near * far / (near - far), 0.0),
+        ],
+    }
+}
+
+/// The light space of a directional light shining in `direction`, fitted around the sphere at `center` so that
+/// everything within `radius` of it lands in the shadow map.
+///
+/// ```
+/// # use ultraviolet::{Vec3, Vec4};
+/// #
+/// # use learnopengl_but_its_wgpu::shadow::directional_light_space;
+/// #
+/// let light_space = directional_light_space(Vec3::new(0.0, -1.0, 0.0), Vec3::zero(), 10.0);
+///
+/// // The center of the sphere lands in the middle of the shadow map, and points further along the light direction
+/// // are deeper.
+/// let center = light_space * Vec4::new(0.0, 0.0, 0.0, 1.0);
+/// let below = light_space * Vec4::new(0.0, -5.0, 0.0, 1.0);
+/// assert!(center.x.abs() < 1e-6 && center.y.abs() < 1e-6);
+/// assert!(0.0 < center.z && center.z < below.z && below.z < 1.0);
+///
+/// // The edges of the sphere land on the edges of the shadow map.
+/// let edge = light_space * Vec4::new(10.0, 0.0, 0.0, 1.0);
+/// assert!((edge.x.abs().max(edge.y.abs()) - 1.0).abs() < 1e-5);
+/// ```
+pub fn directional_light_space(direction: Vec3, center: Vec3, radius: f32) -> Mat4 {
+    let direction = direction.normalized();
+    let eye = center - direction * (radius * 2.0);
+    let view = look_at(eye, center, Vec3::new(0.0, 1.0, 0.0));
+    orthographic(-radius, radius, -radius, radius, radius, radius * 3.0) * view
+}
+
+/// Splits the view distance between `near` and `far` into `count` cascades, returning the far distance of each.
+///
+/// This is the "practical" split scheme, which blends logarithmic splits (`lambda = 1.0`), giving every cascade the
+/// same resolution relative to its distance from the camera, with uniform splits (`lambda = 0.0`), which waste
+/// resolution close to the camera but don't make the far cascades quite as huge.
+///
+/// ```
+/// # use learnopengl_but_its_wgpu::shadow::cascade_splits;
+/// #
+/// let uniform = cascade_splits(0.1, 100.0, 4, 0.0);
+/// assert!((uniform[0] - 25.075).abs() < 1e-3);
+///
+/// let logarithmic = cascade_splits(0.1, 100.0, 4, 1.0);
+/// assert!((logarithmic[0] - 0.1 * 1000.0f32.powf(0.25)).abs() < 1e-3);
+///
+/// let practical = cascade_splits(0.1, 100.0, 4, 0.75);
+/// assert_eq!(practical.len(), 4);
+/// assert!((practical[3] - 100.0).abs() < 1e-3);
+/// assert!(practical.windows(2).all(|splits| splits[0] < splits[1]));
+/// ```
+pub fn cascade_splits(near: f32, far: f32, count: usize, lambda: f32) -> Vec<f32> {
+    (1..=count)
+        .map(|i| {
+            let fraction = i as f32 / count as f32;
+            let logarithmic = near * (far / near).powf(fraction);
+            let uniform = near + (far - near) * fraction;
+            lambda * logarithmic + (1.0 - lambda) * uniform
+        })
+        .collect()
+}
+
+/// The corners of the slice of a perspective camera's frustum between the distances `near` and `far` along
+/// `forward`.
+pub fn frustum_slice_corners(
+    translation: Vec3,
+    forward: Vec3,
+    vertical_fov: f32,
+    aspect_ratio: f32,
+    near: f32,
+    far: f32,
+) -> [Vec3; 8] {
+    let forward = forward.normalized();
+    let right = if forward.cross(Vec3::new(0.0, 1.0, 0.0)).mag_sq() < 1e-6 {
+        Vec3::new(1.0, 0.0, 0.0)
+    } else {
+        forward.cross(Vec3::new(0.0, 1.0, 0.0)).normalized()
+    };
+    let up = right.cross(forward);
+    let tan_half_fov = (vertical_fov / 2.0).tan();
+
+    let mut corners = [Vec3::zero(); 8];
+    for (i, &distance) in [near, far].iter().enumerate() {
+        let center = translation + forward * distance;
+        let half_height = up * (distance * tan_half_fov);
+        let half_width = right * (distance * tan_half_fov * aspect_ratio);
+        corners[i * 4] = center - half_width - half_height;
+        corners[i * 4 + 1] = center + half_width - half_height;
+        corners[i * 4 + 2] = center + half_width + half_height;
+        corners[i * 4 + 3] = center - half_width + half_height;
+    }
+    corners
+}
+
+/// The light space of a directional light covering the bounding sphere of `corners`, snapped to the texels of a
+/// shadow map `shadow_map_size` texels wide.
+///
+/// Fitting a sphere instead of a box keeps the size of the projection the same as the camera rotates, and snapping
+/// keeps it from moving by fractions of a texel as the camera moves, both of which would make the shadows' edges
+/// shimmer. The projection reaches twice the sphere's radius further towards the light to catch the occluders outside
+/// of the frustum.
+///
+/// ```
+/// # use ultraviolet::{Vec3, Vec4};
+/// #
+/// # use learnopengl_but_its_wgpu::shadow::{frustum_slice_corners, stable_directional_light_space};
+/// #
+/// let light_direction = Vec3::new(-0.2, -1.0, -0.3);
+/// let light_space = |camera_translation: Vec3| {
+///     let corners = frustum_slice_corners(
+///         camera_translation,
+///         Vec3::new(0.0, 0.0, -1.0),
+///         45.0f32.to_radians(),
+///         16.0 / 9.0,
+///         0.1,
+///         20.0,
+///     );
+///     stable_directional_light_space(light_direction, &corners, 1024)
+/// };
+///
+/// // Moving the camera slightly moves the shadows by whole texels, or not at all.
+/// let point = Vec4::new(1.0, 2.0, -5.0, 1.0);
+/// let before = light_space(Vec3::zero()) * point;
+/// let after = light_space(Vec3::new(0.013, 0.0, -0.007)) * point;
+/// let texels = (before - after) * (1024.0 / 2.0);
+/// assert!((texels.x - texels.x.round()).abs() < 1e-2);
+/// assert!((texels.y - texels.y.round()).abs() < 1e-2);
+/// ```
+pub fn stable_directional_light_space(
+    direction: Vec3,
+    corners: &[Vec3],
+    shadow_map_size: u32,
+) -> Mat4 {
+    let center = corners
+        .iter()
+        .fold(Vec3::zero(), |sum, &corner| sum + corner)
+        / corners.len() as f32;
+    let radius = corners
+        .iter()
+        .map(|&corner| (corner - center).mag())
+        .fold(0.0, f32::max);
+    // Rounding up hides the floating point error that would otherwise make the radius vary as the camera rotates.
+    let radius = (radius * 16.0).ceil() / 16.0;
+
+    let direction = direction.normalized();
+    let eye = center - direction * (radius * 3.0);
+    let view = look_at(eye, center, Vec3::new(0.0, 1.0, 0.0));
+    let mut light_space = orthographic(-radius, radius, -radius, radius, 0.0, radius * 4.0) * view;
+
+    // Move the projection so that the world's origin lands exactly on a texel.
+    let half_size = shadow_map_size as f32 / 2.0;
+    let origin = light_space * Vec4::new(0.0, 0.0, 0.0, 1.0);
+    let (texel_x, texel_y) = (origin.x * half_size, origin.y * half_size);
+    light_space.cols[3].x += (texel_x.round() - texel_x) / half_size;
+    light_space.cols[3].y += (texel_y.round() - texel_y) / half_size;
+    light_space
+}
+
+/// The light spaces of the six faces of a point light's shadow cube map, in the order of the cube map's layers: +X,
+/// -X, +Y, -Y, +Z and -Z.
+///
+/// Each face is a 90 degree perspective projection, oriented so that what it renders lands where a cube map lookup
+/// in the face's direction expects it.
+///
+/// ```
+/// # use ultraviolet::{Vec3, Vec4};
+/// #
+/// # use learnopengl_but_its_wgpu::shadow::point_light_face_spaces;
+/// #
+/// let translation = Vec3::new(1.0, 2.0, 3.0);
+/// let faces = point_light_face_spaces(translation, 0.1, 25.0);
+/// let point = |direction: Vec3| {
+///     let point = translation + direction;
+///     Vec4::new(point.x, point.y, point.z, 1.0)
+/// };
+///
+/// // Looking up the direction `d` in the +X face reads the texel at `(-d.z / d.x, -d.y / d.x) * 0.5 + 0.5`.
+/// let direction = Vec3::new(1.0, 0.2, 0.3);
+/// let clip = faces[0] * point(direction);
+/// assert!((clip.x / clip.w - -0.3).abs() < 1e-5);
+/// assert!((clip.y / clip.w - -0.2).abs() < 1e-5);
+///
+/// // Looking up the direction `d` in the +Y face reads the texel at `(d.x / d.y, d.z / d.y) * 0.5 + 0.5`.
+/// let direction = Vec3::new(0.2, 1.0, 0.3);
+/// let clip = faces[2] * point(direction);
+/// assert!((clip.x / clip.w - 0.2).abs() < 1e-5);
+/// assert!((clip.y / clip.w - 0.3).abs() < 1e-5);
+/// ```
+pub fn point_light_face_spaces(translation: Vec3, near: f32, far: f32) -> [Mat4; 6] {
+    let projection = perspective(std::f32::consts::FRAC_PI_2, 1.0, near, far);
+    let face =
+        |direction: Vec3, up: Vec3| projection * look_at(translation, translation + direction, up);
+    [
+        face(Vec3::new(1.0, 0.0, 0.0), Vec3::new(0.0, -1.0, 0.0)),
+        face(Vec3::new(-1.0, 0.0, 0.0), Vec3::new(0.0, -1.0, 0.0)),
+        face(Vec3::new(0.0, 1.0, 0.0), Vec3::new(0.0, 0.0, 1.0)),
+        face(Vec3::new(0.0, -1.0, 0.0), Vec3::new(0.0, 0.0, -1.0)),
+        face(Vec3::new(0.0, 0.0, 1.0), Vec3::new(0.0, -1.0, 0.0)),
+        face(Vec3::new(0.0, 0.0, -1.0), Vec3::new(0.0, -1.0, 0.0)),
+    ]
+}
+
+/// The light space of a spot light, with a field of view just wide enough to fit its outer cone.
+///
+/// `outer_cutoff` is the cosine of the outer cone's half-angle, as stored in [`SpotLight`](crate::lighting::SpotLight).
+///
+/// ```
+/// # use ultraviolet::{Vec3, Vec4};
+/// #
+/// # use learnopengl_but_its_wgpu::shadow::spot_light_space;
+/// #
+/// let outer_cutoff = 30.0f32.to_radians().cos();
+/// let light_space = spot_light_space(Vec3::zero(), Vec3::new(0.0, 0.0, -1.0), outer_cutoff, 0.1, 50.0);
+///
+/// // A point on the edge of the cone lands on the edge of the shadow map.
+/// let edge = light_space * Vec4::new(0.0, 30.0f32.to_radians().tan() * 10.0, -10.0, 1.0);
+/// assert!((edge.y / edge.w - 1.0).abs() < 1e-4);
+///
+/// // Depths increase away from the light.
+/// let near = light_space * Vec4::new(0.0, 0.0, -1.0, 1.0);
+/// let far = light_space * Vec4::new(0.0, 0.0, -40.0, 1.0);
+/// assert!(0.0 < near.z / near.w && near.z / near.w < far.z / far.w && far.z / far.w < 1.0);
+/// ```
+pub fn spot_light_space(
+    translation: Vec3,
+    direction: Vec3,
+    outer_cutoff: f32,
+    near: f32,
+    far: f32,
+) -> Mat4 {
+    let view = look_at(
+        translation,
+        translation + direction,
+        Vec3::new(0.0, 1.0, 0.0),
+    );
+    let fov = outer_cutoff.acos() * 2.0;
+    perspective(fov, 1.0, near, far) * view
+}
@@ -0,0 +1,395 @@
+//! Ambient lighting from spherical harmonics: a handful of coefficients approximating the light arriving from every
+//! direction, cheap enough to evaluate for every fragment without any textures.
+//!
+//! Spherical harmonics are to functions on the sphere what a Fourier series is to periodic functions. The first three
+//! bands (L2) only hold 9 coefficients per colour channel, which is far too few to capture sharp details like the sun,
+//! but irradiance is so smooth that they reproduce it to within a few percent.
+//!
+//! The basis functions are the real spherical harmonics in the usual order: `Y(0, 0)`, then `Y(1, -1)` to `Y(1, 1)`
+//! and `Y(2, -2)` to `Y(2, 2)`, which are polynomials in the X, Y and Z components of a direction.
+
+use std::f32::consts::PI;
+
+use ultraviolet::{Vec3, Vec4};
+
+use crate::{
+    ibl::{equirectangular_direction, face_direction, CubeMap},
+    radiance::HdrImage,
+};
+
+/// The number of coefficients of the first three bands.
+pub const COEFFICIENT_COUNT: usize = 9;
+
+/// The radiance arriving from every direction, projected onto the first three bands of spherical harmonics.
+#[derive(Clone, Copy, Debug)]
+pub struct SphericalHarmonics {
+    pub coefficients: [Vec3; COEFFICIENT_COUNT],
+}
+
+impl SphericalHarmonics {
+    /// Projects `radiance` onto spherical harmonics, integrating it over the texels of a cube map with `size` texels
+    /// along each edge of a face.
+    ///
+    /// Functions made up of the first three bands, which includes every polynomial of up to the second degree, are
+    /// reconstructed exactly:
+    ///
+    /// ```
+    /// # use ultraviolet::Vec3;
+    /// #
+    /// # use learnopengl_but_its_wgpu::spherical_harmonics::SphericalHarmonics;
+    /// #
+    /// let function = |d: Vec3| Vec3::new(1.0, 0.5 + d.z, d.x * d.x - d.y * d.y + 2.0 * d.x * d.y);
+    /// let sh = SphericalHarmonics::project(32, function);
+    ///
+    /// for &direction in &[Vec3::new(1.0, 2.0, 3.0), Vec3::new(-1.0, 0.0, 0.5), Vec3::new(0.2, -1.0, -0.7)] {
+    ///     let direction = direction.normalized();
+    ///     assert!((sh.evaluate(direction) - function(direction)).mag() < 1e-3);
+    /// }
+    /// ```
+    pub fn project<F: FnMut(Vec3) -> Vec3>(size: u32, mut radiance: F) -> Self {
+        let mut coefficients = [Vec3::zero(); COEFFICIENT_COUNT];
+        let mut total_weight = 0.0;
+        for face in 0..6 {
+            for y in 0..size {
+                for x in 0..size {
+                    let u = (x as f32 + 0.5) / size as f32;
+                    let v = (y as f32 + 0.5) / size as f32;
+                    // Texels towards the corners of a face are further away from the center of the cube and cover a
+                    // smaller solid angle.
+                    let s = u * 2.0 - 1.0;
+                    let t = v * 2.0 - 1.0;
+                    let weight = 1.0 / (1.0 + s * s + t * t).powf(1.5);
+
+                    let direction = face_direction(face, u, v);
+                    let value = radiance(direction);
+                    for (coefficient, &basis) in coefficients.iter_mut().zip(&basis(direction)) {
+                        *coefficient += value * (basis * weight);
+                    }
+                    total_weight += weight;
+                }
+            }
+        }
+
+        // The weights are only proportional to the solid angles, which add up to the whole sphere.
+        let scale = 4.0 * PI / total_weight;
+        for coefficient in &mut coefficients {
+            *coefficient *= scale;
+        }
+        Self { coefficients }
+    }
+
+    /// Projects the radiance stored in a cube map onto spherical harmonics.
+    pub fn from_cube_map(cube_map: &CubeMap) -> Self {
+        // `project` visits the texels in the order they're stored in.
+        let mut texels = cube_map.texels.iter();
+        Self::project(cube_map.size, |_| *texels.next().unwrap())
+    }
+
+    /// Projects an equirectangular image onto spherical harmonics, using every one of its pixels.
+    ///
+    /// ```
+    /// # use ultraviolet::Vec3;
+    /// #
+    /// # use learnopengl_but_its_wgpu::{
+    /// #     ibl::equirectangular_direction, radiance::HdrImage, spherical_harmonics::SphericalHarmonics,
+    /// # };
+    /// #
+    /// let (width, height) = (64, 32);
+    /// let mut pixels = Vec::new();
+    /// for y in 0..height {
+    ///     for x in 0..width {
+    ///         let u = (x as f32 + 0.5) / width as f32;
+    ///         let v = (y as f32 + 0.5) / height as f32;
+    ///         pixels.push(Vec3::new(1.0, 1.0, 1.0) * (1.0 + equirectangular_direction(u, v).x));
+    ///     }
+    /// }
+    /// let sh = SphericalHarmonics::from_equirectangular(&HdrImage { width, height, pixels });
+    ///
+    /// let direction = Vec3::new(0.5, -0.5, 0.5).normalized();
+    /// assert!((sh.evaluate(direction).x - (1.0 + direction.x)).abs() < 1e-2);
+    /// ```
+    pub fn from_equirectangular(image: &HdrImage) -> Self {
+        let mut coefficients = [Vec3::zero(); COEFFICIENT_COUNT];
+        let mut total_weight = 0.0;
+        for y in 0..image.height {
+            let v = (y as f32 + 0.5) / image.height as f32;
+            // Rows get narrower towards the poles.
+            let weight = (v * PI).sin();
+            for x in 0..image.width {
+                let u = (x as f32 + 0.5) / image.width as f32;
+                let direction = equirectangular_direction(u, v);
+                let value = image.pixel(x, y);
+                for (coefficient, &basis) in coefficients.iter_mut().zip(&basis(direction)) {
+                    *coefficient += value * (basis * weight);
+                }
+                total_weight += weight;
+            }
+        }
+
+        let scale = 4.0 * PI / total_weight;
+        for coefficient in &mut coefficients {
+            *coefficient *= scale;
+        }
+        Self { coefficients }
+    }
+
+    /// Reconstructs the radiance arriving from `direction`, which has to be normalized.
+    pub fn evaluate(&self, direction: Vec3) -> Vec3 {
+        self.coefficients
+            .iter()
+            .zip(&basis(direction))
+            .fold(Vec3::zero(), |sum, (&coefficient, &basis)| {
+                sum + coefficient * basis
+            })
+    }
+
+    /// The light received by a diffuse surface facing `normal`, which has to be normalized. Like
+    /// [`ibl::irradiance_map`](crate::ibl::irradiance_map), the result is divided by pi, so it just has to be
+    /// multiplied with the albedo.
+    ///
+    /// Convolving with the cosine lobe of a diffuse surface just scales each band, which dampens the higher bands the
+    /// most:
+    ///
+    /// ```
+    /// # use ultraviolet::Vec3;
+    /// #
+    /// # use learnopengl_but_its_wgpu::spherical_harmonics::SphericalHarmonics;
+    /// #
+    /// let white = Vec3::new(1.0, 1.0, 1.0);
+    /// let normal = Vec3::new(0.6, 0.0, 0.8);
+    ///
+    /// // Uniform light is received with pi from every direction.
+    /// let uniform = SphericalHarmonics::project(16, |_| white);
+    /// assert!((uniform.irradiance(normal) - white).mag() < 1e-4);
+    ///
+    /// // Light varying linearly with the direction keeps its shape, at two thirds of its strength.
+    /// let linear = SphericalHarmonics::project(16, |d| white * d.z);
+    /// assert!((linear.irradiance(normal) - white * (2.0 / 3.0 * normal.z)).mag() < 1e-4);
+    ///
+    /// // Light only coming from above, which needs more bands than there are, is received with 2 pi / 3 by a surface
+    /// // facing up and not at all by one facing down.
+    /// let sky = SphericalHarmonics::project(32, |d| white * d.y.max(0.0));
+    /// assert!((sky.irradiance(Vec3::new(0.0, 1.0, 0.0)).x - 2.0 / 3.0).abs() < 0.02);
+    /// assert!(sky.irradiance(Vec3::new(0.0, -1.0, 0.0)).x.abs() < 0.02);
+    /// ```
+    pub fn irradiance(&self, normal: Vec3) -> Vec3 {
+        self.irradiance_coefficients()
+            .iter()
+            .zip(&basis(normal))
+            .fold(Vec3::zero(), |sum, (&coefficient, &basis)| {
+                sum + Vec3::new(coefficient.x, coefficient.y, coefficient.z) * basis
+            })
+    }
+
+    /// The coefficients convolved with the cosine lobe and divided by pi, padded to be uploaded as a uniform array.
+    /// Evaluating them like [`evaluate`](Self::evaluate) does gives the [`irradiance`](Self::irradiance).
+    pub fn irradiance_coefficients(&self) -> [Vec4; COEFFICIENT_COUNT] {
+        // The convolution scales the bands by pi, 2 pi / 3 and pi / 4.
+        let band_scales = [1.0, 2.0 / 3.0, 1.0 / 4.0];
+        let mut coefficients = [Vec4::zero(); COEFFICIENT_COUNT];
+        for (i, (irradiance, &radiance)) in
+            coefficients.iter_mut().zip(&self.coefficients).enumerate()
+        {
+            let band = match i {
+                0 => 0,
+                1..=3 => 1,
+                _ => 2,
+            };
+            let scaled = radiance * band_scales[band];
+            *irradiance = Vec4::new(scaled.x, scaled.y, scaled.z, 0.0);
+        }
+        coefficients
+    }
+}
+
+/// The values of the 9 basis functions in `direction`, which has to be normalized. The shaders evaluating the
+/// coefficients have to use the same constants.
+pub fn basis(direction: Vec3) -> [f32; COEFFICIENT_COUNT] {
+    let Vec3 { x, y, z } = direction;
+    [
+        0.282_095,
+        0.488_603 * y,
+        0.488_603 * z,
+        0.488_603 * x,
+        1.092_548 * x * y,
+        1.092_548 * y * z,
+        0.315_392 * (3.0 * z * z - 1.0),
+        1.092_548 * x * z,
+        0.546_274 * (x * x - y * y),
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const WHITE: Vec3 = Vec3 {
+        x: 1.0,
+        y: 1.0,
+        z: 1.0,
+    };
+
+    fn directions() -> Vec<Vec3> {
+        vec![
+            Vec3::new(1.0, 2.0, 3.0).normalized(),
+            Vec3::new(-1.0, 0.0, 0.5).normalized(),
+            Vec3::new(0.2, -1.0, -0.7).normalized(),
+            Vec3::new(0.0, 1.0, 0.0),
+            Vec3::new(0.0, 0.0, -1.0),
+        ]
+    }
+
+    fn assert_close(expected: Vec3, actual: Vec3, tolerance: f32) {
+        assert!(
+            (expected - actual).mag() < tolerance,
+            "expected {:?}, got {:?}",
+            expected,
+            actual
+        );
+    }
+
+    fn equirectangular_image(width: u32, height: u32, radiance: impl Fn(Vec3) -> Vec3) -> HdrImage {
+        let mut pixels = Vec::new();
+        for y in 0..height {
+            for x in 0..width {
+                let u = (x as f32 + 0.5) / width as f32;
+                let v = (y as f32 + 0.5) / height as f32;
+                pixels.push(radiance(equirectangular_direction(u, v)));
+            }
+        }
+        HdrImage {
+            width,
+            height,
+            pixels,
+        }
+    }
+
+    #[test]
+    fn projecting_a_basis_function_isolates_its_coefficient() {
+        for i in 0..COEFFICIENT_COUNT {
+            let sh = SphericalHarmonics::project(32, |direction| WHITE * basis(direction)[i]);
+            for (j, &coefficient) in sh.coefficients.iter().enumerate() {
+                let expected = if i == j { 1.0 } else { 0.0 };
+                assert_close(WHITE * expected, coefficient, 1e-2);
+            }
+        }
+    }
+
+    #[test]
+    fn projection_reconstructs_second_degree_polynomials() {
+        let radiance = |d: Vec3| {
+            Vec3::new(
+                0.5 + d.x * d.y,
+                2.0 - d.z,
+                3.0 * d.z * d.z + d.x - 0.5 * d.y,
+            )
+        };
+        let sh = SphericalHarmonics::project(32, radiance);
+        for direction in directions() {
+            assert_close(radiance(direction), sh.evaluate(direction), 1e-3);
+        }
+    }
+
+    #[test]
+    fn projection_smooths_out_higher_frequencies() {
+        // A sharp spot light can't be reconstructed from three bands, but the average over the sphere survives.
+        let sh = SphericalHarmonics::project(32, |d| WHITE * d.y.max(0.0).powi(16));
+        let average = 1.0 / 34.0;
+        assert_close(
+            WHITE * average * (4.0 * PI).sqrt(),
+            sh.coefficients[0],
+            1e-3,
+        );
+        assert!(sh.evaluate(Vec3::new(0.0, 1.0, 0.0)).x < 1.0);
+    }
+
+    #[test]
+    fn from_cube_map_matches_projecting_the_same_radiance() {
+        let radiance = |d: Vec3| Vec3::new(1.0 + d.x, 1.0 - d.y, d.z * d.z);
+        let size = 8;
+        let mut texels = Vec::new();
+        for face in 0..6 {
+            for y in 0..size {
+                for x in 0..size {
+                    let u = (x as f32 + 0.5) / size as f32;
+                    let v = (y as f32 + 0.5) / size as f32;
+                    texels.push(radiance(face_direction(face, u, v)));
+                }
+            }
+        }
+
+        let from_cube_map = SphericalHarmonics::from_cube_map(&CubeMap { size, texels });
+        let projected = SphericalHarmonics::project(size, radiance);
+        for (&expected, &actual) in projected
+            .coefficients
+            .iter()
+            .zip(&from_cube_map.coefficients)
+        {
+            assert_close(expected, actual, 1e-6);
+        }
+    }
+
+    #[test]
+    fn irradiance_coefficients_scale_each_band() {
+        let mut sh = SphericalHarmonics {
+            coefficients: [Vec3::zero(); COEFFICIENT_COUNT],
+        };
+        for (i, coefficient) in sh.coefficients.iter_mut().enumerate() {
+            *coefficient = Vec3::new(1.0, 2.0, 3.0) * (i + 1) as f32;
+        }
+
+        let irradiance_coefficients = sh.irradiance_coefficients();
+        for (i, (&radiance, &irradiance)) in sh
+            .coefficients
+            .iter()
+            .zip(&irradiance_coefficients)
+            .enumerate()
+        {
+            let band_scale = match i {
+                0 => 1.0,
+                1..=3 => 2.0 / 3.0,
+                _ => 1.0 / 4.0,
+            };
+            let irradiance_xyz = Vec3::new(irradiance.x, irradiance.y, irradiance.z);
+            assert_close(radiance * band_scale, irradiance_xyz, 1e-6);
+            assert_eq!(0.0, irradiance.w);
+        }
+    }
+
+    #[test]
+    fn irradiance_evaluates_the_irradiance_coefficients() {
+        let sh =
+            SphericalHarmonics::project(16, |d| Vec3::new(1.0 + d.x, 0.5 + d.y * d.z, d.z * d.z));
+        let irradiance_coefficients = sh.irradiance_coefficients();
+        for direction in directions() {
+            let expected = irradiance_coefficients.iter().zip(&basis(direction)).fold(
+                Vec3::zero(),
+                |sum, (coefficient, &basis)| {
+                    sum + Vec3::new(coefficient.x, coefficient.y, coefficient.z) * basis
+                },
+            );
+            assert_close(expected, sh.irradiance(direction), 1e-6);
+        }
+    }
+
+    #[test]
+    fn from_equirectangular_reconstructs_second_degree_polynomials() {
+        let radiance = |d: Vec3| Vec3::new(1.0 + d.x, 2.0 - d.y * d.z, d.y * d.y);
+        let sh =
+            SphericalHarmonics::from_equirectangular(&equirectangular_image(128, 64, radiance));
+        for direction in directions() {
+            assert_close(radiance(direction), sh.evaluate(direction), 1e-2);
+        }
+    }
+
+    #[test]
+    fn from_equirectangular_agrees_with_projecting_a_cube_map() {
+        let radiance = |d: Vec3| WHITE * (0.25 + d.y.max(0.0));
+        let from_image =
+            SphericalHarmonics::from_equirectangular(&equirectangular_image(128, 64, radiance));
+        let projected = SphericalHarmonics::project(32, radiance);
+        for (&expected, &actual) in projected.coefficients.iter().zip(&from_image.coefficients) {
+            assert_close(expected, actual, 1e-2);
+        }
+    }
+}
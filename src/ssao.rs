@@ -0,0 +1,132 @@
+//! Screen-space ambient occlusion: an estimate of how much of the ambient light reaching each pixel is blocked by the
+//! geometry around it, worked out from the G-buffer alone.
+//!
+//! For every pixel, a kernel of sample points is placed in the hemisphere above the surface in view space. Each sample
+//! is projected onto the screen and compared against the depth of the surface actually visible there, and the fraction
+//! of samples that end up behind that surface is how occluded the pixel is. The kernel is rotated per pixel by a small
+//! tiled noise texture, which trades banding for a regular noise pattern that a blur the size of a tile removes again.
+
+use ultraviolet::{Vec3, Vec4};
+
+/// A single channel holding how much ambient light reaches each pixel, from `0.0` for fully occluded to `1.0`.
+pub const OCCLUSION_FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::R8Unorm;
+/// The width and height of the noise texture tiled over the screen. The blur averages a square of the same size.
+pub const NOISE_SIZE: u32 = 4;
+/// The largest number of samples in the kernel.
+pub const MAX_KERNEL_SIZE: usize = 64;
+
+/// The targets the occlusion is rendered into and then blurred. They have to be recreated whenever the window is
+/// resized.
+pub struct SsaoTargets {
+    pub occlusion_texture: wgpu::Texture,
+    pub occlusion_view: wgpu::TextureView,
+    pub blurred_texture: wgpu::Texture,
+    pub blurred_view: wgpu::TextureView,
+    pub sampler: wgpu::Sampler,
+}
+
+impl SsaoTargets {
+    pub fn new(device: &wgpu::Device, width: u32, height: u32) -> Self {
+        let (occlusion_texture, occlusion_view) = create_target(device, width, height);
+        let (blurred_texture, blurred_view) = create_target(device, width, height);
+        let sampler = device.create_sampler(&wgpu::SamplerDescriptor {
+            address_mode_u: wgpu::AddressMode::ClampToEdge,
+            address_mode_v: wgpu::AddressMode::ClampToEdge,
+            address_mode_w: wgpu::AddressMode::ClampToEdge,
+            mag_filter: wgpu::FilterMode::Nearest,
+            min_filter: wgpu::FilterMode::Nearest,
+            mipmap_filter: wgpu::FilterMode::Nearest,
+            lod_min_clamp: 0.0,
+            lod_max_clamp: 0.0,
+            compare_function: wgpu::CompareFunction::Never,
+        });
+
+        Self {
+            occlusion_texture,
+            occlusion_view,
+            blurred_texture,
+            blurred_view,
+            sampler,
+        }
+    }
+}
+
+fn create_target(
+    device: &wgpu::Device,
+    width: u32,
+    height: u32,
+) -> (wgpu::Texture, wgpu::TextureView) {
+    let texture = device.create_texture(&wgpu::TextureDescriptor {
+        size: wgpu::Extent3d {
+            width,
+            height,
+            depth: 1,
+        },
+        array_layer_count: 1,
+        mip_level_count: 1,
+        sample_count: 1,
+        dimension: wgpu::TextureDimension::D2,
+        format: OCCLUSION_FORMAT,
+        usage: wgpu::TextureUsage::OUTPUT_ATTACHMENT | wgpu::TextureUsage::SAMPLED,
+    });
+    let view = texture.create_default_view();
+    (texture, view)
+}
+
+/// Creates `count` sample points in the unit hemisphere around +Z, using `random` for numbers in `0.0..1.0`. They're
+/// padded to `Vec4`s to be uploaded as a uniform array.
+///
+/// The samples get further from the center along the kernel, with more of them close by, since nearby geometry
+/// occludes the most. A shader using only the first few samples still covers the whole hemisphere, just more coarsely.
+///
+/// ```
+/// # use ultraviolet::Vec3;
+/// #
+/// # use learnopengl_but_its_wgpu::ssao::sample_kernel;
+/// #
+/// let mut state = 1u32;
+/// let mut random = || {
+///     state = state.wrapping_mul(1_664_525).wrapping_add(1_013_904_223);
+///     (state >> 8) as f32 / (1 << 24) as f32
+/// };
+/// let kernel = sample_kernel(64, &mut random);
+///
+/// assert_eq!(64, kernel.len());
+/// let length = |i: usize| Vec3::new(kernel[i].x, kernel[i].y, kernel[i].z).mag();
+/// for i in 0..kernel.len() {
+///     assert!(kernel[i].z >= 0.0);
+///     assert!(length(i) <= 1.0 + 1e-6);
+/// }
+/// // The first sample lies within a tenth of the radius, while the last ones can reach all of it.
+/// assert!(length(0) <= 0.1 + 1e-6);
+/// ```
+pub fn sample_kernel<R: FnMut() -> f32>(count: usize, mut random: R) -> Vec<Vec4> {
+    (0..count)
+        .map(|i| {
+            let direction =
+                Vec3::new(random() * 2.0 - 1.0, random() * 2.0 - 1.0, random()).normalized();
+            let t = i as f32 / count as f32;
+            // Lerps from 0.1 to 1.0 along the square of how far along the kernel the sample is.
+            let scale = 0.1 + t * t * 0.9;
+            let sample = direction * random() * scale;
+            Vec4::new(sample.x, sample.y, sample.z, 0.0)
+        })
+        .collect()
+}
+
+/// Creates the texels of the [`NOISE_SIZE`] square noise texture as RGBA8, using `random` for numbers in `0.0..1.0`.
+/// Each texel is a random direction in the XY plane to rotate the sample kernel around the surface normal by, encoded
+/// in `0..=255` like a normal map.
+pub fn noise_texels<R: FnMut() -> f32>(mut random: R) -> Vec<u8> {
+    (0..NOISE_SIZE * NOISE_SIZE)
+        .flat_map(|_| {
+            let x = random() * 2.0 - 1.0;
+            let y = random() * 2.0 - 1.0;
+            vec![encode(x), encode(y), encode(0.0), 255]
+        })
+        .collect()
+}
+
+fn encode(value: f32) -> u8 {
+    ((value * 0.5 + 0.5) * 255.0).round() as u8
+}
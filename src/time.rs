@@ -0,0 +1,58 @@
+//! A clock that's advanced once per frame, so that everything animated during a frame sees the same point in time.
+
+use std::time::Instant;
+
+/// The time that has passed since the clock was created, leaving out the time it spent paused.
+pub struct Time {
+    last_frame: Instant,
+    elapsed: f32,
+    delta: f32,
+    is_paused: bool,
+}
+
+impl Time {
+    pub fn new() -> Self {
+        Self {
+            last_frame: Instant::now(),
+            elapsed: 0.0,
+            delta: 0.0,
+            is_paused: false,
+        }
+    }
+
+    /// Advances the clock to now. This should happen once at the start of every frame.
+    pub fn update(&mut self) {
+        let now = Instant::now();
+        self.delta = if self.is_paused {
+            0.0
+        } else {
+            (now - self.last_frame).as_secs_f32()
+        };
+        self.elapsed += self.delta;
+        self.last_frame = now;
+    }
+
+    /// The seconds that have passed up to the last update.
+    pub fn elapsed(&self) -> f32 {
+        self.elapsed
+    }
+
+    /// The seconds between the last two updates, which is zero while the clock is paused.
+    pub fn delta(&self) -> f32 {
+        self.delta
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.is_paused
+    }
+
+    pub fn set_paused(&mut self, is_paused: bool) {
+        self.is_paused = is_paused;
+    }
+}
+
+impl Default for Time {
+    fn default() -> Self {
+        Self::new()
+    }
+}